@@ -6,6 +6,7 @@ use mrubyedge::yamrb::helpers::mrb_funcall;
 use mrubyedge::yamrb::value::RObject;
 use mrubyedge::yamrb::value::RValue;
 use mrubyedge::yamrb::vm::VM;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
 use serde_json::Value;
 
@@ -154,8 +155,87 @@ impl From<Value> for JsonValue {
     }
 }
 
+// Deserializing straight into Ruby objects keeps object members in document
+// order, which serde_json::Value (a sorted map by default) would lose.
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(JsonValueVisitor)
+    }
+}
+
+struct JsonValueVisitor;
+
+impl<'de> Visitor<'de> for JsonValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(JsonValue::new(RObject::nil().to_refcount_assigned()))
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Self::Value, E> {
+        Ok(JsonValue::new(RObject::boolean(b).to_refcount_assigned()))
+    }
+
+    fn visit_i64<E>(self, i: i64) -> Result<Self::Value, E> {
+        Ok(JsonValue::new(RObject::integer(i).to_refcount_assigned()))
+    }
+
+    fn visit_u64<E>(self, u: u64) -> Result<Self::Value, E> {
+        Ok(JsonValue::new(
+            RObject::integer(u as i64).to_refcount_assigned(),
+        ))
+    }
+
+    fn visit_f64<E>(self, f: f64) -> Result<Self::Value, E> {
+        Ok(JsonValue::new(RObject::float(f).to_refcount_assigned()))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E> {
+        Ok(JsonValue::new(
+            RObject::string(s.to_string()).to_refcount_assigned(),
+        ))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Self::Value, E> {
+        Ok(JsonValue::new(RObject::string(s).to_refcount_assigned()))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut vec = Vec::new();
+        while let Some(item) = seq.next_element::<JsonValue>()? {
+            vec.push(item.get_inner());
+        }
+        Ok(JsonValue::new(RObject::array(vec).to_refcount_assigned()))
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some((k, v)) = access.next_entry::<String, JsonValue>()? {
+            let key = RObject::string(k).to_refcount_assigned();
+            entries.push((
+                key.as_hash_key().expect("object cannot use for hashed key"),
+                (key.clone(), v.get_inner()),
+            ));
+        }
+        let hash = RObject::hash(entries.into_iter().collect());
+        Ok(JsonValue::new(hash.to_refcount_assigned()))
+    }
+}
+
 pub(crate) fn mrb_json_load(_vm: &mut VM, json_str: impl Into<String>) -> Result<JsonValue, Error> {
-    let value = serde_json::from_str::<serde_json::Value>(&json_str.into())
-        .map_err(|e| Error::RuntimeError(format!("Failed to parse JSON string: {}", e)))?;
-    Ok(value.into())
+    serde_json::from_str::<JsonValue>(&json_str.into())
+        .map_err(|e| Error::RuntimeError(format!("Failed to parse JSON string: {}", e)))
 }
@@ -192,3 +192,24 @@ fn test_json_dump_to_json() {
 }
 
 // FIXME: panic!s when to_json is not defined in user-defined class
+
+#[test]
+fn test_json_dump_round_trip_keeps_key_order() {
+    let code = r#"
+    json = '{"zeta":1,"alpha":{"z":true,"a":null},"mid":[3],"beta":"b"}'
+    dumped = JSON.dump({"zeta" => 1, "alpha" => {"z" => true, "a" => nil}, "mid" => [3], "beta" => "b"})
+    raise "round trip changed #{json}" unless JSON.dump(JSON.load(json)) == json
+    dumped
+    "#;
+    let binary = mrbc_compile("json_dump_key_order", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    mrubyedge_serde_json::init_json(&mut vm);
+
+    let result = vm.run().unwrap();
+    let json_str: String = result.as_ref().try_into().unwrap();
+    assert_eq!(
+        json_str,
+        r#"{"zeta":1,"alpha":{"z":true,"a":null},"mid":[3],"beta":"b"}"#
+    );
+}
@@ -159,7 +159,7 @@ fn test_json_load_boolean() {
 
     let result = vm.run().unwrap();
     let value: bool = result.as_ref().try_into().unwrap();
-    assert_eq!(value, true);
+    assert!(value);
 }
 
 #[test]
@@ -174,7 +174,7 @@ fn test_json_load_boolean_2() {
 
     let result = vm.run().unwrap();
     let value: bool = result.as_ref().try_into().unwrap();
-    assert_eq!(value, false);
+    assert!(!value);
 }
 
 #[test]
@@ -223,3 +223,19 @@ fn test_json_load_key() {
     let value: String = result.as_ref().try_into().unwrap();
     assert_eq!(value, "ok");
 }
+
+#[test]
+fn test_json_load_keeps_key_order() {
+    let code = r#"
+    result = JSON.load('{"zeta": 1, "alpha": 2, "mid": 3, "beta": 4, "omega": 5}')
+    result.keys.join(",")
+    "#;
+    let binary = mrbc_compile("json_load_key_order", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    mrubyedge_serde_json::init_json(&mut vm);
+
+    let result = vm.run().unwrap();
+    let keys: String = result.as_ref().try_into().unwrap();
+    assert_eq!(keys, "zeta,alpha,mid,beta,omega");
+}
@@ -9,7 +9,7 @@ pub struct FuncDef {
 
 impl FuncDef {
     pub fn args_decl(&self) -> &str {
-        if self.argstype.len() == 0 {
+        if self.argstype.is_empty() {
             return "";
         }
 
@@ -31,7 +31,7 @@ impl FuncDef {
     }
 
     pub fn args_let_vec(&self) -> &str {
-        if self.argstype.len() == 0 {
+        if self.argstype.is_empty() {
             return "vec![]";
         }
 
@@ -53,7 +53,7 @@ impl FuncDef {
     }
 
     pub fn str_args_converter(&self) -> &str {
-        if self.argstype.len() == 0 {
+        if self.argstype.is_empty() {
             return "";
         }
         let mut buf = String::new();
@@ -251,13 +251,11 @@ fn def(input: &str) -> Res<&str, ()> {
 }
 
 fn alpha_just_1(input: &str) -> Res<&str, char> {
-    satisfy(|c| c == '_' || ('a' <= c && c <= 'z') || ('A' <= c && c <= 'Z'))(input)
+    satisfy(|c: char| c == '_' || c.is_ascii_alphabetic())(input)
 }
 
 fn alphanumeric_just_1(input: &str) -> Res<&str, char> {
-    satisfy(|c| {
-        c == '_' || ('0' <= c && c <= '9') || ('a' <= c && c <= 'z') || ('A' <= c && c <= 'Z')
-    })(input)
+    satisfy(|c: char| c == '_' || c.is_ascii_alphanumeric())(input)
 }
 
 fn symbol(input: &str) -> Res<&str, String> {
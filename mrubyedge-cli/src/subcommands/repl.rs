@@ -211,16 +211,14 @@ pub fn execute(args: ReplArgs) -> Result<(), Box<dyn std::error::Error>> {
                     KeyEvent {
                         code: KeyCode::Backspace,
                         ..
-                    } => {
-                        if !current_line.is_empty() {
-                            current_line.pop();
-                            cs_execute!(
-                                stdout,
-                                cursor::MoveLeft(1),
-                                terminal::Clear(ClearType::UntilNewLine)
-                            )?;
-                            stdout.flush()?;
-                        }
+                    } if !current_line.is_empty() => {
+                        current_line.pop();
+                        cs_execute!(
+                            stdout,
+                            cursor::MoveLeft(1),
+                            terminal::Clear(ClearType::UntilNewLine)
+                        )?;
+                        stdout.flush()?;
                     }
                     // Regular character input
                     KeyEvent {
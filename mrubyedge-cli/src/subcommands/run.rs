@@ -1,5 +1,10 @@
 use clap::Args;
-use std::{fs::File, io::Read, path::PathBuf};
+use std::{
+    ffi::CString,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
 
 use mruby_compiler2_sys as mrbc;
 use mrubyedge;
@@ -19,13 +24,18 @@ pub struct RunArgs {
 }
 
 pub fn execute(args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let script_name = match (&args.eval, &args.file) {
+        (Some(_), _) => "-e".to_string(),
+        (None, Some(file)) => file.display().to_string(),
+        (None, None) => "-".to_string(),
+    };
     let buf = if let Some(code) = &args.eval {
         // Execute code from -e option
         code.clone().into_bytes()
-    } else if let Some(file) = args.file {
+    } else if let Some(file) = &args.file {
         // Read from file
         let mut buf = Vec::new();
-        File::open(&file)?.read_to_end(&mut buf)?;
+        File::open(file)?.read_to_end(&mut buf)?;
         buf
     } else {
         return Err("Either -e option or file path must be provided".into());
@@ -38,16 +48,79 @@ pub fn execute(args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
             buf.to_vec()
         } else {
             let buf = String::from_utf8(buf)?;
-            let mut ctx = mrbc::MRubyCompiler2Context::new();
             if args.dump_insns {
+                let mut ctx = mrbc::MRubyCompiler2Context::new();
                 ctx.dump_bytecode(&buf)?;
             }
-            ctx.compile(&buf)?
+            match &args.file {
+                Some(file) if args.eval.is_none() => compile_with_debug_info(file)?,
+                _ => {
+                    let src =
+                        std::env::temp_dir().join(format!("mrbedge-run-{}.rb", std::process::id()));
+                    std::fs::write(&src, &buf)?;
+                    let res = compile_with_debug_info(&src);
+                    std::fs::remove_file(&src)?;
+                    res?
+                }
+            }
         };
         let mut rite = mrubyedge::rite::load(&mrb_bin)?;
+        // Backtraces name the script as given, e.g. `-e`, not a temporary file
+        for irep in rite.irep.iter_mut() {
+            for file in irep.debug_files.iter_mut() {
+                file.filename = script_name.clone();
+            }
+        }
         let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
-        vm.run()?;
+        if let Err(e) = vm.run() {
+            report_uncaught_error(&vm, &script_name, e.as_ref());
+            std::process::exit(1);
+        }
     }
 
     Ok(())
 }
+
+/// Compiles a Ruby source file with `mrbc -g`, so that the binary carries
+/// filenames and line numbers for backtraces.
+fn compile_with_debug_info(src: &Path) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let dest = std::env::temp_dir().join(format!("mrbedge-run-{}.mrb", std::process::id()));
+    let args = [
+        CString::new("mrbc")?,
+        CString::new("-g")?,
+        CString::new("-o")?,
+        CString::new(dest.to_string_lossy().as_bytes())?,
+        CString::new(src.to_string_lossy().as_bytes())?,
+    ];
+    let mut argv: Vec<*mut std::os::raw::c_char> =
+        args.iter().map(|arg| arg.as_ptr() as *mut _).collect();
+    let status = unsafe { mec_mrbc_sys::mrbc_main(argv.len() as i32, argv.as_mut_ptr()) };
+    if status != 0 {
+        return Err(format!("failed to compile {}", src.display()).into());
+    }
+    let bin = std::fs::read(&dest)?;
+    std::fs::remove_file(&dest)?;
+    Ok(bin)
+}
+
+/// Prints an uncaught exception in Ruby's format:
+/// `file:line:in 'method': message (Class)` followed by `from` lines.
+fn report_uncaught_error(
+    vm: &mrubyedge::yamrb::vm::VM,
+    script_name: &str,
+    error: &dyn std::error::Error,
+) {
+    let Some(exc) = vm.exception.as_ref() else {
+        eprintln!("{}: {}", script_name, error);
+        return;
+    };
+    let mut backtrace = exc.backtrace.iter();
+    let origin = backtrace
+        .next()
+        .map(|entry| entry.as_str())
+        .unwrap_or(script_name);
+    eprintln!("{}: {} ({})", origin, exc.message, exc.class.full_name());
+    for entry in backtrace {
+        eprintln!("\tfrom {}", entry);
+    }
+}
@@ -0,0 +1,17 @@
+use std::process::Command;
+
+#[test]
+fn uncaught_error_reports_file_and_line_test() {
+    let code = "def boom(x)\n  raise ArgumentError, \"bad #{x}\"\nend\nboom(1)\n";
+    let output = Command::new(env!("CARGO_BIN_EXE_mrbedge"))
+        .args(["run", "-e", code])
+        .output()
+        .expect("failed to run mrbedge");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("-e:2:in 'boom': bad 1 (ArgumentError)\n\tfrom -e:4:in '<main>'"),
+        "stderr = {}",
+        stderr
+    );
+}
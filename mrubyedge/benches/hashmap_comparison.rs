@@ -1,5 +1,6 @@
 use criterion::{Criterion, black_box, criterion_group, criterion_main};
 use fnv::FnvHashMap;
+use mrubyedge::yamrb::value::{RHash, RHashMap, RObject};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
@@ -184,6 +185,44 @@ fn bench_fnv_50char(c: &mut Criterion) {
     });
 }
 
+// Ruby Hash storage: insertion-ordered RHash vs. a bare map with the same keys
+fn bench_rhash_10char(c: &mut Criterion) {
+    let pairs: Vec<_> = (0..10000)
+        .map(|i| {
+            let key = RObject::string(KEYS_10CHAR[i].clone()).to_refcount_assigned();
+            let value = RObject::string(VALUES[i].clone()).to_refcount_assigned();
+            (key.as_hash_key().unwrap(), (key, value))
+        })
+        .collect();
+    c.bench_function("RHash 10000 entries insert+get (10 char keys)", |b| {
+        b.iter(|| {
+            let mut map = RHash::default();
+            for (hashed, kv) in pairs.iter() {
+                map.insert(black_box(hashed.clone()), kv.clone());
+            }
+            for (hashed, _) in pairs.iter() {
+                black_box(map.get(hashed));
+            }
+            map
+        })
+    });
+    c.bench_function(
+        "unordered RHashMap 10000 entries insert+get (10 char keys)",
+        |b| {
+            b.iter(|| {
+                let mut map = RHashMap::default();
+                for (hashed, kv) in pairs.iter() {
+                    map.insert(black_box(hashed.clone()), kv.clone());
+                }
+                for (hashed, _) in pairs.iter() {
+                    black_box(map.get(hashed));
+                }
+                map
+            })
+        },
+    );
+}
+
 criterion_group!(
     benches,
     bench_fnv_1char,
@@ -196,5 +235,6 @@ criterion_group!(
     bench_default_20char,
     bench_fnv_50char,
     bench_default_50char,
+    bench_rhash_10char,
 );
 criterion_main!(benches);
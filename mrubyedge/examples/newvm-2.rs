@@ -62,6 +62,9 @@ fn main() {
         reps: Vec::new(),
         lv: None,
         catch_target_pos: Vec::new(),
+        catch_handlers: Vec::new(),
+        debug_files: Vec::new(),
+        inline_caches: Default::default(),
     };
 
     // irep 0x600000f20000 nregs=7 nlocals=3 pools=0 syms=1 reps=1 ilen=27
@@ -152,6 +155,9 @@ fn main() {
         reps: vec![Rc::new(irep1)],
         lv: None,
         catch_target_pos: Vec::new(),
+        catch_handlers: Vec::new(),
+        debug_files: Vec::new(),
+        inline_caches: Default::default(),
     };
     let mut vm = vm::VM::new_by_raw_irep(irep0);
     let ret = vm.run().unwrap();
@@ -195,6 +195,9 @@ fn main() {
         reps: Vec::new(),
         lv: None,
         catch_target_pos: Vec::new(),
+        catch_handlers: Vec::new(),
+        debug_files: Vec::new(),
+        inline_caches: Default::default(),
     };
 
     // irep0:
@@ -262,6 +265,9 @@ fn main() {
         reps: vec![Rc::new(irep1)],
         lv: None,
         catch_target_pos: Vec::new(),
+        catch_handlers: Vec::new(),
+        debug_files: Vec::new(),
+        inline_caches: Default::default(),
     };
     let mut vm = vm::VM::new_by_raw_irep(irep0);
     let ret = vm.run().unwrap();
@@ -78,6 +78,9 @@ fn main() {
         reps: Vec::new(),
         lv: None,
         catch_target_pos: Vec::new(),
+        catch_handlers: Vec::new(),
+        debug_files: Vec::new(),
+        inline_caches: Default::default(),
     };
     let mut vm = vm::VM::new_by_raw_irep(irep);
     let ret = vm.run().unwrap();
@@ -4,6 +4,7 @@ use std::rc::Rc;
 
 use crate::yamrb::value::RClass;
 use crate::yamrb::value::RObject;
+use crate::yamrb::value::RValue;
 use crate::yamrb::vm::VM;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -20,9 +21,13 @@ pub enum Error {
     ZeroDivisionError,
 
     TaggedError(&'static str, String),
+    /// An exception object raised from Ruby code (`raise obj`, `raise Klass`)
+    Exception(Rc<RObject>),
 
     Break(Rc<RObject>),
     BlockReturn(usize, Rc<RObject>),
+    /// `throw tag, value` unwinding to the matching `catch`
+    Throw(Rc<RObject>, Rc<RObject>),
 }
 
 impl fmt::Display for Error {
@@ -52,15 +57,22 @@ impl Error {
             Error::ZeroDivisionError => "divided by 0".to_string(),
 
             Error::TaggedError(tag, msg) => format!("[{}] {}", tag, msg),
+            Error::Exception(obj) => obj.exception_message(),
 
             Error::Break(_) => "[Break]".to_string(),
             Error::BlockReturn(_, _) => "[BlockReturn]".to_string(),
+            Error::Throw(_, _) => "[Throw]".to_string(),
         }
     }
 
     pub fn is_instance_of(&self, other: Rc<RClass>) -> bool {
+        if let Error::Exception(obj) = self
+            && let RValue::Exception(e) = &obj.value
+        {
+            return Rc::ptr_eq(&e.class, &other);
+        }
         matches!(
-            (self, other.sym_id.name.as_str()),
+            (self, other.name().as_str()),
             (Error::General, "StandardError")
                 | (Error::Internal(_), "InternalError")
                 | (Error::InvalidOpCode, "StandardError")
@@ -75,16 +87,7 @@ impl Error {
     }
 
     pub fn is_a(&self, vm: &mut VM, other: Rc<RClass>) -> bool {
-        let mut klass: Option<Rc<RClass>> = RClass::from_error(vm, self).into();
-        let target_klass_name = other.sym_id.name.as_str();
-
-        while let Some(k) = klass {
-            if k.sym_id.name.as_str() == target_klass_name {
-                return true;
-            }
-            klass = k.super_class.clone();
-        }
-        false
+        RClass::from_error(vm, self).is_subclass_of(&other)
     }
 }
 
@@ -120,9 +123,11 @@ impl From<Error> for StaticError {
             Error::ZeroDivisionError => StaticError::General("divided by 0".to_string()),
 
             Error::TaggedError(tag, msg) => StaticError::General(format!("[{}] {}", tag, msg)),
+            Error::Exception(obj) => StaticError::General(obj.exception_message()),
 
             Error::Break(_) => StaticError::General("[Break]".to_string()),
             Error::BlockReturn(_, _) => StaticError::General("[BlockReturn]".to_string()),
+            Error::Throw(_, _) => StaticError::General("[Throw]".to_string()),
         }
     }
 }
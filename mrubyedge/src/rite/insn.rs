@@ -2,14 +2,16 @@ use std::fmt::Debug;
 
 use crate::Error;
 
+/// Decoded operands. `a` and `b` are widened to 16 bits so that
+/// EXT1/EXT2/EXT3 prefixed instructions fit in the same shape.
 #[derive(Copy, Clone, Debug)]
 pub enum Fetched {
     Z,
-    B(u8),
-    BB(u8, u8),
-    BBB(u8, u8, u8),
-    BS(u8, u16),
-    BSS(u8, u16, u16),
+    B(u16),
+    BB(u16, u16),
+    BBB(u16, u16, u8),
+    BS(u16, u16),
+    BSS(u16, u16, u16),
     S(u16),
     W(u32), // u24 in real layout
 }
@@ -24,35 +26,35 @@ impl Fetched {
         }
     }
 
-    pub fn as_b(self) -> FetchResult<u8> {
+    pub fn as_b(self) -> FetchResult<u16> {
         match self {
             Fetched::B(a) => Ok(a),
             _ => Err(Error::internal("invaid operand")),
         }
     }
 
-    pub fn as_bb(self) -> FetchResult<(u8, u8)> {
+    pub fn as_bb(self) -> FetchResult<(u16, u16)> {
         match self {
             Fetched::BB(a, b) => Ok((a, b)),
             _ => Err(Error::internal("invaid operand")),
         }
     }
 
-    pub fn as_bbb(self) -> FetchResult<(u8, u8, u8)> {
+    pub fn as_bbb(self) -> FetchResult<(u16, u16, u8)> {
         match self {
             Fetched::BBB(a, b, c) => Ok((a, b, c)),
             _ => Err(Error::internal("invalid operand")),
         }
     }
 
-    pub fn as_bs(self) -> FetchResult<(u8, u16)> {
+    pub fn as_bs(self) -> FetchResult<(u16, u16)> {
         match self {
             Fetched::BS(a, b) => Ok((a, b)),
             _ => Err(Error::internal("invalid operand")),
         }
     }
 
-    pub fn as_bss(self) -> FetchResult<(u8, u16, u16)> {
+    pub fn as_bss(self) -> FetchResult<(u16, u16, u16)> {
         match self {
             Fetched::BSS(a, b, c) => Ok((a, b, c)),
             _ => Err(Error::internal("invalid operand")),
@@ -73,19 +75,6 @@ impl Fetched {
         }
     }
 
-    pub fn len(&self) -> usize {
-        match self {
-            Fetched::Z => 0,
-            Fetched::B(_) => 1,
-            Fetched::BB(_, _) => 2,
-            Fetched::BBB(_, _, _) => 3,
-            Fetched::BS(_, _) => 3,
-            Fetched::BSS(_, _, _) => 5,
-            Fetched::S(_) => 2,
-            Fetched::W(_) => 3,
-        }
-    }
-
     pub fn is_empty(&self) -> bool {
         matches!(self, Fetched::Z)
     }
@@ -450,80 +439,109 @@ impl Debug for OpCode {
     }
 }
 
-fn fetch_z(bin: &mut &[u8]) -> Result<Fetched, Error> {
+/// Operand widening requested by an `EXT1`/`EXT2`/`EXT3` prefix.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Ext {
+    pub a: bool,
+    pub b: bool,
+}
+
+impl Ext {
+    pub const NONE: Ext = Ext { a: false, b: false };
+
+    /// Returns the widening for an extension prefix, or `None` for other opcodes.
+    pub fn from_prefix(code: OpCode) -> Option<Ext> {
+        match code {
+            EXT1 => Some(Ext { a: true, b: false }),
+            EXT2 => Some(Ext { a: false, b: true }),
+            EXT3 => Some(Ext { a: true, b: true }),
+            _ => None,
+        }
+    }
+}
+
+fn read_b(bin: &[u8], pos: &mut usize, wide: bool) -> Result<u16, Error> {
+    if wide {
+        read_s(bin, pos)
+    } else {
+        let v = *bin
+            .get(*pos)
+            .ok_or_else(|| Error::internal("byte code too short"))?;
+        *pos += 1;
+        Ok(v as u16)
+    }
+}
+fn read_s(bin: &[u8], pos: &mut usize) -> Result<u16, Error> {
+    if bin.len() < *pos + 2 {
+        return Err(Error::internal("byte code too short"));
+    }
+    let s = ((bin[*pos] as u16) << 8) | bin[*pos + 1] as u16;
+    *pos += 2;
+    Ok(s)
+}
+
+fn fetch_z(bin: &mut &[u8], _ext: Ext) -> Result<Fetched, Error> {
     if bin.is_empty() {
         return Err(Error::internal("byte code too short"));
     }
     *bin = &bin[1..];
     Ok(Fetched::Z)
 }
-fn fetch_b(bin: &mut &[u8]) -> Result<Fetched, Error> {
-    if bin.len() < 2 {
-        return Err(Error::internal("byte code too short"));
-    }
-    let a = bin[1];
+fn fetch_b(bin: &mut &[u8], ext: Ext) -> Result<Fetched, Error> {
+    let mut pos = 1;
+    let a = read_b(bin, &mut pos, ext.a)?;
     let operand = Fetched::B(a);
 
-    *bin = &bin[2..];
+    *bin = &bin[pos..];
     Ok(operand)
 }
-fn fetch_bb(bin: &mut &[u8]) -> Result<Fetched, Error> {
-    if bin.len() < 3 {
-        return Err(Error::internal("byte code too short"));
-    }
-    let a = bin[1];
-    let b = bin[2];
+fn fetch_bb(bin: &mut &[u8], ext: Ext) -> Result<Fetched, Error> {
+    let mut pos = 1;
+    let a = read_b(bin, &mut pos, ext.a)?;
+    let b = read_b(bin, &mut pos, ext.b)?;
     let operand = Fetched::BB(a, b);
 
-    *bin = &bin[3..];
+    *bin = &bin[pos..];
     Ok(operand)
 }
-fn fetch_bbb(bin: &mut &[u8]) -> Result<Fetched, Error> {
-    if bin.len() < 4 {
-        return Err(Error::internal("byte code too short"));
-    }
-    let a = bin[1];
-    let b = bin[2];
-    let c = bin[3];
+fn fetch_bbb(bin: &mut &[u8], ext: Ext) -> Result<Fetched, Error> {
+    let mut pos = 1;
+    let a = read_b(bin, &mut pos, ext.a)?;
+    let b = read_b(bin, &mut pos, ext.b)?;
+    let c = read_b(bin, &mut pos, false)? as u8;
     let operand = Fetched::BBB(a, b, c);
 
-    *bin = &bin[4..];
+    *bin = &bin[pos..];
     Ok(operand)
 }
-fn fetch_bs(bin: &mut &[u8]) -> Result<Fetched, Error> {
-    if bin.len() < 4 {
-        return Err(Error::internal("byte code too short"));
-    }
-    let a = bin[1];
-    let s = ((bin[2] as u16) << 8) | bin[3] as u16;
+fn fetch_bs(bin: &mut &[u8], ext: Ext) -> Result<Fetched, Error> {
+    let mut pos = 1;
+    let a = read_b(bin, &mut pos, ext.a)?;
+    let s = read_s(bin, &mut pos)?;
     let operand = Fetched::BS(a, s);
 
-    *bin = &bin[4..];
+    *bin = &bin[pos..];
     Ok(operand)
 }
-fn fetch_bss(bin: &mut &[u8]) -> Result<Fetched, Error> {
-    if bin.len() < 6 {
-        return Err(Error::internal("byte code too short"));
-    }
-    let a = bin[1];
-    let s1 = ((bin[2] as u16) << 8) | bin[3] as u16;
-    let s2 = ((bin[4] as u16) << 8) | bin[5] as u16;
+fn fetch_bss(bin: &mut &[u8], ext: Ext) -> Result<Fetched, Error> {
+    let mut pos = 1;
+    let a = read_b(bin, &mut pos, ext.a)?;
+    let s1 = read_s(bin, &mut pos)?;
+    let s2 = read_s(bin, &mut pos)?;
     let operand = Fetched::BSS(a, s1, s2);
 
-    *bin = &bin[6..];
+    *bin = &bin[pos..];
     Ok(operand)
 }
-fn fetch_s(bin: &mut &[u8]) -> Result<Fetched, Error> {
-    if bin.len() < 3 {
-        return Err(Error::internal("byte code too short"));
-    }
-    let s = ((bin[1] as u16) << 8) | bin[2] as u16;
+fn fetch_s(bin: &mut &[u8], _ext: Ext) -> Result<Fetched, Error> {
+    let mut pos = 1;
+    let s = read_s(bin, &mut pos)?;
     let operand = Fetched::S(s);
 
-    *bin = &bin[3..];
+    *bin = &bin[pos..];
     Ok(operand)
 }
-fn fetch_w(bin: &mut &[u8]) -> Result<Fetched, Error> {
+fn fetch_w(bin: &mut &[u8], _ext: Ext) -> Result<Fetched, Error> {
     if bin.len() < 4 {
         return Err(Error::internal("byte code too short"));
     }
@@ -534,16 +552,38 @@ fn fetch_w(bin: &mut &[u8]) -> Result<Fetched, Error> {
     Ok(operand)
 }
 
-const Z: fn(&mut &[u8]) -> Result<Fetched, Error> = fetch_z;
-const B: fn(&mut &[u8]) -> Result<Fetched, Error> = fetch_b;
-const BB: fn(&mut &[u8]) -> Result<Fetched, Error> = fetch_bb;
-const BBB: fn(&mut &[u8]) -> Result<Fetched, Error> = fetch_bbb;
-const BS: fn(&mut &[u8]) -> Result<Fetched, Error> = fetch_bs;
-const BSS: fn(&mut &[u8]) -> Result<Fetched, Error> = fetch_bss;
-const S: fn(&mut &[u8]) -> Result<Fetched, Error> = fetch_s;
-const W: fn(&mut &[u8]) -> Result<Fetched, Error> = fetch_w;
+const Z: FetchFn = fetch_z;
+const B: FetchFn = fetch_b;
+const BB: FetchFn = fetch_bb;
+const BBB: FetchFn = fetch_bbb;
+const BS: FetchFn = fetch_bs;
+const BSS: FetchFn = fetch_bss;
+const S: FetchFn = fetch_s;
+const W: FetchFn = fetch_w;
+
+type FetchFn = fn(&mut &[u8], Ext) -> Result<Fetched, Error>;
 
-type FetchFn = fn(&mut &[u8]) -> Result<Fetched, Error>;
+/// Fetches one instruction from the head of `bin`, consuming an
+/// `EXT1`/`EXT2`/`EXT3` prefix together with the instruction it widens.
+/// Returns the opcode, its operands and the consumed length in bytes.
+pub fn fetch(bin: &mut &[u8]) -> Result<(OpCode, Fetched, usize), Error> {
+    let before = bin.len();
+    let first = *bin
+        .first()
+        .ok_or_else(|| Error::internal("byte code too short"))?;
+    let mut code: OpCode = first.try_into()?;
+    let mut ext = Ext::NONE;
+    if let Some(prefix) = Ext::from_prefix(code) {
+        *bin = &bin[1..];
+        let next = *bin
+            .first()
+            .ok_or_else(|| Error::internal("byte code too short"))?;
+        code = next.try_into()?;
+        ext = prefix;
+    }
+    let fetched = FETCH_TABLE[code as usize](bin, ext)?;
+    Ok((code, fetched, before - bin.len()))
+}
 
 pub const FETCH_TABLE: [FetchFn; OpCode::NumberOfOpcode as usize] = [
     Z, BB, BB, BB, BB, B, B, B, B, B, B, B, B, B, BS, BSS, BB, B, B, B, B, BB, BB, BB, BB, BB, BB,
@@ -18,7 +18,7 @@ pub enum PoolValue {
     SStr(CString),   // IREP_TT_SSTR = 2 (static)
     Int64(i64),      // IREP_TT_INT64 = 3
     Float(f64),      // IREP_TT_FLOAT = 5
-    BigInt(Vec<u8>), // IREP_TT_BIGINT = 7 (not yet fully supported)
+    BigInt(Vec<u8>), // IREP_TT_BIGINT = 7 (signed radix byte followed by digits)
 }
 
 #[derive(Debug, Default)]
@@ -39,6 +39,7 @@ pub struct Irep<'a> {
     pub syms: Vec<CString>,
     pub catch_handlers: Vec<CatchHandler>,
     pub lv: Vec<Option<CString>>, // Local variable names (indices into LVar::syms)
+    pub debug_files: Vec<DebugFile>, // Filled by section_debug if present
 }
 
 impl Irep<'_> {
@@ -59,7 +60,10 @@ impl Irep<'_> {
     }
 }
 
-#[derive(Debug)]
+pub const CATCH_TYPE_RESCUE: u8 = 0;
+pub const CATCH_TYPE_ENSURE: u8 = 1;
+
+#[derive(Debug, Clone)]
 pub struct CatchHandler {
     pub type_: u8,
     pub start: usize,
@@ -67,6 +71,26 @@ pub struct CatchHandler {
     pub target: usize,
 }
 
+/// Line table of one source file contributing to an irep (DBG section).
+#[derive(Debug, Clone)]
+pub struct DebugFile {
+    pub start_pos: usize,
+    pub filename: String,
+    /// `(pos, line)` pairs sorted by instruction byte position; each line
+    /// applies from `pos` until the next entry.
+    pub lines: Vec<(usize, usize)>,
+}
+
+impl DebugFile {
+    pub fn line_at(&self, pos: usize) -> Option<usize> {
+        self.lines
+            .iter()
+            .take_while(|(start, _)| *start <= pos)
+            .last()
+            .map(|(_, line)| *line)
+    }
+}
+
 #[derive(Debug)]
 pub struct LVar {
     pub header: SectionMiscHeader,
@@ -112,7 +136,7 @@ pub fn load<'a>(src: &'a [u8]) -> Result<Rite<'a>, Error> {
                 head = &head[cur..];
             }
             DBG => {
-                let cur = section_skip(head)?;
+                let cur = section_debug(head, &mut rite.irep)?;
                 head = &head[cur..];
             }
             END => {
@@ -225,7 +249,7 @@ pub fn section_irep_1(head: &[u8]) -> Result<(usize, SectionIrepHeader, Vec<Irep
                     let data = &head[cur..cur + 8];
                     let mut bytes = [0u8; 8];
                     bytes.copy_from_slice(data);
-                    let intval = i64::from_le_bytes(bytes);
+                    let intval = i64::from_be_bytes(bytes);
                     pool.push(PoolValue::Int64(intval));
                     cur += 8;
                 }
@@ -239,13 +263,13 @@ pub fn section_irep_1(head: &[u8]) -> Result<(usize, SectionIrepHeader, Vec<Irep
                     cur += 8;
                 }
                 7 => {
-                    // IREP_TT_BIGINT: Big integer (not yet fully supported)
-                    let data = &head[cur..cur + 2];
-                    let bigint_len = be16_to_u16([data[0], data[1]]) as usize;
-                    cur += 2;
-                    let bigint_data = head[cur..cur + bigint_len].to_vec();
+                    // IREP_TT_BIGINT: u8 digit count, radix byte (negated for
+                    // negative literals) and the digits as written in source
+                    let bigint_len = head[cur] as usize;
+                    cur += 1;
+                    let bigint_data = head[cur..cur + bigint_len + 1].to_vec();
                     pool.push(PoolValue::BigInt(bigint_data));
-                    cur += bigint_len;
+                    cur += bigint_len + 1;
                 }
                 v => {
                     return Err(Error::UnknownPoolType(v));
@@ -278,6 +302,7 @@ pub fn section_irep_1(head: &[u8]) -> Result<(usize, SectionIrepHeader, Vec<Irep
             syms,
             catch_handlers,
             lv: Vec::new(), // Will be filled by section_lvar if present
+            debug_files: Vec::new(),
         };
         ireps.push(irep);
     }
@@ -371,6 +396,114 @@ fn read_lv_records(
     Ok((cur, child_irep_idx))
 }
 
+const DEBUG_LINE_ARY: u8 = 0;
+const DEBUG_LINE_FLAT_MAP: u8 = 1;
+const DEBUG_LINE_PACKED_MAP: u8 = 2;
+
+pub fn section_debug(head: &[u8], ireps: &mut [Irep]) -> Result<usize, Error> {
+    let header = SectionMiscHeader::from_bytes(head)?;
+    let section_size = be32_to_u32(header.size) as usize;
+    if head.len() < section_size {
+        return Err(Error::TooShort);
+    }
+    let head = &head[..section_size];
+    let mut cur = mem::size_of::<SectionMiscHeader>();
+
+    let filenames_len = read_u16(head, &mut cur)? as usize;
+    let mut filenames = Vec::with_capacity(filenames_len);
+    for _ in 0..filenames_len {
+        let len = read_u16(head, &mut cur)? as usize;
+        let name = head.get(cur..cur + len).ok_or(Error::TooShort)?;
+        filenames.push(String::from_utf8_lossy(name).into_owned());
+        cur += len;
+    }
+
+    // Records are laid out in the same depth-first order as the IREP section
+    for irep in ireps.iter_mut() {
+        let record_start = cur;
+        let record_size = read_u32(head, &mut cur)? as usize;
+        let flen = read_u16(head, &mut cur)? as usize;
+        for _ in 0..flen {
+            let start_pos = read_u32(head, &mut cur)? as usize;
+            let filename_idx = read_u16(head, &mut cur)? as usize;
+            let entry_count = read_u32(head, &mut cur)? as usize;
+            let line_type = *head.get(cur).ok_or(Error::TooShort)?;
+            cur += 1;
+
+            let mut lines = Vec::new();
+            match line_type {
+                DEBUG_LINE_ARY => {
+                    for i in 0..entry_count {
+                        let line = read_u16(head, &mut cur)? as usize;
+                        lines.push((start_pos + i, line));
+                    }
+                }
+                DEBUG_LINE_FLAT_MAP => {
+                    for _ in 0..entry_count {
+                        let pos = read_u32(head, &mut cur)? as usize;
+                        let line = read_u16(head, &mut cur)? as usize;
+                        lines.push((pos, line));
+                    }
+                }
+                DEBUG_LINE_PACKED_MAP => {
+                    let packed = head.get(cur..cur + entry_count).ok_or(Error::TooShort)?;
+                    let mut p = 0;
+                    // Deltas are u32: a line stepping backwards is stored
+                    // as its two's complement and wraps around
+                    let (mut pos, mut line) = (0u32, 0u32);
+                    while p < packed.len() {
+                        pos = pos.wrapping_add(read_packed_int(packed, &mut p));
+                        line = line.wrapping_add(read_packed_int(packed, &mut p));
+                        lines.push((pos as usize, line as usize));
+                    }
+                    cur += entry_count;
+                }
+                _ => return Err(Error::InvalidFormat),
+            }
+
+            let filename = filenames
+                .get(filename_idx)
+                .cloned()
+                .ok_or(Error::InvalidFormat)?;
+            irep.debug_files.push(DebugFile {
+                start_pos,
+                filename,
+                lines,
+            });
+        }
+        cur = record_start + record_size;
+    }
+
+    Ok(section_size)
+}
+
+fn read_u16(head: &[u8], cur: &mut usize) -> Result<u16, Error> {
+    let data = head.get(*cur..*cur + 2).ok_or(Error::TooShort)?;
+    *cur += 2;
+    Ok(be16_to_u16([data[0], data[1]]))
+}
+
+fn read_u32(head: &[u8], cur: &mut usize) -> Result<u32, Error> {
+    let data = head.get(*cur..*cur + 4).ok_or(Error::TooShort)?;
+    *cur += 4;
+    Ok(be32_to_u32([data[0], data[1], data[2], data[3]]))
+}
+
+// Decodes a LEB128-style varint as written by mrb_packed_int_encode
+fn read_packed_int(data: &[u8], p: &mut usize) -> u32 {
+    let mut n = 0u32;
+    let mut shift = 0;
+    while let Some(byte) = data.get(*p) {
+        *p += 1;
+        n |= ((byte & 0x7f) as u32) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 || shift >= 32 {
+            break;
+        }
+    }
+    n
+}
+
 pub fn section_skip(head: &[u8]) -> Result<usize, Error> {
     let header = SectionMiscHeader::from_bytes(head)?;
     // eprintln!("skipped section {:?}", header.ident.as_ascii());
@@ -0,0 +1,472 @@
+//! Arbitrary-precision integers backing Ruby's Bignum range.
+//! Values are sign-magnitude with little-endian 32-bit limbs; zero has no limbs.
+
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct RBigInt {
+    negative: bool,
+    mag: Vec<u32>,
+}
+
+impl RBigInt {
+    pub fn zero() -> Self {
+        RBigInt::default()
+    }
+
+    fn from_parts(negative: bool, mut mag: Vec<u32>) -> Self {
+        while mag.last() == Some(&0) {
+            mag.pop();
+        }
+        let negative = negative && !mag.is_empty();
+        RBigInt { negative, mag }
+    }
+
+    pub fn from_i64(n: i64) -> Self {
+        Self::from_i128(n as i128)
+    }
+
+    pub fn from_i128(n: i128) -> Self {
+        let mut abs = n.unsigned_abs();
+        let mut mag = Vec::new();
+        while abs > 0 {
+            mag.push(abs as u32);
+            abs >>= 32;
+        }
+        Self::from_parts(n < 0, mag)
+    }
+
+    /// Truncates toward zero like `Float#to_i`. Returns `None` for NaN and infinities.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+        let negative = f < 0.0;
+        let mut abs = f.abs().trunc();
+        let mut mag = Vec::new();
+        while abs >= 1.0 {
+            let limb = abs % 4294967296.0;
+            mag.push(limb as u32);
+            abs = ((abs - limb) / 4294967296.0).trunc();
+        }
+        Some(Self::from_parts(negative, mag))
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.mag.len() > 2 {
+            return None;
+        }
+        let abs = self
+            .mag
+            .iter()
+            .rev()
+            .fold(0u64, |acc, &limb| (acc << 32) | limb as u64);
+        if self.negative {
+            if abs <= i64::MAX as u64 + 1 {
+                Some((abs as i64).wrapping_neg())
+            } else {
+                None
+            }
+        } else {
+            i64::try_from(abs).ok()
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let abs = self
+            .mag
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &limb| acc * 4294967296.0 + limb as f64);
+        if self.negative { -abs } else { abs }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mag.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn neg(&self) -> Self {
+        Self::from_parts(!self.negative, self.mag.clone())
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::from_parts(false, self.mag.clone())
+    }
+
+    /// Number of significant bits of the magnitude
+    pub fn bit_length(&self) -> u64 {
+        match self.mag.last() {
+            Some(top) => (self.mag.len() as u64 - 1) * 32 + (32 - top.leading_zeros() as u64),
+            None => 0,
+        }
+    }
+
+    /// Parses digits in `radix` (2..=36), with an optional sign and `_` separators.
+    pub fn parse(s: &str, radix: u32) -> Option<Self> {
+        let (negative, digits) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+        let mut mag = Vec::new();
+        for ch in digits.chars() {
+            if ch == '_' {
+                continue;
+            }
+            let digit = ch.to_digit(radix)?;
+            mag_mul_small_add(&mut mag, radix, digit);
+        }
+        Some(Self::from_parts(negative, mag))
+    }
+
+    pub fn to_string_radix(&self, radix: u32) -> String {
+        assert!((2..=36).contains(&radix), "invalid radix");
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        // Peel off as many digits per division as fit in a limb
+        let mut chunk = radix;
+        let mut chunk_digits = 1;
+        while let Some(next) = chunk.checked_mul(radix) {
+            chunk = next;
+            chunk_digits += 1;
+        }
+        let mut mag = self.mag.clone();
+        let mut digits = Vec::new();
+        while !mag.is_empty() {
+            let mut rem = mag_divrem_small(&mut mag, chunk);
+            for _ in 0..chunk_digits {
+                if mag.is_empty() && rem == 0 {
+                    break;
+                }
+                digits.push(std::char::from_digit(rem % radix, radix).unwrap());
+                rem /= radix;
+            }
+        }
+        if self.negative {
+            digits.push('-');
+        }
+        digits.iter().rev().collect()
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        if self.negative == other.negative {
+            return Self::from_parts(self.negative, mag_add(&self.mag, &other.mag));
+        }
+        match mag_cmp(&self.mag, &other.mag) {
+            Ordering::Less => Self::from_parts(other.negative, mag_sub(&other.mag, &self.mag)),
+            _ => Self::from_parts(self.negative, mag_sub(&self.mag, &other.mag)),
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        Self::from_parts(
+            self.negative != other.negative,
+            mag_mul(&self.mag, &other.mag),
+        )
+    }
+
+    /// Floored division as in Ruby's `Integer#divmod`. `None` when dividing by zero.
+    pub fn divmod(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (q, r) = mag_divrem(&self.mag, &other.mag);
+        let mut q = Self::from_parts(self.negative != other.negative, q);
+        let mut r = Self::from_parts(self.negative, r);
+        if !r.is_zero() && r.negative != other.negative {
+            q = q.sub(&Self::from_i64(1));
+            r = r.add(other);
+        }
+        Some((q, r))
+    }
+
+    pub fn pow(&self, mut exp: u64) -> Self {
+        let mut base = self.clone();
+        let mut result = Self::from_i64(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul(&base);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base);
+            }
+        }
+        result
+    }
+
+    pub fn shl(&self, bits: u64) -> Self {
+        if self.is_zero() {
+            return self.clone();
+        }
+        let limbs = (bits / 32) as usize;
+        let shift = (bits % 32) as u32;
+        let mut mag = vec![0; limbs];
+        let mut carry = 0u32;
+        for &limb in self.mag.iter() {
+            if shift == 0 {
+                mag.push(limb);
+            } else {
+                mag.push((limb << shift) | carry);
+                carry = limb >> (32 - shift);
+            }
+        }
+        if carry != 0 {
+            mag.push(carry);
+        }
+        Self::from_parts(self.negative, mag)
+    }
+
+    /// Arithmetic shift: negative values round toward negative infinity
+    pub fn shr(&self, bits: u64) -> Self {
+        if self.negative {
+            let one = Self::from_i64(1);
+            return self.add(&one).neg().shr(bits).neg().sub(&one);
+        }
+        let limbs = (bits / 32) as usize;
+        if limbs >= self.mag.len() {
+            return Self::zero();
+        }
+        let shift = (bits % 32) as u32;
+        let src = &self.mag[limbs..];
+        let mut mag = Vec::with_capacity(src.len());
+        for (i, &limb) in src.iter().enumerate() {
+            if shift == 0 {
+                mag.push(limb);
+            } else {
+                let high = src.get(i + 1).map_or(0, |next| next << (32 - shift));
+                mag.push((limb >> shift) | high);
+            }
+        }
+        Self::from_parts(false, mag)
+    }
+
+    pub fn bitand(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a & b)
+    }
+
+    pub fn bitor(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a | b)
+    }
+
+    pub fn bitxor(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+
+    /// `~n`, i.e. `-n - 1`
+    pub fn not(&self) -> Self {
+        self.neg().sub(&Self::from_i64(1))
+    }
+
+    /// Applies `op` limb by limb over infinite two's complement representations
+    fn bitwise(&self, other: &Self, op: impl Fn(u32, u32) -> u32) -> Self {
+        let len = self.mag.len().max(other.mag.len()) + 1;
+        let lhs = self.to_twos_complement(len);
+        let rhs = other.to_twos_complement(len);
+        let limbs: Vec<u32> = lhs
+            .iter()
+            .zip(rhs.iter())
+            .map(|(&a, &b)| op(a, b))
+            .collect();
+        Self::from_twos_complement(limbs)
+    }
+
+    fn to_twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut limbs = self.mag.clone();
+        limbs.resize(len, 0);
+        if self.negative {
+            let mut carry = true;
+            for limb in limbs.iter_mut() {
+                let (v, c) = (!*limb).overflowing_add(carry as u32);
+                *limb = v;
+                carry = c;
+            }
+        }
+        limbs
+    }
+
+    fn from_twos_complement(mut limbs: Vec<u32>) -> Self {
+        let negative = limbs.last().is_some_and(|top| top & 0x8000_0000 != 0);
+        if negative {
+            let mut carry = true;
+            for limb in limbs.iter_mut() {
+                let (v, c) = (!*limb).overflowing_add(carry as u32);
+                *limb = v;
+                carry = c;
+            }
+        }
+        Self::from_parts(negative, limbs)
+    }
+}
+
+impl Ord for RBigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => mag_cmp(&self.mag, &other.mag),
+            (true, true) => mag_cmp(&other.mag, &self.mag),
+        }
+    }
+}
+
+impl PartialOrd for RBigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for RBigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_string_radix(10))
+    }
+}
+
+fn mag_cmp(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn mag_add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let sum = limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry != 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+/// `a - b` where `a >= b`
+fn mag_sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut diff = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        }
+        result.push(diff as u32);
+    }
+    result
+}
+
+fn mag_mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let cur = result[i + j] as u64 + x as u64 * y as u64 + carry;
+            result[i + j] = cur as u32;
+            carry = cur >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    result
+}
+
+fn mag_mul_small_add(mag: &mut Vec<u32>, mul: u32, add: u32) {
+    let mut carry = add as u64;
+    for limb in mag.iter_mut() {
+        let cur = *limb as u64 * mul as u64 + carry;
+        *limb = cur as u32;
+        carry = cur >> 32;
+    }
+    if carry != 0 {
+        mag.push(carry as u32);
+    }
+}
+
+/// Divides in place by a single limb and returns the remainder
+fn mag_divrem_small(mag: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut rem = 0u64;
+    for limb in mag.iter_mut().rev() {
+        let cur = (rem << 32) | *limb as u64;
+        *limb = (cur / divisor as u64) as u32;
+        rem = cur % divisor as u64;
+    }
+    while mag.last() == Some(&0) {
+        mag.pop();
+    }
+    rem as u32
+}
+
+/// Truncated division of magnitudes; `b` must be non-zero
+fn mag_divrem(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if mag_cmp(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let mut q = a.to_vec();
+        let r = mag_divrem_small(&mut q, b[0]);
+        return (q, if r == 0 { Vec::new() } else { vec![r] });
+    }
+    // Binary long division; divisors this wide are rare in practice
+    let mut q = vec![0u32; a.len()];
+    let mut r: Vec<u32> = Vec::new();
+    for i in (0..a.len() * 32).rev() {
+        r = RBigInt::from_parts(false, r).shl(1).mag;
+        if (a[i / 32] >> (i % 32)) & 1 == 1 {
+            if r.is_empty() {
+                r.push(1);
+            } else {
+                r[0] |= 1;
+            }
+        }
+        if mag_cmp(&r, b) != Ordering::Less {
+            r = mag_sub(&r, b);
+            while r.last() == Some(&0) {
+                r.pop();
+            }
+            q[i / 32] |= 1 << (i % 32);
+        }
+    }
+    (q, r)
+}
+
+#[test]
+fn test_bigint_roundtrip() {
+    let n = RBigInt::parse("-123456789012345678901234567890", 10).unwrap();
+    assert_eq!(n.to_string(), "-123456789012345678901234567890");
+    assert_eq!(n.to_string_radix(16), "-18ee90ff6c373e0ee4e3f0ad2");
+    assert_eq!(RBigInt::from_i64(i64::MIN).to_i64(), Some(i64::MIN));
+    assert_eq!(
+        RBigInt::from_i64(i64::MAX)
+            .add(&RBigInt::from_i64(1))
+            .to_i64(),
+        None
+    );
+}
+
+#[test]
+fn test_bigint_divmod_floors() {
+    let a = RBigInt::parse("100000000000000000000000", 10).unwrap();
+    let b = RBigInt::parse("-30000000000000000000", 10).unwrap();
+    let (q, r) = a.divmod(&b).unwrap();
+    assert_eq!(q.to_string(), "-3334");
+    assert_eq!(r.to_string(), "-20000000000000000000");
+    assert_eq!(q.mul(&b).add(&r), a);
+}
@@ -1,10 +1,12 @@
+use std::mem;
 use std::rc::Rc;
 
 use crate::{Error, yamrb::vm::Breadcrumb};
 
 use super::{
+    method_cache::invalidate_method_caches,
     optable::push_callinfo,
-    value::{RClass, RFn, RModule, RObject, RProc, RSym, RValue, resolve_method},
+    value::{RClass, RFn, RModule, RObject, RProc, RSym, RValue, Visibility},
     vm::VM,
 };
 
@@ -14,13 +16,28 @@ fn call_block(
     recv: Rc<RObject>,
     args: &[Rc<RObject>],
     method_info: Option<(RSym, Rc<RModule>)>,
+    block_arg: Option<Rc<RObject>>,
     return_register: usize,
 ) -> Result<Rc<RObject>, Error> {
+    // Blocks run in their method's frame as far as `$~` goes; only method
+    // calls save and clear it
+    let is_block = method_info.is_none();
     let (method_id, method_owner) = match method_info {
         Some((id, owner)) => (id, Some(owner)),
         None => (RSym::new("<block>".to_string()), None),
     };
+    let irep = block
+        .irep
+        .clone()
+        .ok_or_else(|| Error::RuntimeError("No IREP".to_string()))?;
+    vm.enter_native_depth()?;
     push_callinfo(vm, method_id, args.len(), method_owner, return_register);
+    if let Some(ci) = vm.current_callinfo.as_ref() {
+        ci.has_block.set(block_arg.is_some());
+        if is_block {
+            vm.last_match = ci.last_match.clone();
+        }
+    }
 
     let old_callinfo = vm.current_callinfo.take();
 
@@ -33,22 +50,33 @@ fn call_block(
         let old = vm.current_regs()[i + 1].replace(arg.clone());
         prev_args.push(old);
     }
+    // A method's block sits right after its arguments, as op_send places it
+    let prev_block = block_arg.map(|block| vm.current_regs()[args.len() + 1].replace(block));
 
     vm.pc.set(0);
-    vm.current_irep = block
-        .irep
-        .as_ref()
-        .ok_or_else(|| Error::RuntimeError("No IREP".to_string()))?
-        .clone();
-    vm.upper = block.environ;
+    vm.current_irep = irep;
+    let prev_upper = mem::replace(&mut vm.upper, block.environ);
 
+    if let Some(ci) = old_callinfo.as_ref() {
+        vm.outer_callinfo.push(ci.clone());
+    }
     let res = vm.run_internal();
+    vm.leave_native_depth();
+    if old_callinfo.is_some() {
+        vm.outer_callinfo.pop();
+    }
 
     if let Some(prev) = prev_self {
         vm.current_regs()[0].replace(prev);
     } else {
         vm.current_regs()[0].take();
     }
+    if let Some(prev) = prev_block {
+        match prev {
+            Some(prev) => vm.current_regs()[args.len() + 1].replace(prev),
+            None => vm.current_regs()[args.len() + 1].take(),
+        };
+    }
     for (i, prev_arg) in prev_args.into_iter().enumerate() {
         if let Some(prev) = prev_arg {
             vm.current_regs()[i + 1].replace(prev);
@@ -65,12 +93,15 @@ fn call_block(
         vm.pc.set(ci.pc);
         vm.current_regs_offset = ci.current_regs_offset;
         vm.target_class = ci.target_class.clone();
+        if !is_block {
+            vm.last_match = ci.last_match.clone();
+        }
+        vm.current_exception = ci.current_exception.clone();
+        vm.handler_clauses = ci.handler_clauses.clone();
     }
-    if let Some(upper) = vm.upper.take()
-        && let Some(upper) = &upper.as_ref().upper
-    {
-        vm.upper.replace(upper.clone());
-    }
+    // The block may come from another frame than the caller's,
+    // e.g. one stored by an Enumerator::Lazy
+    vm.upper = prev_upper;
 
     match &res {
         Ok(res) => Ok(res.clone()),
@@ -125,12 +156,18 @@ pub fn mrb_call_block(
     });
     vm.current_breadcrumb.replace(new_breadcrumb);
     let res = if block.is_rb_func {
-        call_block(vm, block, recv, args, None, return_register)
+        call_block(vm, block, recv, args, None, None, return_register)
     } else if block.is_fnblock {
         let func = vm.pop_fnblock()?;
         let res = func(vm, args);
         vm.push_fnblock(func)?;
         res
+    } else if let Some(func) = block.func {
+        // Native procs such as Symbol#to_proc
+        let func = vm
+            .get_fn(func)
+            .ok_or_else(|| Error::internal("native proc function not found"))?;
+        func(vm, args)
     } else {
         Err(Error::RuntimeError(
             "Cannot call non-block RProc".to_string(),
@@ -162,13 +199,35 @@ pub fn mrb_funcall(
     top_self: Option<Rc<RObject>>,
     name: &str,
     args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    mrb_funcall_with_block(vm, top_self, name, args, None)
+}
+
+/// Calls a method on an object by name, passing a block separately from the arguments.
+///
+/// Native methods receive the block as their last argument, Ruby methods
+/// receive it as their block, so that `yield` and `block_given?` work.
+///
+/// # Arguments
+///
+/// * `vm` - The virtual machine instance
+/// * `top_self` - Optional receiver object. If None, uses the "top self"
+/// * `name` - The name of the method to call
+/// * `args` - Array of arguments to pass to the method
+/// * `block` - Optional block (Proc) to pass to the method
+pub fn mrb_funcall_with_block(
+    vm: &mut VM,
+    top_self: Option<Rc<RObject>>,
+    name: &str,
+    args: &[Rc<RObject>],
+    block: Option<Rc<RObject>>,
 ) -> Result<Rc<RObject>, Error> {
     let recv: Rc<RObject> = match &top_self {
         Some(obj) => obj.clone(),
         None => vm.getself()?,
     };
     let binding = recv.singleton_or_this_class(vm);
-    let (owner_module, method) = match resolve_method(&binding, name) {
+    let (owner_module, method) = match vm.method_cache.resolve(&binding, name) {
         Some((owner, method)) => (owner, method),
         None => {
             if name == "method_missing" {
@@ -179,7 +238,7 @@ pub fn mrb_funcall(
 
             let mut mm_args = vec![Rc::new(RObject::symbol(RSym::new(name.to_string())))];
             mm_args.extend_from_slice(args);
-            return mrb_funcall(vm, top_self, "method_missing", &mm_args);
+            return mrb_funcall_with_block(vm, top_self, "method_missing", &mm_args, block);
         }
     };
 
@@ -203,12 +262,20 @@ pub fn mrb_funcall(
             recv.clone(),
             args,
             Some((method_id, owner_module)),
+            block,
             0, // unused
         )
     } else {
         let prev = vm.current_regs()[0].replace(recv.clone());
         let func = vm.fn_table.get(method.func.unwrap()).unwrap();
-        let res = func(vm, args);
+        let res = match block {
+            Some(block) => {
+                let mut args = args.to_vec();
+                args.push(block);
+                func(vm, &args)
+            }
+            None => func(vm, args),
+        };
         if let Some(prev) = prev {
             vm.current_regs()[0].replace(prev);
         } else {
@@ -227,7 +294,9 @@ pub fn mrb_funcall(
 
 pub fn mrb_call_inspect(vm: &mut VM, recv: Rc<RObject>) -> Result<Rc<RObject>, Error> {
     let binding = recv.get_class(vm);
-    let (owner_module, method) = resolve_method(&binding, "inspect")
+    let (owner_module, method) = vm
+        .method_cache
+        .resolve(&binding, "inspect")
         .ok_or_else(|| Error::NoMethodError("inspect".to_string()))?;
     if method.is_rb_func {
         let method_id = method
@@ -240,6 +309,7 @@ pub fn mrb_call_inspect(vm: &mut VM, recv: Rc<RObject>) -> Result<Rc<RObject>, E
             recv.clone(),
             &[],
             Some((method_id, owner_module)),
+            None,
             0, // unused
         )
     } else {
@@ -283,9 +353,11 @@ pub fn mrb_define_cmethod(vm: &mut VM, klass: Rc<RClass>, name: &str, cmethod: R
         func: Some(index),
         environ: None,
         block_self: None,
+        visibility: Visibility::Public,
     };
     let mut procs = klass.procs.borrow_mut();
     procs.insert(name.to_string(), method);
+    invalidate_method_caches();
 }
 
 /// Defines a Ruby method (RProc) on a Ruby class.
@@ -299,6 +371,7 @@ pub fn mrb_define_cmethod(vm: &mut VM, klass: Rc<RClass>, name: &str, cmethod: R
 pub fn mrb_define_method(_vm: &mut VM, klass: Rc<RClass>, name: &str, method: RProc) {
     let mut procs = klass.procs.borrow_mut();
     procs.insert(name.to_string(), method);
+    invalidate_method_caches();
 }
 
 pub fn mrb_define_class_cmethod(vm: &mut VM, klass: Rc<RClass>, name: &str, cmethod: RFn) {
@@ -312,10 +385,12 @@ pub fn mrb_define_class_cmethod(vm: &mut VM, klass: Rc<RClass>, name: &str, cmet
         func: Some(index),
         environ: None,
         block_self: None,
+        visibility: Visibility::Public,
     };
     let klass_singleton = RObject::class_singleton(klass, vm);
     let mut procs = klass_singleton.procs.borrow_mut();
     procs.insert(name.to_string(), method);
+    invalidate_method_caches();
 }
 
 /// Defines a singleton C method (native Rust function) on a specific Ruby object.
@@ -339,10 +414,12 @@ pub fn mrb_define_singleton_cmethod(vm: &mut VM, dest: Rc<RObject>, name: &str,
         func: Some(index),
         environ: None,
         block_self: None,
+        visibility: Visibility::Public,
     };
     let klass = dest.initialize_or_get_singleton_class(vm);
     let mut procs = klass.procs.borrow_mut();
     procs.insert(name.to_string(), method);
+    invalidate_method_caches();
 }
 
 /// Defines a singleton Ruby method (RProc) on a specific Ruby object.
@@ -359,6 +436,7 @@ pub fn mrb_define_singleton_method(vm: &mut VM, dest: Rc<RObject>, name: &str, m
     let klass = dest.initialize_or_get_singleton_class(vm);
     let mut procs = klass.procs.borrow_mut();
     procs.insert(name.to_string(), method);
+    invalidate_method_caches();
 }
 
 /// Defines a C method (native Rust function) on a Ruby module.
@@ -380,9 +458,11 @@ pub fn mrb_define_module_cmethod(vm: &mut VM, module: Rc<RModule>, name: &str, c
         func: Some(index),
         environ: None,
         block_self: None,
+        visibility: Visibility::Public,
     };
     let mut procs = module.procs.borrow_mut();
     procs.insert(name.to_string(), method);
+    invalidate_method_caches();
 }
 
 /// Defines a Ruby method (RProc) on a Ruby module.
@@ -396,6 +476,7 @@ pub fn mrb_define_module_cmethod(vm: &mut VM, module: Rc<RModule>, name: &str, c
 pub fn mrb_define_module_method(_vm: &mut VM, module: Rc<RModule>, name: &str, method: RProc) {
     let mut procs = module.procs.borrow_mut();
     procs.insert(name.to_string(), method);
+    invalidate_method_caches();
 }
 
 #[test]
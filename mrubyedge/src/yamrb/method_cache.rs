@@ -0,0 +1,99 @@
+//! Method lookup caches for SEND dispatch.
+//! Resolving a method walks the whole ancestor chain, so results are kept
+//! in a per-VM table and in a one-entry cache on each send instruction.
+//! Both are discarded whenever the method state changes.
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use super::value::{RClass, RHashMap, RModule, RProc, resolve_method};
+
+thread_local! {
+    static METHOD_STATE: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Generation number of all method tables in this thread.
+pub fn method_state() -> u64 {
+    METHOD_STATE.with(|s| s.get())
+}
+
+/// Drops every cached method lookup. Must be called after anything that can
+/// change method resolution: defining, aliasing or removing a method,
+/// including or extending a module, and creating a singleton class.
+pub fn invalidate_method_caches() {
+    METHOD_STATE.with(|s| s.set(s.get().wrapping_add(1)));
+}
+
+pub type ResolvedMethod = Option<(Rc<RModule>, RProc)>;
+
+/// Per-VM cache from a class and method name to the resolved method.
+/// Misses are cached too, so repeated `method_missing` dispatch stays cheap.
+#[derive(Debug, Default)]
+pub struct MethodCache {
+    state: u64,
+    // Entries are keyed by class address; the Weak keeps that address from
+    // being reused by another class while the entry is alive.
+    entries: RHashMap<usize, (Weak<RClass>, RHashMap<String, ResolvedMethod>)>,
+}
+
+impl MethodCache {
+    pub fn resolve(&mut self, klass: &Rc<RClass>, name: &str) -> ResolvedMethod {
+        let state = method_state();
+        if self.state != state {
+            self.entries.clear();
+            self.state = state;
+        }
+        let (_, methods) = self
+            .entries
+            .entry(Rc::as_ptr(klass) as usize)
+            .or_insert_with(|| (Rc::downgrade(klass), RHashMap::default()));
+        if let Some(found) = methods.get(name) {
+            return found.clone();
+        }
+        let found = resolve_method(klass, name);
+        methods.insert(name.to_string(), found.clone());
+        found
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[derive(Debug, Clone)]
+struct InlineCacheEntry {
+    state: u64,
+    class: Weak<RClass>,
+    found: ResolvedMethod,
+}
+
+/// Monomorphic caches for the send instructions of one IREP, indexed by
+/// instruction position. Slots are allocated on first use.
+#[derive(Debug, Clone, Default)]
+pub struct InlineCaches {
+    slots: RefCell<Vec<Option<InlineCacheEntry>>>,
+}
+
+impl InlineCaches {
+    /// Returns the cached lookup when `klass` matches the receiver class
+    /// last seen at `pos` and no method table has changed since.
+    pub fn get(&self, pos: usize, klass: &Rc<RClass>) -> Option<ResolvedMethod> {
+        let slots = self.slots.borrow();
+        let entry = slots.get(pos)?.as_ref()?;
+        if entry.state != method_state() || entry.class.as_ptr() != Rc::as_ptr(klass) {
+            return None;
+        }
+        Some(entry.found.clone())
+    }
+
+    pub fn set(&self, pos: usize, klass: &Rc<RClass>, found: ResolvedMethod) {
+        let mut slots = self.slots.borrow_mut();
+        if slots.len() <= pos {
+            slots.resize(pos + 1, None);
+        }
+        slots[pos] = Some(InlineCacheEntry {
+            state: method_state(),
+            class: Rc::downgrade(klass),
+            found,
+        });
+    }
+}
@@ -1,9 +1,12 @@
 //! Yet Another mruby (yamrb) runtime layer.
 //! Provides value representation, opcode tables, helpers, and the VM itself
 //! so mruby bytecode can execute inside Rust.
+pub mod bigint;
 pub mod helpers;
+pub mod method_cache;
 pub mod op;
 pub mod optable;
+pub mod ordered_hash;
 pub mod shared_memory;
 pub mod value;
 pub mod vm;
@@ -3,14 +3,18 @@ use std::cell::RefCell;
 
 #[cfg(feature = "mrubyedge-debug")]
 use std::env;
+use std::mem;
 use std::rc::Rc;
 
 use crate::Error;
 use crate::rite::insn::{Fetched, OpCode};
+use crate::rite::{CATCH_TYPE_ENSURE, CATCH_TYPE_RESCUE, CatchHandler};
 use crate::yamrb::helpers::mrb_call_inspect;
 
+use super::method_cache::invalidate_method_caches;
 use super::prelude::hash::mrb_hash_delete;
-use super::prelude::object::mrb_object_is_equal;
+use super::prelude::integer::{integer_add, integer_divmod, integer_mul, integer_sub};
+use super::prelude::object::{mrb_is_a, mrb_object_is_equal};
 use super::value::RHashMap;
 use super::{helpers::mrb_funcall, value::*, vm::*};
 
@@ -143,6 +147,9 @@ const ENTER_K_MASK: u32 = 0b11111 << 2;
 const ENTER_D_MASK: u32 = 0b1 << 1;
 const ENTER_B_MASK: u32 = 0b1 << 0;
 
+// n or k of a send-like op meaning the arguments are packed in one register
+const CALL_MAXARGS: usize = 15;
+
 pub(crate) fn consume_expr(
     vm: &mut VM,
     code: OpCode,
@@ -221,24 +228,24 @@ pub(crate) fn consume_expr(
         SETGV => {
             op_setgv(vm, operand)?;
         }
-        // GETSV => {
-        //     // op_getsv(vm, &operand)?;
-        // }
-        // SETSV => {
-        //     // op_setsv(vm, &operand)?;
-        // }
+        GETSV => {
+            op_getsv(vm, operand)?;
+        }
+        SETSV => {
+            op_setsv(vm, operand)?;
+        }
         GETIV => {
             op_getiv(vm, operand)?;
         }
         SETIV => {
             op_setiv(vm, operand)?;
         }
-        // GETCV => {
-        //     // op_getcv(vm, &operand)?;
-        // }
-        // SETCV => {
-        //     // op_setcv(vm, &operand)?;
-        // }
+        GETCV => {
+            op_getcv(vm, operand)?;
+        }
+        SETCV => {
+            op_setcv(vm, operand)?;
+        }
         GETCONST => {
             op_getconst(vm, operand)?;
         }
@@ -248,9 +255,9 @@ pub(crate) fn consume_expr(
         GETMCNST => {
             op_getmcnst(vm, operand)?;
         }
-        // SETMCNST => {
-        //     // op_setmcnst(vm, &operand)?;
-        // }
+        SETMCNST => {
+            op_setmcnst(vm, operand)?;
+        }
         GETUPVAR => {
             op_getupvar(vm, operand)?;
         }
@@ -305,9 +312,9 @@ pub(crate) fn consume_expr(
         SUPER => {
             op_super(vm, operand)?;
         }
-        // ARGARY => {
-        //     // op_argary(vm, &operand)?;
-        // }
+        ARGARY => {
+            op_argary(vm, operand)?;
+        }
         ENTER => {
             op_enter(vm, operand)?;
         }
@@ -374,24 +381,24 @@ pub(crate) fn consume_expr(
         ARYCAT => {
             op_arycat(vm, operand)?;
         }
-        // ARYPUSH => {
-        //     // op_arypush(vm, &operand)?;
-        // }
-        // ARYSPLAT => {
-        //     // op_arysplat(vm, &operand)?;
-        // }
+        ARYPUSH => {
+            op_arypush(vm, operand)?;
+        }
+        ARYSPLAT => {
+            op_arysplat(vm, operand)?;
+        }
         AREF => {
             op_aref(vm, operand)?;
         }
-        // ASET => {
-        //     // op_aset(vm, &operand)?;
-        // }
+        ASET => {
+            op_aset(vm, operand)?;
+        }
         APOST => {
             op_apost(vm, operand)?;
         }
-        // INTERN => {
-        //     // op_intern(vm, &operand)?;
-        // }
+        INTERN => {
+            op_intern(vm, operand)?;
+        }
         SYMBOL => {
             op_symbol(vm, operand)?;
         }
@@ -404,12 +411,12 @@ pub(crate) fn consume_expr(
         HASH => {
             op_hash(vm, operand)?;
         }
-        // HASHADD => {
-        //     // op_hashadd(vm, &operand)?;
-        // }
-        // HASHCAT => {
-        //     // op_hashcat(vm, &operand)?;
-        // }
+        HASHADD => {
+            op_hashadd(vm, operand)?;
+        }
+        HASHCAT => {
+            op_hashcat(vm, operand)?;
+        }
         LAMBDA => {
             op_lambda(vm, operand)?;
         }
@@ -458,15 +465,10 @@ pub(crate) fn consume_expr(
         // ERR => {
         //     // op_err(vm, &operand)?;
         // }
-        // EXT1 => {
-        //     // op_ext1(vm, &operand)?;
-        // }
-        // EXT2 => {
-        //     // op_ext2(vm, &operand)?;
-        // }
-        // EXT3 => {
-        //     // op_ext3(vm, &operand)?;
-        // }
+        EXT1 | EXT2 | EXT3 => {
+            // Prefixes are folded into the widened op by insn::fetch
+            return Err(Error::internal("unexpected operand extension prefix"));
+        }
         STOP => {
             op_stop(vm, operand)?;
         }
@@ -495,6 +497,12 @@ pub(crate) fn push_callinfo(
         target_class: vm.target_class.clone(),
         method_owner,
         has_block: Cell::new(false),
+        last_match: vm.last_match.take(),
+        current_exception: vm.current_exception.take(),
+        handler_clauses: mem::take(&mut vm.handler_clauses),
+        upper: vm.upper.clone(),
+        visibility: Cell::new(Visibility::Public),
+        module_function: Cell::new(false),
     };
     vm.current_callinfo = Some(Rc::new(callinfo));
 }
@@ -514,6 +522,10 @@ pub(crate) fn pop_callinfo(vm: &mut VM) {
     vm.pc.set(ci.pc);
     vm.current_regs_offset = ci.current_regs_offset;
     vm.target_class = ci.target_class.clone();
+    vm.last_match = ci.last_match.clone();
+    vm.current_exception = ci.current_exception.clone();
+    vm.handler_clauses = ci.handler_clauses.clone();
+    vm.upper = ci.upper.clone();
 }
 
 fn calcurate_pc(irep: &IREP, pc: usize, original_pc: usize) -> usize {
@@ -546,6 +558,7 @@ pub(crate) fn op_loadl(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let val = match pool_val {
         RPool::Str(s) => Rc::new(RObject::string(s)),
         RPool::Int(i) => Rc::new(RObject::integer(i)),
+        RPool::BigInt(n) => Rc::new(RObject::bigint(n)),
         RPool::Float(f) => Rc::new(RObject::float(f)),
         RPool::Data(_) => {
             return Err(Error::Internal(
@@ -559,14 +572,15 @@ pub(crate) fn op_loadl(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 
 pub(crate) fn op_loadi16(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bs()?;
-    let val = RObject::integer(b as i64);
+    // The operand is a signed 16-bit immediate
+    let val = RObject::integer(b as i16 as i64);
     vm.current_regs()[a as usize].replace(Rc::new(val));
     Ok(())
 }
 
 pub(crate) fn op_loadi32(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b, c) = operand.as_bss()?;
-    let val = RObject::integer((b as i64) << 16 | c as i64);
+    let val = RObject::integer(((b as u32) << 16 | c as u32) as i32 as i64);
     vm.current_regs()[a as usize].replace(Rc::new(val));
     Ok(())
 }
@@ -588,7 +602,8 @@ pub(crate) fn op_loadineg(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 pub(crate) fn op_loadsym(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let val = vm.current_irep.syms[b as usize].clone();
-    vm.current_regs()[a as usize].replace(Rc::new(RObject::symbol(val)));
+    let val = vm.intern(&val.name);
+    vm.current_regs()[a as usize].replace(val);
     Ok(())
 }
 
@@ -623,6 +638,19 @@ pub(crate) fn op_loadf(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 pub(crate) fn op_getgv(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let val = vm.current_irep.syms[b as usize].clone();
+    // mrbc emits GETGV for $~, $1 etc., which are frame-local in Ruby
+    if is_special_var(&val.name) {
+        return op_getsv(vm, operand);
+    }
+    // $! is the exception being handled in the current frame
+    if val.name == "$!" {
+        let val = vm
+            .current_exception
+            .clone()
+            .unwrap_or_else(|| Rc::new(RObject::nil()));
+        vm.current_regs()[a as usize].replace(val);
+        return Ok(());
+    }
     let val = vm
         .globals
         .get(&val.name)
@@ -636,10 +664,47 @@ pub(crate) fn op_setgv(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let val = vm.get_current_regs_cloned(a as usize)?;
     let sym = vm.current_irep.syms[b as usize].clone();
+    if is_special_var(&sym.name) {
+        return op_setsv(vm, operand);
+    }
     vm.globals.insert(sym.name.clone(), val);
     Ok(())
 }
 
+pub(crate) fn op_getsv(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let (a, b) = operand.as_bb()?;
+    let name = vm.current_irep.syms[b as usize].name.clone();
+    #[cfg(feature = "mruby-regexp")]
+    let val = super::prelude::regexp::mrb_regexp_special_var_get(vm, &name)?;
+    #[cfg(not(feature = "mruby-regexp"))]
+    let val = {
+        let _ = name;
+        Rc::new(RObject::nil())
+    };
+    vm.current_regs()[a as usize].replace(val);
+    Ok(())
+}
+
+pub(crate) fn op_setsv(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let (a, b) = operand.as_bb()?;
+    let val = vm.get_current_regs_cloned(a as usize)?;
+    let name = vm.current_irep.syms[b as usize].name.clone();
+    #[cfg(feature = "mruby-regexp")]
+    super::prelude::regexp::mrb_regexp_special_var_set(vm, &name, val)?;
+    #[cfg(not(feature = "mruby-regexp"))]
+    let _ = (name, val);
+    Ok(())
+}
+
+fn is_special_var(name: &str) -> bool {
+    match name {
+        "$~" | "$&" | "$`" | "$'" => true,
+        _ => {
+            name.len() > 1 && name.starts_with('$') && name[1..].bytes().all(|b| b.is_ascii_digit())
+        }
+    }
+}
+
 pub(crate) fn op_getiv(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let this = vm.getself()?;
@@ -653,17 +718,114 @@ pub(crate) fn op_setiv(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let this = vm.getself()?;
     let val = vm.get_current_regs_cloned(a as usize)?;
     let key = vm.current_irep.syms[b as usize].name.clone();
+    this.check_frozen(vm)?;
     this.set_ivar(&key, val.clone());
     Ok(())
 }
 
+pub(crate) fn op_getcv(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let (a, b) = operand.as_bb()?;
+    let owner = class_var_owner(vm)?;
+    let key = vm.current_irep.syms[b as usize].name.clone();
+    match resolve_class_var(&owner, &key)? {
+        Some(val) => {
+            vm.current_regs()[a as usize].replace(val);
+            Ok(())
+        }
+        None => Err(Error::NameError(format!(
+            "uninitialized class variable {} in {}",
+            key,
+            owner_name(&owner)
+        ))),
+    }
+}
+
+pub(crate) fn op_setcv(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let (a, b) = operand.as_bb()?;
+    let owner = class_var_owner(vm)?;
+    let val = vm.get_current_regs_cloned(a as usize)?;
+    let key = vm.current_irep.syms[b as usize].name.clone();
+    assign_class_var(&owner, &key, val)
+}
+
+// Inside a method, class variables belong to the class the method is defined
+// in, so class methods inherited by a subclass share the superclass's
+// variables. Elsewhere they belong to self when it is a class or module,
+// otherwise to the class of self.
+fn class_var_owner(vm: &mut VM) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    if let Some(owner) = defining_module(vm)
+        && let Some(owner) = class_var_cref(&this, owner)
+    {
+        return Ok(RObject::class_or_module(owner, vm));
+    }
+    match &this.value {
+        RValue::Class(_) | RValue::Module(_) => Ok(this),
+        _ => {
+            let klass = this.get_class(vm);
+            Ok(RObject::class(klass, vm))
+        }
+    }
+}
+
+// Owner of the running method; blocks take the method they were called from
+fn defining_module(vm: &VM) -> Option<Rc<RModule>> {
+    let ci = vm.active_callinfo()?;
+    if ci.method_owner.is_some() {
+        return ci.method_owner.clone();
+    }
+    if ci.method_id.name != "<block>" {
+        return None;
+    }
+    // A block belongs to the method it is written in, whoever yields to it
+    vm.get_outermost_env()
+        .and_then(|env| env.method_owner.clone())
+}
+
+// A singleton method's owner is the singleton class; class variables live on
+// the class or module it is attached to, found among self and its superclasses.
+fn class_var_cref(this: &RObject, owner: Rc<RModule>) -> Option<Rc<RModule>> {
+    let sclass = owner.underlying.borrow().as_ref().and_then(|w| w.upgrade());
+    let Some(sclass) = sclass.filter(|sclass| sclass.is_singleton) else {
+        return Some(owner);
+    };
+    let is_attached = |slot: &RefCell<Option<Rc<RClass>>>| {
+        slot.borrow()
+            .as_ref()
+            .is_some_and(|candidate| Rc::ptr_eq(candidate, &sclass))
+    };
+    match &this.value {
+        RValue::Class(klass) => {
+            let mut current = Some(klass.clone());
+            while let Some(klass) = current {
+                if is_attached(&klass.singleton_class_ref) {
+                    return Some(klass.module.clone());
+                }
+                current = klass.super_class.clone();
+            }
+            None
+        }
+        RValue::Module(module) if is_attached(&module.singleton_class_ref) => Some(module.clone()),
+        _ => None,
+    }
+}
+
+fn owner_name(owner: &RObject) -> String {
+    match &owner.value {
+        RValue::Class(klass) => klass.full_name(),
+        RValue::Module(module) => module.full_name(),
+        _ => "(unknown)".to_string(),
+    }
+}
+
 pub(crate) fn op_getconst(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let name = vm.current_irep.syms[b as usize].name.clone();
-    let mut current = current_namespace(vm);
+    let scope = lexical_scope(vm)?;
 
-    // Walk namespace chain upwards until found or reach top-level
-    while let Some(ns) = current.clone() {
+    // Walk the lexical scope first, then the ancestors of the innermost scope
+    let mut current = scope.clone();
+    while let Some(ns) = current {
         if let Some(val) = ns.consts.borrow().get(&name).cloned() {
             vm.current_regs()[a as usize].replace(val);
             return Ok(());
@@ -671,19 +833,24 @@ pub(crate) fn op_getconst(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         current = ns.parent.borrow().clone();
     }
 
-    if let Some(val) = vm.consts.get(&name).cloned() {
-        vm.current_regs()[a as usize].replace(val);
-        return Ok(());
-    }
-
-    Err(Error::NameError(name))
+    let scope = scope.unwrap_or_else(|| vm.object_class.module.clone());
+    let val = match resolve_const(vm, &scope, &name, true) {
+        Some(val) => val,
+        None => call_const_missing(vm, &scope, &name)?,
+    };
+    vm.current_regs()[a as usize].replace(val);
+    Ok(())
 }
 
 pub(crate) fn op_setconst(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let name = vm.current_irep.syms[b as usize].name.clone();
     let val = vm.get_current_regs_cloned(a as usize)?;
-    vm.consts.insert(name, val);
+    let scope = current_namespace(vm).unwrap_or_else(|| vm.object_class.module.clone());
+    if let RValue::Class(class) = &val.value {
+        vm.name_anonymous_class(class, &scope, &name);
+    }
+    scope.consts.borrow_mut().insert(name, val);
     Ok(())
 }
 
@@ -691,21 +858,62 @@ pub(crate) fn op_getmcnst(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let recv = vm.get_current_regs_cloned(a as usize)?;
     let name = vm.current_irep.syms[b as usize].name.clone();
-    let mut module = match &recv.value {
-        RValue::Class(klass) => Some(klass.module.clone()),
-        RValue::Module(module) => Some(module.clone()),
-        _ => None,
+    let module = const_scope_of(&recv)?;
+
+    let val = match resolve_const(vm, &module, &name, false) {
+        Some(val) => val,
+        None => call_const_missing(vm, &module, &name)?,
     };
+    vm.current_regs()[a as usize].replace(val);
+    Ok(())
+}
 
-    while let Some(current) = module.clone() {
-        if let Some(val) = current.consts.borrow().get(&name).cloned() {
-            vm.current_regs()[a as usize].replace(val);
-            return Ok(());
-        }
-        module = current.parent.borrow().clone();
+pub(crate) fn op_setmcnst(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let (a, b) = operand.as_bb()?;
+    let val = vm.get_current_regs_cloned(a as usize)?;
+    let recv = vm.get_current_regs_cloned(a as usize + 1)?;
+    let name = vm.current_irep.syms[b as usize].name.clone();
+    let module = const_scope_of(&recv)?;
+    if let RValue::Class(class) = &val.value {
+        vm.name_anonymous_class(class, &module, &name);
     }
+    module.consts.borrow_mut().insert(name, val);
+    Ok(())
+}
 
-    Err(Error::NameError(name.clone()))
+fn const_scope_of(recv: &RObject) -> Result<Rc<RModule>, Error> {
+    match &recv.value {
+        RValue::Class(klass) => Ok(klass.module.clone()),
+        RValue::Module(module) => Ok(module.clone()),
+        _ => Err(Error::TypeMismatch),
+    }
+}
+
+// The innermost lexical scope: the class body being executed,
+// the owner of the running method, or the class of self (e.g. in blocks).
+fn lexical_scope(vm: &mut VM) -> Result<Option<Rc<RModule>>, Error> {
+    if let Some(ns) = current_namespace(vm) {
+        return Ok(Some(ns));
+    }
+    if let Some(owner) = vm
+        .current_callinfo
+        .as_ref()
+        .and_then(|ci| ci.method_owner.clone())
+    {
+        return Ok(Some(owner));
+    }
+    let this = vm.getself()?;
+    let klass = this.get_class(vm);
+    if Rc::ptr_eq(&klass, &vm.object_class) {
+        return Ok(None);
+    }
+    Ok(Some(klass.module.clone()))
+}
+
+fn call_const_missing(vm: &mut VM, scope: &Rc<RModule>, name: &str) -> Result<Rc<RObject>, Error> {
+    let recv = RObject::class_or_module(scope.clone(), vm);
+    let sym = vm.intern(name);
+    mrb_funcall(vm, Some(recv), "const_missing", &[sym])
 }
 
 pub(crate) fn op_getupvar(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
@@ -722,9 +930,8 @@ pub(crate) fn op_getupvar(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
             .ok_or_else(|| Error::internal("op_getupvar failed to find upvar"))?;
     }
     let environ = environ.clone();
-    let up_regs = &vm.regs[environ.current_regs_offset..];
     if !environ.expired() {
-        if let Some(val) = up_regs[b as usize].as_ref().cloned() {
+        if let Some(val) = vm.with_env_regs(&environ, |up_regs| up_regs[b as usize].clone())? {
             vm.current_regs()[a as usize].replace(val);
         } else {
             return Err(Error::internal(format!("register {} is empty", b)));
@@ -755,13 +962,10 @@ pub(crate) fn op_setupvar(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
             .ok_or_else(|| Error::internal("op_getupvar failed to find upvar"))?;
     }
     let environ = environ.clone();
-    let current_regs_offset = environ.current_regs_offset;
 
     let val = vm.get_current_regs_cloned(a as usize)?;
     if !environ.expired() {
-        let up_regs = &mut vm.regs[current_regs_offset..];
-        let target = &mut up_regs[b as usize];
-        target.replace(val);
+        vm.with_env_regs(&environ, |up_regs| up_regs[b as usize].replace(val))?;
     } else {
         let mut captured = environ.captured.borrow_mut();
         let captured = captured
@@ -773,13 +977,30 @@ pub(crate) fn op_setupvar(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     Ok(())
 }
 
+/// Calls a method for an instruction operating on register `a`. The callee's
+/// registers start at `a`, as OP_SEND arranges them, so that a method
+/// written in Ruby does not overwrite the caller's registers below `a`.
+fn send_at(
+    vm: &mut VM,
+    a: usize,
+    recv: Rc<RObject>,
+    name: &str,
+    args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    vm.reserve_frame(a)?;
+    vm.current_regs_offset += a;
+    let res = mrb_funcall(vm, Some(recv), name, args);
+    vm.current_regs_offset -= a;
+    res
+}
+
 pub(crate) fn op_getidx(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let a = operand.as_b()? as usize;
     let recv = vm.get_current_regs_cloned(a)?;
     let idx = vm.get_current_regs_cloned(a + 1)?;
     let args = vec![idx];
     // TODO: direct call of array_index for performance
-    let val = mrb_funcall(vm, Some(recv), "[]", &args)?;
+    let val = send_at(vm, a, recv, "[]", &args)?;
     vm.current_regs()[a].replace(val);
     Ok(())
 }
@@ -790,33 +1011,29 @@ pub(crate) fn op_setidx(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let idx = vm.get_current_regs_cloned(a + 1)?;
     let val = vm.get_current_regs_cloned(a + 2)?;
     let args = vec![idx, val];
-    mrb_funcall(vm, Some(recv), "[]=", &args)?;
+    send_at(vm, a, recv, "[]=", &args)?;
     Ok(())
 }
 
 pub(crate) fn op_jmp(vm: &mut VM, operand: &Fetched, end_pos: usize) -> Result<(), Error> {
     let a = operand.as_s()?;
     let offset = a as i16;
-    let next_pc = calcurate_pc(
-        &vm.current_irep,
-        0,
-        (end_pos as isize + offset as isize) as usize,
-    );
-    vm.pc.set(next_pc);
+    jump_to(vm, (end_pos as isize + offset as isize) as usize);
     Ok(())
 }
 
+fn jump_to(vm: &mut VM, dest: usize) {
+    leave_handler_clauses(vm, dest);
+    let next_pc = calcurate_pc(&vm.current_irep, 0, dest);
+    vm.pc.set(next_pc);
+}
+
 pub(crate) fn op_jmpif(vm: &mut VM, operand: &Fetched, end_pos: usize) -> Result<(), Error> {
     let (a, b) = operand.as_bs()?;
     let val = vm.get_current_regs_cloned(a as usize)?;
     if val.is_truthy() {
         let offset = b as i16;
-        let next_pc = calcurate_pc(
-            &vm.current_irep,
-            0,
-            (end_pos as isize + offset as isize) as usize,
-        );
-        vm.pc.set(next_pc);
+        jump_to(vm, (end_pos as isize + offset as isize) as usize);
     }
     Ok(())
 }
@@ -826,12 +1043,7 @@ pub(crate) fn op_jmpnot(vm: &mut VM, operand: &Fetched, end_pos: usize) -> Resul
     let val = vm.get_current_regs_cloned(a as usize)?;
     if val.is_falsy() {
         let offset = b as i16;
-        let next_pc = calcurate_pc(
-            &vm.current_irep,
-            0,
-            (end_pos as isize + offset as isize) as usize,
-        );
-        vm.pc.set(next_pc);
+        jump_to(vm, (end_pos as isize + offset as isize) as usize);
     }
     Ok(())
 }
@@ -841,27 +1053,31 @@ pub(crate) fn op_jmpnil(vm: &mut VM, operand: &Fetched, end_pos: usize) -> Resul
     let val = vm.get_current_regs_cloned(a as usize)?;
     if val.is_nil() {
         let offset = b as i16;
-        let next_pc = calcurate_pc(
-            &vm.current_irep,
-            0,
-            (end_pos as isize + offset as isize) as usize,
-        );
-        vm.pc.set(next_pc);
+        jump_to(vm, (end_pos as isize + offset as isize) as usize);
     }
     Ok(())
 }
 
+/// Jumps out of `begin` blocks (break, next, retry), running the ensure
+/// clauses that cover the jump but not its destination, innermost first.
 pub(crate) fn op_jmpuw(vm: &mut VM, operand: &Fetched, end_pos: usize) -> Result<(), Error> {
-    if vm.current_irep.catch_target_pos.is_empty() {
-        op_jmp(vm, operand, end_pos)
-    } else {
-        // TODO multiple catch targets... :(
-        let target_pos = vm.current_irep.catch_target_pos[0];
-        vm.pc.set(target_pos);
-
+    let irep = vm.current_irep.clone();
+    let dest = (end_pos as isize + operand.as_s()? as i16 as isize) as usize;
+    let covers = |ch: &CatchHandler, pos: usize| ch.start < pos && pos <= ch.end;
+    for handler in irep.catch_handlers.iter().rev() {
+        if handler.type_ != CATCH_TYPE_ENSURE || !covers(handler, end_pos) || covers(handler, dest)
+        {
+            continue;
+        }
+        let target = irep
+            .code
+            .iter()
+            .position(|op| op.pos == handler.target)
+            .ok_or_else(|| Error::internal("catch handler target not found"))?;
+        vm.pc.set(target);
         consume_ensure_block(vm)?;
-        op_jmp(vm, operand, end_pos)
     }
+    op_jmp(vm, operand, end_pos)
 }
 
 fn consume_ensure_block(vm: &mut VM) -> Result<(), Error> {
@@ -910,28 +1126,88 @@ fn consume_ensure_block(vm: &mut VM) -> Result<(), Error> {
 
 pub(crate) fn op_except(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let a = operand.as_b()?;
-    let val = vm
-        .exception
-        .take()
-        .map(|e| RObject::exception(e).to_refcount_assigned())
-        .unwrap_or_else(|| RObject::nil().to_refcount_assigned());
+    let val = match vm.exception.take() {
+        Some(e) => {
+            // Objects raised from Ruby are handed back as-is to keep their ivars
+            let raised = match &*e.error_type.borrow() {
+                Error::Exception(obj) => obj.clone(),
+                _ => RObject::exception(e.clone()).to_refcount_assigned(),
+            };
+            enter_handler_clause(vm, a as usize, raised.clone());
+            raised
+        }
+        None => RObject::nil().to_refcount_assigned(),
+    };
     vm.current_regs()[a as usize].replace(val);
     Ok(())
 }
 
+// `$!` is the exception being handled only while its rescue or ensure clause
+// runs. The bytecode has no end marker, so a clause spans from its handler up
+// to where the protected body jumps on success (rescue), or to the RAISEIF
+// re-raising the exception (ensure).
+fn enter_handler_clause(vm: &mut VM, reg: usize, raised: Rc<RObject>) {
+    let irep = vm.current_irep.clone();
+    let index = vm.pc.get() - 1;
+    let pos = irep.code[index].pos;
+    // An exception escaping an inner clause of this frame has left it
+    leave_handler_clauses(vm, pos);
+
+    let end = irep
+        .catch_handlers
+        .iter()
+        .find(|handler| handler.target == pos)
+        .and_then(|handler| match handler.type_ {
+            CATCH_TYPE_RESCUE => {
+                let jmp = irep.code[..index].last()?;
+                let offset = jmp.operand.as_s().ok()? as i16;
+                matches!(jmp.code, OpCode::JMP)
+                    .then(|| (jmp.pos + jmp.len).wrapping_add_signed(offset as isize))
+            }
+            CATCH_TYPE_ENSURE => irep.code[index..]
+                .iter()
+                .find(|op| {
+                    matches!(op.code, OpCode::RAISEIF) && op.operand.as_b().ok() == Some(reg as u16)
+                })
+                .map(|op| op.pos + op.len),
+            _ => None,
+        });
+    let previous = vm.current_exception.replace(raised);
+    if let Some(end) = end {
+        vm.handler_clauses.push(HandlerClause {
+            range: pos..end,
+            previous,
+        });
+    }
+}
+
+/// Restores `$!` for the clauses of the current frame that `pos` lies outside.
+pub(crate) fn leave_handler_clauses(vm: &mut VM, pos: usize) {
+    while let Some(clause) = vm
+        .handler_clauses
+        .pop_if(|clause| !clause.range.contains(&pos))
+    {
+        vm.current_exception = clause.previous;
+    }
+}
+
 pub(crate) fn op_rescue(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let val = vm.get_current_regs_cloned(a as usize)?;
     let exc_klass = vm.take_current_regs(b as usize)?;
-    match (&val.value, exc_klass.value.clone()) {
-        (RValue::Exception(exc), RValue::Class(klass)) => {
-            let etype = exc.error_type.borrow();
-            let is_rescued = etype.is_a(vm, klass);
-            let val = RObject::boolean(is_rescued);
-            vm.current_regs()[b as usize].replace(val.to_refcount_assigned());
+    let is_rescued = match (&val.value, &exc_klass.value) {
+        (RValue::Exception(_), RValue::Class(klass)) => mrb_is_a(vm, val.clone(), klass.clone()),
+        (RValue::Exception(_), RValue::Module(module)) => mrb_is_a(vm, val.clone(), module.clone()),
+        (RValue::Exception(_), _) => {
+            return Err(Error::TaggedError(
+                "TypeError",
+                "class or module required for rescue clause".to_string(),
+            ));
         }
         _ => unreachable!("rescue must be called on exception"),
     };
+    let val = RObject::boolean(is_rescued);
+    vm.current_regs()[b as usize].replace(val.to_refcount_assigned());
     Ok(())
 }
 
@@ -941,7 +1217,14 @@ pub(crate) fn op_raiseif(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     if let Some(val) = val
         && let RValue::Exception(e) = &val.value
     {
-        return Err(e.as_ref().error_type.borrow().clone());
+        // Re-raising keeps the backtrace of the original raise
+        let err = match &*e.error_type.borrow() {
+            // Created by Exception.new; carry the object itself
+            Error::General => Error::Exception(val.clone()),
+            err => err.clone(),
+        };
+        vm.exception = Some(e.clone());
+        return Err(err);
     }
     Ok(())
 }
@@ -956,38 +1239,117 @@ pub(crate) fn op_move(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 
 pub(crate) fn op_ssend(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b, c) = operand.as_bbb()?;
-    do_op_send(vm, 0, None, a, b, c)
+    do_op_send(vm, 0, false, a, b, c)
 }
 
 pub(crate) fn op_ssendb(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b, c) = operand.as_bbb()?;
-    let n: usize = (c & 0x0f) as usize;
-    let k: usize = (c >> 4) as usize;
-    do_op_send(vm, 0, Some(a as usize + n + k * 2 + 1), a, b, c)
+    do_op_send(vm, 0, true, a, b, c)
 }
 
 pub(crate) fn op_send(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b, c) = operand.as_bbb()?;
-    do_op_send(vm, a as usize, None, a, b, c)
+    do_op_send(vm, a as usize, false, a, b, c)
 }
 
 pub(crate) fn op_sendb(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b, c) = operand.as_bbb()?;
-    let n: usize = (c & 0x0f) as usize;
-    let k: usize = (c >> 4) as usize;
-    do_op_send(vm, a as usize, Some(a as usize + n + k * 2 + 1), a, b, c)
+    do_op_send(vm, a as usize, true, a, b, c)
+}
+
+/// Expands packed call arguments in place: with `n == 15` the positional
+/// arguments come as an array in `R[a+1]`, and with `k == 15` the keyword
+/// arguments come as a hash right after them. Returns the expanded `(n, k)`
+/// so that the registers follow the plain `R[a+1]..` layout.
+fn unpack_call_args(
+    vm: &mut VM,
+    a: usize,
+    n: usize,
+    k: usize,
+    has_block: bool,
+) -> Result<(usize, usize), Error> {
+    if n != CALL_MAXARGS && k != CALL_MAXARGS {
+        return Ok((n, k));
+    }
+
+    let mut pos = a + 1;
+    let args = if n == CALL_MAXARGS {
+        let packed = vm.get_current_regs_cloned(pos)?;
+        pos += 1;
+        match &packed.value {
+            RValue::Array(ary) => ary.borrow().clone(),
+            _ => vec![packed.clone()],
+        }
+    } else {
+        let args = (0..n)
+            .map(|i| vm.get_current_regs_cloned(pos + i))
+            .collect::<Result<Vec<_>, _>>()?;
+        pos += n;
+        args
+    };
+    let kwargs = if k == CALL_MAXARGS {
+        let packed = vm.get_current_regs_cloned(pos)?;
+        pos += 1;
+        match &packed.value {
+            RValue::Hash(hash) => hash.borrow().values().cloned().collect(),
+            RValue::Nil => Vec::new(),
+            _ => return Err(Error::TypeMismatch),
+        }
+    } else {
+        let kwargs = (0..k)
+            .map(|i| {
+                Ok((
+                    vm.get_current_regs_cloned(pos + i * 2)?,
+                    vm.get_current_regs_cloned(pos + i * 2 + 1)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        pos += k * 2;
+        kwargs
+    };
+    let block = if has_block {
+        vm.current_regs()[pos].take()
+    } else {
+        None
+    };
+
+    let required = args.len() + kwargs.len() * 2 + 2;
+    if a + required > vm.current_regs().len() {
+        return Err(Error::ArgumentError(format!(
+            "too many arguments: {}",
+            args.len()
+        )));
+    }
+    let (n, k) = (args.len(), kwargs.len());
+    let regs = vm.current_regs();
+    let mut i = a + 1;
+    for arg in args.into_iter() {
+        regs[i].replace(arg);
+        i += 1;
+    }
+    for (key, val) in kwargs.into_iter() {
+        regs[i].replace(key);
+        regs[i + 1].replace(val);
+        i += 2;
+    }
+    if let Some(block) = block {
+        regs[i].replace(block);
+    }
+    Ok((n, k))
 }
 
 pub(crate) fn do_op_send(
     vm: &mut VM,
     recv_index: usize,
-    blk_index: Option<usize>,
-    a: u8,
-    b: u8,
+    has_block: bool,
+    a: u16,
+    b: u16,
     c: u8,
 ) -> Result<(), Error> {
-    let mut n: usize = (c & 0x0f) as usize;
+    let n: usize = (c & 0x0f) as usize;
     let k: usize = (c >> 4) as usize;
+    let (mut n, k) = unpack_call_args(vm, a as usize, n, k, has_block)?;
+    let blk_index = has_block.then_some(a as usize + n + k * 2 + 1);
 
     let method_id = vm.current_irep.syms[b as usize].clone();
     if &method_id.name == "__debug__vm_info" {
@@ -1042,11 +1404,28 @@ pub(crate) fn do_op_send(
     } else {
         recv.singleton_or_this_class(vm)
     };
-    let (owner_module, method) = resolve_method(&klass, &method_id.name)
+    // The send op itself is at pc - 1, as pc already points to the next op
+    let irep = vm.current_irep.clone();
+    let pos = vm.pc.get() - 1;
+    let found = match irep.inline_caches.get(pos, &klass) {
+        Some(found) => found,
+        None => {
+            let found = vm.method_cache.resolve(&klass, &method_id.name);
+            irep.inline_caches.set(pos, &klass, found.clone());
+            found
+        }
+    };
+    // SSEND (recv_index == 0) calls on self and may reach any method
+    if recv_index != 0
+        && let Some((owner_module, method)) = found.as_ref()
+    {
+        check_visibility(vm, &recv, owner_module, method, &method_id.name)?;
+    }
+    let (owner_module, method) = found
         .or_else(|| {
             unshift_method_name(vm, &mut args, &method_id, a as usize, n + k * 2 + 1);
             n += 1;
-            resolve_method(&klass, "method_missing")
+            vm.method_cache.resolve(&klass, "method_missing")
         })
         .ok_or_else(|| {
             Error::Internal(format!(
@@ -1055,6 +1434,7 @@ pub(crate) fn do_op_send(
                 klass.full_name()
             ))
         })?;
+    vm.reserve_frame(a as usize)?;
 
     let upper = vm.current_breadcrumb.take();
     let new_breadcrumb = Rc::new(Breadcrumb {
@@ -1065,6 +1445,11 @@ pub(crate) fn do_op_send(
     });
     vm.current_breadcrumb.replace(new_breadcrumb);
 
+    if let Some(block) = inline_block(vm, &recv, &method_id, &owner_module, &method, blk_index, k) {
+        enter_block(vm, block, n, a as usize)?;
+        return Ok(());
+    }
+
     vm.current_regs()[a as usize].replace(recv.clone());
     if !method.is_rb_func {
         kwarg_op_enter(vm, 0);
@@ -1094,6 +1479,17 @@ pub(crate) fn do_op_send(
                 vm.current_breadcrumb
                     .replace(upper.expect("should have upper breadcrumb"));
             }
+            // `break` in a block the method called ends the call made here
+            Err(Error::Break(val)) if vm.break_target == Some(vm.current_irep.__id) => {
+                vm.break_target = None;
+                vm.exception.take();
+                vm.current_regs()[a as usize].replace(val);
+                let cur = vm
+                    .current_breadcrumb
+                    .take()
+                    .expect("send should push breadcrumb");
+                vm.current_breadcrumb = cur.upper.clone();
+            }
             Err(e) => {
                 vm.current_regs()[a as usize].replace(Rc::new(RObject::nil()));
                 return Err(e);
@@ -1112,10 +1508,119 @@ pub(crate) fn do_op_send(
 
     vm.pc.set(0);
     vm.current_irep = method.irep.ok_or_else(|| Error::internal("empry irep"))?;
+    // mrblib methods share `$~` with their caller, as native methods do
+    if vm.current_irep.is_builtin()
+        && let Some(ci) = vm.current_callinfo.as_ref()
+    {
+        vm.last_match = ci.last_match.clone();
+    }
+    // Blocks given to define_method see the variables they closed over
+    vm.upper = method.environ;
     vm.current_regs_offset += a as usize;
     Ok(())
 }
 
+/// Returns the block when SEND is a plain `Proc#call` (or `yield`) of a Ruby
+/// block. Such a call runs in this interpreter loop, as a method body does,
+/// rather than in a nested one, so `Fiber.yield` can suspend inside it.
+fn inline_block(
+    vm: &VM,
+    recv: &RObject,
+    method_id: &RSym,
+    owner_module: &Rc<RModule>,
+    method: &RProc,
+    blk_index: Option<usize>,
+    kargs: usize,
+) -> Option<RProc> {
+    let RValue::Proc(block) = &recv.value else {
+        return None;
+    };
+    let is_proc_call = method_id.name == "call"
+        && !method.is_rb_func
+        && Rc::ptr_eq(owner_module, &vm.get_class_by_name("Proc").module);
+    (is_proc_call
+        && block.is_rb_func
+        && block.block_self.is_some()
+        && blk_index.is_none()
+        && kargs == 0)
+        .then(|| block.clone())
+}
+
+/// Enters a block called with `n` arguments from register `a` on, the way
+/// `call_block` does for native callers.
+fn enter_block(vm: &mut VM, block: RProc, n: usize, a: usize) -> Result<(), Error> {
+    let irep = block
+        .irep
+        .clone()
+        .ok_or_else(|| Error::internal("block without irep"))?;
+    push_callinfo(vm, RSym::new("<block>".to_string()), n, None, a);
+    // Blocks share `$~` with the frame calling them
+    if let Some(ci) = vm.current_callinfo.as_ref() {
+        vm.last_match = ci.last_match.clone();
+    }
+
+    let nil = Rc::new(RObject::nil());
+    let regs = &mut vm.current_regs()[a..];
+    regs[0] = block.block_self.clone();
+    // Parameters the caller did not pass, and locals, start as nil
+    for reg in regs.iter_mut().take(irep.nregs.max(n + 2)).skip(n + 1) {
+        reg.replace(nil.clone());
+    }
+
+    vm.pc.set(0);
+    vm.current_irep = irep;
+    vm.upper = block.environ;
+    vm.current_regs_offset += a;
+    Ok(())
+}
+
+/// Raises `NoMethodError` when `recv` is called with an explicit receiver
+/// on a method its visibility hides from the caller. Private methods are
+/// still reachable through `self.`, protected ones from instances of the
+/// owner.
+pub(crate) fn check_visibility(
+    vm: &mut VM,
+    recv: &Rc<RObject>,
+    owner_module: &Rc<RModule>,
+    method: &RProc,
+    name: &str,
+) -> Result<(), Error> {
+    let this = vm.getself()?;
+    let allowed = match method.visibility {
+        Visibility::Public => true,
+        Visibility::Private => Rc::ptr_eq(&this, recv),
+        Visibility::Protected => {
+            let klass = this.singleton_or_this_class(vm);
+            build_lookup_chain(&klass)
+                .iter()
+                .any(|module| Rc::ptr_eq(module, owner_module))
+        }
+    };
+    if allowed {
+        return Ok(());
+    }
+    Err(visibility_error(vm, recv, name, method.visibility))
+}
+
+pub(crate) fn visibility_error(
+    vm: &mut VM,
+    recv: &Rc<RObject>,
+    name: &str,
+    visibility: Visibility,
+) -> Error {
+    let recv_name = match &recv.value {
+        RValue::Class(klass) => format!("class {}", klass.full_name()),
+        RValue::Module(module) => format!("module {}", module.full_name()),
+        _ => format!("an instance of {}", recv.get_class(vm).full_name()),
+    };
+    Error::NoMethodError(format!(
+        "{} method `{}` called for {}",
+        visibility.name(),
+        name,
+        recv_name
+    ))
+}
+
 fn unshift_method_name(
     vm: &mut VM,
     args: &mut Vec<Rc<RObject>>,
@@ -1191,8 +1696,7 @@ pub(crate) fn op_call(vm: &mut VM, _operand: &Fetched) -> Result<(), Error> {
 pub(crate) fn op_super(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let callinfo = vm
-        .current_callinfo
-        .as_ref()
+        .active_callinfo()
         .ok_or_else(|| Error::internal("no current callinfo"))?;
     let sym_id = callinfo.method_id.name.clone();
     let owner_module = callinfo
@@ -1200,13 +1704,31 @@ pub(crate) fn op_super(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         .clone()
         .ok_or_else(|| Error::RuntimeError("super called outside of method".to_string()))?;
     let recv = vm.getself()?;
-    let args = (0..b)
+    let n = (b & 0x0f) as usize;
+    let k = (b >> 4) as usize;
+    let (n, k) = unpack_call_args(vm, a as usize, n, k, true)?;
+    let mut args = (0..n)
         .map(|i| {
-            vm.get_current_regs_cloned((a + i + 1) as usize)
+            vm.get_current_regs_cloned(a as usize + i + 1)
                 .expect("args too short for super")
         })
         .collect::<Vec<_>>();
 
+    let mut map = RHashMap::default();
+    for i in 0..k {
+        let key = vm
+            .get_current_regs_cloned(a as usize + n + i * 2 + 1)?
+            .intern()?;
+        let val = vm.get_current_regs_cloned(a as usize + n + i * 2 + 2)?;
+        map.insert(key, val);
+    }
+    vm.kargs.borrow_mut().replace(map);
+
+    let block = vm.current_regs()[a as usize + n + k * 2 + 1]
+        .clone()
+        .filter(|blk| matches!(blk.tt, RType::Proc));
+    let b = n + k * 2;
+
     let klass = match &recv.value {
         RValue::Instance(ins) => ins.class.clone(),
         _ => recv.initialize_or_get_singleton_class(vm),
@@ -1219,8 +1741,13 @@ pub(crate) fn op_super(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         let func = vm.get_fn(method.func.unwrap()).ok_or_else(|| {
             Error::internal(format!("functon registerd but no entry found: {}", &sym_id))
         })?;
+        if let Some(block) = block.as_ref() {
+            args.push(block.clone());
+        }
+        kwarg_op_enter(vm, 0);
         let res = func(vm, &args);
-        for i in (a as usize + 1)..(a as usize + b as usize + 1) {
+        kwarg_op_return(vm);
+        for i in (a as usize + 1)..(a as usize + b + 2) {
             vm.current_regs()[i].take();
         }
         match res {
@@ -1234,6 +1761,7 @@ pub(crate) fn op_super(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         }
         return Ok(());
     }
+    vm.reserve_frame(a as usize)?;
 
     let upper = vm.current_breadcrumb.take();
     let new_breadcrumb = Rc::new(Breadcrumb {
@@ -1248,10 +1776,13 @@ pub(crate) fn op_super(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     push_callinfo(
         vm,
         method.sym_id.clone().unwrap(),
-        b as usize,
+        n,
         Some(next_owner),
         a as usize,
     );
+    if let Some(ci) = vm.current_callinfo.as_ref() {
+        ci.has_block.set(block.is_some());
+    }
 
     vm.pc.set(0);
     vm.current_irep = method
@@ -1259,10 +1790,113 @@ pub(crate) fn op_super(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         .as_ref()
         .ok_or_else(|| Error::internal("empty irep"))?
         .clone();
+    vm.upper = method.environ.clone();
     vm.current_regs_offset += a as usize;
     Ok(())
 }
 
+pub(crate) fn op_argary(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let (a, b) = operand.as_bs()?;
+    let a = a as usize;
+    let m1 = ((b >> 11) & 0x3f) as usize;
+    let r = ((b >> 10) & 0x1) as usize;
+    let m2 = ((b >> 5) & 0x1f) as usize;
+    let kd = ((b >> 4) & 0x1) as usize;
+    let lv = (b & 0xf) as usize;
+
+    // Registers of the method frame, counted from its R[1]
+    let stack = method_frame_regs(vm, lv, m1 + r + m2 + kd + 1)?;
+    let nil = || RObject::nil().to_refcount_assigned();
+    let stack_at = |i: usize| stack.get(i).cloned().flatten().unwrap_or_else(nil);
+
+    let mut args: Vec<Rc<RObject>> = (0..m1).map(stack_at).collect();
+    if r == 1
+        && let RValue::Array(rest) = &stack_at(m1).value
+    {
+        args.extend(rest.borrow().iter().cloned());
+    }
+    args.extend((m1 + r..m1 + r + m2).map(stack_at));
+    vm.current_regs()[a].replace(RObject::array(args).to_refcount_assigned());
+
+    let block = if lv == 0 {
+        // Blocks are kept right after the passed arguments (see op_blkpush)
+        match vm.current_callinfo.as_ref() {
+            Some(ci) if ci.has_block.get() => {
+                let n = ci.n_args;
+                vm.current_regs()[n + 1].clone().unwrap_or_else(nil)
+            }
+            _ => nil(),
+        }
+    } else {
+        stack_at(m1 + r + m2 + kd)
+    };
+
+    if kd == 1 {
+        let mut hash = RHash::default();
+        if lv == 0 {
+            // Keyword locals follow the kwargs and block registers
+            let kw_start = m1 + r + m2 + 3;
+            let k = vm
+                .current_irep
+                .code
+                .iter()
+                .find(|op| matches!(op.code, OpCode::ENTER))
+                .and_then(|op| op.operand.as_w().ok())
+                .map_or(0, |w| EnterArgInfo::from(w).k as usize);
+            let names = vm.current_irep.lv.clone().unwrap_or_default();
+            for reg in kw_start..kw_start + k {
+                if let (Some(name), Some(val)) = (names.get(&reg), vm.current_regs()[reg].clone()) {
+                    let key = RObject::symbol(RSym::new(name.clone())).to_refcount_assigned();
+                    hash.insert(key.as_hash_key()?, (key, val));
+                }
+            }
+        }
+        if let RValue::Hash(rest) = &stack_at(m1 + r + m2).value {
+            for (key, val) in rest.borrow().iter() {
+                hash.insert(key.clone(), val.clone());
+            }
+        }
+        vm.current_regs()[a + 1].replace(RObject::hash(hash).to_refcount_assigned());
+        vm.current_regs()[a + 2].replace(block);
+    } else {
+        vm.current_regs()[a + 1].replace(block);
+    }
+    Ok(())
+}
+
+// Returns R[1]..R[len] of the frame `lv` levels above the current one.
+fn method_frame_regs(
+    vm: &mut VM,
+    lv: usize,
+    len: usize,
+) -> Result<Vec<Option<Rc<RObject>>>, Error> {
+    if lv == 0 {
+        return Ok(vm.current_regs()[1..=len].to_vec());
+    }
+    let mut environ = vm
+        .upper
+        .as_ref()
+        .ok_or_else(|| Error::internal("op_argary expects upper env"))?;
+    for _ in 0..(lv - 1) {
+        environ = environ
+            .upper
+            .as_ref()
+            .ok_or_else(|| Error::internal("op_argary failed to find method env"))?;
+    }
+    let environ = environ.clone();
+    if !environ.expired() {
+        vm.with_env_regs(&environ, |up_regs| up_regs[1..=len].to_vec())
+    } else {
+        let captured = environ.captured.borrow();
+        let captured = captured
+            .as_ref()
+            .ok_or_else(|| Error::internal("captured environment not found"))?;
+        Ok((1..=len)
+            .map(|i| captured.get(i).cloned().flatten())
+            .collect())
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct EnterArgInfo {
@@ -1289,10 +1923,43 @@ impl From<u32> for EnterArgInfo {
     }
 }
 
+fn check_arity(argc: usize, arg_info: &EnterArgInfo) -> Result<(), Error> {
+    let required = (arg_info.m1 + arg_info.m2) as usize;
+    let optional = arg_info.o as usize;
+    let expected = if arg_info.r == 1 {
+        if argc >= required {
+            return Ok(());
+        }
+        format!("{}+", required)
+    } else {
+        if (required..=required + optional).contains(&argc) {
+            return Ok(());
+        }
+        if optional > 0 {
+            format!("{}..{}", required, required + optional)
+        } else {
+            required.to_string()
+        }
+    };
+    Err(Error::ArgumentError(format!(
+        "wrong number of arguments (given {}, expected {})",
+        argc, expected
+    )))
+}
+
 pub(crate) fn op_enter(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let a = operand.as_w()?;
-    let argc = vm.current_callinfo.as_ref().map_or(0, |ci| ci.n_args);
+    let argc = vm.active_callinfo().map_or(0, |ci| ci.n_args);
     let arg_info = EnterArgInfo::from(a);
+    // A block given to define_method runs as a method with its upper
+    // environment, and checks its arity like a lambda
+    if vm.upper.is_some()
+        && vm
+            .active_callinfo()
+            .is_some_and(|ci| ci.method_owner.is_some())
+    {
+        check_arity(argc, &arg_info)?;
+    }
     let m1_argc = arg_info.m1 as usize;
     for i in 0..m1_argc {
         match vm.current_regs()[i + 1].as_ref() {
@@ -1308,7 +1975,7 @@ pub(crate) fn op_enter(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let optional_arg = arg_info.o as usize;
     if optional_arg > 0 {
         let m2_argc = arg_info.m2 as usize;
-        let total_preset_args = argc.saturating_sub(m1_argc + m2_argc);
+        let total_preset_args = argc.saturating_sub(m1_argc + m2_argc).min(optional_arg);
         for peek_pc in 0..total_preset_args {
             match vm.current_irep.code[vm.pc.get() + peek_pc].code {
                 OpCode::JMP => {}
@@ -1341,7 +2008,7 @@ pub(crate) fn op_enter(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     };
     kwarg_op_enter(vm, kwrest_pos);
     if kwrest_arg == 1 {
-        let mut map = RHashMap::default();
+        let mut map = RHash::default();
         for (k, v) in vm
             .get_kwargs()
             .ok_or_else(|| Error::RuntimeError("kwargs not defined".to_string()))?
@@ -1461,6 +2128,8 @@ pub(crate) fn op_return(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     }
 
     let ci = ci.unwrap();
+    // Blocks and mrblib methods leave `$~` to the frame they return to
+    let keeps_last_match = ci.method_id.name == "<block>" || vm.current_irep.is_builtin();
     if let Some(prev) = &ci.prev {
         vm.current_callinfo.replace(prev.clone());
     }
@@ -1468,6 +2137,12 @@ pub(crate) fn op_return(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     vm.pc.set(ci.pc);
     vm.current_regs_offset = ci.current_regs_offset;
     vm.target_class = ci.target_class.clone();
+    if !keeps_last_match {
+        vm.last_match = ci.last_match.clone();
+    }
+    vm.current_exception = ci.current_exception.clone();
+    vm.handler_clauses = ci.handler_clauses.clone();
+    vm.upper = ci.upper.clone();
     if vm.current_regs()[0].is_none() {
         unreachable!("debug");
     }
@@ -1482,6 +2157,13 @@ pub(crate) fn op_return(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 }
 
 pub(crate) fn op_return_blk(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    // A block given to define_method returns from its own method call
+    if vm
+        .active_callinfo()
+        .is_some_and(|ci| ci.method_owner.is_some())
+    {
+        return op_return(vm, operand);
+    }
     let a = operand.as_b()? as usize;
     let val = vm.get_current_regs_cloned(a)?;
     let target_irep_id = vm
@@ -1495,13 +2177,18 @@ pub(crate) fn op_return_blk(vm: &mut VM, operand: &Fetched) -> Result<(), Error>
 pub(crate) fn op_break(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let a = operand.as_b()? as usize;
     let val = vm.get_current_regs_cloned(a)?;
+    // The block's home frame made its upper environment
+    vm.break_target = vm.upper.as_ref().map(|env| env.__irep_id);
 
     Err(Error::Break(val))
 }
 
 pub(crate) fn op_blkpush(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, _s) = operand.as_bs()?;
-    let n = vm.current_callinfo.as_ref().unwrap().n_args;
+    let n = vm
+        .active_callinfo()
+        .ok_or_else(|| Error::internal("op_blkpush expects callinfo"))?
+        .n_args;
     let block = vm.get_current_regs_cloned(n + 1)?;
     vm.current_regs()[a as usize].replace(block);
     Ok(())
@@ -1513,22 +2200,20 @@ pub(crate) fn op_add(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let val1 = vm.take_current_regs(a)?;
     let val2 = vm.get_current_regs_cloned(b)?;
     let result = match (&val1.value, &val2.value) {
-        (RValue::Integer(n1), RValue::Integer(n2)) => Rc::new(RObject::integer(n1 + n2)),
+        (RValue::Integer(_), RValue::Integer(_)) => {
+            integer_add(&val1, &val2).unwrap().to_refcount_assigned()
+        }
         (RValue::Float(n1), RValue::Float(n2)) => Rc::new(RObject::float(n1 + n2)),
         (RValue::Integer(n1), RValue::Float(n2)) => Rc::new(RObject::float(*n1 as f64 + n2)),
         (RValue::Float(n1), RValue::Integer(n2)) => Rc::new(RObject::float(n1 + *n2 as f64)),
-        (RValue::String(n1, _), RValue::String(n2, _)) => {
-            let mut n1 = n1.borrow_mut();
-            let n2 = n2.borrow();
-            for c in n2.iter() {
-                n1.push(*c);
-            }
-            val1.clone()
-        }
-        _ => {
-            let args = vec![val2.clone()];
-            mrb_funcall(vm, Some(val1.clone()), "+", &args)?
-        }
+        (RValue::BigInt(n1), RValue::Float(n2)) => Rc::new(RObject::float(n1.to_f64() + n2)),
+        (RValue::Float(n1), RValue::BigInt(n2)) => Rc::new(RObject::float(n1 + n2.to_f64())),
+        (RValue::String(n1, is_utf8), RValue::String(n2, _)) => {
+            let mut bytes = n1.borrow().clone();
+            bytes.extend_from_slice(&n2.borrow());
+            RObject::string_from_bytes(bytes, is_utf8.get()).to_refcount_assigned()
+        }
+        _ => send_at(vm, a, val1, "+", &[val2])?,
     };
     vm.current_regs()[a].replace(result);
     Ok(())
@@ -1537,15 +2222,15 @@ pub(crate) fn op_add(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 pub(crate) fn op_addi(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let val1 = vm.take_current_regs(a as usize)?;
-    let val2 = b as i64;
+    let val2 = RObject::integer(b as i64);
     let result = match &val1.value {
-        RValue::Integer(n1) => RObject::integer(*n1 + val2),
-        RValue::Float(n1) => RObject::float(n1 + val2 as f64),
-        _ => {
-            unreachable!("addi supports only integer and float")
+        RValue::Integer(_) | RValue::BigInt(_) => {
+            integer_add(&val1, &val2).unwrap().to_refcount_assigned()
         }
+        RValue::Float(n1) => RObject::float(n1 + b as f64).to_refcount_assigned(),
+        _ => send_at(vm, a as usize, val1, "+", &[val2.to_refcount_assigned()])?,
     };
-    vm.current_regs()[a as usize].replace(result.to_refcount_assigned());
+    vm.current_regs()[a as usize].replace(result);
     Ok(())
 }
 
@@ -1555,8 +2240,8 @@ pub(crate) fn op_sub(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let val1 = vm.take_current_regs(a)?;
     let val2 = vm.get_current_regs_cloned(b)?;
     let result = match (&val1.value, &val2.value) {
-        (RValue::Integer(n1), RValue::Integer(n2)) => {
-            RObject::integer(n1 - n2).to_refcount_assigned()
+        (RValue::Integer(_), RValue::Integer(_)) => {
+            integer_sub(&val1, &val2).unwrap().to_refcount_assigned()
         }
         (RValue::Float(n1), RValue::Float(n2)) => RObject::float(n1 - n2).to_refcount_assigned(),
         (RValue::Integer(n1), RValue::Float(n2)) => {
@@ -1565,10 +2250,13 @@ pub(crate) fn op_sub(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         (RValue::Float(n1), RValue::Integer(n2)) => {
             RObject::float(n1 - *n2 as f64).to_refcount_assigned()
         }
-        _ => {
-            let args = vec![val2.clone()];
-            mrb_funcall(vm, Some(val1.clone()), "-", &args)?
+        (RValue::BigInt(n1), RValue::Float(n2)) => {
+            RObject::float(n1.to_f64() - n2).to_refcount_assigned()
+        }
+        (RValue::Float(n1), RValue::BigInt(n2)) => {
+            RObject::float(n1 - n2.to_f64()).to_refcount_assigned()
         }
+        _ => send_at(vm, a, val1, "-", &[val2])?,
     };
     vm.current_regs()[a].replace(result);
     Ok(())
@@ -1577,14 +2265,15 @@ pub(crate) fn op_sub(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 pub(crate) fn op_subi(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let val1 = vm.take_current_regs(a as usize)?;
-    let val2 = b as i64;
+    let val2 = RObject::integer(b as i64);
     let result = match &val1.value {
-        RValue::Integer(n1) => RObject::integer(*n1 - val2),
-        _ => {
-            unreachable!("subi supports only integer")
+        RValue::Integer(_) | RValue::BigInt(_) => {
+            integer_sub(&val1, &val2).unwrap().to_refcount_assigned()
         }
+        RValue::Float(n1) => RObject::float(n1 - b as f64).to_refcount_assigned(),
+        _ => send_at(vm, a as usize, val1, "-", &[val2.to_refcount_assigned()])?,
     };
-    vm.current_regs()[a as usize].replace(result.to_refcount_assigned());
+    vm.current_regs()[a as usize].replace(result);
     Ok(())
 }
 
@@ -1594,8 +2283,8 @@ pub(crate) fn op_mul(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let val1 = vm.take_current_regs(a)?;
     let val2 = vm.get_current_regs_cloned(b)?;
     let result = match (&val1.value, &val2.value) {
-        (RValue::Integer(n1), RValue::Integer(n2)) => {
-            RObject::integer(n1 * n2).to_refcount_assigned()
+        (RValue::Integer(_), RValue::Integer(_)) => {
+            integer_mul(&val1, &val2).unwrap().to_refcount_assigned()
         }
         (RValue::Float(n1), RValue::Float(n2)) => RObject::float(n1 * n2).to_refcount_assigned(),
         (RValue::Integer(n1), RValue::Float(n2)) => {
@@ -1604,7 +2293,13 @@ pub(crate) fn op_mul(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         (RValue::Float(n1), RValue::Integer(n2)) => {
             RObject::float(n1 * *n2 as f64).to_refcount_assigned()
         }
-        _ => mrb_funcall(vm, Some(val1), "*", &[val2])?,
+        (RValue::BigInt(n1), RValue::Float(n2)) => {
+            RObject::float(n1.to_f64() * n2).to_refcount_assigned()
+        }
+        (RValue::Float(n1), RValue::BigInt(n2)) => {
+            RObject::float(n1 * n2.to_f64()).to_refcount_assigned()
+        }
+        _ => send_at(vm, a, val1, "*", &[val2])?,
     };
     vm.current_regs()[a].replace(result);
     Ok(())
@@ -1616,8 +2311,9 @@ pub(crate) fn op_div(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let val1 = vm.take_current_regs(a)?;
     let val2 = vm.get_current_regs_cloned(b)?;
     let result = match (&val1.value, &val2.value) {
-        (RValue::Integer(n1), RValue::Integer(n2)) => {
-            RObject::integer(n1 / n2).to_refcount_assigned()
+        (RValue::Integer(_), RValue::Integer(_)) => {
+            let (q, _) = integer_divmod(&val1, &val2)?.unwrap();
+            q.to_refcount_assigned()
         }
         (RValue::Float(n1), RValue::Float(n2)) => RObject::float(n1 / n2).to_refcount_assigned(),
         (RValue::Integer(n1), RValue::Float(n2)) => {
@@ -1626,7 +2322,13 @@ pub(crate) fn op_div(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         (RValue::Float(n1), RValue::Integer(n2)) => {
             RObject::float(n1 / *n2 as f64).to_refcount_assigned()
         }
-        _ => mrb_funcall(vm, Some(val1), "/", &[val2])?,
+        (RValue::BigInt(n1), RValue::Float(n2)) => {
+            RObject::float(n1.to_f64() / n2).to_refcount_assigned()
+        }
+        (RValue::Float(n1), RValue::BigInt(n2)) => {
+            RObject::float(n1 / n2.to_f64()).to_refcount_assigned()
+        }
+        _ => send_at(vm, a, val1, "/", &[val2])?,
     };
     vm.current_regs()[a].replace(result);
     Ok(())
@@ -1642,8 +2344,12 @@ pub(crate) fn op_lt(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         (RValue::Float(n1), RValue::Float(n2)) => RObject::boolean(n1 < n2),
         (RValue::Integer(n1), RValue::Float(n2)) => RObject::boolean((*n1 as f64) < *n2),
         (RValue::Float(n1), RValue::Integer(n2)) => RObject::boolean(*n1 < (*n2 as f64)),
+        (RValue::BigInt(n1), RValue::Float(n2)) => RObject::boolean(n1.to_f64() < *n2),
+        (RValue::Float(n1), RValue::BigInt(n2)) => RObject::boolean(*n1 < n2.to_f64()),
         _ => {
-            unreachable!("lt supports only numeric")
+            let result = send_at(vm, a, val1, "<", &[val2])?;
+            vm.current_regs()[a].replace(result);
+            return Ok(());
         }
     };
     vm.current_regs()[a].replace(Rc::new(result));
@@ -1660,8 +2366,12 @@ pub(crate) fn op_le(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         (RValue::Float(n1), RValue::Float(n2)) => RObject::boolean(n1 <= n2),
         (RValue::Integer(n1), RValue::Float(n2)) => RObject::boolean((*n1 as f64) <= *n2),
         (RValue::Float(n1), RValue::Integer(n2)) => RObject::boolean(*n1 <= (*n2 as f64)),
+        (RValue::BigInt(n1), RValue::Float(n2)) => RObject::boolean(n1.to_f64() <= *n2),
+        (RValue::Float(n1), RValue::BigInt(n2)) => RObject::boolean(*n1 <= n2.to_f64()),
         _ => {
-            unreachable!("le supports only numeric")
+            let result = send_at(vm, a, val1, "<=", &[val2])?;
+            vm.current_regs()[a].replace(result);
+            return Ok(());
         }
     };
     vm.current_regs()[a].replace(Rc::new(result));
@@ -1673,7 +2383,11 @@ pub(crate) fn op_eq(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let b = a + 1;
     let lhs = vm.take_current_regs(a)?;
     let rhs = vm.get_current_regs_cloned(b)?;
-    let result = mrb_object_is_equal(vm, lhs, rhs);
+    let result = match &lhs.value {
+        // User objects may define == themselves, e.g. through Comparable
+        RValue::Instance(_) | RValue::Data(_) => send_at(vm, a, lhs, "==", &[rhs])?,
+        _ => mrb_object_is_equal(vm, lhs, rhs),
+    };
     vm.current_regs()[a].replace(result);
     Ok(())
 }
@@ -1688,8 +2402,12 @@ pub(crate) fn op_gt(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         (RValue::Float(n1), RValue::Float(n2)) => RObject::boolean(n1 > n2),
         (RValue::Integer(n1), RValue::Float(n2)) => RObject::boolean((*n1 as f64) > *n2),
         (RValue::Float(n1), RValue::Integer(n2)) => RObject::boolean(*n1 > (*n2 as f64)),
+        (RValue::BigInt(n1), RValue::Float(n2)) => RObject::boolean(n1.to_f64() > *n2),
+        (RValue::Float(n1), RValue::BigInt(n2)) => RObject::boolean(*n1 > n2.to_f64()),
         _ => {
-            unreachable!("gt supports only numeric")
+            let result = send_at(vm, a, val1, ">", &[val2])?;
+            vm.current_regs()[a].replace(result);
+            return Ok(());
         }
     };
     vm.current_regs()[a].replace(Rc::new(result));
@@ -1706,8 +2424,12 @@ pub(crate) fn op_ge(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         (RValue::Float(n1), RValue::Float(n2)) => RObject::boolean(n1 >= n2),
         (RValue::Integer(n1), RValue::Float(n2)) => RObject::boolean((*n1 as f64) >= *n2),
         (RValue::Float(n1), RValue::Integer(n2)) => RObject::boolean(*n1 >= (*n2 as f64)),
+        (RValue::BigInt(n1), RValue::Float(n2)) => RObject::boolean(n1.to_f64() >= *n2),
+        (RValue::Float(n1), RValue::BigInt(n2)) => RObject::boolean(*n1 >= n2.to_f64()),
         _ => {
-            unreachable!("ge supports only numeric")
+            let result = send_at(vm, a, val1, ">=", &[val2])?;
+            vm.current_regs()[a].replace(result);
+            return Ok(());
         }
     };
     vm.current_regs()[a].replace(Rc::new(result));
@@ -1743,21 +2465,13 @@ pub(crate) fn op_arycat(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let b = a + 1;
     let val1 = vm.get_current_regs_cloned(a)?;
     let val2 = vm.take_current_regs(b)?;
-    match (&val1.value, &val2.value) {
-        (RValue::Array(ary1), RValue::Array(ary2)) => {
-            let mut ary1 = ary1.borrow_mut();
-            let ary2 = ary2.borrow();
-            for item in ary2.iter() {
-                ary1.push(item.clone());
-            }
+    let splat = ary_splat(vm, val2)?;
+    match &val1.value {
+        RValue::Array(ary1) => {
+            ary1.borrow_mut().extend(splat);
         }
-        (RValue::Nil, RValue::Array(ary2)) => {
-            let mut ary1 = Vec::new();
-            let ary2 = ary2.borrow();
-            for item in ary2.iter() {
-                ary1.push(item.clone());
-            }
-            let val = RObject::array(ary1);
+        RValue::Nil => {
+            let val = RObject::array(splat);
             vm.current_regs()[a].replace(val.to_refcount_assigned());
         }
         _ => {
@@ -1767,6 +2481,51 @@ pub(crate) fn op_arycat(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     Ok(())
 }
 
+pub(crate) fn op_arypush(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let (a, b) = operand.as_bb()?;
+    let a = a as usize;
+    let ary = vm.get_current_regs_cloned(a)?;
+    let vals = (0..b as usize)
+        .map(|i| vm.get_current_regs_cloned(a + i + 1))
+        .collect::<Result<Vec<_>, _>>()?;
+    match &ary.value {
+        RValue::Array(ary) => {
+            ary.borrow_mut().extend(vals);
+        }
+        _ => {
+            unreachable!("arypush supports only array")
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn op_arysplat(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let a = operand.as_b()? as usize;
+    let val = vm.get_current_regs_cloned(a)?;
+    let splat = ary_splat(vm, val)?;
+    vm.current_regs()[a].replace(RObject::array(splat).to_refcount_assigned());
+    Ok(())
+}
+
+// Converts a splatted value into array elements, as `[*val]` does.
+fn ary_splat(vm: &mut VM, val: Rc<RObject>) -> Result<Vec<Rc<RObject>>, Error> {
+    match &val.value {
+        RValue::Array(ary) => Ok(ary.borrow().clone()),
+        RValue::Nil => Ok(Vec::new()),
+        _ => {
+            let klass = val.singleton_or_this_class(vm);
+            if resolve_method(&klass, "to_a").is_none() {
+                return Ok(vec![val]);
+            }
+            let ary = mrb_funcall(vm, Some(val), "to_a", &[])?;
+            match &ary.value {
+                RValue::Array(ary) => Ok(ary.borrow().clone()),
+                _ => Err(Error::TypeMismatch),
+            }
+        }
+    }
+}
+
 pub(crate) fn op_aref(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b, c) = operand.as_bbb()?;
     let array = vm.get_current_regs_cloned(b as usize)?;
@@ -1787,6 +2546,26 @@ pub(crate) fn op_aref(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     Ok(())
 }
 
+pub(crate) fn op_aset(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let (a, b, c) = operand.as_bbb()?;
+    let val = vm.get_current_regs_cloned(a as usize)?;
+    let array = vm.get_current_regs_cloned(b as usize)?;
+    let index = c as usize;
+    match &array.value {
+        RValue::Array(ary) => {
+            let mut ary = ary.borrow_mut();
+            if ary.len() <= index {
+                ary.resize(index + 1, Rc::new(RObject::nil()));
+            }
+            ary[index] = val;
+        }
+        _ => {
+            unreachable!("aset supports only array")
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn op_apost(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b, c) = operand.as_bbb()?;
     if c != 0 {
@@ -1816,9 +2595,17 @@ pub(crate) fn op_apost(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 pub(crate) fn op_symbol(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let symstr = vm.current_irep.pool[b as usize].as_str().to_string();
-    let sym = RSym::new(symstr);
-    let val = RObject::symbol(sym);
-    vm.current_regs()[a as usize].replace(val.to_refcount_assigned());
+    let val = vm.intern(&symstr);
+    vm.current_regs()[a as usize].replace(val);
+    Ok(())
+}
+
+pub(crate) fn op_intern(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let a = operand.as_b()? as usize;
+    let val = vm.get_current_regs_cloned(a)?;
+    let name: String = val.as_ref().try_into()?;
+    let val = vm.intern(&name);
+    vm.current_regs()[a].replace(val);
     Ok(())
 }
 
@@ -1826,6 +2613,9 @@ pub(crate) fn op_string(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let str = vm.current_irep.pool[b as usize].as_str().to_string();
     let val = RObject::string(str);
+    if vm.frozen_string_literal {
+        val.freeze();
+    }
     vm.current_regs()[a as usize].replace(val.to_refcount_assigned());
     Ok(())
 }
@@ -1835,36 +2625,30 @@ pub(crate) fn op_strcat(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let b = a + 1;
     let val1 = vm.get_current_regs_cloned(a)?;
     let val2 = vm.get_current_regs_cloned(b)?;
-    match (&val1.value, &val2.value) {
-        (RValue::String(s1, _), RValue::String(s2, _)) => {
-            let mut s1 = s1.borrow_mut();
-            let s2 = s2.borrow();
-            for c in s2.iter() {
-                s1.push(*c);
-            }
-        }
-        (RValue::String(s1, _), RValue::Integer(s2)) => {
-            let mut s1 = s1.borrow_mut();
-            let s2 = s2.to_string();
-            for c in s2.as_bytes() {
-                s1.push(*c);
-            }
-        }
-        (RValue::String(s1, _), _) => {
-            let mut s1 = s1.borrow_mut();
+    let tail = match &val2.value {
+        RValue::String(s2, _) => s2.borrow().clone(),
+        RValue::Integer(n) => n.to_string().into_bytes(),
+        _ => {
             let s2 = mrb_funcall(vm, Some(val2.clone()), "to_s", &[])?;
-            let s2 = match &s2.value {
-                RValue::String(s, _) => s.borrow(),
+            match &s2.value {
+                RValue::String(s, _) => s.borrow().clone(),
                 _ => unreachable!("to_s must return string"),
-            };
-            for c in s2.to_vec().iter() {
-                s1.push(*c);
             }
         }
-        _ => {
-            unreachable!("strcat supports only string")
-        }
     };
+    let RValue::String(s1, is_utf8) = &val1.value else {
+        unreachable!("strcat supports only string")
+    };
+    if val1.frozen.get() {
+        // Interpolation starts from a fresh literal, frozen under
+        // `frozen_string_literal: true`; build the result on a copy
+        let mut bytes = s1.borrow().clone();
+        bytes.extend_from_slice(&tail);
+        let copy = RObject::string_from_bytes(bytes, is_utf8.get()).to_refcount_assigned();
+        vm.current_regs()[a].replace(copy);
+    } else {
+        s1.borrow_mut().extend_from_slice(&tail);
+    }
     Ok(())
 }
 
@@ -1872,7 +2656,7 @@ pub(crate) fn op_hash(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let a = a as usize;
     let b = b as usize;
-    let mut hash = RHashMap::default();
+    let mut hash = RHash::default();
     for i in 0..b {
         let key = vm.get_current_regs_cloned(a + i * 2)?;
         let val = vm.get_current_regs_cloned(a + i * 2 + 1)?;
@@ -1883,21 +2667,72 @@ pub(crate) fn op_hash(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     Ok(())
 }
 
-pub(crate) fn op_lambda(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+pub(crate) fn op_hashadd(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
-    let irep = Some(vm.current_irep.reps[b as usize].clone());
-    let environ = ENV {
+    let a = a as usize;
+    let hash = vm.get_current_regs_cloned(a)?;
+    let RValue::Hash(hash) = &hash.value else {
+        unreachable!("hashadd supports only hash")
+    };
+    for i in 0..b as usize {
+        let key = vm.get_current_regs_cloned(a + i * 2 + 1)?;
+        let val = vm.get_current_regs_cloned(a + i * 2 + 2)?;
+        hash.borrow_mut().insert(key.as_hash_key()?, (key, val));
+    }
+    Ok(())
+}
+
+pub(crate) fn op_hashcat(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let a = operand.as_b()? as usize;
+    let hash = vm.get_current_regs_cloned(a)?;
+    let other = vm.take_current_regs(a + 1)?;
+    let RValue::Hash(hash) = &hash.value else {
+        unreachable!("hashcat supports only hash")
+    };
+    match &other.value {
+        RValue::Hash(other) => {
+            let other = other.borrow().clone();
+            let mut hash = hash.borrow_mut();
+            for (key, val) in other.into_iter() {
+                hash.insert(key, val);
+            }
+        }
+        RValue::Nil => {}
+        _ => return Err(Error::TypeMismatch),
+    }
+    Ok(())
+}
+
+// Blocks created in the same running frame share one environment, so that
+// every closure sees the registers captured when the frame returns
+fn current_frame_env(vm: &mut VM) -> Rc<ENV> {
+    if let Some(environ) = vm.cur_env.get(&vm.current_irep.__id)
+        && !environ.expired()
+        && environ.current_regs_offset == vm.current_regs_offset
+        && environ.regs_stack.ptr_eq(&Rc::downgrade(&vm.regs_stack))
+    {
+        return environ.clone();
+    }
+    let environ = Rc::new(ENV {
         __irep_id: vm.current_irep.__id,
         upper: vm.upper.clone(),
         current_regs_offset: vm.current_regs_offset,
+        regs_stack: Rc::downgrade(&vm.regs_stack),
         is_expired: Cell::new(false),
         captured: RefCell::new(None),
-    };
-    //let nregs = vm.current_irep.nregs;
-    //environ.capture(&vm.current_regs()[0..nregs]);
-    let environ = Rc::new(environ);
+        method_owner: vm.active_callinfo().and_then(|ci| ci.method_owner.clone()),
+    });
     vm.cur_env.insert(vm.current_irep.__id, environ.clone());
     vm.has_env_ref.insert(vm.current_irep.__id, true);
+    environ
+}
+
+pub(crate) fn op_lambda(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
+    let (a, b) = operand.as_bb()?;
+    let irep = Some(vm.current_irep.reps[b as usize].clone());
+    //let nregs = vm.current_irep.nregs;
+    //environ.capture(&vm.current_regs()[0..nregs]);
+    let environ = current_frame_env(vm);
 
     let val = RObject {
         tt: RType::Proc,
@@ -1910,10 +2745,12 @@ pub(crate) fn op_lambda(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
             func: None,
             environ: Some(environ),
             block_self: Some(vm.getself()?),
+            visibility: Visibility::Public,
         }),
         object_id: u64::MAX.into(),
         singleton_class: RefCell::new(None),
-        ivar: RefCell::new(RHashMap::default()),
+        ivar: RefCell::new(IvarTable::default()),
+        frozen: Cell::new(false),
     };
     vm.current_regs()[a as usize].replace(val.to_refcount_assigned());
     Ok(())
@@ -1922,16 +2759,7 @@ pub(crate) fn op_lambda(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 pub(crate) fn op_block(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let irep = Some(vm.current_irep.reps[b as usize].clone());
-    let environ = ENV {
-        __irep_id: vm.current_irep.__id,
-        upper: vm.upper.clone(),
-        current_regs_offset: vm.current_regs_offset,
-        is_expired: Cell::new(false),
-        captured: RefCell::new(None),
-    };
-    let environ = Rc::new(environ);
-    vm.cur_env.insert(vm.current_irep.__id, environ.clone());
-    vm.has_env_ref.insert(vm.current_irep.__id, true);
+    let environ = current_frame_env(vm);
 
     let val = RObject {
         tt: RType::Proc,
@@ -1944,10 +2772,12 @@ pub(crate) fn op_block(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
             func: None,
             environ: Some(environ),
             block_self: Some(vm.getself()?),
+            visibility: Visibility::Public,
         }),
         object_id: u64::MAX.into(),
         singleton_class: RefCell::new(None),
-        ivar: RefCell::new(RHashMap::default()),
+        ivar: RefCell::new(IvarTable::default()),
+        frozen: Cell::new(false),
     };
     vm.current_regs()[a as usize].replace(val.to_refcount_assigned());
     Ok(())
@@ -1967,10 +2797,12 @@ pub(crate) fn op_method(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
             func: None,
             environ: None,
             block_self: None,
+            visibility: Visibility::Public,
         }),
         object_id: u64::MAX.into(),
         singleton_class: RefCell::new(None),
-        ivar: RefCell::new(RHashMap::default()),
+        ivar: RefCell::new(IvarTable::default()),
+        frozen: Cell::new(false),
     };
     vm.current_regs()[a as usize].replace(val.to_refcount_assigned());
     Ok(())
@@ -2039,7 +2871,10 @@ pub(crate) fn op_class(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
             .borrow_mut()
             .insert(name.clone(), class_value.clone());
     } else {
-        vm.consts.insert(name.clone(), class_value.clone());
+        vm.object_class
+            .consts
+            .borrow_mut()
+            .insert(name.clone(), class_value.clone());
     }
 
     vm.current_regs()[a as usize].replace(class_value);
@@ -2060,7 +2895,10 @@ pub(crate) fn op_module(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
             .borrow_mut()
             .insert(name.clone(), module_value);
     } else {
-        vm.consts.insert(name.clone(), module_value);
+        vm.object_class
+            .consts
+            .borrow_mut()
+            .insert(name.clone(), module_value);
     }
 
     vm.current_regs()[a as usize].replace(Rc::new(module.into()));
@@ -2070,6 +2908,7 @@ pub(crate) fn op_module(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 pub(crate) fn op_exec(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
     let (a, b) = operand.as_bb()?;
     let recv = vm.get_current_regs_cloned(a as usize)?;
+    vm.reserve_frame(a as usize)?;
 
     let upper = vm.current_breadcrumb.take();
     let new_breadcrumb = Rc::new(Breadcrumb {
@@ -2116,14 +2955,42 @@ pub(crate) fn op_def(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
         )),
     }?;
 
+    // `private` and friends without arguments set the visibility of the
+    // methods defined after them in a class body
+    let (visibility, module_function) = match vm.active_callinfo() {
+        Some(ci) => (ci.visibility.get(), ci.module_function.get()),
+        None => (Visibility::Public, false),
+    };
+    let visibility = if matches!(
+        sym.name.as_str(),
+        "initialize" | "initialize_copy" | "respond_to_missing?"
+    ) {
+        Visibility::Private
+    } else {
+        visibility
+    };
+
     // Then, define it on the receiver
     let target_ref = target.as_ref();
     match &target_ref.value {
         RValue::Class(klass) => {
+            let mut method = method;
+            if !klass.is_singleton {
+                method.visibility = visibility;
+            }
             let mut procs = klass.procs.borrow_mut();
             procs.insert(sym.name.clone(), method);
         }
         RValue::Module(module) => {
+            if module_function {
+                let sclass = target.initialize_or_get_singleton_class_for_class(vm);
+                sclass
+                    .procs
+                    .borrow_mut()
+                    .insert(sym.name.clone(), method.clone());
+            }
+            let mut method = method;
+            method.visibility = visibility;
             let mut procs = module.procs.borrow_mut();
             procs.insert(sym.name.clone(), method);
         }
@@ -2139,7 +3006,9 @@ pub(crate) fn op_def(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
             procs.insert(sym.name.clone(), method);
         }
     }
-    vm.current_regs()[a as usize].replace(RObject::symbol(sym).to_refcount_assigned());
+    invalidate_method_caches();
+    let val = vm.intern(&sym.name);
+    vm.current_regs()[a as usize].replace(val);
     Ok(())
 }
 
@@ -2171,6 +3040,7 @@ pub(crate) fn op_alias(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
 
     let mut procs = owner_module.procs.borrow_mut();
     procs.insert(new_name.name.clone(), new_method);
+    invalidate_method_caches();
 
     Ok(())
 }
@@ -2190,6 +3060,7 @@ pub(crate) fn op_undef(vm: &mut VM, operand: &Fetched) -> Result<(), Error> {
             procs.remove(&sym.name);
         }
     };
+    invalidate_method_caches();
     Ok(())
 }
 
@@ -0,0 +1,182 @@
+//! Insertion-ordered storage backing Ruby's Hash.
+//! Lookups go through a hash index into an entry vector that keeps the order
+//! keys were first inserted, so iteration matches CRuby.
+use std::rc::Rc;
+
+use super::value::{RHashMap, RObject, ValueHasher};
+
+type KeyValue = (Rc<RObject>, Rc<RObject>);
+type Entry = (ValueHasher, KeyValue);
+
+#[derive(Debug, Clone, Default)]
+pub struct RHash {
+    // Keys are shared with `entries` so that inserting a String key
+    // does not copy its bytes twice.
+    index: RHashMap<Rc<ValueHasher>, usize>,
+    // Deleted entries leave a hole until the next compaction,
+    // so removal does not shift every later position in `index`.
+    entries: Vec<Option<(Rc<ValueHasher>, KeyValue)>>,
+}
+
+impl RHash {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &ValueHasher) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn get(&self, key: &ValueHasher) -> Option<&KeyValue> {
+        let pos = *self.index.get(key)?;
+        self.entries[pos].as_ref().map(|(_, kv)| kv)
+    }
+
+    pub fn get_mut(&mut self, key: &ValueHasher) -> Option<&mut KeyValue> {
+        let pos = *self.index.get(key)?;
+        self.entries[pos].as_mut().map(|(_, kv)| kv)
+    }
+
+    /// Inserts a key-value pair. An existing key keeps both its position
+    /// and its original key object, as in CRuby; only the value is replaced.
+    pub fn insert(&mut self, key: ValueHasher, kv: KeyValue) -> Option<KeyValue> {
+        if let Some(slot) = self.get_mut(&key) {
+            let old = slot.clone();
+            slot.1 = kv.1;
+            return Some(old);
+        }
+        let key = Rc::new(key);
+        self.index.insert(key.clone(), self.entries.len());
+        self.entries.push(Some((key, kv)));
+        None
+    }
+
+    /// Removes a key. Re-inserting it later appends it at the end.
+    pub fn remove(&mut self, key: &ValueHasher) -> Option<KeyValue> {
+        let pos = self.index.remove(key)?;
+        let (_, kv) = self.entries[pos].take()?;
+        self.compact();
+        Some(kv)
+    }
+
+    /// Removes and returns the oldest entry.
+    pub fn shift(&mut self) -> Option<KeyValue> {
+        let key = self.keys().next()?.clone();
+        self.remove(&key)
+    }
+
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.entries.clear();
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&ValueHasher, &KeyValue)> {
+        self.entries
+            .iter()
+            .filter_map(|e| e.as_ref().map(|(k, kv)| (k.as_ref(), kv)))
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &ValueHasher> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &KeyValue> {
+        self.iter().map(|(_, kv)| kv)
+    }
+
+    /// Keeps only the entries for which `f` returns true, preserving order.
+    pub fn retain(&mut self, mut f: impl FnMut(&ValueHasher, &KeyValue) -> bool) {
+        for entry in self.entries.iter_mut() {
+            if let Some((k, kv)) = entry
+                && !f(k, kv)
+            {
+                self.index.remove(k);
+                *entry = None;
+            }
+        }
+        self.compact();
+    }
+
+    // Drops trailing holes right away, and rebuilds the entry vector once
+    // holes make up more than half of it.
+    fn compact(&mut self) {
+        while matches!(self.entries.last(), Some(None)) {
+            self.entries.pop();
+        }
+        if self.entries.len() - self.index.len() <= self.entries.len() / 2 {
+            return;
+        }
+        self.entries.retain(Option::is_some);
+        for (pos, entry) in self.entries.iter().enumerate() {
+            if let Some((k, _)) = entry {
+                self.index.insert(k.clone(), pos);
+            }
+        }
+    }
+}
+
+impl FromIterator<Entry> for RHash {
+    fn from_iter<T: IntoIterator<Item = Entry>>(iter: T) -> Self {
+        let mut hash = RHash::new();
+        hash.extend(iter);
+        hash
+    }
+}
+
+impl Extend<Entry> for RHash {
+    fn extend<T: IntoIterator<Item = Entry>>(&mut self, iter: T) {
+        for (k, kv) in iter {
+            self.insert(k, kv);
+        }
+    }
+}
+
+impl IntoIterator for RHash {
+    type Item = Entry;
+    type IntoIter = std::vec::IntoIter<Entry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let RHash { index, entries } = self;
+        drop(index);
+        entries
+            .into_iter()
+            .flatten()
+            .map(|(k, kv)| (Rc::unwrap_or_clone(k), kv))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+#[test]
+fn test_insertion_order_after_delete() {
+    let mut hash = RHash::new();
+    for i in 0..6 {
+        let key = Rc::new(RObject::integer(i));
+        hash.insert(key.as_hash_key().unwrap(), (key.clone(), key));
+    }
+    for i in [1, 2, 3, 0] {
+        hash.remove(&ValueHasher::Integer(i));
+    }
+    let key = Rc::new(RObject::integer(1));
+    hash.insert(key.as_hash_key().unwrap(), (key.clone(), key));
+
+    let keys: Vec<_> = hash.keys().cloned().collect();
+    assert_eq!(
+        keys,
+        vec![
+            ValueHasher::Integer(4),
+            ValueHasher::Integer(5),
+            ValueHasher::Integer(1)
+        ]
+    );
+    assert!(hash.get(&ValueHasher::Integer(5)).is_some());
+    assert!(hash.get(&ValueHasher::Integer(2)).is_none());
+}
@@ -3,9 +3,7 @@ use std::rc::Rc;
 use crate::{
     Error,
     yamrb::{
-        helpers::{
-            self, mrb_call_block, mrb_define_class_cmethod, mrb_define_cmethod, mrb_funcall,
-        },
+        helpers::{self, mrb_define_class_cmethod, mrb_define_cmethod, mrb_funcall},
         prelude::module::mrb_include_module,
         value::{RObject, RValue},
         vm::VM,
@@ -50,7 +48,7 @@ pub(crate) fn initialize_array(vm: &mut VM) {
         "delete_at",
         Box::new(mrb_array_delete_at),
     );
-    mrb_define_cmethod(vm, array_class.clone(), "each", Box::new(mrb_array_each));
+    // `each` and `each_with_index` are defined in mrblib/array.rb
     mrb_define_cmethod(vm, array_class.clone(), "empty?", Box::new(mrb_array_empty));
     mrb_define_cmethod(vm, array_class.clone(), "size", Box::new(mrb_array_size));
     mrb_define_cmethod(vm, array_class.clone(), "length", Box::new(mrb_array_size));
@@ -60,6 +58,7 @@ pub(crate) fn initialize_array(vm: &mut VM) {
         "include?",
         Box::new(mrb_array_include),
     );
+    mrb_define_cmethod(vm, array_class.clone(), "<=>", Box::new(mrb_array_cmp));
     mrb_define_cmethod(vm, array_class.clone(), "&", Box::new(mrb_array_and));
     mrb_define_cmethod(vm, array_class.clone(), "|", Box::new(mrb_array_or));
     mrb_define_cmethod(vm, array_class.clone(), "first", Box::new(mrb_array_first));
@@ -171,6 +170,7 @@ pub fn mrb_array_new(_vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
 fn mrb_array_push_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     mrb_array_push(this, args)
 }
 
@@ -208,6 +208,7 @@ pub fn mrb_array_get_index(this: Rc<RObject>, args: &[Rc<RObject>]) -> Result<Rc
 
 fn mrb_array_set_index_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     mrb_array_set_index(this, args)
 }
 
@@ -237,26 +238,6 @@ pub fn mrb_array_set_index(this: Rc<RObject>, args: &[Rc<RObject>]) -> Result<Rc
     Ok(value.clone())
 }
 
-fn mrb_array_each(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this = vm.getself()?;
-    let block = &args[0];
-    match &this.value {
-        RValue::Array(a) => {
-            let a = a.borrow();
-            for elem in a.iter() {
-                let args = vec![elem.clone()];
-                mrb_call_block(vm, block.clone(), None, &args, 0)?;
-            }
-        }
-        _ => {
-            return Err(Error::RuntimeError(
-                "Array#each must be called on an Array".to_string(),
-            ));
-        }
-    };
-    Ok(this.clone())
-}
-
 fn mrb_array_pack(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
     let format: Vec<u8> = args[0].as_ref().try_into()?;
@@ -424,6 +405,7 @@ fn mrb_array_add(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error
 // Array#clear: Removes all elements from the array (destructive)
 fn mrb_array_clear(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     this.array_borrow_mut()?.clear();
     Ok(this)
 }
@@ -431,6 +413,7 @@ fn mrb_array_clear(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Er
 // Array#delete_at: Deletes the element at the specified index (destructive)
 fn mrb_array_delete_at(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let index: i64 = args[0].as_ref().try_into()?;
     let mut arr = this.array_borrow_mut()?;
     let len = arr.len() as i64;
@@ -450,6 +433,24 @@ fn mrb_array_empty(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Er
     Ok(Rc::new(RObject::boolean(this.is_empty())))
 }
 
+// Array#<=>: Compares element by element, then by length; nil if not comparable
+fn mrb_array_cmp(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let lhs: Vec<Rc<RObject>> = this.as_ref().try_into()?;
+    let rhs: Vec<Rc<RObject>> = match &args[0].value {
+        RValue::Array(_) => args[0].as_ref().try_into()?,
+        _ => return Ok(Rc::new(RObject::nil())),
+    };
+    for (l, r) in lhs.iter().zip(rhs.iter()) {
+        let cmp = mrb_funcall(vm, Some(l.clone()), "<=>", std::slice::from_ref(r))?;
+        match &cmp.value {
+            RValue::Integer(0) => continue,
+            _ => return Ok(cmp),
+        }
+    }
+    Ok(Rc::new(RObject::integer(lhs.len().cmp(&rhs.len()) as i64)))
+}
+
 // Array#include?: Returns true if the array contains the given object
 fn mrb_array_include(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this: Vec<Rc<RObject>> = vm.getself()?.as_ref().try_into()?;
@@ -542,6 +543,7 @@ fn mrb_array_last(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Erro
 // Array#pop: Removes and returns the last element (destructive)
 fn mrb_array_pop(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let removed = this.array_borrow_mut()?.pop();
     Ok(removed.unwrap_or_else(|| Rc::new(RObject::nil())))
 }
@@ -549,6 +551,7 @@ fn mrb_array_pop(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Erro
 // Array#shift: Removes and returns the first element (destructive)
 fn mrb_array_shift(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let mut arr = this.array_borrow_mut()?;
     if arr.is_empty() {
         Ok(Rc::new(RObject::nil()))
@@ -560,6 +563,7 @@ fn mrb_array_shift(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Er
 // Array#unshift: Prepends objects to the front of the array (destructive)
 fn mrb_array_unshift(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let mut arr = this.array_borrow_mut()?;
     for (i, arg) in args.iter().enumerate() {
         arr.insert(i, arg.clone());
@@ -577,6 +581,7 @@ fn mrb_array_dup(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Erro
 // Array#uniq!: Removes duplicate elements from self (destructive)
 fn mrb_array_uniq_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let arr: Vec<Rc<RObject>> = this.as_ref().try_into()?;
 
     let unique: Vec<Rc<RObject>> = mrb_funcall(vm, Some(this.clone()), "uniq", &[])?
@@ -594,6 +599,7 @@ fn mrb_array_uniq_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>
 // Array#map!: Invokes the given block once for each element, replacing the element with the value returned by the block (destructive)
 fn mrb_array_map_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let mapped: Vec<Rc<RObject>> = mrb_funcall(vm, Some(this.clone()), "map", args)?
         .as_ref()
         .try_into()?;
@@ -605,6 +611,7 @@ fn mrb_array_map_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 // Array#select!: Invokes the given block for each element, keeping only elements for which the block returns true (destructive)
 fn mrb_array_select_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let arr: Vec<Rc<RObject>> = this.as_ref().try_into()?;
 
     let selected: Vec<Rc<RObject>> = mrb_funcall(vm, Some(this.clone()), "select", args)?
@@ -622,6 +629,7 @@ fn mrb_array_select_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject
 // Array#reject!: Invokes the given block for each element, removing elements for which the block returns true (destructive)
 fn mrb_array_reject_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let arr: Vec<Rc<RObject>> = this.as_ref().try_into()?;
 
     let rejected: Vec<Rc<RObject>> = mrb_funcall(vm, Some(this.clone()), "delete_if", args)?
@@ -639,6 +647,7 @@ fn mrb_array_reject_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject
 // Array#sort!: Sorts the array in place (destructive)
 fn mrb_array_sort_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let sorted: Vec<Rc<RObject>> = mrb_funcall(vm, Some(this.clone()), "sort", &[])?
         .as_ref()
         .try_into()?;
@@ -650,6 +659,7 @@ fn mrb_array_sort_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>
 // Array#sort_by!: Sorts the array in place by the result of the block (destructive)
 fn mrb_array_sort_by_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let sorted: Vec<Rc<RObject>> = mrb_funcall(vm, Some(this.clone()), "sort_by", args)?
         .as_ref()
         .try_into()?;
@@ -707,6 +717,7 @@ fn do_array_flatten_recursive(array: &[Rc<RObject>], result: &mut Vec<Rc<RObject
 /// Array#flatten!: Flattens self in place (recursively)
 fn mrb_array_flatten_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let arr: Vec<Rc<RObject>> = this.as_ref().try_into()?;
 
     let flattened: Vec<Rc<RObject>> = mrb_funcall(vm, Some(this.clone()), "flatten", &[])?
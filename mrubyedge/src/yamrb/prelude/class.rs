@@ -62,13 +62,30 @@ fn mrb_class_new(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error
         }
     };
 
-    let obj = RObject::instance(class).to_refcount_assigned();
+    let exception_class = vm.get_class_by_name("Exception");
+    let obj = if class.is_subclass_of(&exception_class) {
+        RObject::exception(Rc::new(RException::new(class))).to_refcount_assigned()
+    } else {
+        RObject::instance(class).to_refcount_assigned()
+    };
 
     mrb_funcall(vm, Some(obj.clone()), "initialize", args)?;
 
     Ok(obj)
 }
 
+// Accessors follow `private` and friends without arguments, as `def` does
+fn define_attr_method(vm: &mut VM, class: &Rc<RClass>, name: &str, method: RFn) {
+    let visibility = match vm.active_callinfo() {
+        Some(ci) if !class.is_singleton => ci.visibility.get(),
+        _ => Visibility::Public,
+    };
+    mrb_define_cmethod(vm, class.clone(), name, method);
+    if let Some(method) = class.procs.borrow_mut().get_mut(name) {
+        method.visibility = visibility;
+    }
+}
+
 fn mrb_class_attr_reader(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let class_ = vm.getself()?;
     let class = match &class_.value {
@@ -79,6 +96,8 @@ fn mrb_class_attr_reader(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject
             ));
         }
     };
+    // Returns the defined method names, so `private attr_reader :x` works
+    let mut defined = Vec::new();
     for arg in args.iter() {
         match arg.value {
             RValue::Symbol(ref sym) => {
@@ -88,7 +107,8 @@ fn mrb_class_attr_reader(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject
                     let key = format!("@{}", sym_id);
                     Ok(this.get_ivar(&key))
                 };
-                mrb_define_cmethod(vm, class.clone(), sym_id, Box::new(method));
+                define_attr_method(vm, &class, sym_id, Box::new(method));
+                defined.push(vm.intern(sym_id));
             }
             RValue::Nil => {
                 // skip
@@ -100,7 +120,7 @@ fn mrb_class_attr_reader(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject
             }
         }
     }
-    Ok(Rc::new(RObject::nil()))
+    Ok(RObject::array(defined).to_refcount_assigned())
 }
 
 fn mrb_class_attr_writer(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -113,19 +133,23 @@ fn mrb_class_attr_writer(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject
             ));
         }
     };
+    // Returns the defined method names, so `private attr_reader :x` works
+    let mut defined = Vec::new();
     for arg in args.iter() {
         match arg.value {
             RValue::Symbol(ref sym) => {
                 let sym_id: &'static str = sym.name.clone().leak();
                 let method = move |vm: &mut VM, args: &[Rc<RObject>]| {
                     let this = vm.getself()?;
+                    this.check_frozen(vm)?;
                     let key = format!("@{}", sym_id);
                     let value = args[0].clone();
                     this.set_ivar(&key, value.clone());
                     Ok(value)
                 };
                 let sym_id = format!("{}=", sym_id);
-                mrb_define_cmethod(vm, class.clone(), &sym_id, Box::new(method));
+                define_attr_method(vm, &class, &sym_id, Box::new(method));
+                defined.push(vm.intern(&sym_id));
             }
             RValue::Nil => {
                 // skip
@@ -137,12 +161,18 @@ fn mrb_class_attr_writer(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject
             }
         }
     }
-    Ok(Rc::new(RObject::nil()))
+    Ok(RObject::array(defined).to_refcount_assigned())
 }
 
 fn mrb_class_attr_acceccor(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    mrb_class_attr_reader(vm, args)?;
-    mrb_class_attr_writer(vm, args)
+    let readers: Vec<Rc<RObject>> = mrb_class_attr_reader(vm, args)?.as_ref().try_into()?;
+    let writers: Vec<Rc<RObject>> = mrb_class_attr_writer(vm, args)?.as_ref().try_into()?;
+    let defined = readers
+        .into_iter()
+        .zip(writers)
+        .flat_map(|(reader, writer)| [reader, writer])
+        .collect();
+    Ok(RObject::array(defined).to_refcount_assigned())
 }
 
 fn mrb_class_ancestors(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
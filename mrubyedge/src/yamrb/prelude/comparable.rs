@@ -0,0 +1,210 @@
+use std::{cmp::Ordering, rc::Rc};
+
+use crate::{
+    Error,
+    yamrb::{
+        helpers::{mrb_define_module_cmethod, mrb_funcall},
+        value::{RObject, RValue},
+        vm::VM,
+    },
+};
+
+pub(crate) fn initialize_comparable(vm: &mut VM) {
+    let comparable_module = vm.define_module("Comparable", None);
+
+    mrb_define_module_cmethod(
+        vm,
+        comparable_module.clone(),
+        "<",
+        Box::new(mrb_comparable_lt),
+    );
+    mrb_define_module_cmethod(
+        vm,
+        comparable_module.clone(),
+        "<=",
+        Box::new(mrb_comparable_le),
+    );
+    mrb_define_module_cmethod(
+        vm,
+        comparable_module.clone(),
+        ">",
+        Box::new(mrb_comparable_gt),
+    );
+    mrb_define_module_cmethod(
+        vm,
+        comparable_module.clone(),
+        ">=",
+        Box::new(mrb_comparable_ge),
+    );
+    mrb_define_module_cmethod(
+        vm,
+        comparable_module.clone(),
+        "==",
+        Box::new(mrb_comparable_eq),
+    );
+    mrb_define_module_cmethod(
+        vm,
+        comparable_module.clone(),
+        "between?",
+        Box::new(mrb_comparable_between),
+    );
+    mrb_define_module_cmethod(
+        vm,
+        comparable_module.clone(),
+        "clamp",
+        Box::new(mrb_comparable_clamp),
+    );
+}
+
+/// Converts a `<=>` result to an Ordering; nil (or anything non-numeric)
+/// means the two values cannot be compared.
+fn ordering_of(cmp: &RObject) -> Option<Ordering> {
+    match &cmp.value {
+        RValue::Integer(n) => Some(n.cmp(&0)),
+        RValue::Float(f) => f.partial_cmp(&0.0),
+        RValue::BigInt(n) => Some(if n.is_negative() {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }),
+        _ => None,
+    }
+}
+
+fn comparison_failed(vm: &mut VM, lhs: &Rc<RObject>, rhs: &Rc<RObject>) -> Error {
+    let lhs = lhs.get_class(vm).full_name();
+    let rhs = rhs.get_class(vm).full_name();
+    Error::ArgumentError(format!("comparison of {} with {} failed", lhs, rhs))
+}
+
+/// Compares two values with `lhs <=> rhs`, raising ArgumentError when
+/// they cannot be compared.
+pub(crate) fn mrb_compare(
+    vm: &mut VM,
+    lhs: &Rc<RObject>,
+    rhs: &Rc<RObject>,
+) -> Result<Ordering, Error> {
+    let cmp = mrb_funcall(vm, Some(lhs.clone()), "<=>", std::slice::from_ref(rhs))?;
+    ordering_of(&cmp).ok_or_else(|| comparison_failed(vm, lhs, rhs))
+}
+
+/// Stable merge sort with a fallible comparison. The first error aborts the
+/// sort, and an inconsistent user-defined `<=>` cannot break the algorithm.
+pub(crate) fn mrb_sort_by<T, F>(vm: &mut VM, values: Vec<T>, cmp: &mut F) -> Result<Vec<T>, Error>
+where
+    F: FnMut(&mut VM, &T, &T) -> Result<Ordering, Error>,
+{
+    if values.len() <= 1 {
+        return Ok(values);
+    }
+    let mut left = values;
+    let right = left.split_off(left.len() / 2);
+    let left = mrb_sort_by(vm, left, cmp)?;
+    let right = mrb_sort_by(vm, right, cmp)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        if cmp(vm, l, r)? == Ordering::Greater {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
+        }
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+fn compare_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Ordering, Error> {
+    let this = vm.getself()?;
+    let other = args
+        .first()
+        .ok_or_else(|| Error::ArgumentError("wrong number of arguments".to_string()))?;
+    mrb_compare(vm, &this, other)
+}
+
+fn mrb_comparable_lt(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let ord = compare_self(vm, args)?;
+    Ok(Rc::new(RObject::boolean(ord.is_lt())))
+}
+
+fn mrb_comparable_le(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let ord = compare_self(vm, args)?;
+    Ok(Rc::new(RObject::boolean(ord.is_le())))
+}
+
+fn mrb_comparable_gt(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let ord = compare_self(vm, args)?;
+    Ok(Rc::new(RObject::boolean(ord.is_gt())))
+}
+
+fn mrb_comparable_ge(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let ord = compare_self(vm, args)?;
+    Ok(Rc::new(RObject::boolean(ord.is_ge())))
+}
+
+// Comparable#==: true if the same object or `<=>` returns 0; incomparable values are not equal
+fn mrb_comparable_eq(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let other = &args[0];
+    if Rc::ptr_eq(&this, other) {
+        return Ok(Rc::new(RObject::boolean(true)));
+    }
+    let cmp = mrb_funcall(vm, Some(this), "<=>", std::slice::from_ref(other))?;
+    Ok(Rc::new(RObject::boolean(
+        ordering_of(&cmp) == Some(Ordering::Equal),
+    )))
+}
+
+fn mrb_comparable_between(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    if args.len() < 2 {
+        return Err(Error::ArgumentError(
+            "wrong number of arguments (expected 2)".to_string(),
+        ));
+    }
+    let this = vm.getself()?;
+    let between =
+        mrb_compare(vm, &this, &args[0])?.is_ge() && mrb_compare(vm, &this, &args[1])?.is_le();
+    Ok(Rc::new(RObject::boolean(between)))
+}
+
+// Comparable#clamp: accepts (min, max) or an inclusive Range
+fn mrb_comparable_clamp(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (min, max) = match args {
+        [min, max] => (min.clone(), max.clone()),
+        [range] => match &range.value {
+            RValue::Range(_, _, true) => {
+                return Err(Error::ArgumentError(
+                    "cannot clamp with an exclusive range".to_string(),
+                ));
+            }
+            RValue::Range(start, end, false) => (start.clone(), end.clone()),
+            _ => {
+                return Err(Error::ArgumentError(
+                    "wrong argument type (expected Range)".to_string(),
+                ));
+            }
+        },
+        _ => {
+            return Err(Error::ArgumentError(
+                "wrong number of arguments (expected 1..2)".to_string(),
+            ));
+        }
+    };
+    if !min.is_nil() && !max.is_nil() && mrb_compare(vm, &min, &max)?.is_gt() {
+        return Err(Error::ArgumentError(
+            "min argument must be less than or equal to max argument".to_string(),
+        ));
+    }
+
+    let this = vm.getself()?;
+    if !min.is_nil() && mrb_compare(vm, &this, &min)?.is_lt() {
+        return Ok(min);
+    }
+    if !max.is_nil() && mrb_compare(vm, &this, &max)?.is_gt() {
+        return Ok(max);
+    }
+    Ok(this)
+}
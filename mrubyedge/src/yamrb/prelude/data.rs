@@ -0,0 +1,223 @@
+use std::rc::Rc;
+
+use crate::{
+    Error,
+    yamrb::{
+        helpers::{mrb_define_class_cmethod, mrb_define_cmethod},
+        prelude::{
+            enumerator::block_arg,
+            r#struct::{
+                class_members, class_of_self, define_members, eval_class_block, given_kwargs,
+                inspect_members, member_names, members_deconstruct_keys, members_eq, members_of,
+                members_to_h, symbols, unknown_keywords, values_of,
+            },
+        },
+        value::*,
+        vm::VM,
+    },
+};
+
+pub(crate) fn initialize_data(vm: &mut VM) {
+    let data_class = vm.define_standard_class("Data");
+
+    mrb_define_class_cmethod(
+        vm,
+        data_class.clone(),
+        "define",
+        Box::new(mrb_data_s_define),
+    );
+    mrb_define_class_cmethod(vm, data_class.clone(), "new", Box::new(mrb_data_s_new));
+    mrb_define_class_cmethod(vm, data_class.clone(), "[]", Box::new(mrb_data_s_new));
+    mrb_define_class_cmethod(
+        vm,
+        data_class.clone(),
+        "members",
+        Box::new(mrb_data_s_members),
+    );
+
+    mrb_define_cmethod(vm, data_class.clone(), "==", Box::new(mrb_data_eq));
+    mrb_define_cmethod(vm, data_class.clone(), "eql?", Box::new(mrb_data_eq));
+    mrb_define_cmethod(vm, data_class.clone(), "with", Box::new(mrb_data_with));
+    mrb_define_cmethod(vm, data_class.clone(), "to_h", Box::new(mrb_data_to_h));
+    mrb_define_cmethod(
+        vm,
+        data_class.clone(),
+        "deconstruct",
+        Box::new(mrb_data_deconstruct),
+    );
+    mrb_define_cmethod(
+        vm,
+        data_class.clone(),
+        "deconstruct_keys",
+        Box::new(mrb_data_deconstruct_keys),
+    );
+    mrb_define_cmethod(
+        vm,
+        data_class.clone(),
+        "members",
+        Box::new(mrb_data_members),
+    );
+    mrb_define_cmethod(
+        vm,
+        data_class.clone(),
+        "inspect",
+        Box::new(mrb_data_inspect),
+    );
+    mrb_define_cmethod(vm, data_class, "to_s", Box::new(mrb_data_inspect));
+}
+
+fn keyword_list(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| format!(":{}", name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+// Data.define(*members) defines a new value class with readers only
+fn mrb_data_s_define(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let class = class_of_self(vm)?;
+    let block = block_arg(args);
+    let args = &args[..args.len() - block.is_some() as usize];
+    let members = member_names(vm, args)?;
+    let new_class = vm.define_anonymous_class(class);
+    define_members(vm, &new_class, &members, false);
+    let object = RObject::class(new_class.clone(), vm);
+    if let Some(block) = block {
+        eval_class_block(vm, &object, new_class, block)?;
+    }
+    Ok(object)
+}
+
+// Data subclasses take either positional or keyword arguments, all required
+fn mrb_data_s_new(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let class = class_of_self(vm)?;
+    let data_class = vm.get_class_by_name("Data");
+    if Rc::ptr_eq(&class, &data_class) {
+        return Err(Error::NoMethodError(
+            "undefined method 'new' for class Data".to_string(),
+        ));
+    }
+    let members = class_members(vm, &class);
+    if args.len() > members.len() {
+        return Err(Error::ArgumentError(format!(
+            "wrong number of arguments (given {}, expected 0..{})",
+            args.len(),
+            members.len()
+        )));
+    }
+
+    let mut values: RHashMap<String, Rc<RObject>> = members
+        .iter()
+        .zip(args.iter())
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    if let Some(kwargs) = given_kwargs(vm) {
+        let unknown = unknown_keywords(&members, &kwargs);
+        if !unknown.is_empty() {
+            return Err(Error::ArgumentError(format!(
+                "unknown keyword{}: {}",
+                plural(unknown.len()),
+                keyword_list(&unknown)
+            )));
+        }
+        values.extend(kwargs);
+    }
+    let missing: Vec<&str> = members
+        .iter()
+        .filter(|name| !values.contains_key(*name))
+        .map(|name| name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(Error::ArgumentError(format!(
+            "missing keyword{}: {}",
+            plural(missing.len()),
+            keyword_list(&missing)
+        )));
+    }
+
+    let obj = RObject::instance(class).to_refcount_assigned();
+    for name in members.iter() {
+        obj.set_ivar(name, values[name].clone());
+    }
+    obj.freeze();
+    Ok(obj)
+}
+
+fn mrb_data_s_members(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let class = class_of_self(vm)?;
+    let members = class_members(vm, &class);
+    Ok(symbols(&members))
+}
+
+fn mrb_data_eq(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let eq = members_eq(vm, &this, &args[0])?;
+    Ok(Rc::new(RObject::boolean(eq)))
+}
+
+// Data#with(**kwargs): a copy with the given members replaced
+fn mrb_data_with(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    if !args.is_empty() {
+        return Err(Error::ArgumentError(format!(
+            "wrong number of arguments (given {}, expected 0)",
+            args.len()
+        )));
+    }
+    let Some(kwargs) = given_kwargs(vm) else {
+        return Ok(this);
+    };
+    let members = members_of(vm, &this);
+    let unknown = unknown_keywords(&members, &kwargs);
+    if !unknown.is_empty() {
+        return Err(Error::ArgumentError(format!(
+            "unknown keyword{}: {}",
+            plural(unknown.len()),
+            keyword_list(&unknown)
+        )));
+    }
+
+    let class = this.get_class(vm);
+    let obj = RObject::instance(class).to_refcount_assigned();
+    for name in members.iter() {
+        let value = kwargs
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| this.get_ivar(name));
+        obj.set_ivar(name, value);
+    }
+    obj.freeze();
+    Ok(obj)
+}
+
+fn mrb_data_to_h(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    members_to_h(vm, &this)
+}
+
+fn mrb_data_deconstruct(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let values = values_of(vm, &this);
+    Ok(RObject::array(values).to_refcount_assigned())
+}
+
+fn mrb_data_deconstruct_keys(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    members_deconstruct_keys(vm, &this, &args[0])
+}
+
+fn mrb_data_members(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let members = members_of(vm, &this);
+    Ok(symbols(&members))
+}
+
+fn mrb_data_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    inspect_members(vm, &this, "data")
+}
@@ -0,0 +1,102 @@
+use std::rc::Rc;
+
+use crate::{
+    Error,
+    yamrb::{
+        helpers::mrb_define_cmethod,
+        value::{RObject, RValue},
+        vm::VM,
+    },
+};
+
+// Strings only distinguish UTF-8 from binary, so these are the only encodings
+const UTF_8: &str = "UTF-8";
+const ASCII_8BIT: &str = "ASCII-8BIT";
+
+const NAME_KEY: &str = "name";
+
+pub(crate) fn initialize_encoding(vm: &mut VM) {
+    let encoding_class = vm.define_standard_class("Encoding");
+
+    let utf8 = RObject::instance(encoding_class.clone()).to_refcount_assigned();
+    utf8.set_ivar(
+        NAME_KEY,
+        RObject::string(UTF_8.to_string()).to_refcount_assigned(),
+    );
+    let binary = RObject::instance(encoding_class.clone()).to_refcount_assigned();
+    binary.set_ivar(
+        NAME_KEY,
+        RObject::string(ASCII_8BIT.to_string()).to_refcount_assigned(),
+    );
+    {
+        let mut const_table = encoding_class.consts.borrow_mut();
+        const_table.insert("UTF_8".to_string(), utf8);
+        const_table.insert("ASCII_8BIT".to_string(), binary.clone());
+        const_table.insert("BINARY".to_string(), binary);
+    }
+
+    mrb_define_cmethod(
+        vm,
+        encoding_class.clone(),
+        "name",
+        Box::new(mrb_encoding_name),
+    );
+    mrb_define_cmethod(
+        vm,
+        encoding_class.clone(),
+        "to_s",
+        Box::new(mrb_encoding_name),
+    );
+    mrb_define_cmethod(
+        vm,
+        encoding_class,
+        "inspect",
+        Box::new(mrb_encoding_inspect),
+    );
+}
+
+/// `Encoding::UTF_8` or `Encoding::ASCII_8BIT`
+pub(crate) fn encoding_object(vm: &mut VM, is_utf8: bool) -> Rc<RObject> {
+    let encoding_class = vm.get_class_by_name("Encoding");
+    let name = if is_utf8 { "UTF_8" } else { "ASCII_8BIT" };
+    encoding_class
+        .consts
+        .borrow()
+        .get(name)
+        .cloned()
+        .unwrap_or_else(|| Rc::new(RObject::nil()))
+}
+
+/// Whether an Encoding or encoding name given to `force_encoding` means UTF-8.
+pub(crate) fn encoding_is_utf8(arg: &Rc<RObject>) -> Result<bool, Error> {
+    let name: String = match &arg.value {
+        RValue::String(_, _) => arg.as_ref().try_into()?,
+        RValue::Instance(_) => arg.get_ivar(NAME_KEY).as_ref().try_into()?,
+        _ => {
+            return Err(Error::TaggedError(
+                "TypeError",
+                "no implicit conversion into String".to_string(),
+            ));
+        }
+    };
+    match name.to_ascii_uppercase().as_str() {
+        "UTF-8" | "UTF8" | "US-ASCII" | "ASCII" => Ok(true),
+        "ASCII-8BIT" | "BINARY" => Ok(false),
+        _ => Err(Error::ArgumentError(format!(
+            "unknown encoding name - {}",
+            name
+        ))),
+    }
+}
+
+fn mrb_encoding_name(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let name: String = this.get_ivar(NAME_KEY).as_ref().try_into()?;
+    Ok(RObject::string(name).to_refcount_assigned())
+}
+
+fn mrb_encoding_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let name: String = this.get_ivar(NAME_KEY).as_ref().try_into()?;
+    Ok(RObject::string(format!("#<Encoding:{}>", name)).to_refcount_assigned())
+}
@@ -1,10 +1,17 @@
-use std::{cell::Cell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 use crate::{
     Error,
     yamrb::{
-        helpers::{mrb_call_block, mrb_define_module_cmethod, mrb_funcall},
-        value::{RFn, RObject, RProc},
+        helpers::{mrb_call_block, mrb_define_module_cmethod, mrb_funcall, mrb_funcall_with_block},
+        prelude::comparable::{mrb_compare, mrb_sort_by},
+        prelude::enumerator::{
+            Step, block_arg, mrb_each_until, mrb_lazy_new, mrb_to_enum, yielded_value,
+        },
+        value::{RFn, RObject, RProc, Visibility},
         vm::VM,
     },
 };
@@ -120,9 +127,21 @@ pub(crate) fn initialize_enumerable(vm: &mut VM) {
         "sum",
         Box::new(mrb_enumerable_sum),
     );
+    mrb_define_module_cmethod(
+        vm,
+        enumerable_module.clone(),
+        "first",
+        Box::new(mrb_enumerable_first),
+    );
+    mrb_define_module_cmethod(
+        vm,
+        enumerable_module.clone(),
+        "lazy",
+        Box::new(mrb_enumerable_lazy),
+    );
 }
 
-fn rproc_from_rust_block(vm: &mut VM, rfn: RFn) -> Result<Rc<RObject>, Error> {
+pub(crate) fn rproc_from_rust_block(vm: &mut VM, rfn: RFn) -> Result<Rc<RObject>, Error> {
     vm.push_fnblock(Rc::new(rfn))?;
     let block = RProc {
         is_rb_func: false,
@@ -133,6 +152,7 @@ fn rproc_from_rust_block(vm: &mut VM, rfn: RFn) -> Result<Rc<RObject>, Error> {
         func: None,
         environ: None,
         block_self: vm.getself().ok(),
+        visibility: Visibility::Public,
     };
     Ok(RObject::proc(block).to_refcount_assigned())
 }
@@ -146,14 +166,14 @@ fn mrb_enumerable_to_a(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>
             vm,
             Some(results_ref.clone()),
             "push",
-            std::slice::from_ref(&args[0]),
+            &[yielded_value(args)],
         )?;
         Ok(Rc::new(RObject::nil()))
     });
 
     let this = vm.getself()?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     Ok(results)
@@ -161,10 +181,10 @@ fn mrb_enumerable_to_a(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>
 
 // Enumerable#map: Returns a new array with the results of running block once for every element
 fn mrb_enumerable_map(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let original_block = args
-        .last()
-        .cloned()
-        .ok_or_else(|| Error::ArgumentError("block should be specified".to_string()))?;
+    let Some(original_block) = block_arg(args) else {
+        let this = vm.getself()?;
+        return Ok(mrb_to_enum(vm, this, "map", &[]));
+    };
     let results: Rc<RObject> = RObject::array(vec![]).to_refcount_assigned();
     let results_ref = results.clone();
     let wrapping_block: RFn = Box::new(move |vm: &mut VM, args: &[Rc<RObject>]| {
@@ -181,7 +201,7 @@ fn mrb_enumerable_map(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
     let this = vm.getself()?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     Ok(results)
@@ -189,10 +209,10 @@ fn mrb_enumerable_map(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
 // Enumerable#find: Returns the first element for which the block returns true
 fn mrb_enumerable_find(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let original_block = args
-        .last()
-        .cloned()
-        .ok_or_else(|| Error::ArgumentError("block should be specified".to_string()))?;
+    let Some(original_block) = block_arg(args) else {
+        let this = vm.getself()?;
+        return Ok(mrb_to_enum(vm, this, "find", &[]));
+    };
     let found = Cell::new(false);
     let result_box: Rc<RObject> = RObject::array(vec![]).to_refcount_assigned();
     let result_box_ref = result_box.clone();
@@ -216,7 +236,7 @@ fn mrb_enumerable_find(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
     });
     let this = vm.getself()?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     let found = mrb_funcall(vm, result_box.into(), "pop", &[])?;
@@ -225,10 +245,10 @@ fn mrb_enumerable_find(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
 // Enumerable#select: Returns a new array containing all elements for which the block returns true
 fn mrb_enumerable_select(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let original_block = args
-        .last()
-        .cloned()
-        .ok_or_else(|| Error::ArgumentError("block should be specified".to_string()))?;
+    let Some(original_block) = block_arg(args) else {
+        let this = vm.getself()?;
+        return Ok(mrb_to_enum(vm, this, "select", &[]));
+    };
     let results: Rc<RObject> = RObject::array(vec![]).to_refcount_assigned();
     let results_ref = results.clone();
     let wrapping_block: RFn = Box::new(move |vm: &mut VM, args: &[Rc<RObject>]| {
@@ -247,7 +267,7 @@ fn mrb_enumerable_select(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject
 
     let this = vm.getself()?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     Ok(results)
@@ -276,7 +296,7 @@ fn mrb_enumerable_all(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
     let this = vm.getself()?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     Ok(Rc::new(RObject::boolean(all_true.get())))
@@ -305,7 +325,7 @@ fn mrb_enumerable_any(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
     let this = vm.getself()?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     Ok(Rc::new(RObject::boolean(found_true.get())))
@@ -334,8 +354,9 @@ fn mrb_enumerable_delete_if(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObj
     });
 
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     Ok(results)
@@ -343,10 +364,10 @@ fn mrb_enumerable_delete_if(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObj
 
 // Enumerable#each_with_index: Calls block with two arguments, the item and its index
 fn mrb_enumerable_each_with_index(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let original_block = args
-        .last()
-        .cloned()
-        .ok_or_else(|| Error::ArgumentError("block should be specified".to_string()))?;
+    let Some(original_block) = block_arg(args) else {
+        let this = vm.getself()?;
+        return Ok(mrb_to_enum(vm, this, "each_with_index", &[]));
+    };
     let index = Rc::new(Cell::new(0i64));
     let index_ref = index.clone();
     let wrapping_block: RFn = Box::new(move |vm: &mut VM, args: &[Rc<RObject>]| {
@@ -361,7 +382,7 @@ fn mrb_enumerable_each_with_index(vm: &mut VM, args: &[Rc<RObject>]) -> Result<R
 
     let this = vm.getself()?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     Ok(this)
@@ -371,32 +392,18 @@ fn mrb_enumerable_each_with_index(vm: &mut VM, args: &[Rc<RObject>]) -> Result<R
 fn mrb_enumerable_sort(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
     let array = mrb_funcall(vm, Some(this), "to_a", &[])?;
-    let mut collected: Vec<Rc<RObject>> = array.as_ref().try_into()?;
-
-    collected.sort_by(|a, b| {
-        let args = vec![b.clone()];
-        let cmp_result = mrb_funcall(vm, Some(a.clone()), "<=>", &args);
-        match cmp_result {
-            Ok(cmp_obj) => {
-                let cmp_val: Result<i64, _> = cmp_obj.as_ref().try_into();
-                match cmp_val {
-                    Ok(v) => v.cmp(&0),
-                    Err(_) => std::cmp::Ordering::Equal,
-                }
-            }
-            Err(_) => std::cmp::Ordering::Equal,
-        }
-    });
+    let collected: Vec<Rc<RObject>> = array.as_ref().try_into()?;
 
-    Ok(RObject::array(collected).to_refcount_assigned())
+    let sorted = mrb_sort_by(vm, collected, &mut mrb_compare)?;
+    Ok(RObject::array(sorted).to_refcount_assigned())
 }
 
 // Enumerable#sort_by: Returns an array with elements sorted by the block's return value
 fn mrb_enumerable_sort_by(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let original_block = args
-        .last()
-        .cloned()
-        .ok_or_else(|| Error::ArgumentError("block should be specified".to_string()))?;
+    let Some(original_block) = block_arg(args) else {
+        let this = vm.getself()?;
+        return Ok(mrb_to_enum(vm, this, "sort_by", &[]));
+    };
 
     // Collect elements first using to_a
     let this = vm.getself()?;
@@ -416,26 +423,8 @@ fn mrb_enumerable_sort_by(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObjec
         sort_keys.push(key);
     }
 
-    let mut elements = elements;
-    let mut sort_keys = sort_keys;
-
-    let mut pairs: Vec<(Rc<RObject>, Rc<RObject>)> =
-        elements.drain(..).zip(sort_keys.drain(..)).collect();
-
-    pairs.sort_by(|a, b| {
-        let args = vec![b.1.clone()];
-        let cmp_result = mrb_funcall(vm, Some(a.1.clone()), "<=>", &args);
-        match cmp_result {
-            Ok(cmp_obj) => {
-                let cmp_val: Result<i64, _> = cmp_obj.as_ref().try_into();
-                match cmp_val {
-                    Ok(v) => v.cmp(&0),
-                    Err(_) => std::cmp::Ordering::Equal,
-                }
-            }
-            Err(_) => std::cmp::Ordering::Equal,
-        }
-    });
+    let pairs: Vec<(Rc<RObject>, Rc<RObject>)> = elements.into_iter().zip(sort_keys).collect();
+    let pairs = mrb_sort_by(vm, pairs, &mut |vm, a, b| mrb_compare(vm, &a.1, &b.1))?;
 
     let sorted: Vec<Rc<RObject>> = pairs.into_iter().map(|(elem, _)| elem).collect();
     Ok(RObject::array(sorted).to_refcount_assigned())
@@ -453,11 +442,7 @@ fn mrb_enumerable_max(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
     let mut max = collected[0].clone();
     for elem in collected.iter().skip(1) {
-        let args = vec![elem.clone()];
-        let cmp: i64 = mrb_funcall(vm, Some(max.clone()), "<=>", &args)?
-            .as_ref()
-            .try_into()?;
-        if cmp < 0 {
+        if mrb_compare(vm, &max, elem)?.is_lt() {
             max = elem.clone();
         }
     }
@@ -476,11 +461,7 @@ fn mrb_enumerable_min(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
     let mut min = collected[0].clone();
     for elem in collected.iter().skip(1) {
-        let args = vec![elem.clone()];
-        let cmp: i64 = mrb_funcall(vm, Some(min.clone()), "<=>", &args)?
-            .as_ref()
-            .try_into()?;
-        if cmp > 0 {
+        if mrb_compare(vm, &min, elem)?.is_gt() {
             min = elem.clone();
         }
     }
@@ -504,19 +485,10 @@ fn mrb_enumerable_minmax(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObjec
     let mut max = collected[0].clone();
 
     for elem in collected.iter().skip(1) {
-        let args = vec![elem.clone()];
-        let cmp_min: i64 = mrb_funcall(vm, Some(min.clone()), "<=>", &args)?
-            .as_ref()
-            .try_into()?;
-        if cmp_min > 0 {
+        if mrb_compare(vm, &min, elem)?.is_gt() {
             min = elem.clone();
         }
-
-        let args = vec![elem.clone()];
-        let cmp_max: i64 = mrb_funcall(vm, Some(max.clone()), "<=>", &args)?
-            .as_ref()
-            .try_into()?;
-        if cmp_max < 0 {
+        if mrb_compare(vm, &max, elem)?.is_lt() {
             max = elem.clone();
         }
     }
@@ -542,7 +514,7 @@ fn mrb_enumerable_compact(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObje
 
     let this = vm.getself()?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     Ok(results)
@@ -562,7 +534,7 @@ fn mrb_enumerable_count(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>
 
         let this = vm.getself()?;
         let block = rproc_from_rust_block(vm, wrapping_block)?;
-        mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+        mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
         vm.pop_fnblock()?;
     } else {
         // Count elements matching the block condition
@@ -579,7 +551,7 @@ fn mrb_enumerable_count(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>
 
         let this = vm.getself()?;
         let block = rproc_from_rust_block(vm, wrapping_block)?;
-        mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+        mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
         vm.pop_fnblock()?;
     }
 
@@ -659,7 +631,7 @@ fn mrb_enumerable_reduce(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject
 
     let this = vm.getself()?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     // Return the final accumulator value
@@ -702,10 +674,63 @@ fn mrb_enumerable_sum(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
     let this = vm.getself()?;
     let block = rproc_from_rust_block(vm, wrapping_block)?;
-    mrb_funcall(vm, Some(this.clone()), "each", &[block])?;
+    mrb_funcall_with_block(vm, Some(this.clone()), "each", &[], Some(block))?;
     vm.pop_fnblock()?;
 
     // Return the final accumulator value
     let result = mrb_funcall(vm, Some(accumulator), "first", &[])?;
     Ok(result)
 }
+
+// Enumerable#first: Returns the first element, or an array of the first n elements
+fn mrb_enumerable_first(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let count = match args.first() {
+        Some(n) => {
+            let n: i64 = n.as_ref().try_into()?;
+            if n < 0 {
+                return Err(Error::ArgumentError(
+                    "attempt to take negative size".to_string(),
+                ));
+            }
+            Some(n as usize)
+        }
+        None => None,
+    };
+    let limit = count.unwrap_or(1);
+    let results = Rc::new(RefCell::new(Vec::new()));
+    if limit > 0 {
+        let results_ref = results.clone();
+        let this = vm.getself()?;
+        // Stops the iteration as soon as enough elements are taken,
+        // so this works on endless sources as well
+        mrb_each_until(
+            vm,
+            this,
+            "each",
+            &[],
+            Box::new(move |_vm, args| {
+                let mut results = results_ref.borrow_mut();
+                results.push(yielded_value(args));
+                if results.len() >= limit {
+                    return Ok(Step::Stop);
+                }
+                Ok(Step::Next(Rc::new(RObject::nil())))
+            }),
+        )?;
+    }
+
+    let results = results.take();
+    match count {
+        Some(_) => Ok(RObject::array(results).to_refcount_assigned()),
+        None => Ok(results
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| Rc::new(RObject::nil()))),
+    }
+}
+
+// Enumerable#lazy: Returns an Enumerator::Lazy evaluating chained operations on demand
+fn mrb_enumerable_lazy(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    Ok(mrb_lazy_new(vm, this))
+}
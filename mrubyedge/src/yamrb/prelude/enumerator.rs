@@ -0,0 +1,991 @@
+//! Enumerator and Enumerator::Lazy.
+//! An Enumerator calls its source iterator again for every iteration.
+//! `next` and `peek` run the source once, in a fiber suspended at every
+//! value. Native iterators cannot be suspended, so a source that is a
+//! native method is read to its end on the first `next` instead. A Lazy
+//! reads the values of its parent one at a time and applies its operation.
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::{
+    Error,
+    yamrb::{
+        helpers::{
+            mrb_call_block, mrb_call_inspect, mrb_define_class_cmethod, mrb_define_cmethod,
+            mrb_funcall, mrb_funcall_with_block,
+        },
+        prelude::{
+            enumerable::rproc_from_rust_block,
+            fiber::{fiber_alive, fiber_resume, mrb_fiber_for_method, mrb_fiber_new},
+            module::mrb_include_module,
+        },
+        value::*,
+        vm::VM,
+    },
+};
+
+pub(crate) fn initialize_enumerator(vm: &mut VM) {
+    let enumerator_class = vm.define_standard_class("Enumerator");
+    let enumerable_module = vm.get_module_by_name("Enumerable");
+    mrb_include_module(&enumerator_class, enumerable_module).expect("failed to include Enumerable");
+
+    mrb_define_class_cmethod(
+        vm,
+        enumerator_class.clone(),
+        "new",
+        Box::new(mrb_enumerator_new),
+    );
+    mrb_define_cmethod(
+        vm,
+        enumerator_class.clone(),
+        "each",
+        Box::new(mrb_enumerator_each),
+    );
+    mrb_define_cmethod(
+        vm,
+        enumerator_class.clone(),
+        "next",
+        Box::new(mrb_enumerator_next),
+    );
+    mrb_define_cmethod(
+        vm,
+        enumerator_class.clone(),
+        "peek",
+        Box::new(mrb_enumerator_peek),
+    );
+    mrb_define_cmethod(
+        vm,
+        enumerator_class.clone(),
+        "rewind",
+        Box::new(mrb_enumerator_rewind),
+    );
+    mrb_define_cmethod(
+        vm,
+        enumerator_class.clone(),
+        "size",
+        Box::new(mrb_enumerator_size),
+    );
+    mrb_define_cmethod(
+        vm,
+        enumerator_class.clone(),
+        "with_index",
+        Box::new(mrb_enumerator_with_index),
+    );
+    mrb_define_cmethod(
+        vm,
+        enumerator_class.clone(),
+        "each_with_index",
+        Box::new(mrb_enumerator_with_index),
+    );
+    mrb_define_cmethod(
+        vm,
+        enumerator_class.clone(),
+        "with_object",
+        Box::new(mrb_enumerator_with_object),
+    );
+    mrb_define_cmethod(
+        vm,
+        enumerator_class.clone(),
+        "inspect",
+        Box::new(mrb_enumerator_inspect),
+    );
+
+    let enumerator_module = enumerator_class.module.clone();
+    let yielder_class = vm.define_class("Yielder", None, Some(enumerator_module.clone()));
+    vm.builtin_class_table
+        .insert("Enumerator::Yielder", yielder_class);
+    // Yielder's methods are defined in mrblib/enumerator.rb, so a fiber
+    // can suspend in the block they call
+
+    let lazy_class = vm.define_class(
+        "Lazy",
+        Some(enumerator_class.clone()),
+        Some(enumerator_module),
+    );
+    vm.builtin_class_table
+        .insert("Enumerator::Lazy", lazy_class.clone());
+    mrb_define_cmethod(vm, lazy_class.clone(), "each", Box::new(mrb_lazy_each));
+    for name in ["map", "collect"] {
+        mrb_define_cmethod(vm, lazy_class.clone(), name, Box::new(mrb_lazy_map));
+    }
+    for name in ["select", "filter"] {
+        mrb_define_cmethod(vm, lazy_class.clone(), name, Box::new(mrb_lazy_select));
+    }
+    mrb_define_cmethod(vm, lazy_class.clone(), "reject", Box::new(mrb_lazy_reject));
+    mrb_define_cmethod(
+        vm,
+        lazy_class.clone(),
+        "filter_map",
+        Box::new(mrb_lazy_filter_map),
+    );
+    mrb_define_cmethod(
+        vm,
+        lazy_class.clone(),
+        "take_while",
+        Box::new(mrb_lazy_take_while),
+    );
+    mrb_define_cmethod(
+        vm,
+        lazy_class.clone(),
+        "drop_while",
+        Box::new(mrb_lazy_drop_while),
+    );
+    mrb_define_cmethod(vm, lazy_class.clone(), "take", Box::new(mrb_lazy_take));
+    mrb_define_cmethod(vm, lazy_class.clone(), "drop", Box::new(mrb_lazy_drop));
+    for name in ["force", "to_a"] {
+        mrb_define_cmethod(vm, lazy_class.clone(), name, Box::new(mrb_lazy_force));
+    }
+    mrb_define_cmethod(vm, lazy_class.clone(), "eager", Box::new(mrb_lazy_eager));
+    mrb_define_cmethod(vm, lazy_class.clone(), "lazy", Box::new(mrb_lazy_lazy));
+
+    let object_class = vm.object_class.clone();
+    for name in ["to_enum", "enum_for"] {
+        mrb_define_cmethod(vm, object_class.clone(), name, Box::new(mrb_object_to_enum));
+    }
+}
+
+#[derive(Clone)]
+enum LazyOp {
+    Map(Rc<RObject>),
+    Select(Rc<RObject>),
+    Reject(Rc<RObject>),
+    FilterMap(Rc<RObject>),
+    TakeWhile(Rc<RObject>),
+    DropWhile(Rc<RObject>),
+    Take(usize),
+    Drop(usize),
+}
+
+impl LazyOp {
+    fn name(&self) -> String {
+        match self {
+            LazyOp::Map(_) => "map".to_string(),
+            LazyOp::Select(_) => "select".to_string(),
+            LazyOp::Reject(_) => "reject".to_string(),
+            LazyOp::FilterMap(_) => "filter_map".to_string(),
+            LazyOp::TakeWhile(_) => "take_while".to_string(),
+            LazyOp::DropWhile(_) => "drop_while".to_string(),
+            LazyOp::Take(n) => format!("take({})", n),
+            LazyOp::Drop(n) => format!("drop({})", n),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Source {
+    /// `receiver.method(*args)`
+    Method {
+        receiver: Rc<RObject>,
+        method: String,
+        args: Vec<Rc<RObject>>,
+    },
+    /// `Enumerator.new { |yielder| ... }`
+    Generator(Rc<RObject>),
+    /// `parent.lazy`, or `op` applied to the values of another Lazy
+    Lazy {
+        parent: Rc<RObject>,
+        op: Option<LazyOp>,
+    },
+}
+
+pub struct REnumerator {
+    source: Source,
+    /// Fiber running the source for `next` and `peek`
+    fiber: RefCell<Option<Rc<RObject>>>,
+    /// Enumerator over the parent that `next` reads a Lazy from
+    cursor: RefCell<Option<Rc<RObject>>>,
+    /// Values of a native source method, read to its end at once
+    buffer: RefCell<VecDeque<Rc<RObject>>>,
+    /// Value read by `peek` and not yet returned by `next`
+    peeked: RefCell<Option<Rc<RObject>>>,
+    /// Values a Lazy take or drop counted so far
+    count: Cell<usize>,
+    /// Whether a Lazy drop_while is still dropping
+    dropping: Cell<bool>,
+    finished: Cell<bool>,
+}
+
+/// Value returned from a native block passed to [`mrb_each_until`].
+pub(crate) enum Step {
+    Next(Rc<RObject>),
+    Stop,
+}
+
+type StepFn = Box<dyn Fn(&mut VM, &[Rc<RObject>]) -> Result<Step, Error>>;
+
+/// Calls `recv.method(*args)` with a native block running `f` for every
+/// yielded value. Returns `None` when `f` stopped the iteration early,
+/// otherwise the value returned by the method.
+pub(crate) fn mrb_each_until(
+    vm: &mut VM,
+    recv: Rc<RObject>,
+    method: &str,
+    args: &[Rc<RObject>],
+    f: StepFn,
+) -> Result<Option<Rc<RObject>>, Error> {
+    // Raised through the iterator to leave it, like `break`, but invisible
+    // to `rescue` and distinct from any other iteration in progress
+    let stop = Rc::new(RObject::exception(Rc::new(RException::new(
+        vm.get_class_by_name("_StopEach"),
+    ))));
+    let stop_ref = stop.clone();
+    let wrapping_block: RFn =
+        Box::new(
+            move |vm: &mut VM, args: &[Rc<RObject>]| match f(vm, args)? {
+                Step::Next(val) => Ok(val),
+                Step::Stop => Err(Error::Exception(stop_ref.clone())),
+            },
+        );
+
+    let block = rproc_from_rust_block(vm, wrapping_block)?;
+    let res = mrb_funcall_with_block(vm, Some(recv), method, args, Some(block));
+    vm.pop_fnblock()?;
+
+    match res {
+        Ok(val) => Ok(Some(val)),
+        Err(Error::Exception(e)) if Rc::ptr_eq(&e, &stop) => {
+            // Left through a Ruby frame, which recorded it as raised
+            vm.exception.take();
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// A value yielded as `yield a, b` is seen as `[a, b]` by `next` and friends.
+pub(crate) fn yielded_value(args: &[Rc<RObject>]) -> Rc<RObject> {
+    match args {
+        [] => Rc::new(RObject::nil()),
+        [value] => value.clone(),
+        values => RObject::array(values.to_vec()).to_refcount_assigned(),
+    }
+}
+
+/// The block passed to a native method, if any.
+pub(crate) fn block_arg(args: &[Rc<RObject>]) -> Option<Rc<RObject>> {
+    args.last()
+        .filter(|arg| matches!(arg.value, RValue::Proc(_)))
+        .cloned()
+}
+
+fn new_enumerator_object(vm: &mut VM, class_name: &str, source: Source) -> Rc<RObject> {
+    let enumerator = REnumerator {
+        source,
+        fiber: RefCell::new(None),
+        cursor: RefCell::new(None),
+        buffer: RefCell::new(VecDeque::new()),
+        peeked: RefCell::new(None),
+        count: Cell::new(0),
+        dropping: Cell::new(true),
+        finished: Cell::new(false),
+    };
+    let enumerator_data = RData {
+        class: vm.get_class_by_name(class_name),
+        data: RefCell::new(Some(Rc::new(Box::new(enumerator)))),
+        ref_count: 1,
+    };
+    Rc::new(RObject {
+        tt: RType::Data,
+        value: RValue::Data(Rc::new(enumerator_data)),
+        object_id: Cell::new(u64::MAX),
+        singleton_class: RefCell::new(None),
+        ivar: RefCell::new(IvarTable::default()),
+        frozen: Cell::new(false),
+    })
+}
+
+/// Enumerator over `recv.method(*args)`, returned by iterators called without a block.
+pub fn mrb_to_enum(
+    vm: &mut VM,
+    recv: Rc<RObject>,
+    method: &str,
+    args: &[Rc<RObject>],
+) -> Rc<RObject> {
+    let source = Source::Method {
+        receiver: recv,
+        method: method.to_string(),
+        args: args.to_vec(),
+    };
+    new_enumerator_object(vm, "Enumerator", source)
+}
+
+/// `Enumerable#lazy`
+pub fn mrb_lazy_new(vm: &mut VM, recv: Rc<RObject>) -> Rc<RObject> {
+    let source = Source::Lazy {
+        parent: recv,
+        op: None,
+    };
+    new_enumerator_object(vm, "Enumerator::Lazy", source)
+}
+
+fn get_enumerator_data(obj: &RObject) -> Result<Rc<Box<dyn Any>>, Error> {
+    match &obj.value {
+        RValue::Data(data) => data
+            .data
+            .borrow()
+            .clone()
+            .filter(|d| d.is::<REnumerator>())
+            .ok_or_else(|| Error::RuntimeError("Invalid Enumerator data".to_string())),
+        _ => Err(Error::RuntimeError(
+            "Enumerator method must be called on an Enumerator".to_string(),
+        )),
+    }
+}
+
+fn downcast(data: &Rc<Box<dyn Any>>) -> &REnumerator {
+    data.downcast_ref::<REnumerator>()
+        .expect("checked in get_enumerator_data")
+}
+
+fn mrb_enumerator_new(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let block = block_arg(args)
+        .ok_or_else(|| Error::ArgumentError("Enumerator.new expects a block".to_string()))?;
+    Ok(new_enumerator_object(
+        vm,
+        "Enumerator",
+        Source::Generator(block),
+    ))
+}
+
+fn mrb_enumerator_each(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let Some(block) = block_arg(args) else {
+        return Ok(this);
+    };
+    let data = get_enumerator_data(&this)?;
+    match &downcast(&data).source {
+        Source::Method {
+            receiver,
+            method,
+            args,
+        } => mrb_funcall_with_block(vm, Some(receiver.clone()), method, args, Some(block)),
+        Source::Generator(generator) => {
+            let yielder = new_yielder(vm, block)?;
+            mrb_call_block(vm, generator.clone(), None, &[yielder], 0)
+        }
+        Source::Lazy { .. } => Err(Error::internal("Lazy#each is defined on Lazy")),
+    }
+}
+
+/// `Enumerator::Yielder.new(&block)`
+fn new_yielder(vm: &mut VM, block: Rc<RObject>) -> Result<Rc<RObject>, Error> {
+    let yielder_class = vm.get_class_by_name("Enumerator::Yielder");
+    let yielder_class = RObject::class(yielder_class, vm);
+    mrb_funcall_with_block(vm, Some(yielder_class), "new", &[], Some(block))
+}
+
+/// Enumerator over the values of `recv.each`, with a position of its own.
+fn new_cursor(vm: &mut VM, recv: &Rc<RObject>) -> Rc<RObject> {
+    match get_enumerator_data(recv) {
+        Ok(data) => {
+            let class_name = match &downcast(&data).source {
+                Source::Lazy { .. } => "Enumerator::Lazy",
+                _ => "Enumerator",
+            };
+            new_enumerator_object(vm, class_name, downcast(&data).source.clone())
+        }
+        Err(_) => mrb_to_enum(vm, recv.clone(), "each", &[]),
+    }
+}
+
+/// Reads the next value of `this` for `next` and `peek`, `None` past the end.
+fn read_next(
+    vm: &mut VM,
+    this: &Rc<RObject>,
+    enumerator: &REnumerator,
+) -> Result<Option<Rc<RObject>>, Error> {
+    if let Some(val) = enumerator.buffer.borrow_mut().pop_front() {
+        return Ok(Some(val));
+    }
+    if enumerator.finished.get() {
+        return Ok(None);
+    }
+    let res = match &enumerator.source {
+        Source::Lazy { parent, op } => lazy_read_next(vm, enumerator, parent, op.as_ref()),
+        // Another enumerator's values, read without moving its position
+        Source::Method {
+            receiver,
+            method,
+            args,
+        } if method == "each" && args.is_empty() && get_enumerator_data(receiver).is_ok() => {
+            cursor_read_next(vm, enumerator, receiver)
+        }
+        _ => fiber_read_next(vm, this, enumerator),
+    };
+    if !matches!(res, Ok(Some(_))) {
+        enumerator.finished.set(true);
+    }
+    res
+}
+
+fn cursor_read_next(
+    vm: &mut VM,
+    enumerator: &REnumerator,
+    parent: &Rc<RObject>,
+) -> Result<Option<Rc<RObject>>, Error> {
+    let cursor = enumerator.cursor.borrow().clone();
+    let cursor = match cursor {
+        Some(cursor) => cursor,
+        None => {
+            let cursor = new_cursor(vm, parent);
+            enumerator.cursor.replace(Some(cursor.clone()));
+            cursor
+        }
+    };
+    let data = get_enumerator_data(&cursor)?;
+    read_next(vm, &cursor, downcast(&data))
+}
+
+fn fiber_read_next(
+    vm: &mut VM,
+    this: &Rc<RObject>,
+    enumerator: &REnumerator,
+) -> Result<Option<Rc<RObject>>, Error> {
+    let started = enumerator.fiber.borrow().clone();
+    let (fiber, args) = match started {
+        Some(fiber) => (fiber, vec![]),
+        None => {
+            let block = mrb_funcall(vm, Some(this.clone()), "__fiber_block", &[])?;
+            let (fiber, args) = match &enumerator.source {
+                Source::Generator(generator) => {
+                    let yielder = new_yielder(vm, block.clone())?;
+                    (
+                        mrb_fiber_new(vm, std::slice::from_ref(generator))?,
+                        vec![yielder],
+                    )
+                }
+                Source::Method {
+                    receiver,
+                    method,
+                    args,
+                } => {
+                    let klass = receiver.singleton_or_this_class(vm);
+                    match vm.method_cache.resolve(&klass, method) {
+                        Some((owner, rb_method)) if rb_method.is_rb_func => {
+                            let method_id = rb_method
+                                .sym_id
+                                .clone()
+                                .unwrap_or_else(|| RSym::new(method.clone()));
+                            let fiber = mrb_fiber_for_method(
+                                vm,
+                                receiver.clone(),
+                                method_id,
+                                owner,
+                                &rb_method,
+                                args,
+                                block,
+                            )?;
+                            (fiber, vec![])
+                        }
+                        _ => return read_to_end(vm, this, enumerator),
+                    }
+                }
+                Source::Lazy { .. } => return Err(Error::internal("Lazy read by a fiber")),
+            };
+            enumerator.fiber.replace(Some(fiber.clone()));
+            (fiber, args)
+        }
+    };
+    let val = fiber_resume(vm, &fiber, &args)?;
+    // A value comes from Fiber.yield as long as the source runs
+    Ok(fiber_alive(&fiber)?.then_some(val))
+}
+
+/// Runs a native source method to its end, keeping its values for `next`.
+fn read_to_end(
+    vm: &mut VM,
+    this: &Rc<RObject>,
+    enumerator: &REnumerator,
+) -> Result<Option<Rc<RObject>>, Error> {
+    let values = Rc::new(RefCell::new(VecDeque::new()));
+    let values_ref = values.clone();
+    mrb_each_until(
+        vm,
+        this.clone(),
+        "each",
+        &[],
+        Box::new(move |_vm, args| {
+            values_ref.borrow_mut().push_back(yielded_value(args));
+            Ok(Step::Next(Rc::new(RObject::nil())))
+        }),
+    )?;
+    let mut values = values.take();
+    let first = values.pop_front();
+    enumerator.buffer.replace(values);
+    enumerator.finished.set(true);
+    Ok(first)
+}
+
+fn lazy_read_next(
+    vm: &mut VM,
+    enumerator: &REnumerator,
+    parent: &Rc<RObject>,
+    op: Option<&LazyOp>,
+) -> Result<Option<Rc<RObject>>, Error> {
+    loop {
+        if let Some(LazyOp::Take(n)) = op
+            && enumerator.count.get() >= *n
+        {
+            return Ok(None);
+        }
+        let Some(val) = cursor_read_next(vm, enumerator, parent)? else {
+            return Ok(None);
+        };
+        let args = [val.clone()];
+        match op {
+            None => return Ok(Some(val)),
+            Some(LazyOp::Map(f)) => return mrb_call_block(vm, f.clone(), None, &args, 0).map(Some),
+            Some(LazyOp::Select(f)) => {
+                if mrb_call_block(vm, f.clone(), None, &args, 0)?.is_truthy() {
+                    return Ok(Some(val));
+                }
+            }
+            Some(LazyOp::Reject(f)) => {
+                if !mrb_call_block(vm, f.clone(), None, &args, 0)?.is_truthy() {
+                    return Ok(Some(val));
+                }
+            }
+            Some(LazyOp::FilterMap(f)) => {
+                let mapped = mrb_call_block(vm, f.clone(), None, &args, 0)?;
+                if mapped.is_truthy() {
+                    return Ok(Some(mapped));
+                }
+            }
+            Some(LazyOp::TakeWhile(f)) => {
+                let taken = mrb_call_block(vm, f.clone(), None, &args, 0)?.is_truthy();
+                return Ok(taken.then_some(val));
+            }
+            Some(LazyOp::DropWhile(f)) => {
+                if !enumerator.dropping.get()
+                    || !mrb_call_block(vm, f.clone(), None, &args, 0)?.is_truthy()
+                {
+                    enumerator.dropping.set(false);
+                    return Ok(Some(val));
+                }
+            }
+            Some(LazyOp::Take(_)) => {
+                enumerator.count.set(enumerator.count.get() + 1);
+                return Ok(Some(val));
+            }
+            Some(LazyOp::Drop(n)) => {
+                if enumerator.count.get() >= *n {
+                    return Ok(Some(val));
+                }
+                enumerator.count.set(enumerator.count.get() + 1);
+            }
+        }
+    }
+}
+
+fn stop_iteration() -> Error {
+    Error::TaggedError("StopIteration", "iteration reached an end".to_string())
+}
+
+fn mrb_enumerator_next(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let data = get_enumerator_data(&this)?;
+    let enumerator = downcast(&data);
+    if let Some(val) = enumerator.peeked.take() {
+        return Ok(val);
+    }
+    read_next(vm, &this, enumerator)?.ok_or_else(stop_iteration)
+}
+
+fn mrb_enumerator_peek(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let data = get_enumerator_data(&this)?;
+    let enumerator = downcast(&data);
+    if let Some(val) = enumerator.peeked.borrow().clone() {
+        return Ok(val);
+    }
+    let val = read_next(vm, &this, enumerator)?.ok_or_else(stop_iteration)?;
+    enumerator.peeked.replace(Some(val.clone()));
+    Ok(val)
+}
+
+fn mrb_enumerator_rewind(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let data = get_enumerator_data(&this)?;
+    let enumerator = downcast(&data);
+    enumerator.fiber.take();
+    enumerator.cursor.take();
+    enumerator.buffer.borrow_mut().clear();
+    enumerator.peeked.take();
+    enumerator.count.set(0);
+    enumerator.dropping.set(true);
+    enumerator.finished.set(false);
+    Ok(this)
+}
+
+/// Number of values `recv.each` yields, or nil when it cannot be known
+/// without iterating.
+fn each_size(vm: &mut VM, recv: &Rc<RObject>) -> Result<Rc<RObject>, Error> {
+    let size = match &recv.value {
+        RValue::Array(a) => RObject::integer(a.borrow().len() as i64),
+        RValue::Hash(h) => RObject::integer(h.borrow().len() as i64),
+        RValue::Range(start, end, exclusive) => match (&start.value, &end.value) {
+            (RValue::Integer(start), RValue::Integer(end)) => {
+                let last = if *exclusive { *end - 1 } else { *end };
+                RObject::integer((last - *start + 1).max(0))
+            }
+            (RValue::Integer(_), RValue::Nil) => RObject::float(f64::INFINITY),
+            (RValue::Integer(_), RValue::Float(end)) if *end == f64::INFINITY => {
+                RObject::float(f64::INFINITY)
+            }
+            _ => RObject::nil(),
+        },
+        RValue::Data(_) if get_enumerator_data(recv).is_ok() => {
+            return enumerator_size(vm, recv);
+        }
+        _ => RObject::nil(),
+    };
+    Ok(Rc::new(size))
+}
+
+fn enumerator_size(vm: &mut VM, this: &Rc<RObject>) -> Result<Rc<RObject>, Error> {
+    let data = get_enumerator_data(this)?;
+    match &downcast(&data).source {
+        Source::Method {
+            receiver,
+            method,
+            args,
+        } => match (&receiver.value, method.as_str()) {
+            (RValue::Integer(n), "times") => Ok(Rc::new(RObject::integer((*n).max(0)))),
+            (RValue::Integer(n), "upto" | "downto") => {
+                let upto = method == "upto";
+                let limit = match args.first().map(|arg| &arg.value) {
+                    Some(RValue::Integer(limit)) => *limit,
+                    Some(RValue::Float(limit)) if upto => limit.floor() as i64,
+                    Some(RValue::Float(limit)) => limit.ceil() as i64,
+                    _ => return Ok(Rc::new(RObject::nil())),
+                };
+                let span = if upto {
+                    limit.saturating_sub(*n)
+                } else {
+                    n.saturating_sub(limit)
+                };
+                Ok(Rc::new(RObject::integer(span.saturating_add(1).max(0))))
+            }
+            (
+                _,
+                "each" | "map" | "collect" | "select" | "filter" | "reject" | "find"
+                | "each_with_index" | "with_index" | "with_object" | "sort_by",
+            ) => each_size(vm, receiver),
+            _ => Ok(Rc::new(RObject::nil())),
+        },
+        Source::Generator(_) => Ok(Rc::new(RObject::nil())),
+        Source::Lazy { parent, op } => match op {
+            None | Some(LazyOp::Map(_)) => each_size(vm, parent),
+            Some(LazyOp::Take(n)) => {
+                let size = each_size(vm, parent)?;
+                let n = *n as i64;
+                Ok(Rc::new(match &size.value {
+                    RValue::Integer(m) => RObject::integer(n.min(*m)),
+                    RValue::Float(_) => RObject::integer(n),
+                    _ => RObject::nil(),
+                }))
+            }
+            Some(_) => Ok(Rc::new(RObject::nil())),
+        },
+    }
+}
+
+fn mrb_enumerator_size(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    enumerator_size(vm, &this)
+}
+
+// Enumerator#with_index: yields each value with its index, starting from offset
+fn mrb_enumerator_with_index(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let Some(block) = block_arg(args) else {
+        return Ok(mrb_to_enum(vm, this, "with_index", args));
+    };
+    let offset = match args.first().map(|arg| &arg.value) {
+        Some(RValue::Integer(n)) => *n,
+        _ => 0,
+    };
+    let index = Cell::new(offset);
+    let res = mrb_each_until(
+        vm,
+        this,
+        "each",
+        &[],
+        Box::new(move |vm, args| {
+            let i = index.get();
+            index.set(i + 1);
+            let block_args = vec![yielded_value(args), Rc::new(RObject::integer(i))];
+            let val = mrb_call_block(vm, block.clone(), None, &block_args, 0)?;
+            Ok(Step::Next(val))
+        }),
+    )?;
+    Ok(res.unwrap_or_else(|| Rc::new(RObject::nil())))
+}
+
+// Enumerator#with_object: yields each value with the given object and returns it
+fn mrb_enumerator_with_object(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let memo = args
+        .first()
+        .filter(|arg| !matches!(arg.value, RValue::Proc(_)))
+        .cloned()
+        .ok_or_else(|| Error::ArgumentError("wrong number of arguments".to_string()))?;
+    let Some(block) = block_arg(&args[1..]) else {
+        return Ok(mrb_to_enum(vm, this, "with_object", args));
+    };
+    let memo_ref = memo.clone();
+    mrb_each_until(
+        vm,
+        this,
+        "each",
+        &[],
+        Box::new(move |vm, args| {
+            let block_args = vec![yielded_value(args), memo_ref.clone()];
+            let val = mrb_call_block(vm, block.clone(), None, &block_args, 0)?;
+            Ok(Step::Next(val))
+        }),
+    )?;
+    Ok(memo)
+}
+
+fn inspect_string(vm: &mut VM, obj: Rc<RObject>) -> Result<String, Error> {
+    let inspect = mrb_call_inspect(vm, obj)?;
+    inspect.as_ref().try_into()
+}
+
+fn mrb_enumerator_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let data = get_enumerator_data(&this)?;
+    let inspect = match &downcast(&data).source {
+        Source::Method {
+            receiver,
+            method,
+            args,
+        } => {
+            let mut inspect = format!(
+                "#<Enumerator: {}:{}",
+                inspect_string(vm, receiver.clone())?,
+                method
+            );
+            if !args.is_empty() {
+                let mut inspected = Vec::new();
+                for arg in args.iter() {
+                    inspected.push(inspect_string(vm, arg.clone())?);
+                }
+                inspect.push_str(&format!("({})", inspected.join(", ")));
+            }
+            inspect.push('>');
+            inspect
+        }
+        Source::Generator(_) => "#<Enumerator: #<Enumerator::Generator>:each>".to_string(),
+        Source::Lazy { parent, op } => {
+            let parent = inspect_string(vm, parent.clone())?;
+            match op {
+                Some(op) => format!("#<Enumerator::Lazy: {}:{}>", parent, op.name()),
+                None => format!("#<Enumerator::Lazy: {}>", parent),
+            }
+        }
+    };
+    Ok(Rc::new(RObject::string(inspect)))
+}
+
+fn mrb_lazy_each(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let Some(block) = block_arg(args) else {
+        return Ok(this);
+    };
+    let data = get_enumerator_data(&this)?;
+    let Source::Lazy { parent, op } = &downcast(&data).source else {
+        return Err(Error::internal("Lazy#each called on a plain Enumerator"));
+    };
+    let Some(op) = op.clone() else {
+        return mrb_funcall_with_block(vm, Some(parent.clone()), "each", &[], Some(block));
+    };
+    if matches!(op, LazyOp::Take(0)) {
+        return Ok(this);
+    }
+
+    // Per-iteration state: values counted for take/drop, and
+    // whether drop_while is still dropping
+    let count = Cell::new(0usize);
+    let dropping = Cell::new(true);
+    let call =
+        move |vm: &mut VM, args: &[Rc<RObject>]| mrb_call_block(vm, block.clone(), None, args, 0);
+    mrb_each_until(
+        vm,
+        parent.clone(),
+        "each",
+        &[],
+        Box::new(move |vm, args| {
+            let nil = Rc::new(RObject::nil());
+            match &op {
+                LazyOp::Map(f) => {
+                    let val = mrb_call_block(vm, f.clone(), None, args, 0)?;
+                    call(vm, &[val])?;
+                }
+                LazyOp::Select(f) => {
+                    if mrb_call_block(vm, f.clone(), None, args, 0)?.is_truthy() {
+                        call(vm, args)?;
+                    }
+                }
+                LazyOp::Reject(f) => {
+                    if !mrb_call_block(vm, f.clone(), None, args, 0)?.is_truthy() {
+                        call(vm, args)?;
+                    }
+                }
+                LazyOp::FilterMap(f) => {
+                    let val = mrb_call_block(vm, f.clone(), None, args, 0)?;
+                    if val.is_truthy() {
+                        call(vm, &[val])?;
+                    }
+                }
+                LazyOp::TakeWhile(f) => {
+                    if !mrb_call_block(vm, f.clone(), None, args, 0)?.is_truthy() {
+                        return Ok(Step::Stop);
+                    }
+                    call(vm, args)?;
+                }
+                LazyOp::DropWhile(f) => {
+                    if dropping.get() && mrb_call_block(vm, f.clone(), None, args, 0)?.is_truthy() {
+                        return Ok(Step::Next(nil));
+                    }
+                    dropping.set(false);
+                    call(vm, args)?;
+                }
+                LazyOp::Take(n) => {
+                    call(vm, args)?;
+                    count.set(count.get() + 1);
+                    // Stop right away so the source is not asked for one more value
+                    if count.get() >= *n {
+                        return Ok(Step::Stop);
+                    }
+                }
+                LazyOp::Drop(n) => {
+                    if count.get() < *n {
+                        count.set(count.get() + 1);
+                    } else {
+                        call(vm, args)?;
+                    }
+                }
+            }
+            Ok(Step::Next(nil))
+        }),
+    )?;
+    Ok(this)
+}
+
+fn lazy_with(vm: &mut VM, op: LazyOp) -> Result<Rc<RObject>, Error> {
+    let parent = vm.getself()?;
+    Ok(new_enumerator_object(
+        vm,
+        "Enumerator::Lazy",
+        Source::Lazy {
+            parent,
+            op: Some(op),
+        },
+    ))
+}
+
+fn lazy_block(args: &[Rc<RObject>], name: &str) -> Result<Rc<RObject>, Error> {
+    block_arg(args)
+        .ok_or_else(|| Error::ArgumentError(format!("tried to call lazy {} without a block", name)))
+}
+
+fn lazy_count(args: &[Rc<RObject>]) -> Result<usize, Error> {
+    let n: i64 = args
+        .first()
+        .ok_or_else(|| Error::ArgumentError("wrong number of arguments".to_string()))?
+        .as_ref()
+        .try_into()?;
+    if n < 0 {
+        return Err(Error::ArgumentError(
+            "attempt to take negative size".to_string(),
+        ));
+    }
+    Ok(n as usize)
+}
+
+fn mrb_lazy_map(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let block = lazy_block(args, "map")?;
+    lazy_with(vm, LazyOp::Map(block))
+}
+
+fn mrb_lazy_select(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let block = lazy_block(args, "select")?;
+    lazy_with(vm, LazyOp::Select(block))
+}
+
+fn mrb_lazy_reject(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let block = lazy_block(args, "reject")?;
+    lazy_with(vm, LazyOp::Reject(block))
+}
+
+fn mrb_lazy_filter_map(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let block = lazy_block(args, "filter_map")?;
+    lazy_with(vm, LazyOp::FilterMap(block))
+}
+
+fn mrb_lazy_take_while(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let block = lazy_block(args, "take_while")?;
+    lazy_with(vm, LazyOp::TakeWhile(block))
+}
+
+fn mrb_lazy_drop_while(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let block = lazy_block(args, "drop_while")?;
+    lazy_with(vm, LazyOp::DropWhile(block))
+}
+
+fn mrb_lazy_take(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let n = lazy_count(args)?;
+    lazy_with(vm, LazyOp::Take(n))
+}
+
+fn mrb_lazy_drop(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let n = lazy_count(args)?;
+    lazy_with(vm, LazyOp::Drop(n))
+}
+
+// Enumerator::Lazy#force: runs the chain and collects every value
+fn mrb_lazy_force(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let results = Rc::new(RefCell::new(Vec::new()));
+    let results_ref = results.clone();
+    mrb_each_until(
+        vm,
+        this,
+        "each",
+        &[],
+        Box::new(move |_vm, args| {
+            results_ref.borrow_mut().push(yielded_value(args));
+            Ok(Step::Next(Rc::new(RObject::nil())))
+        }),
+    )?;
+    Ok(RObject::array(results.take()).to_refcount_assigned())
+}
+
+fn mrb_lazy_eager(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    Ok(mrb_to_enum(vm, this, "each", &[]))
+}
+
+fn mrb_lazy_lazy(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    vm.getself()
+}
+
+// Object#to_enum: Enumerator over the given method, `each` by default
+fn mrb_object_to_enum(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let (method, rest) = match args.split_first() {
+        Some((method, rest)) => {
+            let method: String = match &method.value {
+                RValue::Symbol(sym) => sym.name.clone(),
+                _ => method.as_ref().try_into()?,
+            };
+            (method, rest)
+        }
+        None => ("each".to_string(), args),
+    };
+    Ok(mrb_to_enum(vm, this, &method, rest))
+}
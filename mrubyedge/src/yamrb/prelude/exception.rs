@@ -1,8 +1,12 @@
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 use crate::{
     Error,
-    yamrb::{helpers::mrb_define_cmethod, value::*, vm::VM},
+    yamrb::{
+        helpers::{mrb_define_class_cmethod, mrb_define_cmethod, mrb_funcall},
+        value::*,
+        vm::VM,
+    },
 };
 
 pub(crate) fn initialize_exception(vm: &mut VM) {
@@ -12,10 +16,16 @@ pub(crate) fn initialize_exception(vm: &mut VM) {
     // fill in ruby's standard exceptions:
     let std_exp_class: Rc<RClass> =
         vm.define_standard_class_with_superclass("StandardError", exp_class.clone());
-    let _ = vm.define_standard_class_with_superclass("RuntimeError", std_exp_class.clone());
+    let runtime_error =
+        vm.define_standard_class_with_superclass("RuntimeError", std_exp_class.clone());
+    let _ = vm.define_standard_class_with_superclass("FrozenError", runtime_error);
     let _ = vm.define_standard_class_with_superclass("TypeError", std_exp_class.clone());
-    let _ = vm.define_standard_class_with_superclass("ArgumentError", std_exp_class.clone());
-    let _ = vm.define_standard_class_with_superclass("RangeError", std_exp_class.clone());
+    let argument_error =
+        vm.define_standard_class_with_superclass("ArgumentError", std_exp_class.clone());
+    let uncaught_throw_error =
+        vm.define_standard_class_with_superclass("UncaughtThrowError", argument_error);
+    let range_error = vm.define_standard_class_with_superclass("RangeError", std_exp_class.clone());
+    let _ = vm.define_standard_class_with_superclass("FloatDomainError", range_error);
     let _ = vm.define_standard_class_with_superclass("ZeroDivisionError", std_exp_class.clone());
     let _ = vm.define_standard_class_with_superclass("NoMemoryError", exp_class.clone());
     let _ = vm.define_standard_class_with_superclass("ScriptError", exp_class.clone());
@@ -30,23 +40,210 @@ pub(crate) fn initialize_exception(vm: &mut VM) {
     let _ = vm.define_standard_class_with_superclass("SystemCallError", std_exp_class.clone());
     let _ = vm.define_standard_class_with_superclass("NoMethodError", std_exp_class.clone());
     let _ = vm.define_standard_class_with_superclass("NameError", std_exp_class.clone());
+    let _ = vm.define_standard_class_with_superclass("FiberError", std_exp_class.clone());
+    let index_error = vm.define_standard_class_with_superclass("IndexError", std_exp_class.clone());
+    let _ = vm.define_standard_class_with_superclass("StopIteration", index_error.clone());
+    let _ = vm.define_standard_class_with_superclass("KeyError", index_error);
 
     // Dummy class for 'break' control flow
     let _ = vm.define_standard_class("_Break");
     let _ = vm.define_standard_class("_BlockReturn");
+    let _ = vm.define_standard_class("_Throw");
+    // Dummy class for leaving an iterator from a native block
+    let _ = vm.define_standard_class("_StopEach");
 
-    mrb_define_cmethod(vm, exp_class, "message", Box::new(mrb_exception_message));
+    mrb_define_class_cmethod(
+        vm,
+        exp_class.clone(),
+        "exception",
+        Box::new(mrb_exception_s_exception),
+    );
+    mrb_define_cmethod(
+        vm,
+        exp_class.clone(),
+        "initialize",
+        Box::new(mrb_exception_initialize),
+    );
+    mrb_define_cmethod(
+        vm,
+        exp_class.clone(),
+        "exception",
+        Box::new(mrb_exception_exception),
+    );
+    mrb_define_cmethod(vm, exp_class.clone(), "to_s", Box::new(mrb_exception_to_s));
+    mrb_define_cmethod(
+        vm,
+        exp_class.clone(),
+        "message",
+        Box::new(mrb_exception_message),
+    );
+    mrb_define_cmethod(
+        vm,
+        exp_class.clone(),
+        "inspect",
+        Box::new(mrb_exception_inspect),
+    );
+    mrb_define_cmethod(
+        vm,
+        exp_class.clone(),
+        "full_message",
+        Box::new(mrb_exception_full_message),
+    );
+    mrb_define_cmethod(
+        vm,
+        exp_class.clone(),
+        "cause",
+        Box::new(mrb_exception_cause),
+    );
+    mrb_define_cmethod(
+        vm,
+        exp_class.clone(),
+        "backtrace",
+        Box::new(mrb_exception_backtrace),
+    );
+
+    mrb_define_cmethod(
+        vm,
+        uncaught_throw_error.clone(),
+        "tag",
+        Box::new(mrb_uncaught_throw_error_tag),
+    );
+    mrb_define_cmethod(
+        vm,
+        uncaught_throw_error,
+        "value",
+        Box::new(mrb_uncaught_throw_error_value),
+    );
+
+    // Builtin subclasses need their singleton classes to inherit `Exception.exception`
+    let subclasses: Vec<Rc<RClass>> = vm
+        .builtin_class_table
+        .values()
+        .filter(|c| c.is_subclass_of(&exp_class))
+        .cloned()
+        .collect();
+    for class in subclasses {
+        RObject::class_singleton(class, vm);
+    }
 }
 
-pub fn mrb_exception_message(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+fn exception_self(vm: &mut VM, method: &str) -> Result<Rc<RObject>, Error> {
     let exp = vm.getself()?;
     match &exp.value {
-        RValue::Exception(e) => {
-            let message = e.as_ref().message.clone();
-            Ok(RObject::string(message).to_refcount_assigned())
-        }
-        _ => Err(Error::RuntimeError(
-            "Exception#message must be called on an Exception".to_string(),
-        )),
+        RValue::Exception(_) => Ok(exp),
+        _ => Err(Error::RuntimeError(format!(
+            "Exception#{} must be called on an Exception",
+            method
+        ))),
+    }
+}
+
+fn mrb_exception_s_exception(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let class = vm.getself()?;
+    mrb_funcall(vm, Some(class), "new", args)
+}
+
+fn mrb_exception_initialize(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let exp = exception_self(vm, "initialize")?;
+    if let Some(message) = args.first() {
+        exp.set_ivar("mesg", message.clone());
+    }
+    Ok(RObject::nil().to_refcount_assigned())
+}
+
+/// `exc.exception(msg)` returns a copy of `exc` carrying the new message
+fn mrb_exception_exception(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let exp = exception_self(vm, "exception")?;
+    let Some(message) = args.first() else {
+        return Ok(exp);
+    };
+    let RValue::Exception(e) = &exp.value else {
+        unreachable!("checked by exception_self");
+    };
+    let copied = RObject::exception(Rc::new(RException {
+        class: e.class.clone(),
+        error_type: RefCell::new(e.error_type.borrow().clone()),
+        message: e.message.clone(),
+        backtrace: e.backtrace.clone(),
+    }))
+    .to_refcount_assigned();
+    for (key, value) in exp.ivar.borrow().iter() {
+        copied.set_ivar(key, value.clone());
+    }
+    copied.set_ivar("mesg", message.clone());
+    Ok(copied)
+}
+
+pub fn mrb_exception_to_s(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let exp = exception_self(vm, "to_s")?;
+    Ok(RObject::string(exp.exception_message()).to_refcount_assigned())
+}
+
+pub fn mrb_exception_message(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let exp = exception_self(vm, "message")?;
+    mrb_funcall(vm, Some(exp), "to_s", &[])
+}
+
+fn mrb_exception_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let exp = exception_self(vm, "inspect")?;
+    let class_name = exp.get_class(vm).full_name();
+    let message: String = mrb_funcall(vm, Some(exp), "to_s", &[])?
+        .as_ref()
+        .try_into()?;
+    let inspect = if message.is_empty() {
+        class_name
+    } else {
+        format!("#<{}: {}>", class_name, message)
+    };
+    Ok(RObject::string(inspect).to_refcount_assigned())
+}
+
+fn mrb_uncaught_throw_error_tag(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let exp = exception_self(vm, "tag")?;
+    Ok(exp.get_ivar("tag"))
+}
+
+fn mrb_uncaught_throw_error_value(
+    vm: &mut VM,
+    _args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    let exp = exception_self(vm, "value")?;
+    Ok(exp.get_ivar("value"))
+}
+
+/// Same layout as an uncaught exception report:
+/// `file:line:in 'method': message (Class)` followed by `from` lines
+fn mrb_exception_full_message(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let exp = exception_self(vm, "full_message")?;
+    let class_name = exp.get_class(vm).full_name();
+    let message: String = mrb_funcall(vm, Some(exp.clone()), "message", &[])?
+        .as_ref()
+        .try_into()?;
+    let backtrace = exp.exception_backtrace().unwrap_or_default();
+    let mut entries = backtrace.iter();
+    let mut full_message = match entries.next() {
+        Some(origin) => format!("{}: {} ({})", origin, message, class_name),
+        None => format!("{} ({})", message, class_name),
+    };
+    for entry in entries {
+        full_message.push_str("\n\tfrom ");
+        full_message.push_str(entry);
     }
+    Ok(RObject::string(full_message).to_refcount_assigned())
+}
+
+fn mrb_exception_cause(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let exp = exception_self(vm, "cause")?;
+    Ok(exp.get_ivar("cause"))
+}
+
+pub fn mrb_exception_backtrace(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let exp = exception_self(vm, "backtrace")?;
+    let backtrace = exp
+        .exception_backtrace()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| RObject::string(entry).to_refcount_assigned())
+        .collect();
+    Ok(RObject::array(backtrace).to_refcount_assigned())
 }
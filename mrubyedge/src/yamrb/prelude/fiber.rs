@@ -0,0 +1,400 @@
+//! Fiber: coroutines running on their own register stack inside the VM.
+//! Switching swaps the execution context kept in [`VM`], so no host thread
+//! is needed. A fiber can be suspended from Ruby code it runs, including
+//! blocks given to the iterators of mrblib; `Fiber.yield` inside a block
+//! called by a native method raises FiberError.
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::mem;
+use std::rc::Rc;
+
+use crate::{
+    Error,
+    yamrb::{
+        helpers::{mrb_define_class_cmethod, mrb_define_cmethod},
+        optable::push_callinfo,
+        value::*,
+        vm::{
+            Breadcrumb, CALLINFO, ENV, FRAME_REGS_SIZE, HandlerClause, IREP, KArgs, RegisterStack,
+            TargetContext, VM,
+        },
+    },
+};
+
+pub(crate) fn initialize_fiber(vm: &mut VM) {
+    let fiber_class = vm.define_standard_class("Fiber");
+
+    mrb_define_class_cmethod(vm, fiber_class.clone(), "new", Box::new(mrb_fiber_new));
+    mrb_define_class_cmethod(vm, fiber_class.clone(), "yield", Box::new(mrb_fiber_yield));
+
+    mrb_define_cmethod(
+        vm,
+        fiber_class.clone(),
+        "resume",
+        Box::new(mrb_fiber_resume),
+    );
+    mrb_define_cmethod(vm, fiber_class.clone(), "alive?", Box::new(mrb_fiber_alive));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FiberState {
+    Created,
+    Resumed,
+    Suspended,
+    Terminated,
+}
+
+type EnvTable = RHashMap<usize, Rc<ENV>>;
+
+/// Execution state exchanged with the one in [`VM`] on every switch:
+/// the fiber's own while it is suspended, its resumer's while it runs.
+struct FiberContext {
+    regs_stack: Rc<RegisterStack>,
+    current_regs_offset: usize,
+    current_irep: Rc<IREP>,
+    pc: usize,
+    current_callinfo: Option<Rc<CALLINFO>>,
+    outer_callinfo: Vec<Rc<CALLINFO>>,
+    current_breadcrumb: Option<Rc<Breadcrumb>>,
+    kargs: Option<RHashMap<RSym, Rc<RObject>>>,
+    current_kargs: Option<Rc<KArgs>>,
+    target_class: TargetContext,
+    upper: Option<Rc<ENV>>,
+    cur_env: EnvTable,
+    has_env_ref: RHashMap<usize, bool>,
+    last_match: Option<Rc<RObject>>,
+    current_exception: Option<Rc<RObject>>,
+    handler_clauses: Vec<HandlerClause>,
+    catch_tags: Vec<Rc<RObject>>,
+    current_fiber: Option<Rc<RObject>>,
+}
+
+impl FiberContext {
+    fn swap(&mut self, vm: &mut VM) {
+        *vm.regs_stack.borrow_mut() = mem::take(&mut vm.regs);
+        mem::swap(&mut self.regs_stack, &mut vm.regs_stack);
+        vm.regs = mem::take(&mut *vm.regs_stack.borrow_mut());
+
+        mem::swap(&mut self.current_regs_offset, &mut vm.current_regs_offset);
+        mem::swap(&mut self.current_irep, &mut vm.current_irep);
+        self.pc = vm.pc.replace(self.pc);
+        mem::swap(&mut self.current_callinfo, &mut vm.current_callinfo);
+        mem::swap(&mut self.outer_callinfo, &mut vm.outer_callinfo);
+        mem::swap(&mut self.current_breadcrumb, &mut vm.current_breadcrumb);
+        mem::swap(&mut self.kargs, vm.kargs.get_mut());
+        mem::swap(&mut self.current_kargs, vm.current_kargs.get_mut());
+        mem::swap(&mut self.target_class, &mut vm.target_class);
+        mem::swap(&mut self.upper, &mut vm.upper);
+        mem::swap(&mut self.cur_env, &mut vm.cur_env);
+        mem::swap(&mut self.has_env_ref, &mut vm.has_env_ref);
+        mem::swap(&mut self.last_match, &mut vm.last_match);
+        mem::swap(&mut self.current_exception, &mut vm.current_exception);
+        mem::swap(&mut self.handler_clauses, &mut vm.handler_clauses);
+        mem::swap(&mut self.catch_tags, &mut vm.catch_tags);
+        mem::swap(&mut self.current_fiber, &mut vm.current_fiber);
+    }
+}
+
+/// Method call a fiber created by [`mrb_fiber_for_method`] starts with
+struct FiberEntry {
+    method_id: RSym,
+    owner: Rc<RModule>,
+    n_args: usize,
+}
+
+pub struct RFiber {
+    state: Cell<FiberState>,
+    context: RefCell<FiberContext>,
+    /// `None` for fibers running a block given to `Fiber.new`
+    entry: Option<FiberEntry>,
+    /// Register in the fiber's stack receiving the value of the next `resume`
+    resume_reg: Cell<usize>,
+    /// `VM::native_depth` while the fiber body runs without native frames
+    base_depth: Cell<usize>,
+    /// Value passed from `Fiber.yield` to the waiting `resume`
+    transfer: RefCell<Option<Rc<RObject>>>,
+}
+
+fn fiber_error(msg: &str) -> Error {
+    Error::TaggedError("FiberError", msg.to_string())
+}
+
+fn get_fiber_data(obj: &RObject) -> Result<Rc<Box<dyn Any>>, Error> {
+    match &obj.value {
+        RValue::Data(data) => data
+            .data
+            .borrow()
+            .clone()
+            .filter(|d| d.is::<RFiber>())
+            .ok_or_else(|| Error::RuntimeError("Invalid Fiber data".to_string())),
+        _ => Err(Error::RuntimeError(
+            "Fiber method must be called on a Fiber object".to_string(),
+        )),
+    }
+}
+
+fn fiber_value(args: &[Rc<RObject>]) -> Rc<RObject> {
+    match args {
+        [] => Rc::new(RObject::nil()),
+        [value] => value.clone(),
+        values => RObject::array(values.to_vec()).to_refcount_assigned(),
+    }
+}
+
+pub(crate) fn mrb_fiber_new(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let block = match args.last().map(|b| &b.value) {
+        Some(RValue::Proc(block)) => block.clone(),
+        _ => {
+            return Err(Error::ArgumentError(
+                "tried to create Proc object without a block".to_string(),
+            ));
+        }
+    };
+    let irep = block
+        .irep
+        .clone()
+        .ok_or_else(|| Error::ArgumentError("tried to create Fiber from a native proc".into()))?;
+
+    let block_self = match &block.block_self {
+        Some(block_self) => block_self.clone(),
+        None => vm.getself()?,
+    };
+    Ok(new_fiber(
+        vm,
+        irep,
+        vec![block_self],
+        block.environ.clone(),
+        None,
+    ))
+}
+
+/// Fiber whose first resume calls the Ruby method `method` of `recv` with
+/// `args` and `block`, so a native can run a Ruby iterator step by step.
+pub(crate) fn mrb_fiber_for_method(
+    vm: &mut VM,
+    recv: Rc<RObject>,
+    method_id: RSym,
+    owner: Rc<RModule>,
+    method: &RProc,
+    args: &[Rc<RObject>],
+    block: Rc<RObject>,
+) -> Result<Rc<RObject>, Error> {
+    let irep = method
+        .irep
+        .clone()
+        .ok_or_else(|| Error::internal("Ruby method without irep"))?;
+    // Laid out as SEND leaves them: receiver, arguments, then the block
+    let mut values = vec![recv];
+    values.extend_from_slice(args);
+    values.push(block);
+    let entry = FiberEntry {
+        method_id,
+        owner,
+        n_args: args.len(),
+    };
+    Ok(new_fiber(
+        vm,
+        irep,
+        values,
+        method.environ.clone(),
+        Some(entry),
+    ))
+}
+
+fn new_fiber(
+    vm: &mut VM,
+    irep: Rc<IREP>,
+    values: Vec<Rc<RObject>>,
+    upper: Option<Rc<ENV>>,
+    entry: Option<FiberEntry>,
+) -> Rc<RObject> {
+    let mut regs = vec![None; FRAME_REGS_SIZE];
+    for (reg, value) in regs.iter_mut().zip(values) {
+        *reg = Some(value);
+    }
+    let context = FiberContext {
+        regs_stack: Rc::new(RefCell::new(regs)),
+        current_regs_offset: 0,
+        current_irep: irep,
+        pc: 0,
+        current_callinfo: None,
+        outer_callinfo: Vec::new(),
+        current_breadcrumb: Some(Rc::new(Breadcrumb {
+            upper: None,
+            event: "fiber",
+            caller: Some("Fiber#resume".to_string()),
+            return_reg: None,
+        })),
+        kargs: None,
+        current_kargs: None,
+        target_class: vm.target_class.clone(),
+        upper,
+        cur_env: EnvTable::default(),
+        has_env_ref: RHashMap::default(),
+        last_match: None,
+        current_exception: None,
+        handler_clauses: Vec::new(),
+        catch_tags: Vec::new(),
+        current_fiber: None,
+    };
+    let fiber = RFiber {
+        state: Cell::new(FiberState::Created),
+        context: RefCell::new(context),
+        entry,
+        resume_reg: Cell::new(0),
+        base_depth: Cell::new(0),
+        transfer: RefCell::new(None),
+    };
+
+    let fiber_data = RData {
+        class: vm.get_class_by_name("Fiber"),
+        data: RefCell::new(Some(Rc::new(Box::new(fiber)))),
+        ref_count: 1,
+    };
+    Rc::new(RObject {
+        tt: RType::Data,
+        value: RValue::Data(Rc::new(fiber_data)),
+        object_id: Cell::new(u64::MAX),
+        singleton_class: RefCell::new(None),
+        ivar: RefCell::new(IvarTable::default()),
+        frozen: Cell::new(false),
+    })
+}
+
+fn mrb_fiber_resume(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    fiber_resume(vm, &this, args)
+}
+
+/// `fiber.resume(*args)`
+pub(crate) fn fiber_resume(
+    vm: &mut VM,
+    this: &Rc<RObject>,
+    args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    let data = get_fiber_data(this)?;
+    let fiber = data
+        .downcast_ref::<RFiber>()
+        .expect("checked in get_fiber_data");
+
+    let state = fiber.state.get();
+    match state {
+        FiberState::Terminated => {
+            return Err(fiber_error("attempt to resume a terminated fiber"));
+        }
+        FiberState::Resumed => {
+            let is_current = vm
+                .current_fiber
+                .as_ref()
+                .is_some_and(|f| Rc::ptr_eq(f, this));
+            return Err(fiber_error(if is_current {
+                "attempt to resume the current fiber"
+            } else {
+                "attempt to resume a resuming fiber"
+            }));
+        }
+        FiberState::Created | FiberState::Suspended => {}
+    }
+
+    // The fiber body runs in a nested interpreter loop on the Rust stack
+    vm.enter_native_depth()?;
+    fiber.context.borrow_mut().swap(vm);
+    vm.current_fiber = Some(this.clone());
+    fiber.state.set(FiberState::Resumed);
+    fiber.base_depth.set(vm.native_depth);
+
+    if state == FiberState::Created {
+        // Give the body's ENTER the argument count, as call_block does
+        match &fiber.entry {
+            Some(entry) => {
+                push_callinfo(
+                    vm,
+                    entry.method_id.clone(),
+                    entry.n_args,
+                    Some(entry.owner.clone()),
+                    0,
+                );
+                if let Some(ci) = vm.current_callinfo.as_ref() {
+                    ci.has_block.set(true);
+                }
+            }
+            None => {
+                for (i, arg) in args.iter().enumerate() {
+                    vm.current_regs()[i + 1].replace(arg.clone());
+                }
+                push_callinfo(vm, "<fiber>".into(), args.len(), None, 0);
+            }
+        }
+        let ci = vm.current_callinfo.take().expect("callinfo just pushed");
+        vm.outer_callinfo.push(ci);
+    } else {
+        vm.regs[fiber.resume_reg.get()].replace(fiber_value(args));
+    }
+
+    let res = vm.run_internal();
+
+    let suspended = fiber.state.get() == FiberState::Suspended;
+    if suspended {
+        // Leaving the loop early hands back the frame's self, not a result
+        if let Ok(frame_self) = &res {
+            vm.current_regs()[0].replace(frame_self.clone());
+        }
+        // and keeps the breadcrumb run_internal pushed
+        if let Some(upper) = vm.current_breadcrumb.take().and_then(|b| b.upper.clone()) {
+            vm.current_breadcrumb.replace(upper);
+        }
+    } else {
+        fiber.state.set(FiberState::Terminated);
+    }
+    // Leave no reference from the fiber's saved context to itself
+    vm.current_fiber.take();
+    fiber.context.borrow_mut().swap(vm);
+    vm.leave_native_depth();
+
+    match res {
+        Ok(_) if suspended => Ok(fiber
+            .transfer
+            .take()
+            .unwrap_or_else(|| Rc::new(RObject::nil()))),
+        Ok(val) => Ok(val),
+        Err(e) => Err(match e.downcast_ref::<Error>() {
+            Some(e) => e.clone(),
+            None => Error::RuntimeError(format!("{:?}", e.as_ref())),
+        }),
+    }
+}
+
+fn mrb_fiber_yield(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm
+        .current_fiber
+        .clone()
+        .ok_or_else(|| fiber_error("can't yield from root fiber"))?;
+    let data = get_fiber_data(&this)?;
+    let fiber = data
+        .downcast_ref::<RFiber>()
+        .expect("checked in get_fiber_data");
+    if vm.native_depth != fiber.base_depth.get() {
+        return Err(fiber_error("can't cross native method boundary"));
+    }
+
+    // SEND moves the register offset onto the register receiving our result
+    fiber.resume_reg.set(vm.current_regs_offset);
+    fiber.transfer.replace(Some(fiber_value(args)));
+    fiber.state.set(FiberState::Suspended);
+    vm.flag_preemption.set(true);
+    Ok(Rc::new(RObject::nil()))
+}
+
+fn mrb_fiber_alive(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    Ok(Rc::new(RObject::boolean(fiber_alive(&this)?)))
+}
+
+/// `fiber.alive?`
+pub(crate) fn fiber_alive(this: &RObject) -> Result<bool, Error> {
+    let data = get_fiber_data(this)?;
+    let fiber = data
+        .downcast_ref::<RFiber>()
+        .expect("checked in get_fiber_data");
+    Ok(fiber.state.get() != FiberState::Terminated)
+}
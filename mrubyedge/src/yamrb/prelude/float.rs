@@ -1,7 +1,9 @@
 use std::rc::Rc;
 
 use crate::Error;
+use crate::yamrb::bigint::RBigInt;
 use crate::yamrb::helpers::mrb_define_cmethod;
+use crate::yamrb::prelude::module::mrb_include_module;
 
 use crate::yamrb::{value::RObject, vm::VM};
 
@@ -52,14 +54,18 @@ pub(crate) fn initialize_float(vm: &mut VM) {
         "EPSILON".to_string(),
         RObject::float(f64::EPSILON).to_refcount_assigned(),
     );
+
+    let comparable_module = vm.get_module_by_name("Comparable");
+    mrb_include_module(&float_class, comparable_module).expect("failed to include Comparable");
 }
 
 pub fn mrb_float_to_i(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
     match &this.value {
         crate::yamrb::value::RValue::Float(f) => {
-            let int_value = *f as i64;
-            Ok(RObject::integer(int_value).to_refcount_assigned())
+            let int_value = RBigInt::from_f64(*f)
+                .ok_or_else(|| Error::TaggedError("FloatDomainError", format!("{}", f)))?;
+            Ok(RObject::bigint(int_value).to_refcount_assigned())
         }
         _ => Err(Error::RuntimeError(
             "Float#to_i must be called on a Float".to_string(),
@@ -145,17 +151,9 @@ pub fn mrb_float_clamp(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
     };
 
     // Convert min and max to f64
-    let min = match &args[0].value {
-        crate::yamrb::value::RValue::Float(f) => *f,
-        crate::yamrb::value::RValue::Integer(i) => *i as f64,
-        _ => return Err(Error::TypeMismatch),
-    };
+    let min = float_operand(&args[0])?;
 
-    let max = match &args[1].value {
-        crate::yamrb::value::RValue::Float(f) => *f,
-        crate::yamrb::value::RValue::Integer(i) => *i as f64,
-        _ => return Err(Error::TypeMismatch),
-    };
+    let max = float_operand(&args[1])?;
 
     if min > max {
         return Err(Error::ArgumentError(
@@ -174,6 +172,16 @@ pub fn mrb_float_clamp(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
     Ok(RObject::float(result).to_refcount_assigned())
 }
 
+/// Reads a numeric argument as f64; Integers beyond i64 are rounded
+fn float_operand(arg: &RObject) -> Result<f64, Error> {
+    match &arg.value {
+        crate::yamrb::value::RValue::Float(f) => Ok(*f),
+        crate::yamrb::value::RValue::Integer(i) => Ok(*i as f64),
+        crate::yamrb::value::RValue::BigInt(b) => Ok(b.to_f64()),
+        _ => Err(Error::TypeMismatch),
+    }
+}
+
 pub fn mrb_float_add(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     if args.is_empty() {
         return Err(Error::ArgumentError(
@@ -191,11 +199,7 @@ pub fn mrb_float_add(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
         }
     };
 
-    let other = match &args[0].value {
-        crate::yamrb::value::RValue::Float(f) => *f,
-        crate::yamrb::value::RValue::Integer(i) => *i as f64,
-        _ => return Err(Error::TypeMismatch),
-    };
+    let other = float_operand(&args[0])?;
 
     Ok(RObject::float(this_float + other).to_refcount_assigned())
 }
@@ -217,11 +221,7 @@ pub fn mrb_float_sub(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
         }
     };
 
-    let other = match &args[0].value {
-        crate::yamrb::value::RValue::Float(f) => *f,
-        crate::yamrb::value::RValue::Integer(i) => *i as f64,
-        _ => return Err(Error::TypeMismatch),
-    };
+    let other = float_operand(&args[0])?;
 
     Ok(RObject::float(this_float - other).to_refcount_assigned())
 }
@@ -243,11 +243,7 @@ pub fn mrb_float_mul(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
         }
     };
 
-    let other = match &args[0].value {
-        crate::yamrb::value::RValue::Float(f) => *f,
-        crate::yamrb::value::RValue::Integer(i) => *i as f64,
-        _ => return Err(Error::TypeMismatch),
-    };
+    let other = float_operand(&args[0])?;
 
     Ok(RObject::float(this_float * other).to_refcount_assigned())
 }
@@ -269,11 +265,7 @@ pub fn mrb_float_div(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
         }
     };
 
-    let other = match &args[0].value {
-        crate::yamrb::value::RValue::Float(f) => *f,
-        crate::yamrb::value::RValue::Integer(i) => *i as f64,
-        _ => return Err(Error::TypeMismatch),
-    };
+    let other = float_operand(&args[0])?;
 
     if other == 0.0 {
         return Err(Error::ZeroDivisionError);
@@ -319,11 +311,7 @@ pub fn mrb_float_power(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
         }
     };
 
-    let other = match &args[0].value {
-        crate::yamrb::value::RValue::Float(f) => *f,
-        crate::yamrb::value::RValue::Integer(i) => *i as f64,
-        _ => return Err(Error::TypeMismatch),
-    };
+    let other = float_operand(&args[0])?;
 
     Ok(RObject::float(this_float.powf(other)).to_refcount_assigned())
 }
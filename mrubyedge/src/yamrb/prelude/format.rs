@@ -0,0 +1,748 @@
+use std::rc::Rc;
+
+use crate::{
+    Error,
+    yamrb::{
+        helpers::{mrb_define_cmethod, mrb_funcall},
+        prelude::{hash::mrb_hash_get_index, r#struct::given_kwargs},
+        value::{RHashMap, RObject, RSym, RValue},
+        vm::VM,
+    },
+};
+
+// Initializes Kernel#format, Kernel#sprintf and String#%.
+pub(crate) fn initialize_format(vm: &mut VM) {
+    let object_class = vm.object_class.clone();
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "format",
+        Box::new(mrb_kernel_format),
+    );
+    mrb_define_cmethod(vm, object_class, "sprintf", Box::new(mrb_kernel_format));
+
+    let string_class = vm.get_class_by_name("String");
+    mrb_define_cmethod(vm, string_class, "%", Box::new(mrb_string_format));
+}
+
+fn mrb_kernel_format(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let Some((fmt, rest)) = args.split_first() else {
+        return Err(Error::ArgumentError("too few arguments".to_string()));
+    };
+    let fmt: String = fmt.as_ref().try_into()?;
+    let kwargs = given_kwargs(vm);
+    let result = mrb_format(vm, &fmt, rest, kwargs)?;
+    Ok(RObject::string(result).to_refcount_assigned())
+}
+
+// String#%: an Array supplies all the arguments, anything else is the only one
+fn mrb_string_format(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let fmt: String = vm.getself()?.as_ref().try_into()?;
+    let args = match args.first().map(|arg| &arg.value) {
+        Some(RValue::Array(a)) => a.borrow().clone(),
+        _ => args.to_vec(),
+    };
+    let result = mrb_format(vm, &fmt, &args, None)?;
+    Ok(RObject::string(result).to_refcount_assigned())
+}
+
+/// Formats `args` by the directives in `fmt`, following CRuby's `sprintf`.
+/// `%<name>` and `%{name}` look up `kwargs`, or the single Hash argument
+/// when no keywords are given.
+pub fn mrb_format(
+    vm: &mut VM,
+    fmt: &str,
+    args: &[Rc<RObject>],
+    kwargs: Option<RHashMap<String, Rc<RObject>>>,
+) -> Result<String, Error> {
+    let mut formatter = Formatter {
+        args,
+        kwargs,
+        next: 0,
+        mode: ArgMode::Unset,
+    };
+    let chars: Vec<char> = fmt.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '%' {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        i = formatter.directive(vm, &chars, i + 1, &mut out)?;
+    }
+    Ok(out)
+}
+
+#[derive(Default)]
+struct Spec {
+    minus: bool,
+    plus: bool,
+    space: bool,
+    zero: bool,
+    sharp: bool,
+    width: Option<usize>,
+    precision: Option<usize>,
+}
+
+// CRuby refuses to mix the ways of referring to arguments
+enum ArgMode {
+    Unset,
+    Unnumbered,
+    Numbered,
+    Named,
+}
+
+struct Formatter<'a> {
+    args: &'a [Rc<RObject>],
+    kwargs: Option<RHashMap<String, Rc<RObject>>>,
+    next: usize,
+    mode: ArgMode,
+}
+
+fn malformed(c: char) -> Error {
+    Error::ArgumentError(format!("malformed format string - %{}", c))
+}
+
+impl Formatter<'_> {
+    // Parses one directive starting after its `%`, appends the result and
+    // returns the index just past it
+    fn directive(
+        &mut self,
+        vm: &mut VM,
+        chars: &[char],
+        start: usize,
+        out: &mut String,
+    ) -> Result<usize, Error> {
+        let mut spec = Spec::default();
+        let mut value: Option<Rc<RObject>> = None;
+        let mut value_name: Option<String> = None;
+        let mut i = start;
+        loop {
+            let Some(&c) = chars.get(i) else {
+                return Err(Error::ArgumentError(
+                    "incomplete format specifier; use %% (double %) instead".to_string(),
+                ));
+            };
+            i += 1;
+            match c {
+                '%' if i == start + 1 => {
+                    out.push('%');
+                    return Ok(i);
+                }
+                '\n' | '\0' if i == start + 1 => {
+                    out.push('%');
+                    return Ok(i - 1);
+                }
+                ' ' => spec.space = true,
+                '#' => spec.sharp = true,
+                '+' => spec.plus = true,
+                '-' => spec.minus = true,
+                '0' => spec.zero = true,
+                '1'..='9' => {
+                    let (n, next) = read_number(chars, i - 1);
+                    i = next;
+                    if chars.get(i) == Some(&'$') {
+                        i += 1;
+                        if value.is_some() {
+                            return Err(Error::ArgumentError(format!(
+                                "value given twice - {}$",
+                                n
+                            )));
+                        }
+                        value = Some(self.numbered(n)?);
+                    } else {
+                        if spec.width.is_some() {
+                            return Err(Error::ArgumentError("width given twice".to_string()));
+                        }
+                        spec.width = Some(n);
+                    }
+                }
+                '*' => {
+                    let width = self.star_arg()?;
+                    spec.minus |= width < 0;
+                    spec.width = Some(width.unsigned_abs() as usize);
+                }
+                '.' => {
+                    if spec.precision.is_some() {
+                        return Err(Error::ArgumentError("precision given twice".to_string()));
+                    }
+                    if chars.get(i) == Some(&'*') {
+                        i += 1;
+                        // A negative precision is treated as if it were omitted
+                        let precision = self.star_arg()?;
+                        spec.precision = (precision >= 0).then_some(precision as usize);
+                    } else {
+                        let (n, next) = read_number(chars, i);
+                        i = next;
+                        spec.precision = Some(n);
+                    }
+                }
+                '<' | '{' => {
+                    let close = if c == '<' { '>' } else { '}' };
+                    let Some(len) = chars[i..].iter().position(|&ch| ch == close) else {
+                        return Err(Error::ArgumentError(
+                            "malformed name - unmatched parenthesis".to_string(),
+                        ));
+                    };
+                    let name: String = chars[i..i + len].iter().collect();
+                    i += len + 1;
+                    if let Some(prev) = &value_name {
+                        return Err(Error::ArgumentError(format!(
+                            "named{}{}{} after <{}>",
+                            c, name, close, prev
+                        )));
+                    }
+                    let named = self.named(vm, &name, c, close)?;
+                    if c == '{' {
+                        // %{name} substitutes to_s right away
+                        let s = to_s(vm, &named)?;
+                        out.push_str(&format_str(&spec, &s));
+                        return Ok(i);
+                    }
+                    value = Some(named);
+                    value_name = Some(name);
+                }
+                'd' | 'i' | 'u' | 'x' | 'X' | 'o' | 'b' | 'B' | 'f' | 'e' | 'E' | 'g' | 'G'
+                | 's' | 'p' | 'c' => {
+                    let arg = match value {
+                        Some(v) => v,
+                        None => self.unnumbered()?,
+                    };
+                    let formatted = match c {
+                        'd' | 'i' | 'u' => format_integer(vm, &spec, &arg, 10, false)?,
+                        'x' | 'X' => format_integer(vm, &spec, &arg, 16, c == 'X')?,
+                        'o' => format_integer(vm, &spec, &arg, 8, false)?,
+                        'b' | 'B' => format_integer(vm, &spec, &arg, 2, c == 'B')?,
+                        'f' | 'e' | 'E' | 'g' | 'G' => format_float(&spec, &arg, c)?,
+                        's' => format_str(&spec, &to_s(vm, &arg)?),
+                        'p' => {
+                            let inspected = mrb_funcall(vm, Some(arg), "inspect", &[])?;
+                            format_str(&spec, &to_s(vm, &inspected)?)
+                        }
+                        _ => format_char(vm, &spec, &arg)?,
+                    };
+                    out.push_str(&formatted);
+                    return Ok(i);
+                }
+                _ => return Err(malformed(c)),
+            }
+        }
+    }
+
+    fn unnumbered(&mut self) -> Result<Rc<RObject>, Error> {
+        match self.mode {
+            ArgMode::Numbered => {
+                return Err(Error::ArgumentError(format!(
+                    "unnumbered({}) mixed with numbered",
+                    self.next + 1
+                )));
+            }
+            ArgMode::Named => {
+                return Err(Error::ArgumentError(format!(
+                    "unnumbered({}) mixed with named",
+                    self.next + 1
+                )));
+            }
+            _ => {}
+        }
+        self.mode = ArgMode::Unnumbered;
+        let arg = self
+            .args
+            .get(self.next)
+            .cloned()
+            .ok_or_else(|| Error::ArgumentError("too few arguments".to_string()))?;
+        self.next += 1;
+        Ok(arg)
+    }
+
+    fn numbered(&mut self, n: usize) -> Result<Rc<RObject>, Error> {
+        match self.mode {
+            ArgMode::Unnumbered => {
+                return Err(Error::ArgumentError(format!(
+                    "numbered({}) after unnumbered({})",
+                    n, self.next
+                )));
+            }
+            ArgMode::Named => {
+                return Err(Error::ArgumentError(format!("numbered({}) after named", n)));
+            }
+            _ => {}
+        }
+        self.mode = ArgMode::Numbered;
+        self.args
+            .get(n - 1)
+            .cloned()
+            .ok_or_else(|| Error::ArgumentError("too few arguments".to_string()))
+    }
+
+    fn named(
+        &mut self,
+        vm: &mut VM,
+        name: &str,
+        open: char,
+        close: char,
+    ) -> Result<Rc<RObject>, Error> {
+        match self.mode {
+            ArgMode::Unnumbered => {
+                return Err(Error::ArgumentError(format!(
+                    "named{}{}{} after unnumbered({})",
+                    open, name, close, self.next
+                )));
+            }
+            ArgMode::Numbered => {
+                return Err(Error::ArgumentError(format!(
+                    "named{}{}{} after numbered",
+                    open, name, close
+                )));
+            }
+            _ => {}
+        }
+        self.mode = ArgMode::Named;
+        let not_found = || {
+            Error::TaggedError(
+                "KeyError",
+                format!("key{}{}{} not found", open, name, close),
+            )
+        };
+        if let Some(kwargs) = &self.kwargs {
+            return kwargs.get(name).cloned().ok_or_else(not_found);
+        }
+        let hash = match self.args {
+            [hash] if matches!(hash.value, RValue::Hash(_)) => hash.clone(),
+            _ => {
+                return Err(Error::ArgumentError("one hash required".to_string()));
+            }
+        };
+        let key = RObject::symbol(RSym::new(name.to_string())).to_refcount_assigned();
+        if mrb_funcall(
+            vm,
+            Some(hash.clone()),
+            "has_key?",
+            std::slice::from_ref(&key),
+        )?
+        .is_falsy()
+        {
+            return Err(not_found());
+        }
+        mrb_hash_get_index(hash, key)
+    }
+
+    fn star_arg(&mut self) -> Result<i64, Error> {
+        let arg = self.unnumbered()?;
+        match &arg.value {
+            RValue::Integer(n) => Ok(*n),
+            _ => Err(Error::TaggedError(
+                "TypeError",
+                "no implicit conversion into Integer".to_string(),
+            )),
+        }
+    }
+}
+
+fn read_number(chars: &[char], mut i: usize) -> (usize, usize) {
+    let mut n: usize = 0;
+    while let Some(d) = chars.get(i).and_then(|c| c.to_digit(10)) {
+        n = n.saturating_mul(10).saturating_add(d as usize);
+        i += 1;
+    }
+    (n, i)
+}
+
+fn to_s(vm: &mut VM, obj: &Rc<RObject>) -> Result<String, Error> {
+    if let RValue::String(s, _) = &obj.value {
+        return Ok(String::from_utf8_lossy(&s.borrow()).to_string());
+    }
+    let s = mrb_funcall(vm, Some(obj.clone()), "to_s", &[])?;
+    s.as_ref().try_into()
+}
+
+fn class_name_for_error(vm: &mut VM, obj: &Rc<RObject>) -> String {
+    match &obj.value {
+        RValue::Nil => "nil".to_string(),
+        RValue::Bool(true) => "true".to_string(),
+        RValue::Bool(false) => "false".to_string(),
+        _ => obj.get_class(vm).full_name(),
+    }
+}
+
+// Pads `body` to the width, on the right for `-`
+fn pad(spec: &Spec, body: String) -> String {
+    let len = body.chars().count();
+    match spec.width {
+        Some(width) if width > len => {
+            let fill = " ".repeat(width - len);
+            if spec.minus {
+                body + &fill
+            } else {
+                fill + &body
+            }
+        }
+        _ => body,
+    }
+}
+
+// Pads with zeros between the sign or prefix and the digits when `0` is given
+fn pad_numeric(spec: &Spec, sign: &str, digits: String, zero_fill: char) -> String {
+    let len = sign.chars().count() + digits.chars().count();
+    match spec.width {
+        Some(width) if spec.zero && !spec.minus && width > len => {
+            let fill: String = std::iter::repeat_n(zero_fill, width - len).collect();
+            format!("{}{}{}", sign, fill, digits)
+        }
+        _ => pad(spec, format!("{}{}", sign, digits)),
+    }
+}
+
+fn format_str(spec: &Spec, s: &str) -> String {
+    let s = match spec.precision {
+        Some(precision) => s.chars().take(precision).collect(),
+        None => s.to_string(),
+    };
+    pad(spec, s)
+}
+
+fn format_char(vm: &mut VM, spec: &Spec, arg: &Rc<RObject>) -> Result<String, Error> {
+    let c = match &arg.value {
+        RValue::String(_, _) => to_s(vm, arg)?
+            .chars()
+            .next()
+            .ok_or_else(|| Error::ArgumentError("%c requires a character".to_string()))?,
+        _ => {
+            let code = integer_arg(vm, arg)?
+                .to_i64()
+                .ok_or_else(|| Error::RangeError("invalid character".to_string()))?;
+            u32::try_from(code)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| Error::RangeError(format!("{} out of char range", code)))?
+        }
+    };
+    Ok(pad(spec, c.to_string()))
+}
+
+enum IntArg {
+    Fixed(i64),
+    Big(crate::yamrb::bigint::RBigInt),
+}
+
+impl IntArg {
+    fn to_i64(&self) -> Option<i64> {
+        match self {
+            IntArg::Fixed(n) => Some(*n),
+            IntArg::Big(n) => n.to_i64(),
+        }
+    }
+}
+
+// Converts like Kernel#Integer: floats are truncated, strings must be numeric
+fn integer_arg(vm: &mut VM, arg: &Rc<RObject>) -> Result<IntArg, Error> {
+    use crate::yamrb::bigint::RBigInt;
+    match &arg.value {
+        RValue::Integer(n) => Ok(IntArg::Fixed(*n)),
+        RValue::BigInt(n) => Ok(IntArg::Big(n.as_ref().clone())),
+        RValue::Float(f) => {
+            if f.is_nan() || f.is_infinite() {
+                return Err(Error::TaggedError(
+                    "FloatDomainError",
+                    format_special(*f, &Spec::default()),
+                ));
+            }
+            let t = f.trunc();
+            if t.abs() < 9.0e18 {
+                Ok(IntArg::Fixed(t as i64))
+            } else {
+                RBigInt::from_f64(t)
+                    .map(IntArg::Big)
+                    .ok_or_else(|| Error::RangeError(format!("{} out of range", f)))
+            }
+        }
+        RValue::String(_, _) => {
+            let s = to_s(vm, arg)?;
+            parse_integer(&s).ok_or_else(|| {
+                Error::ArgumentError(format!("invalid value for Integer(): {:?}", s))
+            })
+        }
+        _ => Err(Error::TaggedError(
+            "TypeError",
+            format!(
+                "can't convert {} into Integer",
+                class_name_for_error(vm, arg)
+            ),
+        )),
+    }
+}
+
+fn parse_integer(s: &str) -> Option<IntArg> {
+    use crate::yamrb::bigint::RBigInt;
+    let s = s.trim();
+    let (negative, body) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let lower = body.to_ascii_lowercase();
+    let (radix, digits) = if let Some(d) = lower.strip_prefix("0x") {
+        (16, d)
+    } else if let Some(d) = lower.strip_prefix("0b") {
+        (2, d)
+    } else if let Some(d) = lower.strip_prefix("0o") {
+        (8, d)
+    } else if lower.len() > 1 && lower.starts_with('0') {
+        (8, &lower[1..])
+    } else {
+        (10, lower.as_str())
+    };
+    if digits.is_empty()
+        || digits.starts_with('_')
+        || digits.ends_with('_')
+        || digits.contains("__")
+    {
+        return None;
+    }
+    let digits = digits.replace('_', "");
+    let n = RBigInt::parse(&digits, radix)?;
+    let n = if negative { n.neg() } else { n };
+    Some(match n.to_i64() {
+        Some(n) => IntArg::Fixed(n),
+        None => IntArg::Big(n),
+    })
+}
+
+fn format_integer(
+    vm: &mut VM,
+    spec: &Spec,
+    arg: &Rc<RObject>,
+    radix: u32,
+    upper: bool,
+) -> Result<String, Error> {
+    let n = integer_arg(vm, arg)?;
+    let negative = match &n {
+        IntArg::Fixed(n) => *n < 0,
+        IntArg::Big(n) => n.is_negative(),
+    };
+    let explicit_sign = spec.plus || spec.space;
+
+    // Without + or space, negative hex, octal and binary numbers are shown
+    // in two's complement, such as `..f01` for -255
+    let (mut digits, twos_complement) = match &n {
+        IntArg::Fixed(n) if negative && radix != 10 && !explicit_sign => {
+            (twos_complement_digits(*n, radix), true)
+        }
+        IntArg::Fixed(n) => (radix_digits(n.unsigned_abs(), radix), false),
+        IntArg::Big(n) => (n.abs().to_string_radix(radix), false),
+    };
+    let sign_digit = char::from_digit(radix - 1, radix).unwrap_or('0');
+    if let Some(precision) = spec.precision {
+        let len = digits.chars().count();
+        if precision > len {
+            let fill = if twos_complement { sign_digit } else { '0' };
+            digits = std::iter::repeat_n(fill, precision - len).collect::<String>() + &digits;
+        }
+    }
+    if upper {
+        digits = digits.to_ascii_uppercase();
+    }
+
+    let mut prefix = String::new();
+    if negative && !twos_complement {
+        prefix.push('-');
+    } else if spec.plus {
+        prefix.push('+');
+    } else if spec.space {
+        prefix.push(' ');
+    }
+    let is_zero = matches!(n, IntArg::Fixed(0));
+    if spec.sharp && !is_zero {
+        prefix.push_str(match (radix, upper) {
+            (16, false) => "0x",
+            (16, true) => "0X",
+            // The precision padding may already provide the leading 0
+            (8, _) if !digits.starts_with('0') => "0",
+            (2, false) => "0b",
+            (2, true) => "0B",
+            _ => "",
+        });
+    }
+    if twos_complement {
+        prefix.push_str("..");
+    }
+
+    let zero_fill = if twos_complement {
+        if upper {
+            sign_digit.to_ascii_uppercase()
+        } else {
+            sign_digit
+        }
+    } else {
+        '0'
+    };
+    if spec.precision.is_some() {
+        // As in C, a precision disables zero padding
+        return Ok(pad(spec, prefix + &digits));
+    }
+    Ok(pad_numeric(spec, &prefix, digits, zero_fill))
+}
+
+fn radix_digits(mut n: u64, radix: u32) -> String {
+    if n == 0 {
+        return "0".to_string();
+    }
+    let mut digits = Vec::new();
+    while n > 0 {
+        digits.push(char::from_digit((n % radix as u64) as u32, radix).unwrap_or('0'));
+        n /= radix as u64;
+    }
+    digits.iter().rev().collect()
+}
+
+// Digits of a negative number down to the first one whose top bit is set,
+// which stands for the infinite run of sign digits
+fn twos_complement_digits(mut n: i64, radix: u32) -> String {
+    let bits = radix.trailing_zeros();
+    let mask = (radix - 1) as i64;
+    let top = 1i64 << (bits - 1);
+    let mut digits = Vec::new();
+    loop {
+        let digit = n & mask;
+        digits.push(char::from_digit(digit as u32, radix).unwrap_or('0'));
+        n >>= bits;
+        if n == -1 && digit & top != 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+fn float_arg(arg: &Rc<RObject>) -> Result<f64, Error> {
+    match &arg.value {
+        RValue::Float(f) => Ok(*f),
+        RValue::Integer(n) => Ok(*n as f64),
+        RValue::BigInt(n) => Ok(n.to_f64()),
+        RValue::String(s, _) => {
+            let s = String::from_utf8_lossy(&s.borrow()).to_string();
+            let trimmed = s.trim().replace('_', "");
+            let valid = !trimmed.is_empty()
+                && trimmed
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+            match trimmed.parse::<f64>() {
+                Ok(f) if valid => Ok(f),
+                _ => Err(Error::ArgumentError(format!(
+                    "invalid value for Float(): {:?}",
+                    s
+                ))),
+            }
+        }
+        RValue::Nil => Err(Error::TaggedError(
+            "TypeError",
+            "can't convert nil into Float".to_string(),
+        )),
+        RValue::Bool(b) => Err(Error::TaggedError(
+            "TypeError",
+            format!("can't convert {} into Float", b),
+        )),
+        _ => Err(Error::TaggedError(
+            "TypeError",
+            "can't convert object into Float".to_string(),
+        )),
+    }
+}
+
+// Inf and NaN keep their sign flags but are never zero padded
+fn format_special(f: f64, spec: &Spec) -> String {
+    let body = if f.is_nan() { "NaN" } else { "Inf" };
+    let sign = if f.is_sign_negative() && !f.is_nan() {
+        "-"
+    } else if spec.plus {
+        "+"
+    } else if spec.space {
+        " "
+    } else {
+        ""
+    };
+    format!("{}{}", sign, body)
+}
+
+fn format_float(spec: &Spec, arg: &Rc<RObject>, conv: char) -> Result<String, Error> {
+    let f = float_arg(arg)?;
+    if f.is_nan() || f.is_infinite() {
+        return Ok(pad(spec, format_special(f, spec)));
+    }
+    let precision = spec.precision.unwrap_or(6);
+    let abs = f.abs();
+    let mut digits = match conv {
+        'f' => fixed(abs, precision, spec.sharp),
+        'e' | 'E' => exponential(abs, precision, spec.sharp),
+        _ => general(abs, precision, spec.sharp),
+    };
+    if conv.is_ascii_uppercase() {
+        digits = digits.to_ascii_uppercase();
+    }
+    let sign = if f.is_sign_negative() {
+        "-"
+    } else if spec.plus {
+        "+"
+    } else if spec.space {
+        " "
+    } else {
+        ""
+    };
+    Ok(pad_numeric(spec, sign, digits, '0'))
+}
+
+fn fixed(f: f64, precision: usize, sharp: bool) -> String {
+    let mut s = format!("{:.*}", precision, f);
+    if sharp && precision == 0 {
+        s.push('.');
+    }
+    s
+}
+
+// C style exponent: at least two digits with an explicit sign
+fn exponential(f: f64, precision: usize, sharp: bool) -> String {
+    let s = format!("{:.*e}", precision, f);
+    let (mantissa, exp) = s.split_once('e').unwrap_or((&s, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    let mut mantissa = mantissa.to_string();
+    if sharp && precision == 0 {
+        mantissa.push('.');
+    }
+    format!(
+        "{}e{}{:02}",
+        mantissa,
+        if exp < 0 { '-' } else { '+' },
+        exp.abs()
+    )
+}
+
+// %g picks %e or %f by the exponent and drops trailing zeros unless `#`
+fn general(f: f64, precision: usize, sharp: bool) -> String {
+    let precision = precision.max(1);
+    let exp = if f == 0.0 {
+        0
+    } else {
+        let s = format!("{:.*e}", precision - 1, f);
+        s.split_once('e')
+            .and_then(|(_, e)| e.parse::<i32>().ok())
+            .unwrap_or(0)
+    };
+    let s = if exp < -4 || exp >= precision as i32 {
+        exponential(f, precision - 1, sharp)
+    } else {
+        fixed(f, (precision as i32 - 1 - exp) as usize, sharp)
+    };
+    if sharp {
+        return s;
+    }
+    let (mantissa, exp_part) = match s.find('e') {
+        Some(pos) => s.split_at(pos),
+        None => (s.as_str(), ""),
+    };
+    let mantissa = if mantissa.contains('.') {
+        mantissa.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        mantissa
+    };
+    format!("{}{}", mantissa, exp_part)
+}
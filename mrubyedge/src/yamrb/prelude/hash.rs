@@ -3,9 +3,9 @@ use std::rc::Rc;
 use crate::{
     Error,
     yamrb::{
-        helpers::{mrb_call_block, mrb_call_inspect, mrb_define_class_cmethod, mrb_define_cmethod},
+        helpers::{mrb_call_inspect, mrb_define_class_cmethod, mrb_define_cmethod},
         prelude::module::mrb_include_module,
-        value::{RHashMap, RObject, RValue},
+        value::{RHash, RObject, RValue},
         vm::VM,
     },
 };
@@ -50,7 +50,7 @@ pub(crate) fn initialize_hash(vm: &mut VM) {
     );
     mrb_define_cmethod(vm, hash_class.clone(), "key", Box::new(mrb_hash_key));
     mrb_define_cmethod(vm, hash_class.clone(), "keys", Box::new(mrb_hash_keys));
-    mrb_define_cmethod(vm, hash_class.clone(), "each", Box::new(mrb_hash_each));
+    // `each` is defined in mrblib/hash.rb
     mrb_define_cmethod(vm, hash_class.clone(), "size", Box::new(mrb_hash_size));
     mrb_define_cmethod(vm, hash_class.clone(), "length", Box::new(mrb_hash_size));
     mrb_define_cmethod(vm, hash_class.clone(), "count", Box::new(mrb_hash_size));
@@ -82,7 +82,7 @@ pub(crate) fn initialize_hash(vm: &mut VM) {
 }
 
 pub fn mrb_hash_new(_vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    Ok(Rc::new(RObject::hash(RHashMap::default())))
+    Ok(Rc::new(RObject::hash(RHash::default())))
 }
 
 fn mrb_hash_get_index_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -109,6 +109,7 @@ pub fn mrb_hash_get_index(this: Rc<RObject>, key: Rc<RObject>) -> Result<Rc<RObj
 
 fn mrb_hash_set_index_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let key = args[0].clone();
     let value = args[1].clone();
     mrb_hash_set_index(this, key, value)
@@ -152,30 +153,11 @@ pub fn mrb_hash_delete(this: Rc<RObject>, key: Rc<RObject>) -> Result<Rc<RObject
 
 fn mrb_hash_delete_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let key = args[0].clone();
     mrb_hash_delete(this, key)
 }
 
-fn mrb_hash_each(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this = vm.getself()?;
-    let block = &args[0];
-    match &this.value {
-        RValue::Hash(hash) => {
-            let hash = hash.borrow();
-            for (_, (key, value)) in hash.iter() {
-                let args = vec![key.clone(), value.clone()];
-                mrb_call_block(vm, block.clone(), None, &args, 0)?;
-            }
-        }
-        _ => {
-            return Err(Error::RuntimeError(
-                "Hash#each must be called on a hash".to_string(),
-            ));
-        }
-    };
-    Ok(this.clone())
-}
-
 fn mrb_hash_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
     let hash = match &this.value {
@@ -210,7 +192,7 @@ fn test_mrb_hash_set_and_index() {
     let mut vm = VM::empty();
     prelude::prelude(&mut vm);
 
-    let hash = Rc::new(RObject::hash(RHashMap::default()));
+    let hash = Rc::new(RObject::hash(RHash::default()));
     let keys = [
         Rc::new(RObject::string("key".to_string())),
         Rc::new(RObject::integer(1234)),
@@ -244,7 +226,7 @@ fn test_mrb_hash_set_and_index_not_found() {
     let mut vm = VM::empty();
     prelude::prelude(&mut vm);
 
-    let hash = Rc::new(RObject::hash(RHashMap::default()));
+    let hash = Rc::new(RObject::hash(RHash::default()));
     let key = Rc::new(RObject::string("key".to_string()));
     let value = Rc::new(RObject::integer(42));
 
@@ -273,6 +255,7 @@ fn mrb_hash_size(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Erro
 // Hash#clear: Removes all key-value pairs from the hash (destructive)
 fn mrb_hash_clear(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     this.hash_borrow_mut()?.clear();
     Ok(this)
 }
@@ -428,6 +411,7 @@ fn mrb_hash_merge(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Erro
 // Hash#merge!: Adds the contents of other_hash to self (destructive)
 fn mrb_hash_merge_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let other = &args[0];
 
     let other_hash = match &other.value {
@@ -477,7 +461,7 @@ fn mrb_hash_flatten(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
 fn test_mrb_hash_size() {
     let mut vm = VM::empty();
 
-    let hash = Rc::new(RObject::hash(RHashMap::default()));
+    let hash = Rc::new(RObject::hash(RHash::default()));
     let key = Rc::new(RObject::string("key".to_string()));
     let value = Rc::new(RObject::integer(42));
     vm.current_regs()[0].replace(hash.clone());
@@ -1,7 +1,12 @@
+use std::cmp::Ordering;
 use std::rc::Rc;
 
 use crate::Error;
-use crate::yamrb::helpers::mrb_define_cmethod;
+use crate::yamrb::bigint::RBigInt;
+use crate::yamrb::helpers::{mrb_define_class_cmethod, mrb_define_cmethod};
+use crate::yamrb::prelude::enumerator::{block_arg, mrb_to_enum};
+use crate::yamrb::prelude::module::mrb_include_module;
+use crate::yamrb::prelude::r#struct::given_kwargs;
 
 use crate::yamrb::value::RValue;
 use crate::yamrb::{helpers::mrb_call_block, value::RObject, vm::VM};
@@ -23,8 +28,21 @@ pub(crate) fn initialize_integer(vm: &mut VM) {
     );
     mrb_define_cmethod(vm, integer_class.clone(), "+", Box::new(mrb_integer_add));
     mrb_define_cmethod(vm, integer_class.clone(), "-", Box::new(mrb_integer_sub));
+    mrb_define_cmethod(vm, integer_class.clone(), "*", Box::new(mrb_integer_mul));
+    mrb_define_cmethod(vm, integer_class.clone(), "/", Box::new(mrb_integer_div));
     mrb_define_cmethod(vm, integer_class.clone(), "**", Box::new(mrb_integer_power));
     mrb_define_cmethod(vm, integer_class.clone(), "%", Box::new(mrb_integer_mod));
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "divmod",
+        Box::new(mrb_integer_divmod),
+    );
+    mrb_define_cmethod(vm, integer_class.clone(), "<=>", Box::new(mrb_integer_cmp));
+    mrb_define_cmethod(vm, integer_class.clone(), "<", Box::new(mrb_integer_lt));
+    mrb_define_cmethod(vm, integer_class.clone(), "<=", Box::new(mrb_integer_le));
+    mrb_define_cmethod(vm, integer_class.clone(), ">", Box::new(mrb_integer_gt));
+    mrb_define_cmethod(vm, integer_class.clone(), ">=", Box::new(mrb_integer_ge));
     mrb_define_cmethod(vm, integer_class.clone(), "&", Box::new(mrb_integer_and));
     mrb_define_cmethod(vm, integer_class.clone(), "|", Box::new(mrb_integer_or));
     mrb_define_cmethod(vm, integer_class.clone(), "^", Box::new(mrb_integer_xor));
@@ -55,12 +73,7 @@ pub(crate) fn initialize_integer(vm: &mut VM) {
         Box::new(mrb_integer_to_f),
     );
     mrb_define_cmethod(vm, integer_class.clone(), "chr", Box::new(mrb_integer_chr));
-    mrb_define_cmethod(
-        vm,
-        integer_class.clone(),
-        "times",
-        Box::new(mrb_integer_times),
-    );
+    // `times` is defined in mrblib/integer.rb
     mrb_define_cmethod(
         vm,
         integer_class.clone(),
@@ -79,142 +92,868 @@ pub(crate) fn initialize_integer(vm: &mut VM) {
         "clamp",
         Box::new(mrb_integer_clamp),
     );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "modulo",
+        Box::new(mrb_integer_mod),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "div",
+        Box::new(mrb_integer_div_floor),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "remainder",
+        Box::new(mrb_integer_remainder),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "fdiv",
+        Box::new(mrb_integer_fdiv),
+    );
+    mrb_define_cmethod(vm, integer_class.clone(), "pow", Box::new(mrb_integer_pow));
+    mrb_define_cmethod(vm, integer_class.clone(), "gcd", Box::new(mrb_integer_gcd));
+    mrb_define_cmethod(vm, integer_class.clone(), "lcm", Box::new(mrb_integer_lcm));
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "digits",
+        Box::new(mrb_integer_digits),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "bit_length",
+        Box::new(mrb_integer_bit_length),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "upto",
+        Box::new(mrb_integer_upto),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "downto",
+        Box::new(mrb_integer_downto),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "step",
+        Box::new(mrb_integer_step),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "succ",
+        Box::new(mrb_integer_succ),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "next",
+        Box::new(mrb_integer_succ),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "pred",
+        Box::new(mrb_integer_pred),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "even?",
+        Box::new(mrb_integer_even),
+    );
+    mrb_define_cmethod(vm, integer_class.clone(), "odd?", Box::new(mrb_integer_odd));
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "zero?",
+        Box::new(mrb_integer_zero),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "positive?",
+        Box::new(mrb_integer_positive),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "negative?",
+        Box::new(mrb_integer_negative_p),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "ceil",
+        Box::new(mrb_integer_ceil),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "floor",
+        Box::new(mrb_integer_floor),
+    );
+    mrb_define_cmethod(
+        vm,
+        integer_class.clone(),
+        "round",
+        Box::new(mrb_integer_round),
+    );
+    mrb_define_class_cmethod(
+        vm,
+        integer_class.clone(),
+        "sqrt",
+        Box::new(mrb_integer_s_sqrt),
+    );
+
+    let comparable_module = vm.get_module_by_name("Comparable");
+    mrb_include_module(&integer_class, comparable_module).expect("failed to include Comparable");
 }
 
-fn mrb_integer_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: i64 = vm.getself()?.as_ref().try_into()?;
-    Ok(Rc::new(RObject::string(this.to_string())))
+// Integer arithmetic shared with the opcode fast paths. Fixnum results that
+// overflow i64 are recomputed as Bignums; Bignum results that fit are demoted.
+
+pub(crate) fn integer_add(lhs: &RObject, rhs: &RObject) -> Option<RObject> {
+    if let (RValue::Integer(a), RValue::Integer(b)) = (&lhs.value, &rhs.value)
+        && let Some(n) = a.checked_add(*b)
+    {
+        return Some(RObject::integer(n));
+    }
+    let (a, b) = bigint_operands(lhs, rhs)?;
+    Some(RObject::bigint(a.add(&b)))
 }
 
-fn mrb_integer_times(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: i64 = vm.getself()?.as_ref().try_into()?;
-    for i in 0..this {
-        let block = args[0].clone();
-        let args = vec![Rc::new(RObject::integer(i))];
-        mrb_call_block(vm, block, None, &args, 0)?;
+pub(crate) fn integer_sub(lhs: &RObject, rhs: &RObject) -> Option<RObject> {
+    if let (RValue::Integer(a), RValue::Integer(b)) = (&lhs.value, &rhs.value)
+        && let Some(n) = a.checked_sub(*b)
+    {
+        return Some(RObject::integer(n));
     }
-    vm.getself()
+    let (a, b) = bigint_operands(lhs, rhs)?;
+    Some(RObject::bigint(a.sub(&b)))
+}
+
+pub(crate) fn integer_mul(lhs: &RObject, rhs: &RObject) -> Option<RObject> {
+    if let (RValue::Integer(a), RValue::Integer(b)) = (&lhs.value, &rhs.value)
+        && let Some(n) = a.checked_mul(*b)
+    {
+        return Some(RObject::integer(n));
+    }
+    let (a, b) = bigint_operands(lhs, rhs)?;
+    Some(RObject::bigint(a.mul(&b)))
+}
+
+/// Floored quotient and modulus, as Ruby defines `Integer#divmod`
+pub(crate) fn integer_divmod(
+    lhs: &RObject,
+    rhs: &RObject,
+) -> Result<Option<(RObject, RObject)>, Error> {
+    if let (RValue::Integer(a), RValue::Integer(b)) = (&lhs.value, &rhs.value)
+        && let (Some(mut q), Some(mut r)) = (a.checked_div(*b), a.checked_rem(*b))
+    {
+        if r != 0 && (r < 0) != (*b < 0) {
+            q -= 1;
+            r += b;
+        }
+        return Ok(Some((RObject::integer(q), RObject::integer(r))));
+    }
+    let Some((a, b)) = bigint_operands(lhs, rhs) else {
+        return Ok(None);
+    };
+    let (q, r) = a.divmod(&b).ok_or(Error::ZeroDivisionError)?;
+    Ok(Some((RObject::bigint(q), RObject::bigint(r))))
+}
+
+/// Compares an Integer with another Integer or a Float
+pub(crate) fn integer_cmp(lhs: &RObject, rhs: &RObject) -> Option<Ordering> {
+    match (&lhs.value, &rhs.value) {
+        (RValue::Integer(a), RValue::Integer(b)) => Some(a.cmp(b)),
+        (_, RValue::Float(b)) => {
+            let a: f64 = lhs.try_into().ok()?;
+            a.partial_cmp(b)
+        }
+        _ => {
+            let (a, b) = bigint_operands(lhs, rhs)?;
+            Some(a.cmp(&b))
+        }
+    }
+}
+
+fn bigint_operands(lhs: &RObject, rhs: &RObject) -> Option<(RBigInt, RBigInt)> {
+    Some((lhs.try_into().ok()?, rhs.try_into().ok()?))
+}
+
+fn mrb_integer_inspect(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this: RBigInt = vm.getself()?.as_ref().try_into()?;
+    let radix = match args.first() {
+        Some(radix) => i64::try_from(radix.as_ref())?,
+        None => 10,
+    };
+    if !(2..=36).contains(&radix) {
+        return Err(Error::ArgumentError(format!("invalid radix {}", radix)));
+    }
+    Ok(Rc::new(RObject::string(this.to_string_radix(radix as u32))))
 }
 
 fn mrb_integer_mod(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let lhs: i64 = vm.getself()?.as_ref().try_into()?;
-    let rhs: i64 = args[0].as_ref().try_into()?;
+    let lhs = vm.getself()?;
+    if let Some((_, r)) = integer_divmod(&lhs, &args[0])? {
+        return Ok(Rc::new(r));
+    }
+    match &args[0].value {
+        RValue::Float(rhs) => {
+            let lhs: f64 = lhs.as_ref().try_into()?;
+            Ok(Rc::new(RObject::float(lhs - rhs * (lhs / rhs).floor())))
+        }
+        _ => Err(Error::TypeMismatch),
+    }
+}
+
+fn mrb_integer_div(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let lhs = vm.getself()?;
+    if let Some((q, _)) = integer_divmod(&lhs, &args[0])? {
+        return Ok(Rc::new(q));
+    }
+    match &args[0].value {
+        RValue::Float(rhs) => {
+            let lhs: f64 = lhs.as_ref().try_into()?;
+            Ok(Rc::new(RObject::float(lhs / rhs)))
+        }
+        _ => Err(Error::TypeMismatch),
+    }
+}
+
+fn mrb_integer_divmod(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let lhs = vm.getself()?;
+    let (q, r) = match integer_divmod(&lhs, &args[0])? {
+        Some(qr) => qr,
+        None => {
+            let RValue::Float(rhs) = args[0].value else {
+                return Err(Error::TypeMismatch);
+            };
+            let lhs: f64 = lhs.as_ref().try_into()?;
+            let q = floored_quotient(lhs, rhs)?;
+            (q, RObject::float(lhs - rhs * (lhs / rhs).floor()))
+        }
+    };
+    Ok(RObject::array(vec![Rc::new(q), Rc::new(r)]).to_refcount_assigned())
+}
+
+// `Integer#div` and `#divmod` give an Integer quotient even for a Float divisor
+fn floored_quotient(lhs: f64, rhs: f64) -> Result<RObject, Error> {
+    if rhs == 0.0 {
+        return Err(Error::ZeroDivisionError);
+    }
+    let q = (lhs / rhs).floor();
+    let q = RBigInt::from_f64(q)
+        .ok_or_else(|| Error::TaggedError("FloatDomainError", format!("{}", q)))?;
+    Ok(RObject::bigint(q))
+}
+
+fn mrb_integer_div_floor(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let lhs = vm.getself()?;
+    if let Some((q, _)) = integer_divmod(&lhs, &args[0])? {
+        return Ok(Rc::new(q));
+    }
+    match &args[0].value {
+        RValue::Float(rhs) => {
+            let lhs: f64 = lhs.as_ref().try_into()?;
+            Ok(Rc::new(floored_quotient(lhs, *rhs)?))
+        }
+        _ => Err(Error::TypeMismatch),
+    }
+}
+
+// Truncated remainder: the result takes the sign of self, unlike `%`
+fn mrb_integer_remainder(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let lhs = vm.getself()?;
+    if let RValue::Float(rhs) = args[0].value {
+        let lhs: f64 = lhs.as_ref().try_into()?;
+        return Ok(Rc::new(RObject::float(lhs % rhs)));
+    }
+    let a = integer_arg(&lhs)?;
+    let b = integer_arg(&args[0])?;
+    let (_, r) = a.divmod(&b).ok_or(Error::ZeroDivisionError)?;
+    let r = if !r.is_zero() && r.is_negative() != a.is_negative() {
+        r.sub(&b)
+    } else {
+        r
+    };
+    Ok(Rc::new(RObject::bigint(r)))
+}
+
+fn mrb_integer_fdiv(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let lhs: f64 = vm.getself()?.as_ref().try_into()?;
+    let rhs: f64 = args[0].as_ref().try_into()?;
+    Ok(Rc::new(RObject::float(lhs / rhs)))
+}
+
+fn integer_arg(arg: &RObject) -> Result<RBigInt, Error> {
+    arg.try_into()
+        .map_err(|_| Error::TaggedError("TypeError", "not an integer".to_string()))
+}
+
+fn one() -> RBigInt {
+    RBigInt::from_i64(1)
+}
+
+/// Floored modulus by a nonzero `m`
+fn modulo(a: &RBigInt, m: &RBigInt) -> RBigInt {
+    a.divmod(m).map(|(_, r)| r).unwrap_or_default()
+}
+
+fn gcd(a: &RBigInt, b: &RBigInt) -> RBigInt {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while let Some((_, r)) = a.divmod(&b) {
+        a = b;
+        b = r;
+    }
+    a
+}
+
+fn mrb_integer_gcd(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let a = integer_arg(&*vm.getself()?)?;
+    let b = integer_arg(&args[0])?;
+    Ok(Rc::new(RObject::bigint(gcd(&a, &b))))
+}
+
+fn mrb_integer_lcm(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let a = integer_arg(&*vm.getself()?)?;
+    let b = integer_arg(&args[0])?;
+    if a.is_zero() || b.is_zero() {
+        return Ok(Rc::new(RObject::integer(0)));
+    }
+    let (q, _) = a
+        .mul(&b)
+        .abs()
+        .divmod(&gcd(&a, &b))
+        .ok_or(Error::ZeroDivisionError)?;
+    Ok(Rc::new(RObject::bigint(q)))
+}
+
+// pow(b, m) by square-and-multiply, reducing modulo m at every step
+fn mrb_integer_pow(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let [exponent, modulus] = args else {
+        return mrb_integer_power(vm, args);
+    };
+    let mut base = integer_arg(&*vm.getself()?)?;
+    let mut exponent = integer_arg(exponent)?;
+    let modulus = integer_arg(modulus)?;
+    if exponent.is_negative() {
+        return Err(Error::RangeError(
+            "Integer#pow() 2nd argument not allowed to be negative when 3rd argument specified"
+                .to_string(),
+        ));
+    }
+    if modulus.is_zero() {
+        return Err(Error::ZeroDivisionError);
+    }
+
+    let mut result = modulo(&one(), &modulus);
+    base = modulo(&base, &modulus);
+    while !exponent.is_zero() {
+        if !exponent.bitand(&one()).is_zero() {
+            result = modulo(&result.mul(&base), &modulus);
+        }
+        base = modulo(&base.mul(&base), &modulus);
+        exponent = exponent.shr(1);
+    }
+    Ok(Rc::new(RObject::bigint(result)))
+}
+
+fn mrb_integer_digits(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let mut this = integer_arg(&*vm.getself()?)?;
+    let base = match args.first() {
+        Some(base) => integer_arg(base)?,
+        None => RBigInt::from_i64(10),
+    };
+    if base.is_negative() {
+        return Err(Error::ArgumentError("negative radix".to_string()));
+    }
+    if base < RBigInt::from_i64(2) {
+        return Err(Error::ArgumentError(format!("invalid radix {}", base)));
+    }
+    if this.is_negative() {
+        return Err(Error::ArgumentError("out of domain".to_string()));
+    }
+
+    let mut digits = Vec::new();
+    loop {
+        let (q, r) = this.divmod(&base).ok_or(Error::ZeroDivisionError)?;
+        digits.push(Rc::new(RObject::bigint(r)));
+        if q.is_zero() {
+            break;
+        }
+        this = q;
+    }
+    Ok(RObject::array(digits).to_refcount_assigned())
+}
+
+fn mrb_integer_bit_length(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = integer_arg(&*vm.getself()?)?;
+    let this = if this.is_negative() { this.not() } else { this };
+    Ok(Rc::new(RObject::integer(this.bit_length() as i64)))
+}
+
+// Float limits are cut down to the last Integer actually reached
+fn iteration_limit(limit: &RObject, upward: bool) -> Result<i64, Error> {
+    match limit.value {
+        RValue::Float(f) if upward => Ok(f.floor() as i64),
+        RValue::Float(f) => Ok(f.ceil() as i64),
+        _ => limit.try_into(),
+    }
+}
+
+fn mrb_integer_upto(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let Some(block) = block_arg(args) else {
+        return Ok(mrb_to_enum(vm, this, "upto", args));
+    };
+    let from: i64 = this.as_ref().try_into()?;
+    let to = iteration_limit(&args[0], true)?;
+    for i in from..=to {
+        mrb_call_block(vm, block.clone(), None, &[Rc::new(RObject::integer(i))], 0)?;
+    }
+    Ok(this)
+}
+
+fn mrb_integer_downto(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let Some(block) = block_arg(args) else {
+        return Ok(mrb_to_enum(vm, this, "downto", args));
+    };
+    let from: i64 = this.as_ref().try_into()?;
+    let to = iteration_limit(&args[0], false)?;
+    for i in (to..=from).rev() {
+        mrb_call_block(vm, block.clone(), None, &[Rc::new(RObject::integer(i))], 0)?;
+    }
+    Ok(this)
+}
+
+// step(limit = nil, step = 1), or step(by:, to:). Any Float operand makes every
+// value a Float, computed from the start so errors do not accumulate.
+fn mrb_integer_step(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let block = block_arg(args);
+    let args = &args[..args.len() - block.is_some() as usize];
+    let kwargs = given_kwargs(vm);
+    let kwarg = |name: &str| kwargs.as_ref().and_then(|kwargs| kwargs.get(name).cloned());
+    let limit = kwarg("to")
+        .or_else(|| args.first().cloned())
+        .filter(|limit| !limit.is_nil());
+    let step = kwarg("by")
+        .or_else(|| args.get(1).cloned())
+        .unwrap_or_else(|| Rc::new(RObject::integer(1)));
+    let Some(block) = block else {
+        // The enumerator calls step again without keywords, so pass them
+        // on as positional arguments
+        if kwargs.is_none() {
+            return Ok(mrb_to_enum(vm, this, "step", args));
+        }
+        let limit = limit.unwrap_or_else(|| Rc::new(RObject::nil()));
+        return Ok(mrb_to_enum(vm, this, "step", &[limit, step]));
+    };
+
+    let is_float = |obj: &RObject| matches!(obj.value, RValue::Float(_));
+    if is_float(&step) || limit.as_ref().is_some_and(|limit| is_float(limit)) {
+        let start: f64 = this.as_ref().try_into()?;
+        let unit: f64 = step.as_ref().try_into()?;
+        if unit == 0.0 {
+            return Err(Error::ArgumentError("step can't be 0".to_string()));
+        }
+        match limit {
+            Some(limit) => {
+                let end: f64 = limit.as_ref().try_into()?;
+                let n = (end - start) / unit;
+                let err =
+                    (start.abs() + end.abs() + (end - start).abs()) / unit.abs() * f64::EPSILON;
+                if n + err < 0.0 {
+                    return Ok(this);
+                }
+                let count = (n + err).floor() as i64 + 1;
+                for i in 0..count {
+                    let mut d = i as f64 * unit + start;
+                    if (unit >= 0.0 && end < d) || (unit < 0.0 && d < end) {
+                        d = end;
+                    }
+                    mrb_call_block(vm, block.clone(), None, &[Rc::new(RObject::float(d))], 0)?;
+                }
+            }
+            None => {
+                for i in 0.. {
+                    let d = i as f64 * unit + start;
+                    mrb_call_block(vm, block.clone(), None, &[Rc::new(RObject::float(d))], 0)?;
+                }
+            }
+        }
+        return Ok(this);
+    }
+
+    let mut i: i64 = this.as_ref().try_into()?;
+    let step: i64 = step.as_ref().try_into()?;
+    if step == 0 {
+        return Err(Error::ArgumentError("step can't be 0".to_string()));
+    }
+    let limit: Option<i64> = limit.map(|limit| limit.as_ref().try_into()).transpose()?;
+    loop {
+        if let Some(limit) = limit
+            && ((step > 0 && i > limit) || (step < 0 && i < limit))
+        {
+            break;
+        }
+        mrb_call_block(vm, block.clone(), None, &[Rc::new(RObject::integer(i))], 0)?;
+        let Some(next) = i.checked_add(step) else {
+            break;
+        };
+        i = next;
+    }
+    Ok(this)
+}
+
+fn mrb_integer_succ(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let succ = integer_add(&this, &RObject::integer(1)).ok_or(Error::TypeMismatch)?;
+    Ok(Rc::new(succ))
+}
+
+fn mrb_integer_pred(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let pred = integer_sub(&this, &RObject::integer(1)).ok_or(Error::TypeMismatch)?;
+    Ok(Rc::new(pred))
+}
+
+fn mrb_integer_even(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this: RBigInt = vm.getself()?.as_ref().try_into()?;
+    Ok(Rc::new(RObject::boolean(this.bitand(&one()).is_zero())))
+}
 
-    Ok(Rc::new(RObject::integer(lhs % rhs)))
+fn mrb_integer_odd(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this: RBigInt = vm.getself()?.as_ref().try_into()?;
+    Ok(Rc::new(RObject::boolean(!this.bitand(&one()).is_zero())))
+}
+
+fn mrb_integer_zero(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this: RBigInt = vm.getself()?.as_ref().try_into()?;
+    Ok(Rc::new(RObject::boolean(this.is_zero())))
+}
+
+fn mrb_integer_positive(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this: RBigInt = vm.getself()?.as_ref().try_into()?;
+    Ok(Rc::new(RObject::boolean(
+        !this.is_zero() && !this.is_negative(),
+    )))
+}
+
+fn mrb_integer_negative_p(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this: RBigInt = vm.getself()?.as_ref().try_into()?;
+    Ok(Rc::new(RObject::boolean(this.is_negative())))
+}
+
+/// Newton's method from a power of two no smaller than the root
+fn isqrt(n: &RBigInt) -> RBigInt {
+    if n.is_zero() {
+        return n.clone();
+    }
+    let mut x = one().shl(n.bit_length().div_ceil(2));
+    loop {
+        let (q, _) = n.divmod(&x).unwrap_or_default();
+        let y = x.add(&q).shr(1);
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+fn mrb_integer_s_sqrt(_vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let n = match args[0].value {
+        RValue::Float(f) => RBigInt::from_f64(f.trunc())
+            .ok_or_else(|| Error::TaggedError("FloatDomainError", format!("{}", f)))?,
+        _ => integer_arg(&args[0])?,
+    };
+    if n.is_negative() {
+        return Err(Error::ArgumentError(
+            r#"Numerical argument is out of domain - "isqrt""#.to_string(),
+        ));
+    }
+    Ok(Rc::new(RObject::bigint(isqrt(&n))))
+}
+
+#[derive(Clone, Copy)]
+enum HalfMode {
+    Up,
+    Even,
+    Down,
+}
+
+enum Rounding {
+    Floor,
+    Ceil,
+    Round(HalfMode),
+}
+
+// ceil/floor/round with negative digits round to a multiple of 10**-digits;
+// any other digit count leaves an Integer unchanged
+fn round_to_digits(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+    rounding: Rounding,
+) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let digits: i64 = match args.first() {
+        Some(digits) => digits.as_ref().try_into()?,
+        None => 0,
+    };
+    if digits >= 0 {
+        return Ok(this);
+    }
+    let n = integer_arg(&this)?;
+    let unit = RBigInt::from_i64(10).pow(digits.unsigned_abs());
+    let (q, r) = n.divmod(&unit).ok_or(Error::ZeroDivisionError)?;
+    let down = n.sub(&r);
+    let up = match rounding {
+        Rounding::Floor => false,
+        Rounding::Ceil => !r.is_zero(),
+        Rounding::Round(half) => match r.add(&r).cmp(&unit) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => match half {
+                HalfMode::Up => !n.is_negative(),
+                HalfMode::Down => n.is_negative(),
+                HalfMode::Even => !q.bitand(&one()).is_zero(),
+            },
+        },
+    };
+    let result = if up { down.add(&unit) } else { down };
+    Ok(Rc::new(RObject::bigint(result)))
+}
+
+fn mrb_integer_ceil(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    round_to_digits(vm, args, Rounding::Ceil)
+}
+
+fn mrb_integer_floor(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    round_to_digits(vm, args, Rounding::Floor)
+}
+
+fn mrb_integer_round(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let half = match given_kwargs(vm).and_then(|kwargs| kwargs.get("half").cloned()) {
+        None => HalfMode::Up,
+        Some(mode) if mode.is_nil() => HalfMode::Up,
+        Some(mode) => {
+            let name = match &mode.value {
+                RValue::Symbol(sym) => sym.name.clone(),
+                _ => mode.as_ref().try_into()?,
+            };
+            match name.as_str() {
+                "up" => HalfMode::Up,
+                "even" => HalfMode::Even,
+                "down" => HalfMode::Down,
+                _ => {
+                    return Err(Error::ArgumentError(format!(
+                        "invalid rounding mode: {}",
+                        name
+                    )));
+                }
+            }
+        }
+    };
+    round_to_digits(vm, args, Rounding::Round(half))
 }
 
 fn mrb_integer_bitref(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: i64 = vm.getself()?.as_ref().try_into()?;
+    let this: RBigInt = vm.getself()?.as_ref().try_into()?;
     let index: i64 = args[0].as_ref().try_into()?;
 
     if index < 0 {
         return Ok(Rc::new(RObject::integer(0)));
     }
 
-    let bit = (this >> index) & 1;
-    Ok(Rc::new(RObject::integer(bit)))
+    let bit = this.shr(index as u64).bitand(&RBigInt::from_i64(1));
+    Ok(Rc::new(RObject::bigint(bit)))
 }
 
 fn mrb_integer_negative(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: i64 = vm.getself()?.as_ref().try_into()?;
-    Ok(Rc::new(RObject::integer(-this)))
+    let this: RBigInt = vm.getself()?.as_ref().try_into()?;
+    Ok(Rc::new(RObject::bigint(this.neg())))
 }
 
 fn mrb_integer_add(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let lhs: i64 = vm.getself()?.as_ref().try_into()?;
+    let lhs = vm.getself()?;
     let rhs_obj = &args[0];
+    if let Some(sum) = integer_add(&lhs, rhs_obj) {
+        return Ok(Rc::new(sum));
+    }
 
     match &rhs_obj.as_ref().value {
-        RValue::Integer(rhs) => Ok(Rc::new(RObject::integer(lhs + rhs))),
-        RValue::Float(rhs) => Ok(Rc::new(RObject::float(lhs as f64 + rhs))),
+        RValue::Float(rhs) => {
+            let lhs: f64 = lhs.as_ref().try_into()?;
+            Ok(Rc::new(RObject::float(lhs + rhs)))
+        }
         _ => Err(Error::TypeMismatch),
     }
 }
 
 fn mrb_integer_sub(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let lhs: i64 = vm.getself()?.as_ref().try_into()?;
+    let lhs = vm.getself()?;
+    let rhs_obj = &args[0];
+    if let Some(diff) = integer_sub(&lhs, rhs_obj) {
+        return Ok(Rc::new(diff));
+    }
+
+    match &rhs_obj.as_ref().value {
+        RValue::Float(rhs) => {
+            let lhs: f64 = lhs.as_ref().try_into()?;
+            Ok(Rc::new(RObject::float(lhs - rhs)))
+        }
+        _ => Err(Error::TypeMismatch),
+    }
+}
+
+fn mrb_integer_mul(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let lhs = vm.getself()?;
     let rhs_obj = &args[0];
+    if let Some(product) = integer_mul(&lhs, rhs_obj) {
+        return Ok(Rc::new(product));
+    }
 
     match &rhs_obj.as_ref().value {
-        RValue::Integer(rhs) => Ok(Rc::new(RObject::integer(lhs - rhs))),
-        RValue::Float(rhs) => Ok(Rc::new(RObject::float(lhs as f64 - rhs))),
+        RValue::Float(rhs) => {
+            let lhs: f64 = lhs.as_ref().try_into()?;
+            Ok(Rc::new(RObject::float(lhs * rhs)))
+        }
         _ => Err(Error::TypeMismatch),
     }
 }
 
+// Results wider than this many bits are reported as Infinity, as CRuby does
+const POW_MAX_BITS: u64 = 1 << 18;
+
 fn mrb_integer_power(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let base: i64 = vm.getself()?.as_ref().try_into()?;
+    let base: RBigInt = vm.getself()?.as_ref().try_into()?;
     let exponent_obj = &args[0];
 
     match &exponent_obj.as_ref().value {
         RValue::Integer(exp) => {
             if *exp >= 0 {
                 // Positive integer exponent
-                let result = base.pow(*exp as u32);
-                Ok(Rc::new(RObject::integer(result)))
+                let exp = *exp as u64;
+                if base.bit_length().saturating_sub(1).saturating_mul(exp) > POW_MAX_BITS {
+                    return Ok(Rc::new(RObject::float(base.to_f64().powf(exp as f64))));
+                }
+                Ok(Rc::new(RObject::bigint(base.pow(exp))))
             } else {
                 // Negative integer exponent - return float
-                let result = (base as f64).powf(*exp as f64);
+                let result = base.to_f64().powf(*exp as f64);
                 Ok(Rc::new(RObject::float(result)))
             }
         }
         RValue::Float(exp) => {
             // Float exponent - return float
-            let result = (base as f64).powf(*exp);
+            let result = base.to_f64().powf(*exp);
             Ok(Rc::new(RObject::float(result)))
         }
         _ => Err(Error::TypeMismatch),
     }
 }
 
+fn compare_with(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Ordering, Error> {
+    let lhs = vm.getself()?;
+    integer_cmp(&lhs, &args[0]).ok_or_else(|| {
+        Error::ArgumentError(format!(
+            "comparison of Integer with {} failed",
+            args[0].get_class(vm).full_name()
+        ))
+    })
+}
+
+fn mrb_integer_cmp(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let lhs = vm.getself()?;
+    match integer_cmp(&lhs, &args[0]) {
+        Some(ord) => Ok(Rc::new(RObject::integer(ord as i64))),
+        None => Ok(Rc::new(RObject::nil())),
+    }
+}
+
+fn mrb_integer_lt(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let ord = compare_with(vm, args)?;
+    Ok(Rc::new(RObject::boolean(ord.is_lt())))
+}
+
+fn mrb_integer_le(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let ord = compare_with(vm, args)?;
+    Ok(Rc::new(RObject::boolean(ord.is_le())))
+}
+
+fn mrb_integer_gt(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let ord = compare_with(vm, args)?;
+    Ok(Rc::new(RObject::boolean(ord.is_gt())))
+}
+
+fn mrb_integer_ge(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let ord = compare_with(vm, args)?;
+    Ok(Rc::new(RObject::boolean(ord.is_ge())))
+}
+
 fn mrb_integer_and(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let lhs: i64 = vm.getself()?.as_ref().try_into()?;
-    let rhs: i64 = args[0].as_ref().try_into()?;
-    Ok(Rc::new(RObject::integer(lhs & rhs)))
+    let lhs: RBigInt = vm.getself()?.as_ref().try_into()?;
+    let rhs: RBigInt = args[0].as_ref().try_into()?;
+    Ok(Rc::new(RObject::bigint(lhs.bitand(&rhs))))
 }
 
 fn mrb_integer_or(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let lhs: i64 = vm.getself()?.as_ref().try_into()?;
-    let rhs: i64 = args[0].as_ref().try_into()?;
-    Ok(Rc::new(RObject::integer(lhs | rhs)))
+    let lhs: RBigInt = vm.getself()?.as_ref().try_into()?;
+    let rhs: RBigInt = args[0].as_ref().try_into()?;
+    Ok(Rc::new(RObject::bigint(lhs.bitor(&rhs))))
 }
 
 fn mrb_integer_xor(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let lhs: i64 = vm.getself()?.as_ref().try_into()?;
-    let rhs: i64 = args[0].as_ref().try_into()?;
-    Ok(Rc::new(RObject::integer(lhs ^ rhs)))
+    let lhs: RBigInt = vm.getself()?.as_ref().try_into()?;
+    let rhs: RBigInt = args[0].as_ref().try_into()?;
+    Ok(Rc::new(RObject::bigint(lhs.bitxor(&rhs))))
 }
 
 fn mrb_integer_not(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: i64 = vm.getself()?.as_ref().try_into()?;
-    Ok(Rc::new(RObject::integer(!this)))
+    let this: RBigInt = vm.getself()?.as_ref().try_into()?;
+    Ok(Rc::new(RObject::bigint(this.not())))
 }
 
 fn mrb_integer_lshift(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let lhs: i64 = vm.getself()?.as_ref().try_into()?;
+    let lhs: RBigInt = vm.getself()?.as_ref().try_into()?;
     let rhs: i64 = args[0].as_ref().try_into()?;
 
     if rhs < 0 {
         return Err(Error::ArgumentError("negative shift count".to_string()));
     }
 
-    Ok(Rc::new(RObject::integer(lhs << rhs)))
+    Ok(Rc::new(RObject::bigint(lhs.shl(rhs as u64))))
 }
 
 fn mrb_integer_rshift(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let lhs: i64 = vm.getself()?.as_ref().try_into()?;
+    let lhs: RBigInt = vm.getself()?.as_ref().try_into()?;
     let rhs: i64 = args[0].as_ref().try_into()?;
 
     if rhs < 0 {
         return Err(Error::ArgumentError("negative shift count".to_string()));
     }
 
-    Ok(Rc::new(RObject::integer(lhs >> rhs)))
+    Ok(Rc::new(RObject::bigint(lhs.shr(rhs as u64))))
 }
 
 fn mrb_integer_abs(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: i64 = vm.getself()?.as_ref().try_into()?;
-    Ok(Rc::new(RObject::integer(this.abs())))
+    let this: RBigInt = vm.getself()?.as_ref().try_into()?;
+    Ok(Rc::new(RObject::bigint(this.abs())))
 }
 
 fn mrb_integer_to_i(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -222,8 +961,8 @@ fn mrb_integer_to_i(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
 }
 
 fn mrb_integer_to_f(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: i64 = vm.getself()?.as_ref().try_into()?;
-    Ok(Rc::new(RObject::float(this as f64)))
+    let this: f64 = vm.getself()?.as_ref().try_into()?;
+    Ok(Rc::new(RObject::float(this)))
 }
 
 fn mrb_integer_chr(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
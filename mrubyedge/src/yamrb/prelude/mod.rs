@@ -6,19 +6,27 @@ use super::vm::VM;
 
 pub mod array;
 pub mod class;
+pub mod comparable;
+pub mod data;
+pub mod encoding;
 pub mod enumerable;
+pub mod enumerator;
 pub mod exception;
 pub mod falseclass;
+pub mod fiber;
 pub mod float;
+pub mod format;
 pub mod hash;
 pub mod integer;
 pub mod module;
+pub mod mrblib;
 pub mod nilclass;
 pub mod object;
 pub mod proc;
 pub mod range;
 pub mod shared_memory;
 pub mod string;
+pub mod r#struct;
 pub mod symbol;
 pub mod trueclass;
 
@@ -30,24 +38,33 @@ pub mod regexp;
 
 pub fn prelude(vm: &mut VM) {
     object::initialize_object(vm);
-    exception::initialize_exception(vm);
     module::initialize_module(vm);
     class::initialize_class(vm);
+    exception::initialize_exception(vm);
+    comparable::initialize_comparable(vm);
     integer::initialize_integer(vm);
     nilclass::initialize_nilclass(vm);
     trueclass::initialize_trueclass(vm);
     falseclass::initialize_falseclass(vm);
     symbol::initialize_symbol(vm);
     proc::initialize_proc(vm);
+    fiber::initialize_fiber(vm);
+    encoding::initialize_encoding(vm);
     string::initialize_string(vm);
+    format::initialize_format(vm);
     enumerable::initialize_enumerable(vm);
+    enumerator::initialize_enumerator(vm);
     array::initialize_array(vm);
     hash::initialize_hash(vm);
     range::initialize_range(vm);
+    r#struct::initialize_struct(vm);
+    data::initialize_data(vm);
     shared_memory::initialize_shared_memory(vm);
     float::initialize_float(vm);
     #[cfg(feature = "mruby-random")]
     rand::initialize_rand(vm);
     #[cfg(feature = "mruby-regexp")]
     regexp::initialize_regexp(vm);
+    // Ruby-defined methods last, once all the classes they reopen exist
+    mrblib::initialize_mrblib(vm);
 }
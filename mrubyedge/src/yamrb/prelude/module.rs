@@ -2,7 +2,12 @@ use std::rc::Rc;
 
 use crate::{
     Error,
-    yamrb::{helpers::mrb_define_cmethod, value::*, vm::VM},
+    yamrb::{
+        helpers::{mrb_call_block, mrb_define_cmethod, mrb_define_module_method, mrb_funcall},
+        method_cache::invalidate_method_caches,
+        value::*,
+        vm::VM,
+    },
 };
 
 pub(crate) fn initialize_module(vm: &mut VM) {
@@ -15,10 +20,106 @@ pub(crate) fn initialize_module(vm: &mut VM) {
     );
     mrb_define_cmethod(
         vm,
-        module_class,
+        module_class.clone(),
         "ancestors",
         Box::new(mrb_module_ancestors),
     );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "class_variable_get",
+        Box::new(mrb_module_class_variable_get),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "class_variable_set",
+        Box::new(mrb_module_class_variable_set),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "class_variable_defined?",
+        Box::new(mrb_module_class_variable_defined),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "class_variables",
+        Box::new(mrb_module_class_variables),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "const_get",
+        Box::new(mrb_module_const_get),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "const_set",
+        Box::new(mrb_module_const_set),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "const_defined?",
+        Box::new(mrb_module_const_defined),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "constants",
+        Box::new(mrb_module_constants),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "const_missing",
+        Box::new(mrb_module_const_missing),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "define_method",
+        Box::new(mrb_module_define_method),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "public",
+        Box::new(mrb_module_public),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "private",
+        Box::new(mrb_module_private),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "protected",
+        Box::new(mrb_module_protected),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "module_function",
+        Box::new(mrb_module_module_function),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class.clone(),
+        "public_class_method",
+        Box::new(mrb_module_public_class_method),
+    );
+    mrb_define_cmethod(
+        vm,
+        module_class,
+        "private_class_method",
+        Box::new(mrb_module_private_class_method),
+    );
 }
 
 fn mrb_module_include(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -70,6 +171,7 @@ pub fn mrb_include_module(target: &impl AsModule, mixin: Rc<RModule>) -> Result<
     }
 
     target.mixed_in_modules.borrow_mut().insert(0, mixin);
+    invalidate_method_caches();
     Ok(())
 }
 
@@ -89,3 +191,440 @@ fn mrb_module_ancestors(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject
         .collect();
     Ok(RObject::array(ancestors).to_refcount_assigned())
 }
+
+fn class_variable_name(arg: &RObject) -> Result<String, Error> {
+    let name = arg.intern()?.name;
+    if !name.starts_with("@@") || name.len() < 3 {
+        return Err(Error::NameError(format!(
+            "'{}' is not allowed as a class variable name",
+            name
+        )));
+    }
+    Ok(name)
+}
+
+fn mrb_module_class_variable_get(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let self_obj = vm.getself()?;
+    let arg0 = args
+        .first()
+        .ok_or_else(|| Error::ArgumentError("wrong number of arguments".to_string()))?;
+    let name = class_variable_name(arg0)?;
+    match resolve_class_var(&self_obj, &name)? {
+        Some(val) => Ok(val),
+        None => {
+            let owner = match &self_obj.value {
+                RValue::Class(klass) => klass.full_name(),
+                RValue::Module(module) => module.full_name(),
+                _ => unreachable!("resolve_class_var accepts only class or module"),
+            };
+            Err(Error::NameError(format!(
+                "uninitialized class variable {} in {}",
+                name, owner
+            )))
+        }
+    }
+}
+
+fn mrb_module_class_variable_set(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let self_obj = vm.getself()?;
+    if args.len() < 2 {
+        return Err(Error::ArgumentError(
+            "wrong number of arguments".to_string(),
+        ));
+    }
+    let name = class_variable_name(&args[0])?;
+    assign_class_var(&self_obj, &name, args[1].clone())?;
+    Ok(args[1].clone())
+}
+
+fn mrb_module_class_variable_defined(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    let self_obj = vm.getself()?;
+    let arg0 = args
+        .first()
+        .ok_or_else(|| Error::ArgumentError("wrong number of arguments".to_string()))?;
+    let name = class_variable_name(arg0)?;
+    let defined = resolve_class_var(&self_obj, &name)?.is_some();
+    Ok(RObject::boolean(defined).to_refcount_assigned())
+}
+
+fn mrb_module_class_variables(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let self_obj = vm.getself()?;
+    let mut names: Vec<String> = Vec::new();
+    for module in class_var_lookup_chain(&self_obj)? {
+        for name in module.class_vars.borrow().keys() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+    }
+    let names: Vec<Rc<RObject>> = names.into_iter().map(|name| vm.intern(&name)).collect();
+    Ok(RObject::array(names).to_refcount_assigned())
+}
+
+// define_method(name, proc) or define_method(name) { ... }
+fn mrb_module_define_method(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let module = self_module(vm)?;
+    let (name, method) = method_from_block(vm, args)?;
+    mrb_define_module_method(vm, module, &name, method);
+    Ok(vm.intern(&name))
+}
+
+/// Builds the method for define_method and define_singleton_method. A Ruby
+/// block becomes a method running its own code through SEND, with the
+/// environment it closed over and lambda semantics; a native proc such as
+/// `&:sym` is wrapped to be called with the receiver as self.
+pub(crate) fn method_from_block(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+) -> Result<(String, RProc), Error> {
+    let name = args
+        .first()
+        .ok_or_else(|| {
+            Error::ArgumentError("wrong number of arguments (given 0, expected 1..2)".to_string())
+        })?
+        .intern()?
+        .name;
+    let body = match args.get(1) {
+        Some(body) if matches!(body.value, RValue::Proc(_)) => body.clone(),
+        _ => {
+            return Err(Error::ArgumentError(
+                "tried to create Proc object without a block".to_string(),
+            ));
+        }
+    };
+    let RValue::Proc(block) = &body.value else {
+        unreachable!("checked to be a Proc")
+    };
+    if block.is_rb_func {
+        let mut method = block.clone();
+        method.sym_id = Some(RSym::new(name.clone()));
+        method.block_self = None;
+        method.visibility = Visibility::Public;
+        return Ok((name, method));
+    }
+    let func = move |vm: &mut VM, args: &[Rc<RObject>]| {
+        let this = vm.getself()?;
+        mrb_call_block(vm, body.clone(), Some(this), args, 0)
+    };
+    let method = RProc {
+        is_rb_func: false,
+        is_fnblock: false,
+        sym_id: Some(RSym::new(name.clone())),
+        next: None,
+        irep: None,
+        func: Some(vm.register_fn(Box::new(func))),
+        environ: None,
+        block_self: None,
+        visibility: Visibility::Public,
+    };
+    Ok((name, method))
+}
+
+// Method names given as symbols, strings or arrays of them
+fn method_names(args: &[Rc<RObject>]) -> Result<Vec<String>, Error> {
+    let mut names = Vec::new();
+    for arg in args.iter() {
+        match &arg.value {
+            RValue::Array(items) => names.extend(method_names(&items.borrow())?),
+            _ => names.push(arg.intern()?.name),
+        }
+    }
+    Ok(names)
+}
+
+// private(:a) returns :a, private(:a, :b) returns [:a, :b]
+fn method_names_result(args: &[Rc<RObject>]) -> Rc<RObject> {
+    match args {
+        [arg] => arg.clone(),
+        _ => RObject::array(args.to_vec()).to_refcount_assigned(),
+    }
+}
+
+fn undefined_method_error(module: &Rc<RModule>, name: &str) -> Error {
+    let kind = if module.underlying.borrow().is_some() {
+        "class"
+    } else {
+        "module"
+    };
+    Error::NameError(format!(
+        "undefined method '{}' for {} '{}'",
+        name,
+        kind,
+        module.full_name()
+    ))
+}
+
+// The method `name` as seen from `module`, defined there or inherited
+fn find_method(module: &Rc<RModule>, name: &str) -> Option<RProc> {
+    const_lookup_chain(module)
+        .iter()
+        .find_map(|m| m.procs.borrow().get(name).cloned())
+}
+
+// Changes the visibility of `name` in `module`. An inherited method gets a
+// copy in `module`, so its owner keeps the original visibility
+fn set_method_visibility(
+    module: &Rc<RModule>,
+    name: &str,
+    visibility: Visibility,
+) -> Result<(), Error> {
+    let mut method =
+        find_method(module, name).ok_or_else(|| undefined_method_error(module, name))?;
+    method.visibility = visibility;
+    module.procs.borrow_mut().insert(name.to_string(), method);
+    invalidate_method_caches();
+    Ok(())
+}
+
+fn set_visibility(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+    visibility: Visibility,
+) -> Result<Rc<RObject>, Error> {
+    let module = self_module(vm)?;
+    if args.is_empty() {
+        if let Some(ci) = vm.active_callinfo() {
+            ci.visibility.set(visibility);
+            ci.module_function.set(false);
+        }
+        return Ok(RObject::nil().to_refcount_assigned());
+    }
+    for name in method_names(args)? {
+        set_method_visibility(&module, &name, visibility)?;
+    }
+    Ok(method_names_result(args))
+}
+
+fn mrb_module_public(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    set_visibility(vm, args, Visibility::Public)
+}
+
+fn mrb_module_private(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    set_visibility(vm, args, Visibility::Private)
+}
+
+fn mrb_module_protected(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    set_visibility(vm, args, Visibility::Protected)
+}
+
+// module_function(*names) copies the methods to the module's singleton class
+// and makes the instance methods private; without names it applies to the
+// methods defined after it
+fn mrb_module_module_function(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let self_obj = vm.getself()?;
+    let module = match &self_obj.value {
+        RValue::Module(module) => module.clone(),
+        _ => {
+            return Err(Error::TaggedError(
+                "TypeError",
+                "module_function must be called for modules".to_string(),
+            ));
+        }
+    };
+    if args.is_empty() {
+        if let Some(ci) = vm.active_callinfo() {
+            ci.visibility.set(Visibility::Private);
+            ci.module_function.set(true);
+        }
+        return Ok(RObject::nil().to_refcount_assigned());
+    }
+    let sclass = self_obj.initialize_or_get_singleton_class_for_class(vm);
+    for name in method_names(args)? {
+        let mut method =
+            find_method(&module, &name).ok_or_else(|| undefined_method_error(&module, &name))?;
+        method.visibility = Visibility::Public;
+        sclass.procs.borrow_mut().insert(name.clone(), method);
+        set_method_visibility(&module, &name, Visibility::Private)?;
+    }
+    Ok(method_names_result(args))
+}
+
+fn set_class_method_visibility(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+    visibility: Visibility,
+) -> Result<Rc<RObject>, Error> {
+    let self_obj = vm.getself()?;
+    self_module(vm)?;
+    let sclass = self_obj.initialize_or_get_singleton_class_for_class(vm);
+    for name in method_names(args)? {
+        set_method_visibility(&sclass.module, &name, visibility)?;
+    }
+    Ok(RObject::nil().to_refcount_assigned())
+}
+
+fn mrb_module_public_class_method(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    set_class_method_visibility(vm, args, Visibility::Public)
+}
+
+fn mrb_module_private_class_method(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    set_class_method_visibility(vm, args, Visibility::Private)
+}
+
+fn self_module(vm: &mut VM) -> Result<Rc<RModule>, Error> {
+    let self_obj = vm.getself()?;
+    match &self_obj.value {
+        RValue::Class(klass) => Ok(klass.module.clone()),
+        RValue::Module(module) => Ok(module.clone()),
+        _ => Err(Error::RuntimeError(
+            "must be called on class or module".to_string(),
+        )),
+    }
+}
+
+fn constant_name(arg: &RObject) -> Result<String, Error> {
+    let name = arg.intern()?.name;
+    if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return Err(Error::NameError(format!("wrong constant name {}", name)));
+    }
+    Ok(name)
+}
+
+fn inherit_flag(args: &[Rc<RObject>], index: usize) -> bool {
+    args.get(index).is_none_or(|v| v.is_truthy())
+}
+
+/// Result of walking a constant path: the last scope searched,
+/// the last segment name, and the constant if found.
+struct ConstPath {
+    scope: Rc<RModule>,
+    name: String,
+    value: Option<Rc<RObject>>,
+}
+
+/// Resolves a constant path such as `"Foo::Bar"` from `module`.
+fn resolve_const_path(
+    vm: &mut VM,
+    module: Rc<RModule>,
+    path: &str,
+    inherit: bool,
+) -> Result<ConstPath, Error> {
+    let mut scope = module;
+    let mut segments = path.split("::").peekable();
+    if path.starts_with("::") {
+        segments.next();
+        scope = vm.object_class.module.clone();
+    }
+    while let Some(segment) = segments.next() {
+        let name = constant_name(&RObject::string(segment.to_string()))?;
+        let found = if inherit {
+            resolve_const(vm, &scope, &name, true)
+        } else {
+            scope.consts.borrow().get(&name).cloned()
+        };
+        let Some(val) = found else {
+            return Ok(ConstPath {
+                scope,
+                name,
+                value: None,
+            });
+        };
+        if segments.peek().is_none() {
+            return Ok(ConstPath {
+                scope,
+                name,
+                value: Some(val),
+            });
+        }
+        scope = match &val.value {
+            RValue::Class(klass) => klass.module.clone(),
+            RValue::Module(module) => module.clone(),
+            _ => {
+                return Err(Error::TypeMismatch);
+            }
+        };
+    }
+    Err(Error::NameError(format!("wrong constant name {}", path)))
+}
+
+fn mrb_module_const_get(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let module = self_module(vm)?;
+    let path = args
+        .first()
+        .ok_or_else(|| Error::ArgumentError("wrong number of arguments".to_string()))?
+        .intern()?
+        .name;
+    let inherit = inherit_flag(args, 1);
+    match resolve_const_path(vm, module, &path, inherit)? {
+        ConstPath {
+            value: Some(val), ..
+        } => Ok(val),
+        ConstPath { scope, name, .. } => {
+            let recv = RObject::class_or_module(scope, vm);
+            let sym = vm.intern(&name);
+            mrb_funcall(vm, Some(recv), "const_missing", &[sym])
+        }
+    }
+}
+
+fn mrb_module_const_set(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let module = self_module(vm)?;
+    if args.len() < 2 {
+        return Err(Error::ArgumentError(
+            "wrong number of arguments".to_string(),
+        ));
+    }
+    let name = constant_name(&args[0])?;
+    module.consts.borrow_mut().insert(name, args[1].clone());
+    Ok(args[1].clone())
+}
+
+fn mrb_module_const_defined(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let module = self_module(vm)?;
+    let path = args
+        .first()
+        .ok_or_else(|| Error::ArgumentError("wrong number of arguments".to_string()))?
+        .intern()?
+        .name;
+    let inherit = inherit_flag(args, 1);
+    let found = resolve_const_path(vm, module, &path, inherit)?.value;
+    Ok(RObject::boolean(found.is_some()).to_refcount_assigned())
+}
+
+fn mrb_module_constants(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let module = self_module(vm)?;
+    let inherit = inherit_flag(args, 0);
+    let modules = if inherit {
+        const_lookup_chain(&module)
+    } else {
+        vec![module.clone()]
+    };
+    let object_module = vm.object_class.module.clone();
+    let mut names: Vec<String> = Vec::new();
+    for m in modules {
+        // Top-level constants are not listed unless asked on Object itself
+        if Rc::ptr_eq(&m, &object_module) && !Rc::ptr_eq(&module, &object_module) {
+            break;
+        }
+        for name in m.consts.borrow().keys() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+    }
+    let names: Vec<Rc<RObject>> = names.into_iter().map(|name| vm.intern(&name)).collect();
+    Ok(RObject::array(names).to_refcount_assigned())
+}
+
+fn mrb_module_const_missing(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let module = self_module(vm)?;
+    let name = args
+        .first()
+        .ok_or_else(|| Error::ArgumentError("wrong number of arguments".to_string()))?
+        .intern()?
+        .name;
+    if Rc::ptr_eq(&module, &vm.object_class.module) {
+        return Err(Error::NameError(name));
+    }
+    Err(Error::NameError(format!(
+        "{}::{}",
+        module.full_name(),
+        name
+    )))
+}
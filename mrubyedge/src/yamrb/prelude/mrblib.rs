@@ -0,0 +1,16 @@
+//! Builtin methods written in Ruby, as mruby does in its mrblib. Iterators
+//! such as `each`, `times` and `loop` live here, so that their blocks run as
+//! Ruby frames and `Fiber.yield` can suspend inside them.
+//!
+//! `mrblib.mrb` is compiled from `mrblib/*.rb`; `tests/mrblib.rs` checks that
+//! it is up to date.
+
+use crate::{rite, yamrb::vm::VM};
+
+const MRBLIB: &[u8] = include_bytes!("mrblib/mrblib.mrb");
+
+pub(crate) fn initialize_mrblib(vm: &mut VM) {
+    let mut rite = rite::load(MRBLIB).expect("mrblib bytecode is broken");
+    vm.eval_builtin_rite(&mut rite)
+        .expect("failed to define mrblib methods");
+}
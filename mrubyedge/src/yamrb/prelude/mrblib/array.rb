@@ -0,0 +1,21 @@
+class Array
+  def each
+    return to_enum(:each) unless block_given?
+    i = 0
+    while i < size
+      yield self[i]
+      i += 1
+    end
+    self
+  end
+
+  def each_with_index
+    return to_enum(:each_with_index) unless block_given?
+    i = 0
+    while i < size
+      yield self[i], i
+      i += 1
+    end
+    self
+  end
+end
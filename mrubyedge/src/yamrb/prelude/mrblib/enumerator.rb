@@ -0,0 +1,24 @@
+class Enumerator
+  class Yielder
+    def initialize(&block)
+      @block = block
+    end
+
+    def <<(value)
+      @block.call(value)
+      self
+    end
+
+    def yield(*values)
+      @block.call(*values)
+    end
+    alias call yield
+  end
+
+  private
+
+  # Block `next` and `peek` run the source with, inside their fiber
+  def __fiber_block
+    proc { |*values| Fiber.yield(*values) }
+  end
+end
@@ -0,0 +1,14 @@
+class Hash
+  # Iterates over a snapshot, so the block may add or delete keys
+  def each
+    return to_enum(:each) unless block_given?
+    keys = self.keys
+    values = self.values
+    i = 0
+    while i < keys.size
+      yield keys[i], values[i]
+      i += 1
+    end
+    self
+  end
+end
@@ -0,0 +1,11 @@
+class Integer
+  def times
+    return to_enum(:times) unless block_given?
+    i = 0
+    while i < self
+      yield i
+      i += 1
+    end
+    self
+  end
+end
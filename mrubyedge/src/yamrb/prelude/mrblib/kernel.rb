@@ -0,0 +1,10 @@
+class Object
+  def loop
+    return to_enum(:loop) unless block_given?
+    while true
+      yield
+    end
+  rescue StopIteration
+    nil
+  end
+end
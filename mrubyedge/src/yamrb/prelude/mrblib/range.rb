@@ -0,0 +1,25 @@
+class Range
+  def each
+    return to_enum(:each) unless block_given?
+    i = self.begin
+    last = self.end
+    raise TypeError, "can't iterate from #{i.class}" unless i.is_a?(Integer)
+    if last.nil?
+      while true
+        yield i
+        i += 1
+      end
+    elsif exclude_end?
+      while i < last
+        yield i
+        i += 1
+      end
+    else
+      while i <= last
+        yield i
+        i += 1
+      end
+    end
+    self
+  end
+end
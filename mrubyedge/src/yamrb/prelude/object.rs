@@ -1,18 +1,27 @@
-use std::rc::Rc;
+use std::{cmp::Ordering, rc::Rc};
 
 use crate::{
     Error,
     yamrb::{
-        helpers::{mrb_call_block, mrb_define_cmethod, mrb_funcall},
+        helpers::{
+            mrb_call_block, mrb_call_inspect, mrb_define_cmethod, mrb_define_method,
+            mrb_define_singleton_method, mrb_funcall, mrb_funcall_with_block,
+        },
+        method_cache::invalidate_method_caches,
+        optable::visibility_error,
+        prelude::{enumerator::block_arg, integer::integer_cmp, module::method_from_block},
         value::*,
-        vm::VM,
+        vm::{RSymTable, VM},
     },
 };
 
 pub(crate) fn initialize_object(vm: &mut VM) {
     let object_class = vm.object_class.clone();
     let klass = RObject::class(object_class.clone(), vm);
-    vm.consts.insert("Object".to_string(), klass);
+    object_class
+        .consts
+        .borrow_mut()
+        .insert("Object".to_string(), klass);
     vm.builtin_class_table
         .insert("Object", object_class.clone());
 
@@ -72,6 +81,12 @@ pub(crate) fn initialize_object(vm: &mut VM) {
         "raise",
         Box::new(mrb_object_raise),
     );
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "caller",
+        Box::new(mrb_object_caller),
+    );
     mrb_define_cmethod(vm, object_class.clone(), "nil?", Box::new(mrb_object_nil_p));
     mrb_define_cmethod(
         vm,
@@ -122,7 +137,31 @@ pub(crate) fn initialize_object(vm: &mut VM) {
         "extend",
         Box::new(mrb_object_extend),
     );
-    mrb_define_cmethod(vm, object_class.clone(), "loop", Box::new(mrb_object_loop));
+    // `loop` is defined in mrblib/kernel.rb
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "catch",
+        Box::new(mrb_object_catch),
+    );
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "throw",
+        Box::new(mrb_object_throw),
+    );
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "freeze",
+        Box::new(mrb_object_freeze),
+    );
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "frozen?",
+        Box::new(mrb_object_frozen),
+    );
     mrb_define_cmethod(
         vm,
         object_class.clone(),
@@ -135,24 +174,69 @@ pub(crate) fn initialize_object(vm: &mut VM) {
         "public_send",
         Box::new(mrb_object_public_send),
     );
+    mrb_define_cmethod(vm, object_class.clone(), "send", Box::new(mrb_object_send));
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "__send__",
+        Box::new(mrb_object_send),
+    );
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "instance_variable_get",
+        Box::new(mrb_object_instance_variable_get),
+    );
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "instance_variable_set",
+        Box::new(mrb_object_instance_variable_set),
+    );
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "instance_variable_defined?",
+        Box::new(mrb_object_instance_variable_defined),
+    );
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "instance_variables",
+        Box::new(mrb_object_instance_variables),
+    );
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "define_singleton_method",
+        Box::new(mrb_object_define_singleton_method),
+    );
+    mrb_define_cmethod(
+        vm,
+        object_class.clone(),
+        "singleton_methods",
+        Box::new(mrb_object_singleton_methods),
+    );
 
     // define global consts:
-    vm.consts.insert(
+    let mut consts = object_class.consts.borrow_mut();
+    consts.insert(
         "RUBY_VERSION".to_string(),
         Rc::new(RObject::string(crate::yamrb::vm::VERSION.to_string())),
     );
-    vm.consts.insert(
+    consts.insert(
         "MRUBY_VERSION".to_string(),
         Rc::new(RObject::string(crate::yamrb::vm::VERSION.to_string())),
     );
-    vm.consts.insert(
+    consts.insert(
         "MRUBY_EDGE_VERSION".to_string(),
         Rc::new(RObject::string(crate::yamrb::vm::VERSION.to_string())),
     );
-    vm.consts.insert(
+    consts.insert(
         "RUBY_ENGINE".to_string(),
         Rc::new(RObject::string(crate::yamrb::vm::ENGINE.to_string())),
     );
+    drop(consts);
     mrb_define_cmethod(vm, object_class.clone(), "wasm?", Box::new(mrb_is_wasm));
 }
 
@@ -196,12 +280,27 @@ pub fn mrb_kernel_debug(_vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject
     Ok(Rc::new(RObject::nil()))
 }
 
+// Integers equal Floats of the same value, however large the Integer
+fn numeric_eq(lhs: &RObject, rhs: &RObject) -> Option<bool> {
+    match (&lhs.value, &rhs.value) {
+        (RValue::Integer(_) | RValue::BigInt(_), RValue::Float(_)) => {
+            Some(integer_cmp(lhs, rhs) == Some(Ordering::Equal))
+        }
+        (RValue::Float(_), RValue::Integer(_) | RValue::BigInt(_)) => {
+            Some(integer_cmp(rhs, lhs) == Some(Ordering::Equal))
+        }
+        _ => None,
+    }
+}
+
 pub fn mrb_object_is_equal(_vm: &mut VM, lhs: Rc<RObject>, rhs: Rc<RObject>) -> Rc<RObject> {
-    RObject::boolean(lhs.as_eq_value() == rhs.as_eq_value()).to_refcount_assigned()
+    let eq = numeric_eq(&lhs, &rhs).unwrap_or_else(|| lhs.as_eq_value() == rhs.as_eq_value());
+    RObject::boolean(eq).to_refcount_assigned()
 }
 
 pub fn mrb_object_is_not_equal(_vm: &mut VM, lhs: Rc<RObject>, rhs: Rc<RObject>) -> Rc<RObject> {
-    RObject::boolean(lhs.as_eq_value() != rhs.as_eq_value()).to_refcount_assigned()
+    let eq = numeric_eq(&lhs, &rhs).unwrap_or_else(|| lhs.as_eq_value() == rhs.as_eq_value());
+    RObject::boolean(!eq).to_refcount_assigned()
 }
 
 pub fn mrb_object_double_eq(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -213,6 +312,11 @@ pub fn mrb_object_double_eq(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObj
 pub fn mrb_object_not_eq(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let lhs = vm.getself()?;
     let rhs = args[0].clone();
+    if matches!(lhs.value, RValue::Instance(_) | RValue::Data(_)) {
+        // Follow a user-defined ==, as op_eq does
+        let eq = mrb_funcall(vm, Some(lhs), "==", &[rhs])?;
+        return Ok(Rc::new(RObject::boolean(!eq.is_truthy())));
+    }
     Ok(mrb_object_is_not_equal(vm, lhs, rhs))
 }
 
@@ -299,6 +403,26 @@ pub fn mrb_object_compare(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObjec
                 0
             }
         }
+        (BigInt(a), Float(b)) => {
+            let a_float = a.to_f64();
+            if a_float < *b {
+                -1
+            } else if a_float > *b {
+                1
+            } else {
+                0
+            }
+        }
+        (Float(a), BigInt(b)) => {
+            let b_float = b.to_f64();
+            if *a < b_float {
+                -1
+            } else if *a > b_float {
+                1
+            } else {
+                0
+            }
+        }
         (Symbol(a), Symbol(b)) => {
             if a < b {
                 -1
@@ -308,19 +432,20 @@ pub fn mrb_object_compare(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObjec
                 0
             }
         }
-        _ => {
-            return Err(Error::ArgumentError(
-                "comparison of incompatible types".to_string(),
-            ));
-        }
+        // Other objects are only equal to themselves; nil means incomparable
+        _ if Rc::ptr_eq(&lhs, rhs) => 0,
+        _ => return Ok(Rc::new(RObject::nil())),
     };
 
     Ok(Rc::new(RObject::integer(result)))
 }
 
 pub fn mrb_object_object_id(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    // Abstract method; do nothing
-    let x = vm.getself()?.object_id.get();
+    let this = vm.getself()?;
+    let x = match &this.value {
+        RValue::Symbol(sym) => RSymTable::object_id(vm.symbol_table.intern(&sym.name)),
+        _ => this.object_id.get(),
+    };
     // ref: https://stackoverflow.com/questions/74491204/how-do-i-represent-an-i64-in-the-u64-domain
     let to_i64 = ((x as i64) ^ (1 << 63)) & (1 << 63) | (x & (u64::MAX >> 1)) as i64;
     Ok(Rc::new(RObject::integer(to_i64)))
@@ -336,11 +461,55 @@ pub fn mrb_object_to_s(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>
     Ok(RObject::string(format!("#<{}:{}>", class.full_name(), addr)).to_refcount_assigned())
 }
 
-pub fn mrb_object_raise(_vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    // TODO: accept exception class
-    let msg = args[0].as_ref().try_into()?;
-    let err = Error::RuntimeError(msg);
-    Err(err)
+/// `raise`, `raise msg`, `raise Klass[, msg]` and `raise obj[, msg]`
+pub fn mrb_object_raise(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let Some(target) = args.first() else {
+        // Bare `raise` in a rescue clause re-raises `$!`
+        return match vm.current_exception.clone() {
+            Some(exc) => Err(Error::Exception(exc)),
+            None => Err(Error::RuntimeError("unhandled exception".to_string())),
+        };
+    };
+    let exc = match &target.value {
+        RValue::String(_, _) => {
+            let msg = target.as_ref().try_into()?;
+            Error::RuntimeError(msg)
+        }
+        RValue::Class(_) | RValue::Exception(_) => {
+            let exc = mrb_funcall(vm, Some(target.clone()), "exception", &args[1..])?;
+            if !matches!(exc.value, RValue::Exception(_)) {
+                return Err(Error::TaggedError(
+                    "TypeError",
+                    "exception object expected".to_string(),
+                ));
+            }
+            if exc.get_ivar("cause").is_nil()
+                && let Some(cause) = vm.current_exception.clone()
+                && !Rc::ptr_eq(&cause, &exc)
+            {
+                exc.set_ivar("cause", cause);
+            }
+            Error::Exception(exc)
+        }
+        _ => {
+            return Err(Error::TaggedError(
+                "TypeError",
+                "exception class/object expected".to_string(),
+            ));
+        }
+    };
+    Err(exc)
+}
+
+fn mrb_object_caller(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    // The innermost entry is the frame calling `caller` itself
+    let entries = vm
+        .backtrace()
+        .into_iter()
+        .skip(1)
+        .map(|entry| RObject::string(entry).to_refcount_assigned())
+        .collect();
+    Ok(RObject::array(entries).to_refcount_assigned())
 }
 
 fn mrb_object_nil_p(_vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -349,7 +518,7 @@ fn mrb_object_nil_p(_vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
 fn mrb_object_block_given(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     // CALLINFO の has_block フラグをチェック
-    let has_block = if let Some(ci) = vm.current_callinfo.as_ref() {
+    let has_block = if let Some(ci) = vm.active_callinfo() {
         ci.has_block.get()
     } else {
         false
@@ -395,41 +564,226 @@ fn mrb_object_class(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
     Ok(RObject::class_or_module(class.as_module(), vm))
 }
 
-fn mrb_object_loop(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let block = args[0].clone();
-    if !matches!(block.value, RValue::Proc(_)) {
+/// Catch tags match by identity; symbols and immediates by value.
+fn same_tag(lhs: &Rc<RObject>, rhs: &Rc<RObject>) -> bool {
+    match (&lhs.value, &rhs.value) {
+        (RValue::Symbol(a), RValue::Symbol(b)) => a.name == b.name,
+        (RValue::Integer(a), RValue::Integer(b)) => a == b,
+        (RValue::Bool(a), RValue::Bool(b)) => a == b,
+        (RValue::Nil, RValue::Nil) => true,
+        _ => Rc::ptr_eq(lhs, rhs),
+    }
+}
+
+// Object#catch(tag = Object.new) { |tag| ... }: returns the block's value,
+// or the value thrown to `tag` from anywhere inside it
+fn mrb_object_catch(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let Some(block) = block_arg(args) else {
         return Err(Error::ArgumentError(
-            "Object#loop expects a block".to_string(),
+            "Object#catch expects a block".to_string(),
         ));
+    };
+    let tag = match &args[..args.len() - 1] {
+        [tag, ..] => tag.clone(),
+        [] => RObject::instance(vm.object_class.clone()).to_refcount_assigned(),
+    };
+
+    vm.catch_tags.push(tag.clone());
+    let result = mrb_call_block(vm, block, None, std::slice::from_ref(&tag), 0);
+    vm.catch_tags.pop();
+    match result {
+        Err(Error::Throw(thrown, value)) if same_tag(&thrown, &tag) => {
+            // Handled here; do not let the caller's frame raise it again
+            vm.exception.take();
+            Ok(value)
+        }
+        result => result,
     }
+}
 
-    let this = vm.getself()?;
-    loop {
-        mrb_call_block(vm, block.clone(), Some(this.clone()), &[], 0)?;
+// Object#throw(tag, value = nil): unwinds to the innermost catch for `tag`,
+// running ensure clauses on the way
+fn mrb_object_throw(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let Some(tag) = args.first().cloned() else {
+        return Err(Error::ArgumentError(
+            "wrong number of arguments (given 0, expected 1..2)".to_string(),
+        ));
+    };
+    let value = args
+        .get(1)
+        .cloned()
+        .unwrap_or_else(|| Rc::new(RObject::nil()));
+    if vm.catch_tags.iter().any(|caught| same_tag(caught, &tag)) {
+        return Err(Error::Throw(tag, value));
     }
+
+    let inspect: String = mrb_call_inspect(vm, tag.clone())?.as_ref().try_into()?;
+    let class = vm.get_class_by_name("UncaughtThrowError");
+    let class = RObject::class(class, vm);
+    let message = RObject::string(format!("uncaught throw {}", inspect)).to_refcount_assigned();
+    let exception = mrb_funcall(vm, Some(class), "new", &[message])?;
+    exception.set_ivar("tag", tag);
+    exception.set_ivar("value", value);
+    Err(Error::Exception(exception))
 }
 
+fn mrb_object_freeze(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.freeze();
+    Ok(this)
+}
+
+fn mrb_object_frozen(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    Ok(RObject::boolean(this.is_frozen()).to_refcount_assigned())
+}
+
+// respond_to?(name, include_all = false): private and protected methods
+// only count with include_all
 fn mrb_object_respond_to(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let method_name: String = args[0].as_ref().try_into()?;
+    let include_all = args.get(1).is_some_and(|arg| arg.is_truthy());
     let obj = vm.getself()?;
     let klass = obj.singleton_or_this_class(vm);
-    let has_method = resolve_method(&klass, &method_name).is_some();
+    let has_method = match vm.method_cache.resolve(&klass, &method_name) {
+        Some((_, method)) => include_all || method.visibility == Visibility::Public,
+        None => false,
+    };
     Ok(Rc::new(RObject::boolean(has_method)))
 }
 
 fn mrb_object_public_send(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    if let Some(arg0) = args.first() {
+        let method_name = arg0.intern()?.name;
+        let obj = vm.getself()?;
+        let klass = obj.singleton_or_this_class(vm);
+        if let Some((_, method)) = vm.method_cache.resolve(&klass, &method_name)
+            && method.visibility != Visibility::Public
+        {
+            return Err(visibility_error(vm, &obj, &method_name, method.visibility));
+        }
+    }
+    mrb_object_send(vm, args)
+}
+
+fn mrb_object_send(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     if args.is_empty() {
         return Err(Error::ArgumentError(
             "wrong number of arguments (given 0, expected 1+)".to_string(),
         ));
     }
 
-    let method_name: String = args[0].as_ref().try_into()?;
+    let method_name = args[0].intern()?.name;
     let obj = vm.getself()?;
-    let method_args = &args[1..];
+    let (method_args, block) = match block_arg(args) {
+        Some(block) if args.len() > 1 => (&args[1..args.len() - 1], Some(block)),
+        _ => (&args[1..], None),
+    };
+    mrb_funcall_with_block(vm, Some(obj), &method_name, method_args, block)
+}
+
+fn instance_variable_name(arg: &RObject) -> Result<String, Error> {
+    let name = arg.intern()?.name;
+    if !name.starts_with('@') || name.starts_with("@@") || name.len() < 2 {
+        return Err(Error::NameError(format!(
+            "'{}' is not allowed as an instance variable name",
+            name
+        )));
+    }
+    Ok(name)
+}
+
+fn mrb_object_instance_variable_get(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let arg0 = args
+        .first()
+        .ok_or_else(|| Error::ArgumentError("wrong number of arguments".to_string()))?;
+    let name = instance_variable_name(arg0)?;
+    Ok(this.get_ivar(&name))
+}
+
+fn mrb_object_instance_variable_set(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    if args.len() < 2 {
+        return Err(Error::ArgumentError(
+            "wrong number of arguments".to_string(),
+        ));
+    }
+    let name = instance_variable_name(&args[0])?;
+    this.check_frozen(vm)?;
+    this.set_ivar(&name, args[1].clone());
+    Ok(args[1].clone())
+}
 
-    // For now, public_send behaves the same as send since we don't have visibility modifiers
-    mrb_funcall(vm, Some(obj), &method_name, method_args)
+fn mrb_object_instance_variable_defined(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let arg0 = args
+        .first()
+        .ok_or_else(|| Error::ArgumentError("wrong number of arguments".to_string()))?;
+    let name = instance_variable_name(arg0)?;
+    let defined = this.ivar.borrow().contains_key(&name);
+    Ok(RObject::boolean(defined).to_refcount_assigned())
+}
+
+// Internal slots such as an exception's message are stored without the `@` prefix
+// and are not listed here
+fn mrb_object_instance_variables(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let names: Vec<String> = this
+        .ivar
+        .borrow()
+        .keys()
+        .filter(|name| name.starts_with('@'))
+        .cloned()
+        .collect();
+    let names: Vec<Rc<RObject>> = names.into_iter().map(|name| vm.intern(&name)).collect();
+    Ok(RObject::array(names).to_refcount_assigned())
+}
+
+fn mrb_object_define_singleton_method(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let (name, method) = method_from_block(vm, args)?;
+    match &this.value {
+        RValue::Class(klass) => {
+            let singleton = RObject::class_singleton(klass.clone(), vm);
+            mrb_define_method(vm, singleton, &name, method);
+        }
+        _ => {
+            mrb_define_singleton_method(vm, this, &name, method);
+        }
+    }
+    Ok(vm.intern(&name))
+}
+
+fn mrb_object_singleton_methods(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let mut names: Vec<String> = Vec::new();
+    if let Some(sclass) = this.singleton_class() {
+        let mut modules = vec![sclass.module.clone()];
+        modules.extend(sclass.extended_modules.borrow().iter().cloned());
+        for module in modules {
+            for (name, method) in module.procs.borrow().iter() {
+                if method.visibility != Visibility::Private && !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+    }
+    names.sort();
+    let names: Vec<Rc<RObject>> = names.into_iter().map(|name| vm.intern(&name)).collect();
+    Ok(RObject::array(names).to_refcount_assigned())
 }
 
 fn mrb_object_method_missing(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -689,15 +1043,15 @@ fn test_mrb_object_is_equal_hash() {
 
     let mut vm = VM::empty();
 
-    let lhs = RObject::hash(RHashMap::default()).to_refcount_assigned();
-    let rhs = RObject::hash(RHashMap::default()).to_refcount_assigned();
+    let lhs = RObject::hash(RHash::default()).to_refcount_assigned();
+    let rhs = RObject::hash(RHash::default()).to_refcount_assigned();
     let ret: bool = mrb_object_is_equal(&mut vm, lhs, rhs)
         .as_ref()
         .try_into()
         .expect("must return bool");
     assert!(ret);
 
-    let lhs = RObject::hash(RHashMap::default()).to_refcount_assigned();
+    let lhs = RObject::hash(RHash::default()).to_refcount_assigned();
     mrb_hash_set_index(
         lhs.clone(),
         RObject::symbol("key1".into()).to_refcount_assigned(),
@@ -711,7 +1065,7 @@ fn test_mrb_object_is_equal_hash() {
     )
     .expect("set index failed");
 
-    let rhs = RObject::hash(RHashMap::default()).to_refcount_assigned();
+    let rhs = RObject::hash(RHash::default()).to_refcount_assigned();
     mrb_hash_set_index(
         rhs.clone(),
         RObject::symbol("key2".into()).to_refcount_assigned(),
@@ -731,7 +1085,7 @@ fn test_mrb_object_is_equal_hash() {
         .expect("must return bool");
     assert!(ret);
 
-    let lhs = RObject::hash(RHashMap::default()).to_refcount_assigned();
+    let lhs = RObject::hash(RHash::default()).to_refcount_assigned();
     mrb_hash_set_index(
         lhs.clone(),
         RObject::symbol("key1".into()).to_refcount_assigned(),
@@ -745,7 +1099,7 @@ fn test_mrb_object_is_equal_hash() {
     )
     .expect("set index failed");
 
-    let rhs = RObject::hash(RHashMap::default()).to_refcount_assigned();
+    let rhs = RObject::hash(RHash::default()).to_refcount_assigned();
     mrb_hash_set_index(
         rhs.clone(),
         RObject::symbol("key2".into()).to_refcount_assigned(),
@@ -765,7 +1119,7 @@ fn test_mrb_object_is_equal_hash() {
         .expect("must return bool");
     assert!(!ret);
 
-    let lhs = RObject::hash(RHashMap::default()).to_refcount_assigned();
+    let lhs = RObject::hash(RHash::default()).to_refcount_assigned();
     mrb_hash_set_index(
         lhs.clone(),
         RObject::symbol("key1".into()).to_refcount_assigned(),
@@ -779,7 +1133,7 @@ fn test_mrb_object_is_equal_hash() {
     )
     .expect("set index failed");
 
-    let rhs = RObject::hash(RHashMap::default()).to_refcount_assigned();
+    let rhs = RObject::hash(RHash::default()).to_refcount_assigned();
     mrb_hash_set_index(
         rhs.clone(),
         RObject::symbol("key2".into()).to_refcount_assigned(),
@@ -879,6 +1233,7 @@ fn mrb_object_extend(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
             .borrow_mut()
             .insert(0, module);
     }
+    invalidate_method_caches();
 
     Ok(this)
 }
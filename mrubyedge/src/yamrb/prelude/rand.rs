@@ -2,7 +2,7 @@ use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use crate::yamrb::helpers::mrb_funcall;
-use crate::yamrb::value::{RClass, RHashMap};
+use crate::yamrb::value::{IvarTable, RClass};
 use crate::{
     Error,
     yamrb::{
@@ -129,7 +129,8 @@ pub(crate) fn mrb_random_new(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<ROb
         value: RValue::Data(Rc::new(random_data)),
         object_id: Cell::new(u64::MAX),
         singleton_class: RefCell::new(None),
-        ivar: RefCell::new(RHashMap::default()),
+        ivar: RefCell::new(IvarTable::default()),
+        frozen: Cell::new(false),
     });
 
     Ok(random_instance)
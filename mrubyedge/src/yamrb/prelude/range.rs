@@ -3,7 +3,7 @@ use std::rc::Rc;
 use crate::{
     Error,
     yamrb::{
-        helpers::{mrb_call_block, mrb_define_cmethod},
+        helpers::mrb_define_cmethod,
         prelude::module::mrb_include_module,
         value::{RObject, RValue},
         vm::VM,
@@ -19,7 +19,15 @@ pub(crate) fn initialize_range(vm: &mut VM) {
         "include?",
         Box::new(mrb_range_is_include),
     );
-    mrb_define_cmethod(vm, range_class.clone(), "each", Box::new(mrb_range_each));
+    mrb_define_cmethod(vm, range_class.clone(), "begin", Box::new(mrb_range_begin));
+    mrb_define_cmethod(vm, range_class.clone(), "end", Box::new(mrb_range_end));
+    mrb_define_cmethod(
+        vm,
+        range_class.clone(),
+        "exclude_end?",
+        Box::new(mrb_range_exclude_end),
+    );
+    // `each` is defined in mrblib/range.rb
 
     let enumerable_module = vm.get_module_by_name("Enumerable");
     mrb_include_module(&range_class, enumerable_module).expect("failed to include Enumerable");
@@ -55,33 +63,29 @@ pub fn mrb_range_is_include(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObj
     }
 }
 
-pub fn mrb_range_each(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this = vm.getself()?;
-    let block = &args[0];
-    match &this.value {
-        RValue::Range(start, end, exclusive) => match (&start.value, &end.value) {
-            (RValue::Integer(start), RValue::Integer(end)) => {
-                let start = *start;
-                let mut end = *end;
-                if *exclusive {
-                    end -= 1;
-                }
-                for i in start..=end {
-                    let args = vec![Rc::new(RObject::integer(i))];
-                    mrb_call_block(vm, block.clone(), None, &args, 0)?;
-                }
-            }
-            _ => {
-                return Err(Error::RuntimeError(
-                    "Range#each must be called on a integer Range with block (for now)".to_string(),
-                ));
-            }
-        },
-        _ => {
-            return Err(Error::RuntimeError(
-                "Range#each must be called on a Range".to_string(),
-            ));
-        }
+fn mrb_range_begin(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    match &vm.getself()?.value {
+        RValue::Range(start, _, _) => Ok(start.clone()),
+        _ => Err(Error::RuntimeError(
+            "Range#begin must be called on a Range".to_string(),
+        )),
+    }
+}
+
+fn mrb_range_end(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    match &vm.getself()?.value {
+        RValue::Range(_, end, _) => Ok(end.clone()),
+        _ => Err(Error::RuntimeError(
+            "Range#end must be called on a Range".to_string(),
+        )),
+    }
+}
+
+fn mrb_range_exclude_end(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    match &vm.getself()?.value {
+        RValue::Range(_, _, exclusive) => Ok(Rc::new(RObject::boolean(*exclusive))),
+        _ => Err(Error::RuntimeError(
+            "Range#exclude_end? must be called on a Range".to_string(),
+        )),
     }
-    Ok(this.clone())
 }
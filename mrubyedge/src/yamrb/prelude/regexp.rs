@@ -1,19 +1,50 @@
+use std::any::Any;
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
-use regex::Regex;
+use regex::bytes::{Captures, Regex, RegexBuilder};
 
 use crate::{
     Error,
     yamrb::{
-        helpers::{mrb_define_class_cmethod, mrb_define_cmethod, mrb_funcall},
-        value::{RData, RHashMap, RObject, RValue},
+        helpers::{mrb_call_block, mrb_define_class_cmethod, mrb_define_cmethod, mrb_funcall},
+        prelude::{
+            enumerator::block_arg,
+            hash::mrb_hash_set_index,
+            string::{
+                CharIndex, inspect_bytes, mrb_string_partition, mrb_string_rpartition,
+                mrb_string_split, partition_at, string_parts,
+            },
+            r#struct::given_kwargs,
+        },
+        value::{IvarTable, RData, RHash, RHashMap, RObject, RType, RValue},
         vm::VM,
     },
 };
 
+// Same bits as CRuby's Regexp::IGNORECASE, EXTENDED and MULTILINE
+const IGNORECASE: i64 = 1;
+const EXTENDED: i64 = 2;
+const MULTILINE: i64 = 4;
+
 pub(crate) fn initialize_regexp(vm: &mut VM) {
+    let standard_error = vm.get_class_by_name("StandardError");
+    let _ = vm.define_standard_class_with_superclass("RegexpError", standard_error);
+
     let regexp_class = vm.define_standard_class("Regexp");
+    {
+        let mut const_table = regexp_class.consts.borrow_mut();
+        for (name, value) in [
+            ("IGNORECASE", IGNORECASE),
+            ("EXTENDED", EXTENDED),
+            ("MULTILINE", MULTILINE),
+        ] {
+            const_table.insert(
+                name.to_string(),
+                RObject::integer(value).to_refcount_assigned(),
+            );
+        }
+    }
 
     mrb_define_class_cmethod(vm, regexp_class.clone(), "new", Box::new(mrb_regexp_new));
     mrb_define_class_cmethod(
@@ -41,6 +72,24 @@ pub(crate) fn initialize_regexp(vm: &mut VM) {
         "match",
         Box::new(mrb_regexp_match),
     );
+    mrb_define_cmethod(
+        vm,
+        regexp_class.clone(),
+        "match?",
+        Box::new(mrb_regexp_match_p),
+    );
+    mrb_define_cmethod(
+        vm,
+        regexp_class.clone(),
+        "source",
+        Box::new(mrb_regexp_source),
+    );
+    mrb_define_cmethod(
+        vm,
+        regexp_class.clone(),
+        "options",
+        Box::new(mrb_regexp_options),
+    );
     mrb_define_cmethod(
         vm,
         regexp_class.clone(),
@@ -55,6 +104,78 @@ pub(crate) fn initialize_regexp(vm: &mut VM) {
         "[]",
         Box::new(mrb_matchdata_index),
     );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "to_a",
+        Box::new(mrb_matchdata_to_a),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "captures",
+        Box::new(mrb_matchdata_captures),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "named_captures",
+        Box::new(mrb_matchdata_named_captures),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "names",
+        Box::new(mrb_matchdata_names),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "pre_match",
+        Box::new(mrb_matchdata_pre_match),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "post_match",
+        Box::new(mrb_matchdata_post_match),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "begin",
+        Box::new(mrb_matchdata_begin),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "end",
+        Box::new(mrb_matchdata_end),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "size",
+        Box::new(mrb_matchdata_size),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "length",
+        Box::new(mrb_matchdata_size),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "to_s",
+        Box::new(mrb_matchdata_to_s),
+    );
+    mrb_define_cmethod(
+        vm,
+        matchdata_class.clone(),
+        "inspect",
+        Box::new(mrb_matchdata_inspect),
+    );
 
     // Additional counterpart Regexp methods to String
     let string_class = vm.get_class_by_name("String");
@@ -70,94 +191,332 @@ pub(crate) fn initialize_regexp(vm: &mut VM) {
         "!~",
         Box::new(mrb_string_regexp_not_match_tilda),
     );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "match",
+        Box::new(mrb_string_match),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "match?",
+        Box::new(mrb_string_match_p),
+    );
+    mrb_define_cmethod(vm, string_class.clone(), "sub", Box::new(mrb_string_sub));
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "sub!",
+        Box::new(mrb_string_sub_self),
+    );
+    mrb_define_cmethod(vm, string_class.clone(), "gsub", Box::new(mrb_string_gsub));
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "gsub!",
+        Box::new(mrb_string_gsub_self),
+    );
+    mrb_define_cmethod(vm, string_class.clone(), "scan", Box::new(mrb_string_scan));
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "split",
+        Box::new(mrb_string_regexp_split),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "partition",
+        Box::new(mrb_string_regexp_partition),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "rpartition",
+        Box::new(mrb_string_regexp_rpartition),
+    );
 }
 
+#[derive(Clone)]
 pub struct RRegexp {
     pattern: String,
+    options: i64,
+    regex: Regex,
 }
 
 pub struct RMatchData {
-    captures: Vec<(usize, usize)>,
-    haystack: String,
-}
-
-fn get_regexp_from_object(obj: &Rc<RObject>) -> Result<Regex, Error> {
-    let pattern_str: String = match &obj.value {
-        RValue::Data(data) => {
-            let borrow = data.data.borrow();
-            let any_ref = borrow
-                .as_ref()
-                .ok_or_else(|| Error::RuntimeError("Invalid Regexp data".to_string()))?;
-            let regexp = any_ref
-                .downcast_ref::<RRegexp>()
-                .ok_or_else(|| Error::RuntimeError("Invalid Regexp data".to_string()))?;
-            regexp.pattern.clone()
+    /// Byte range of each group, `None` for groups that did not participate
+    captures: Vec<Option<(usize, usize)>>,
+    names: Vec<Option<String>>,
+    haystack: Rc<Vec<u8>>,
+    is_utf8: bool,
+}
+
+impl RMatchData {
+    fn new(regex: &Regex, captures: &Captures, haystack: Rc<Vec<u8>>, is_utf8: bool) -> Self {
+        RMatchData {
+            captures: captures
+                .iter()
+                .map(|cap| cap.map(|m| (m.start(), m.end())))
+                .collect(),
+            names: regex
+                .capture_names()
+                .map(|name| name.map(|name| name.to_string()))
+                .collect(),
+            haystack,
+            is_utf8,
         }
-        _ => {
-            return Err(Error::RuntimeError(
-                "Regexp#=~ must be called on a Regexp".to_string(),
-            ));
+    }
+
+    fn range(&self) -> (usize, usize) {
+        self.captures[0].unwrap_or((0, 0))
+    }
+
+    fn group_bytes(&self, index: usize) -> Option<&[u8]> {
+        let (start, end) = (*self.captures.get(index)?)?;
+        Some(&self.haystack[start..end])
+    }
+
+    fn group(&self, index: usize) -> Rc<RObject> {
+        match self.group_bytes(index) {
+            Some(bytes) => self.new_string(bytes),
+            None => RObject::nil().to_refcount_assigned(),
         }
+    }
+
+    /// Index of a named group. When a name is used more than once,
+    /// the last group that matched wins.
+    fn name_index(&self, name: &str) -> Result<usize, Error> {
+        let indices: Vec<usize> = (0..self.names.len())
+            .filter(|&index| self.names[index].as_deref() == Some(name))
+            .collect();
+        let found = indices
+            .iter()
+            .rev()
+            .find(|&&index| self.captures[index].is_some())
+            .or(indices.last())
+            .copied();
+        found.ok_or_else(|| {
+            Error::TaggedError(
+                "IndexError",
+                format!("undefined group name reference: {}", name),
+            )
+        })
+    }
+
+    /// Group index given as an Integer, String or Symbol
+    fn group_index(&self, arg: &RObject) -> Result<usize, Error> {
+        match &arg.value {
+            RValue::Integer(i) => {
+                let index = *i as usize;
+                if *i < 0 || index >= self.captures.len() {
+                    return Err(Error::TaggedError(
+                        "IndexError",
+                        format!("index {} out of matches", i),
+                    ));
+                }
+                Ok(index)
+            }
+            RValue::Symbol(sym) => self.name_index(&sym.name),
+            RValue::String(_, _) => self.name_index(&String::try_from(arg)?),
+            _ => Err(Error::TaggedError(
+                "TypeError",
+                "no implicit conversion into Integer".to_string(),
+            )),
+        }
+    }
+
+    fn char_pos(&self, offset: usize) -> usize {
+        CharIndex::new(&self.haystack, self.is_utf8).char_index(offset)
+    }
+
+    fn new_string(&self, bytes: &[u8]) -> Rc<RObject> {
+        RObject::string_from_bytes(bytes.to_vec(), self.is_utf8).to_refcount_assigned()
+    }
+}
+
+fn new_data_object(vm: &mut VM, class_name: &str, data: Box<dyn Any>) -> Rc<RObject> {
+    let data = Rc::new(RData {
+        class: vm.get_class_by_name(class_name),
+        data: RefCell::new(Some(Rc::new(data))),
+        ref_count: 1,
+    });
+    RObject {
+        tt: RType::Data,
+        value: RValue::Data(data),
+        object_id: Cell::new(0),
+        singleton_class: RefCell::new(None),
+        ivar: RefCell::new(IvarTable::default()),
+        frozen: Cell::new(false),
+    }
+    .to_refcount_assigned()
+}
+
+fn get_data<T: Clone + 'static>(obj: &RObject) -> Option<T> {
+    match &obj.value {
+        RValue::Data(data) => data
+            .data
+            .borrow()
+            .as_ref()
+            .and_then(|any_ref| any_ref.downcast_ref::<T>().cloned()),
+        _ => None,
+    }
+}
+
+fn get_regexp_from_object(obj: &RObject) -> Result<RRegexp, Error> {
+    get_data::<RRegexp>(obj).ok_or_else(|| {
+        Error::TaggedError(
+            "TypeError",
+            "wrong argument type (expected Regexp)".to_string(),
+        )
+    })
+}
+
+fn get_matchdata_from_object(obj: &RObject) -> Result<Rc<RMatchData>, Error> {
+    get_data::<Rc<RMatchData>>(obj)
+        .ok_or_else(|| Error::RuntimeError("Invalid MatchData data".to_string()))
+}
+
+fn is_regexp(obj: &RObject) -> bool {
+    get_data::<RRegexp>(obj).is_some()
+}
+
+fn is_matchdata(obj: &RObject) -> bool {
+    get_data::<Rc<RMatchData>>(obj).is_some()
+}
+
+fn compile_regex(pattern: &str, options: i64) -> Result<Regex, Error> {
+    RegexBuilder::new(pattern)
+        // `^` and `$` always match at line boundaries in Ruby
+        .multi_line(true)
+        .case_insensitive(options & IGNORECASE != 0)
+        .ignore_whitespace(options & EXTENDED != 0)
+        .dot_matches_new_line(options & MULTILINE != 0)
+        .build()
+        .map_err(|e| {
+            // Syntax errors span several lines; keep the one describing the problem
+            let message = e.to_string();
+            let detail = message
+                .lines()
+                .find_map(|line| line.strip_prefix("error: "))
+                .unwrap_or("invalid pattern");
+            Error::TaggedError("RegexpError", format!("{}: /{}/", detail, pattern))
+        })
+}
+
+/// Options given to `Regexp.new` as an Integer, a String such as `"mi"`,
+/// or any other truthy value for IGNORECASE
+fn parse_options(arg: Option<&Rc<RObject>>) -> Result<i64, Error> {
+    let Some(arg) = arg else {
+        return Ok(0);
     };
+    match &arg.value {
+        RValue::Integer(i) => Ok(*i & (IGNORECASE | EXTENDED | MULTILINE)),
+        RValue::String(_, _) => {
+            let flags: String = arg.as_ref().try_into()?;
+            let mut options = 0;
+            for flag in flags.chars() {
+                options |= match flag {
+                    'i' => IGNORECASE,
+                    'x' => EXTENDED,
+                    'm' => MULTILINE,
+                    _ => {
+                        return Err(Error::ArgumentError(format!(
+                            "unknown regexp option: {}",
+                            flags
+                        )));
+                    }
+                };
+            }
+            Ok(options)
+        }
+        _ if arg.is_truthy() => Ok(IGNORECASE),
+        _ => Ok(0),
+    }
+}
 
-    let pattern = Regex::new(&pattern_str).map_err(|e| {
-        Error::RuntimeError(format!("Invalid regexp pattern in Regexp#=~: {:?}", e))
-    })?;
-    Ok(pattern)
+fn new_regexp(vm: &mut VM, pattern: String, options: i64) -> Result<Rc<RObject>, Error> {
+    let regex = compile_regex(&pattern, options)?;
+    let regexp = RRegexp {
+        pattern,
+        options,
+        regex,
+    };
+    Ok(new_data_object(vm, "Regexp", Box::new(regexp)))
 }
 
 pub fn mrb_regexp_new(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let pattern_obj = args[0].clone();
     match &pattern_obj.value {
         RValue::String(pattern, _) => {
-            // For simplicity, we only support literal patterns without options.
-            let pattern_str = pattern.clone().borrow().to_owned();
-            let regexp = RRegexp {
-                pattern: String::from_utf8(pattern_str).map_err(|e| {
-                    Error::RuntimeError(format!("Invalid regexp expression: {:?}", e))
-                })?,
-            };
-            let regexp_data = Rc::new(RData {
-                class: vm.get_class_by_name("Regexp"),
-                data: RefCell::new(Some(Rc::new(Box::new(regexp) as Box<dyn std::any::Any>))),
-                ref_count: 1,
-            });
-            Ok(RObject {
-                tt: crate::yamrb::value::RType::Data,
-                value: RValue::Data(regexp_data),
-                object_id: Cell::new(0),
-                singleton_class: RefCell::new(None),
-                ivar: RefCell::new(RHashMap::default()),
-            }
-            .to_refcount_assigned())
+            let pattern = String::from_utf8(pattern.borrow().clone()).map_err(|e| {
+                Error::TaggedError("RegexpError", format!("invalid regexp expression: {:?}", e))
+            })?;
+            let options = parse_options(args.get(1))?;
+            new_regexp(vm, pattern, options)
         }
-        _ => Err(Error::RuntimeError(
+        RValue::Data(_) if is_regexp(&pattern_obj) => {
+            let regexp = get_regexp_from_object(&pattern_obj)?;
+            Ok(new_data_object(vm, "Regexp", Box::new(regexp)))
+        }
+        _ => Err(Error::TaggedError(
+            "TypeError",
             "Regexp.new requires a string pattern".to_string(),
         )),
     }
 }
 
-fn mrb_regexp_match_tilda(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let regexp_obj = vm.getself()?;
-    let target_obj = args[0].clone();
-
-    let regexp = get_regexp_from_object(&regexp_obj)?;
-
-    let target_str = match &target_obj.value {
-        RValue::String(s, _) => s.clone().borrow().to_owned(),
-        _ => {
-            return Err(Error::RuntimeError(
-                "Regexp#=~ requires a string argument".to_string(),
-            ));
+/// Pattern argument of the String methods. A String is matched literally,
+/// unless `as_regexp` is set as `String#match` does.
+fn pattern_regex(pattern: &RObject, as_regexp: bool) -> Result<Regex, Error> {
+    match &pattern.value {
+        RValue::String(_, _) => {
+            let source: String = pattern.try_into()?;
+            if as_regexp {
+                compile_regex(&source, 0)
+            } else {
+                compile_regex(&regex::escape(&source), 0)
+            }
         }
-    };
+        _ => Ok(get_regexp_from_object(pattern)?.regex),
+    }
+}
 
-    let haystack = String::from_utf8(target_str).map_err(|e| {
-        Error::RuntimeError(format!("Invalid string argument for Regexp#=~: {:?}", e))
-    })?;
+fn target_parts(target: &RObject) -> Result<(Vec<u8>, bool), Error> {
+    string_parts(target).map_err(|_| {
+        Error::TaggedError(
+            "TypeError",
+            "no implicit conversion into String".to_string(),
+        )
+    })
+}
 
-    match regexp.find(&haystack) {
-        Some(matched) => Ok(RObject::integer(matched.start() as i64).to_refcount_assigned()),
+/// Searches `target` and updates `$~`. Returns the match, or nil when
+/// `target` is nil or does not match.
+fn search(vm: &mut VM, regex: &Regex, target: &RObject) -> Result<Option<Rc<RMatchData>>, Error> {
+    if target.is_nil() {
+        vm.last_match = None;
+        return Ok(None);
+    }
+    let (bytes, is_utf8) = target_parts(target)?;
+    let haystack = Rc::new(bytes);
+    let matchdata = regex
+        .captures(&haystack)
+        .map(|captures| Rc::new(RMatchData::new(regex, &captures, haystack.clone(), is_utf8)));
+    vm.last_match = matchdata
+        .clone()
+        .map(|m| new_data_object(vm, "MatchData", Box::new(m)));
+    Ok(matchdata)
+}
+
+fn mrb_regexp_match_tilda(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let regexp = get_regexp_from_object(&*vm.getself()?)?;
+    match search(vm, &regexp.regex, &args[0])? {
+        Some(matchdata) => {
+            let start = matchdata.char_pos(matchdata.range().0);
+            Ok(RObject::integer(start as i64).to_refcount_assigned())
+        }
         None => Ok(RObject::nil().to_refcount_assigned()),
     }
 }
@@ -170,125 +529,171 @@ fn mrb_regexp_not_match_tilda(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RO
 }
 
 fn mrb_regexp_match(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let regexp_obj = vm.getself()?;
-    let target_obj = args[0].clone();
+    let regexp = get_regexp_from_object(&*vm.getself()?)?;
+    search(vm, &regexp.regex, &args[0])?;
+    Ok(vm
+        .last_match
+        .clone()
+        .unwrap_or_else(|| RObject::nil().to_refcount_assigned()))
+}
 
-    let regexp = get_regexp_from_object(&regexp_obj)?;
+// match? leaves $~ untouched
+fn mrb_regexp_match_p(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let regexp = get_regexp_from_object(&*vm.getself()?)?;
+    if args[0].is_nil() {
+        return Ok(RObject::boolean(false).to_refcount_assigned());
+    }
+    let (bytes, _) = target_parts(&args[0])?;
+    Ok(RObject::boolean(regexp.regex.is_match(&bytes)).to_refcount_assigned())
+}
 
-    let target_str = match &target_obj.value {
-        RValue::String(s, _) => s.clone().borrow().to_owned(),
-        _ => {
-            return Err(Error::RuntimeError(
-                "Regexp#match requires a string argument".to_string(),
-            ));
-        }
-    };
+fn mrb_regexp_source(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let regexp = get_regexp_from_object(&*vm.getself()?)?;
+    Ok(RObject::string(regexp.pattern).to_refcount_assigned())
+}
 
-    let haystack = String::from_utf8(target_str).map_err(|e| {
-        Error::RuntimeError(format!("Invalid string argument for Regexp#match: {:?}", e))
-    })?;
-
-    match regexp.captures(&haystack) {
-        Some(captures) => {
-            let mut caps_vec = Vec::new();
-            for cap in captures.iter() {
-                if let Some(m) = cap {
-                    caps_vec.push((m.start(), m.end()));
-                } else {
-                    caps_vec.push((usize::MAX, usize::MAX)); // Indicate no match
-                }
-            }
-            let matchdata = RMatchData {
-                captures: caps_vec,
-                haystack,
-            };
-            let matchdata_data = Rc::new(RData {
-                class: vm.get_class_by_name("MatchData"),
-                data: RefCell::new(Some(Rc::new(Box::new(matchdata) as Box<dyn std::any::Any>))),
-                ref_count: 1,
-            });
-            Ok(RObject {
-                tt: crate::yamrb::value::RType::Data,
-                value: RValue::Data(matchdata_data),
-                object_id: Cell::new(0),
-                singleton_class: RefCell::new(None),
-                ivar: RefCell::new(RHashMap::default()),
-            }
-            .to_refcount_assigned())
-        }
-        None => Ok(RObject::nil().to_refcount_assigned()),
-    }
+fn mrb_regexp_options(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let regexp = get_regexp_from_object(&*vm.getself()?)?;
+    Ok(RObject::integer(regexp.options).to_refcount_assigned())
 }
 
 fn mrb_regexp_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let regexp_obj = vm.getself()?;
-    let pattern_str: String = match &regexp_obj.value {
-        RValue::Data(data) => {
-            let borrow = data.data.borrow();
-            let any_ref = borrow
-                .as_ref()
-                .ok_or_else(|| Error::RuntimeError("Invalid Regexp data".to_string()))?;
-            let regexp = any_ref
-                .downcast_ref::<RRegexp>()
-                .ok_or_else(|| Error::RuntimeError("Invalid Regexp data".to_string()))?;
-            regexp.pattern.clone()
-        }
-        _ => {
-            return Err(Error::RuntimeError(
-                "Regexp#inspect must be called on a Regexp".to_string(),
-            ));
+    let regexp = get_regexp_from_object(&*vm.getself()?)?;
+    let mut inspect_str = format!("/{}/", regexp.pattern);
+    for (flag, bit) in [('m', MULTILINE), ('i', IGNORECASE), ('x', EXTENDED)] {
+        if regexp.options & bit != 0 {
+            inspect_str.push(flag);
         }
-    };
-    let inspect_str = format!("/{}/", pattern_str);
+    }
     Ok(RObject::string(inspect_str).to_refcount_assigned())
 }
 
 fn mrb_matchdata_index(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let matchdata_obj = vm.getself()?;
-    let index_obj = args[0].clone();
-
-    let index = match &index_obj.value {
-        RValue::Integer(i) => *i as isize,
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    let index = match &args[0].value {
+        RValue::Integer(i) => {
+            let len = matchdata.captures.len() as i64;
+            let index = if *i < 0 { *i + len } else { *i };
+            if index < 0 || index >= len {
+                return Ok(RObject::nil().to_refcount_assigned());
+            }
+            index as usize
+        }
+        RValue::String(_, _) | RValue::Symbol(_) => matchdata.group_index(&args[0])?,
         _ => {
             return Err(Error::ArgumentError(
-                "MatchData#[] requires an integer index".to_string(),
+                "MatchData#[] requires an integer index or a group name".to_string(),
             ));
         }
     };
+    Ok(matchdata.group(index))
+}
 
-    let matchdata = match &matchdata_obj.value {
-        RValue::Data(data) => {
-            let borrow = data.data.borrow();
-            let any_ref = borrow
-                .as_ref()
-                .ok_or_else(|| Error::RuntimeError("Invalid MatchData data".to_string()))?;
-            any_ref.clone()
-        }
-        _ => {
-            return Err(Error::RuntimeError(
-                "MatchData#[] must be called on a MatchData".to_string(),
-            ));
+fn mrb_matchdata_to_a(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    let groups = (0..matchdata.captures.len())
+        .map(|i| matchdata.group(i))
+        .collect();
+    Ok(RObject::array(groups).to_refcount_assigned())
+}
+
+fn mrb_matchdata_captures(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    let groups = (1..matchdata.captures.len())
+        .map(|i| matchdata.group(i))
+        .collect();
+    Ok(RObject::array(groups).to_refcount_assigned())
+}
+
+fn mrb_matchdata_named_captures(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    let mut hash = RHash::default();
+    for name in matchdata.names.iter().flatten() {
+        let key = RObject::string(name.clone()).to_refcount_assigned();
+        let value = matchdata.group(matchdata.name_index(name)?);
+        hash.insert(key.as_hash_key()?, (key, value));
+    }
+    Ok(RObject::hash(hash).to_refcount_assigned())
+}
+
+fn mrb_matchdata_names(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    let mut names: Vec<&String> = Vec::new();
+    for name in matchdata.names.iter().flatten() {
+        if !names.contains(&name) {
+            names.push(name);
         }
-    };
-    let matchdata = matchdata
-        .downcast_ref::<RMatchData>()
-        .ok_or_else(|| Error::RuntimeError("Invalid MatchData data".to_string()))?;
+    }
+    let names = names
+        .into_iter()
+        .map(|name| RObject::string(name.clone()).to_refcount_assigned())
+        .collect();
+    Ok(RObject::array(names).to_refcount_assigned())
+}
 
-    let cap_index = if index < 0 {
-        (matchdata.captures.len() as isize + index) as usize
-    } else {
-        index as usize
-    };
+fn mrb_matchdata_pre_match(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    let (start, _) = matchdata.range();
+    Ok(matchdata.new_string(&matchdata.haystack[..start]))
+}
 
-    if cap_index >= matchdata.captures.len() {
-        return Ok(RObject::nil().to_refcount_assigned());
+fn mrb_matchdata_post_match(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    let (_, end) = matchdata.range();
+    Ok(matchdata.new_string(&matchdata.haystack[end..]))
+}
+
+// begin(n) and end(n) count characters, not bytes
+fn mrb_matchdata_begin(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    match matchdata.captures[matchdata.group_index(&args[0])?] {
+        Some((start, _)) => {
+            Ok(RObject::integer(matchdata.char_pos(start) as i64).to_refcount_assigned())
+        }
+        None => Ok(RObject::nil().to_refcount_assigned()),
     }
-    let (start, end) = matchdata.captures[cap_index];
-    if start == usize::MAX && end == usize::MAX {
-        return Ok(RObject::nil().to_refcount_assigned());
+}
+
+fn mrb_matchdata_end(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    match matchdata.captures[matchdata.group_index(&args[0])?] {
+        Some((_, end)) => {
+            Ok(RObject::integer(matchdata.char_pos(end) as i64).to_refcount_assigned())
+        }
+        None => Ok(RObject::nil().to_refcount_assigned()),
+    }
+}
+
+fn mrb_matchdata_size(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    Ok(RObject::integer(matchdata.captures.len() as i64).to_refcount_assigned())
+}
+
+fn mrb_matchdata_to_s(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    Ok(matchdata.group(0))
+}
+
+// #<MatchData "2024-05" year:"2024" 2:"05">
+fn mrb_matchdata_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let matchdata = get_matchdata_from_object(&*vm.getself()?)?;
+    let mut out = String::from("#<MatchData");
+    for index in 0..matchdata.captures.len() {
+        if index > 0 {
+            match &matchdata.names[index] {
+                Some(name) => out.push_str(&format!(" {}:", name)),
+                None => out.push_str(&format!(" {}:", index)),
+            }
+        } else {
+            out.push(' ');
+        }
+        match matchdata.group_bytes(index) {
+            Some(bytes) => out.push_str(&inspect_bytes(bytes, matchdata.is_utf8)),
+            None => out.push_str("nil"),
+        }
     }
-    let matched_str = &matchdata.haystack[start..end];
-    Ok(RObject::string(matched_str.to_string()).to_refcount_assigned())
+    out.push('>');
+    Ok(RObject::string(out).to_refcount_assigned())
 }
 
 fn mrb_string_regexp_match_tilda(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -305,3 +710,396 @@ fn mrb_string_regexp_not_match_tilda(
     let regexp_obj = args[0].clone();
     mrb_funcall(vm, Some(regexp_obj), "!~", &[string_obj])
 }
+
+// A String pattern is compiled as a Regexp here, unlike sub and scan
+fn mrb_string_match(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let regex = pattern_regex(&args[0], true)?;
+    search(vm, &regex, &this)?;
+    Ok(vm
+        .last_match
+        .clone()
+        .unwrap_or_else(|| RObject::nil().to_refcount_assigned()))
+}
+
+fn mrb_string_match_p(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, _) = string_parts(&*vm.getself()?)?;
+    let regex = pattern_regex(&args[0], true)?;
+    Ok(RObject::boolean(regex.is_match(&bytes)).to_refcount_assigned())
+}
+
+/// Expands `\0`-`\9`, `\&`, `` \` ``, `\'`, `\\` and `\k<name>` in a
+/// replacement string.
+fn expand_replacement(template: &[u8], matchdata: &RMatchData) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(template.len());
+    let mut pos = 0;
+    while pos < template.len() {
+        let ch = template[pos];
+        if ch != b'\\' || pos + 1 == template.len() {
+            out.push(ch);
+            pos += 1;
+            continue;
+        }
+        let next = template[pos + 1];
+        pos += 2;
+        let (start, end) = matchdata.range();
+        match next {
+            b'0'..=b'9' => {
+                let index = (next - b'0') as usize;
+                out.extend_from_slice(matchdata.group_bytes(index).unwrap_or_default());
+            }
+            b'&' => out.extend_from_slice(&matchdata.haystack[start..end]),
+            b'`' => out.extend_from_slice(&matchdata.haystack[..start]),
+            b'\'' => out.extend_from_slice(&matchdata.haystack[end..]),
+            b'\\' => out.push(b'\\'),
+            b'k' if template.get(pos) == Some(&b'<') => {
+                let Some(close) = template[pos..].iter().position(|&b| b == b'>') else {
+                    out.extend_from_slice(b"\\k");
+                    continue;
+                };
+                let name = String::from_utf8_lossy(&template[pos + 1..pos + close]).to_string();
+                let index = matchdata.name_index(&name)?;
+                out.extend_from_slice(matchdata.group_bytes(index).unwrap_or_default());
+                pos += close + 1;
+            }
+            _ => {
+                out.push(b'\\');
+                out.push(next);
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn kwargs_hash(kwargs: RHashMap<String, Rc<RObject>>) -> Result<Rc<RObject>, Error> {
+    let hash = Rc::new(RObject::hash(RHash::default()));
+    for (key, value) in kwargs {
+        mrb_hash_set_index(hash.clone(), Rc::new(RObject::string(key)), value)?;
+    }
+    Ok(hash)
+}
+
+fn to_s_bytes(vm: &mut VM, obj: Rc<RObject>) -> Result<Vec<u8>, Error> {
+    let s = mrb_funcall(vm, Some(obj), "to_s", &[])?;
+    Ok(string_parts(&s)?.0)
+}
+
+/// Shared body of sub and gsub. Returns the new contents, or None when
+/// nothing matched.
+fn substitute(vm: &mut VM, args: &[Rc<RObject>], global: bool) -> Result<Option<Vec<u8>>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let block = block_arg(args);
+    let args = if block.is_some() {
+        &args[..args.len() - 1]
+    } else {
+        args
+    };
+    let replacement = match args.get(1).cloned() {
+        Some(replacement) => Some(replacement),
+        // A braceless replacement hash arrives as keyword arguments
+        None => given_kwargs(vm).map(kwargs_hash).transpose()?,
+    };
+    if replacement.is_none() && block.is_none() {
+        return Err(Error::ArgumentError(format!(
+            "wrong number of arguments (given {}, expected 2)",
+            args.len()
+        )));
+    }
+    let regex = pattern_regex(&args[0], false)?;
+
+    let haystack = Rc::new(bytes);
+    let mut out = Vec::with_capacity(haystack.len());
+    let mut last_end = 0;
+    let mut last_match = None;
+    for captures in captures_all(&regex, &haystack, is_utf8) {
+        let matchdata = Rc::new(RMatchData::new(
+            &regex,
+            &captures,
+            haystack.clone(),
+            is_utf8,
+        ));
+        let (start, end) = matchdata.range();
+        out.extend_from_slice(&haystack[last_end..start]);
+        let matched = matchdata.group(0);
+        let matchdata_obj = new_data_object(vm, "MatchData", Box::new(matchdata.clone()));
+        let replaced = match (&replacement, &block) {
+            (Some(hash), _) if matches!(hash.value, RValue::Hash(_)) => {
+                let value = mrb_funcall(vm, Some(hash.clone()), "[]", &[matched])?;
+                to_s_bytes(vm, value)?
+            }
+            (Some(replacement), _) => {
+                let template = target_parts(replacement)?.0;
+                expand_replacement(&template, &matchdata)?
+            }
+            (None, Some(block)) => {
+                vm.last_match = Some(matchdata_obj.clone());
+                let value = mrb_call_block(vm, block.clone(), None, &[matched], 0)?;
+                to_s_bytes(vm, value)?
+            }
+            (None, None) => unreachable!(),
+        };
+        out.extend_from_slice(&replaced);
+        last_end = end;
+        last_match = Some(matchdata_obj);
+        if !global {
+            break;
+        }
+    }
+    let matched = last_match.is_some();
+    vm.last_match = last_match;
+    if !matched {
+        return Ok(None);
+    }
+    out.extend_from_slice(&haystack[last_end..]);
+    Ok(Some(out))
+}
+
+/// Successive matches as CRuby finds them: the search resumes at the end
+/// of each match, or one character later after an empty one. Unlike
+/// `Regex::captures_iter`, an empty match may directly follow another match,
+/// so `"aaa".gsub(/a*/, "-")` gives `"--"`.
+fn captures_all<'h>(
+    regex: &'h Regex,
+    haystack: &'h [u8],
+    is_utf8: bool,
+) -> impl Iterator<Item = Captures<'h>> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        if pos > haystack.len() {
+            return None;
+        }
+        let captures = regex.captures_at(haystack, pos)?;
+        let m = captures.get(0)?;
+        pos = m.end();
+        if m.is_empty() {
+            pos += 1;
+            while is_utf8 && haystack.get(pos).is_some_and(|b| b & 0xC0 == 0x80) {
+                pos += 1;
+            }
+        }
+        Some(captures)
+    })
+}
+
+fn replace_copy(vm: &mut VM, args: &[Rc<RObject>], global: bool) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let bytes = substitute(vm, args, global)?.unwrap_or(bytes);
+    Ok(RObject::string_from_bytes(bytes, is_utf8).to_refcount_assigned())
+}
+
+fn mrb_string_sub(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    replace_copy(vm, args, false)
+}
+
+fn mrb_string_gsub(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    replace_copy(vm, args, true)
+}
+
+fn replace_self(vm: &mut VM, args: &[Rc<RObject>], global: bool) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.check_frozen(vm)?;
+    match substitute(vm, args, global)? {
+        Some(bytes) => {
+            *this.string_borrow_mut()? = bytes;
+            Ok(this)
+        }
+        None => Ok(RObject::nil().to_refcount_assigned()),
+    }
+}
+
+fn mrb_string_sub_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    replace_self(vm, args, false)
+}
+
+fn mrb_string_gsub_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    replace_self(vm, args, true)
+}
+
+// scan(pattern): each match, or the array of its groups when the pattern has any
+fn mrb_string_scan(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let (bytes, is_utf8) = string_parts(&this)?;
+    let block = block_arg(args);
+    let regex = pattern_regex(&args[0], false)?;
+
+    let haystack = Rc::new(bytes);
+    let mut result = Vec::new();
+    let mut last_match = None;
+    for captures in captures_all(&regex, &haystack, is_utf8) {
+        let matchdata = Rc::new(RMatchData::new(
+            &regex,
+            &captures,
+            haystack.clone(),
+            is_utf8,
+        ));
+        let item = if matchdata.captures.len() == 1 {
+            matchdata.group(0)
+        } else {
+            let groups = (1..matchdata.captures.len())
+                .map(|i| matchdata.group(i))
+                .collect();
+            RObject::array(groups).to_refcount_assigned()
+        };
+        let matchdata_obj = new_data_object(vm, "MatchData", Box::new(matchdata));
+        match &block {
+            Some(block) => {
+                vm.last_match = Some(matchdata_obj.clone());
+                mrb_call_block(vm, block.clone(), None, &[item], 0)?;
+            }
+            None => result.push(item),
+        }
+        last_match = Some(matchdata_obj);
+    }
+    vm.last_match = last_match;
+
+    if block.is_some() {
+        Ok(this)
+    } else {
+        Ok(RObject::array(result).to_refcount_assigned())
+    }
+}
+
+// split(regexp, limit = 0): groups in the pattern are added to the result
+fn mrb_string_regexp_split(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    if !args.first().is_some_and(|arg| is_regexp(arg)) {
+        return mrb_string_split(vm, args);
+    }
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let regex = get_regexp_from_object(&args[0])?.regex;
+    let limit: i64 = match args.get(1) {
+        Some(limit) => limit.as_ref().try_into()?,
+        None => 0,
+    };
+    let new_string =
+        |bytes: &[u8]| RObject::string_from_bytes(bytes.to_vec(), is_utf8).to_refcount_assigned();
+
+    let mut fields = Vec::new();
+    if bytes.is_empty() {
+        return Ok(RObject::array(fields).to_refcount_assigned());
+    }
+    let mut field_start = 0;
+    let mut splits = 0;
+    for captures in regex.captures_iter(&bytes) {
+        if limit > 0 && splits == limit - 1 {
+            break;
+        }
+        let Some(m) = captures.get(0) else {
+            continue;
+        };
+        if m.is_empty() {
+            // An empty match splits between characters, never before the first one
+            let inside_char = is_utf8 && bytes.get(m.start()).is_some_and(|b| b & 0xC0 == 0x80);
+            if m.start() == 0 || m.start() >= bytes.len() || inside_char {
+                continue;
+            }
+        }
+        fields.push(new_string(&bytes[field_start..m.start()]));
+        fields.extend(
+            captures
+                .iter()
+                .skip(1)
+                .flatten()
+                .map(|g| new_string(g.as_bytes())),
+        );
+        field_start = m.end();
+        splits += 1;
+    }
+    fields.push(new_string(&bytes[field_start..]));
+
+    if limit == 0 {
+        while fields
+            .last()
+            .is_some_and(|field| string_parts(field).is_ok_and(|(s, _)| s.is_empty()))
+        {
+            fields.pop();
+        }
+    }
+    Ok(RObject::array(fields).to_refcount_assigned())
+}
+
+fn mrb_string_regexp_partition(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    if !args.first().is_some_and(|arg| is_regexp(arg)) {
+        return mrb_string_partition(vm, args);
+    }
+    regexp_partition(vm, &args[0], false)
+}
+
+fn mrb_string_regexp_rpartition(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    if !args.first().is_some_and(|arg| is_regexp(arg)) {
+        return mrb_string_rpartition(vm, args);
+    }
+    regexp_partition(vm, &args[0], true)
+}
+
+// Splits around the first match, or with `last` around the match starting
+// closest to the end, as rindex finds it. Sets `$~`.
+fn regexp_partition(vm: &mut VM, pattern: &RObject, last: bool) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let regex = get_regexp_from_object(pattern)?.regex;
+    let haystack = Rc::new(bytes);
+    let captures = if last {
+        (0..=haystack.len())
+            .rev()
+            .filter(|&pos| !is_utf8 || haystack.get(pos).is_none_or(|b| b & 0xC0 != 0x80))
+            .find_map(|pos| {
+                regex
+                    .captures_at(&haystack, pos)
+                    .filter(|captures| captures.get(0).is_some_and(|m| m.start() == pos))
+            })
+    } else {
+        regex.captures(&haystack)
+    };
+    let matchdata = captures.map(|captures| {
+        Rc::new(RMatchData::new(
+            &regex,
+            &captures,
+            haystack.clone(),
+            is_utf8,
+        ))
+    });
+    let found = matchdata.as_ref().map(|m| m.range());
+    vm.last_match = matchdata.map(|m| new_data_object(vm, "MatchData", Box::new(m)));
+    Ok(partition_at(&haystack, is_utf8, found, last))
+}
+
+/// Reads a match-related special variable (`$~`, `$&`, `` $` ``, `$'`, `$1`..`$9`)
+/// from the last match of the current frame.
+pub(crate) fn mrb_regexp_special_var_get(vm: &mut VM, name: &str) -> Result<Rc<RObject>, Error> {
+    let Some(last_match) = vm.last_match.clone() else {
+        return Ok(RObject::nil().to_refcount_assigned());
+    };
+    let matchdata = get_matchdata_from_object(&last_match)?;
+    let (start, end) = matchdata.range();
+
+    match name {
+        "$~" => Ok(last_match.clone()),
+        "$`" => Ok(matchdata.new_string(&matchdata.haystack[..start])),
+        "$'" => Ok(matchdata.new_string(&matchdata.haystack[end..])),
+        "$&" => Ok(matchdata.group(0)),
+        _ => {
+            let index: usize = name[1..]
+                .parse()
+                .map_err(|_| Error::NameError(name.to_string()))?;
+            Ok(matchdata.group(index))
+        }
+    }
+}
+
+/// Assigns `$~`. Other match-related special variables are read-only.
+pub(crate) fn mrb_regexp_special_var_set(
+    vm: &mut VM,
+    name: &str,
+    val: Rc<RObject>,
+) -> Result<(), Error> {
+    if name != "$~" {
+        return Err(Error::NameError(format!("Can't set variable {}", name)));
+    }
+    if val.is_nil() {
+        vm.last_match = None;
+    } else if is_matchdata(&val) {
+        vm.last_match = Some(val);
+    } else {
+        return Err(Error::TypeMismatch);
+    }
+    Ok(())
+}
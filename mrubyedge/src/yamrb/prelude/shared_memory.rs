@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use crate::yamrb::helpers::mrb_define_class_cmethod;
@@ -8,7 +8,7 @@ use crate::{
     Error,
     yamrb::{
         helpers::mrb_define_cmethod,
-        value::{RHashMap, RObject, RType, RValue},
+        value::{IvarTable, RObject, RType, RValue},
     },
 };
 
@@ -73,7 +73,8 @@ pub fn mrb_shared_memory_new(_vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RO
         value: RValue::SharedMemory(Rc::new(RefCell::new(SharedMemory::new(size as usize)))),
         object_id: u64::MAX.into(),
         singleton_class: RefCell::new(None),
-        ivar: RefCell::new(RHashMap::default()),
+        ivar: RefCell::new(IvarTable::default()),
+        frozen: Cell::new(false),
     };
     Ok(obj.to_refcount_assigned())
 }
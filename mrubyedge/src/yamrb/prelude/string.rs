@@ -3,9 +3,16 @@ use std::rc::Rc;
 use crate::{
     Error,
     yamrb::{
-        helpers::{mrb_define_class_cmethod, mrb_define_cmethod},
-        prelude::object,
-        value::{RObject, RSym, RValue},
+        bigint::RBigInt,
+        helpers::{mrb_call_block, mrb_define_class_cmethod, mrb_define_cmethod},
+        prelude::{
+            encoding::{encoding_is_utf8, encoding_object},
+            enumerator::{block_arg, mrb_to_enum},
+            module::mrb_include_module,
+            object,
+            r#struct::given_kwargs,
+        },
+        value::{RObject, RValue},
         vm::VM,
     },
 };
@@ -163,6 +170,48 @@ pub(crate) fn initialize_string(vm: &mut VM) {
         "chars",
         Box::new(mrb_string_chars),
     );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "each_char",
+        Box::new(mrb_string_each_char),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "codepoints",
+        Box::new(mrb_string_codepoints),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "encoding",
+        Box::new(mrb_string_encoding),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "force_encoding",
+        Box::new(mrb_string_force_encoding),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "valid_encoding?",
+        Box::new(mrb_string_valid_encoding),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "scrub",
+        Box::new(mrb_string_scrub),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "unicode_normalize",
+        Box::new(mrb_string_unicode_normalize),
+    );
     mrb_define_cmethod(
         vm,
         string_class.clone(),
@@ -187,6 +236,206 @@ pub(crate) fn initialize_string(vm: &mut VM) {
         "downcase!",
         Box::new(mrb_string_downcase_self),
     );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "reverse",
+        Box::new(mrb_string_reverse),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "reverse!",
+        Box::new(mrb_string_reverse_self),
+    );
+    mrb_define_cmethod(vm, string_class.clone(), "tr", Box::new(mrb_string_tr));
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "tr!",
+        Box::new(mrb_string_tr_self),
+    );
+    mrb_define_cmethod(vm, string_class.clone(), "tr_s", Box::new(mrb_string_tr_s));
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "tr_s!",
+        Box::new(mrb_string_tr_s_self),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "delete",
+        Box::new(mrb_string_delete),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "delete!",
+        Box::new(mrb_string_delete_self),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "squeeze",
+        Box::new(mrb_string_squeeze),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "squeeze!",
+        Box::new(mrb_string_squeeze_self),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "count",
+        Box::new(mrb_string_count),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "center",
+        Box::new(mrb_string_center),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "ljust",
+        Box::new(mrb_string_ljust),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "rjust",
+        Box::new(mrb_string_rjust),
+    );
+    mrb_define_cmethod(vm, string_class.clone(), "succ", Box::new(mrb_string_succ));
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "succ!",
+        Box::new(mrb_string_succ_self),
+    );
+    mrb_define_cmethod(vm, string_class.clone(), "next", Box::new(mrb_string_succ));
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "next!",
+        Box::new(mrb_string_succ_self),
+    );
+    mrb_define_cmethod(vm, string_class.clone(), "upto", Box::new(mrb_string_upto));
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "each_line",
+        Box::new(mrb_string_each_line),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "lines",
+        Box::new(mrb_string_lines),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "capitalize",
+        Box::new(mrb_string_capitalize),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "capitalize!",
+        Box::new(mrb_string_capitalize_self),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "swapcase",
+        Box::new(mrb_string_swapcase),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "swapcase!",
+        Box::new(mrb_string_swapcase_self),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "insert",
+        Box::new(mrb_string_insert),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "prepend",
+        Box::new(mrb_string_prepend),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "replace",
+        Box::new(mrb_string_replace),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "casecmp",
+        Box::new(mrb_string_casecmp),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "casecmp?",
+        Box::new(mrb_string_casecmp_p),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "delete_prefix",
+        Box::new(mrb_string_delete_prefix),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "delete_prefix!",
+        Box::new(mrb_string_delete_prefix_self),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "delete_suffix",
+        Box::new(mrb_string_delete_suffix),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "delete_suffix!",
+        Box::new(mrb_string_delete_suffix_self),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "partition",
+        Box::new(mrb_string_partition),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "rpartition",
+        Box::new(mrb_string_rpartition),
+    );
+    mrb_define_cmethod(
+        vm,
+        string_class.clone(),
+        "rindex",
+        Box::new(mrb_string_rindex),
+    );
+    mrb_define_cmethod(vm, string_class.clone(), "hex", Box::new(mrb_string_hex));
+    mrb_define_cmethod(vm, string_class.clone(), "oct", Box::new(mrb_string_oct));
+    mrb_define_cmethod(vm, string_class.clone(), "sum", Box::new(mrb_string_sum));
     mrb_define_cmethod(vm, string_class.clone(), "to_i", Box::new(mrb_string_to_i));
     mrb_define_cmethod(vm, string_class.clone(), "to_f", Box::new(mrb_string_to_f));
     mrb_define_cmethod(
@@ -200,7 +449,7 @@ pub(crate) fn initialize_string(vm: &mut VM) {
         vm,
         string_class.clone(),
         "bytesize",
-        Box::new(mrb_string_size),
+        Box::new(mrb_string_bytesize),
     );
     mrb_define_cmethod(
         vm,
@@ -215,11 +464,14 @@ pub(crate) fn initialize_string(vm: &mut VM) {
         Box::new(mrb_string_inspect),
     );
     mrb_define_cmethod(vm, string_class.clone(), "to_s", Box::new(object::mrb_self));
+
+    let comparable_module = vm.get_module_by_name("Comparable");
+    mrb_include_module(&string_class, comparable_module).expect("failed to include Comparable");
 }
 
 pub fn mrb_string_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: String = vm.getself()?.as_ref().try_into()?;
-    Ok(Rc::new(RObject::string(format!("\"{}\"", this))))
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    Ok(Rc::new(RObject::string(inspect_bytes(&bytes, is_utf8))))
 }
 
 pub fn mrb_string_new(_vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -233,6 +485,220 @@ pub fn mrb_string_new(_vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
     let s: String = args[0].as_ref().try_into()?;
     Ok(Rc::new(RObject::string(s)))
 }
+/// Maps character positions to byte offsets. ASCII-only and binary strings
+/// map one to one, so the offset table is only built for multibyte UTF-8.
+pub(crate) struct CharIndex {
+    /// Start of each character followed by the total byte length
+    offsets: Option<Vec<usize>>,
+    bytesize: usize,
+}
+
+impl CharIndex {
+    pub(crate) fn new(bytes: &[u8], is_utf8: bool) -> Self {
+        if !is_utf8 || bytes.is_ascii() {
+            return CharIndex {
+                offsets: None,
+                bytesize: bytes.len(),
+            };
+        }
+        let mut offsets = Vec::with_capacity(bytes.len() + 1);
+        let mut pos = 0;
+        while pos < bytes.len() {
+            offsets.push(pos);
+            pos += utf8_char_len(&bytes[pos..]);
+        }
+        offsets.push(bytes.len());
+        CharIndex {
+            offsets: Some(offsets),
+            bytesize: bytes.len(),
+        }
+    }
+
+    /// Number of characters
+    pub(crate) fn len(&self) -> usize {
+        match &self.offsets {
+            Some(offsets) => offsets.len() - 1,
+            None => self.bytesize,
+        }
+    }
+
+    /// Byte offset of the character at `index`, clamped to the end
+    pub(crate) fn byte_offset(&self, index: usize) -> usize {
+        match &self.offsets {
+            Some(offsets) => offsets[index.min(offsets.len() - 1)],
+            None => index.min(self.bytesize),
+        }
+    }
+
+    /// Position of the character containing the byte at `offset`
+    pub(crate) fn char_index(&self, offset: usize) -> usize {
+        match &self.offsets {
+            Some(offsets) => match offsets.binary_search(&offset) {
+                Ok(index) => index,
+                Err(index) => index - 1,
+            },
+            None => offset,
+        }
+    }
+
+    /// Byte range of `len` characters starting at `start`
+    pub(crate) fn byte_range(&self, start: usize, len: usize) -> std::ops::Range<usize> {
+        self.byte_offset(start)..self.byte_offset(start.saturating_add(len))
+    }
+}
+
+/// Byte length of the UTF-8 character at the head of `bytes`.
+/// A byte that does not start a valid sequence counts as a character of its own.
+pub(crate) fn utf8_char_len(bytes: &[u8]) -> usize {
+    let width = match bytes.first() {
+        Some(0xC2..=0xDF) => 2,
+        Some(0xE0..=0xEF) => 3,
+        Some(0xF0..=0xF4) => 4,
+        _ => return 1,
+    };
+    if bytes.len() >= width && std::str::from_utf8(&bytes[..width]).is_ok() {
+        width
+    } else {
+        1
+    }
+}
+
+pub(crate) fn find_bytes(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if needle.is_empty() {
+        return (from <= haystack.len()).then_some(from);
+    }
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| pos + from)
+}
+
+/// (bytes, is_utf8) of a String
+pub(crate) fn string_parts(obj: &RObject) -> Result<(Vec<u8>, bool), Error> {
+    match &obj.value {
+        RValue::String(s, is_utf8) => Ok((s.borrow().clone(), is_utf8.get())),
+        _ => Err(Error::TypeMismatch),
+    }
+}
+
+fn new_string(bytes: Vec<u8>, is_utf8: bool) -> Rc<RObject> {
+    RObject::string_from_bytes(bytes, is_utf8).to_refcount_assigned()
+}
+
+fn invalid_byte_sequence() -> Error {
+    Error::ArgumentError("invalid byte sequence in UTF-8".to_string())
+}
+
+/// Double-quoted literal form of a string. Bytes that are not part of a
+/// valid UTF-8 character, and every non-ASCII byte of a binary string,
+/// print as `\xNN`.
+pub(crate) fn inspect_bytes(bytes: &[u8], is_utf8: bool) -> String {
+    let mut out = String::from("\"");
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let width = if is_utf8 {
+            utf8_char_len(&bytes[pos..])
+        } else {
+            1
+        };
+        let chunk = &bytes[pos..pos + width];
+        pos += width;
+        let ch = match std::str::from_utf8(chunk) {
+            Ok(s) => s.chars().next().unwrap_or('\0'),
+            Err(_) => {
+                out.push_str(&format!("\\x{:02X}", chunk[0]));
+                continue;
+            }
+        };
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\x0C' => out.push_str("\\f"),
+            '\x0B' => out.push_str("\\v"),
+            '\x08' => out.push_str("\\b"),
+            '\x07' => out.push_str("\\a"),
+            '\x1B' => out.push_str("\\e"),
+            // Escaped so the literal does not read as interpolation
+            '#' if matches!(bytes.get(pos), Some(b'{' | b'$' | b'@')) => out.push_str("\\#"),
+            c if c.is_control() && is_utf8 => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c if c.is_control() => out.push_str(&format!("\\x{:02X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Resolves the arguments of `[]`, `slice` and `slice!` (an index, a start
+/// and length, or a Range) into a start and length in characters.
+/// `None` when the start is out of range.
+fn slice_range(args: &[Rc<RObject>], len: usize) -> Result<Option<(usize, usize)>, Error> {
+    let len = len as i64;
+    let normalize = |index: i64| if index < 0 { index + len } else { index };
+    let (start, count) = match args {
+        [arg] => match &arg.value {
+            RValue::Range(start, end, exclusive) => {
+                let start = if start.is_nil() {
+                    0
+                } else {
+                    normalize(start.as_ref().try_into()?)
+                };
+                let end = if end.is_nil() {
+                    len
+                } else {
+                    let end = normalize(end.as_ref().try_into()?);
+                    if *exclusive { end } else { end + 1 }
+                };
+                (start, end - start)
+            }
+            _ => {
+                let index = normalize(arg.as_ref().try_into()?);
+                if index >= len {
+                    return Ok(None);
+                }
+                (index, 1)
+            }
+        },
+        [index, count] => {
+            let count: i64 = count.as_ref().try_into()?;
+            if count < 0 {
+                return Ok(None);
+            }
+            (normalize(index.as_ref().try_into()?), count)
+        }
+        _ => {
+            return Err(Error::ArgumentError(format!(
+                "wrong number of arguments (given {}, expected 1..2)",
+                args.len()
+            )));
+        }
+    };
+    if start < 0 || start > len {
+        return Ok(None);
+    }
+    Ok(Some((start as usize, count.clamp(0, len - start) as usize)))
+}
+
+fn char_strings(bytes: &[u8], is_utf8: bool) -> Vec<Rc<RObject>> {
+    char_slices(bytes, is_utf8)
+        .into_iter()
+        .map(|ch| new_string(ch.to_vec(), is_utf8))
+        .collect()
+}
+
+// Full Unicode case mapping for valid UTF-8, ASCII only otherwise
+fn convert_case(bytes: &[u8], is_utf8: bool, upper: bool) -> Vec<u8> {
+    match std::str::from_utf8(bytes) {
+        Ok(s) if is_utf8 && upper => s.to_uppercase().into_bytes(),
+        Ok(s) if is_utf8 => s.to_lowercase().into_bytes(),
+        _ if upper => bytes.to_ascii_uppercase(),
+        _ => bytes.to_ascii_lowercase(),
+    }
+}
 
 fn bytes_of<const N: usize>(value: &[u8], cursor: usize) -> Result<[u8; N], Error> {
     if value.len() < cursor + N {
@@ -273,7 +739,7 @@ fn mrb_string_unpack(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
             b'q' => {
                 let value = i64::from_le_bytes(bytes_of::<8>(&value, cursor)?);
                 cursor += 8;
-                value as i64
+                value
             }
             b'L' | b'I' => {
                 let value = u32::from_le_bytes(bytes_of::<4>(&value, cursor)?);
@@ -313,7 +779,7 @@ fn mrb_string_unpack(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
                 return Err(Error::RuntimeError("Unsupported format".to_string()));
             }
         };
-        mrb_array_push(result.clone(), &[Rc::new(RObject::integer(value as i64))])?;
+        mrb_array_push(result.clone(), &[Rc::new(RObject::integer(value))])?;
     }
 
     Ok(result)
@@ -350,121 +816,104 @@ fn test_mrb_string_unpack() {
     }
 }
 
+// Counts characters, or bytes for a binary string
 fn mrb_string_size(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let len = CharIndex::new(&bytes, is_utf8).len();
+    Ok(Rc::new(RObject::integer(len as i64)))
+}
+
+fn mrb_string_bytesize(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
     let value: Vec<u8> = this.as_ref().try_into()?;
     Ok(Rc::new(RObject::integer(value.len() as i64)))
 }
 
 fn mrb_string_add(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: String = vm.getself()?.as_ref().try_into()?;
-    let other: String = args[0].as_ref().try_into()?;
-    Ok(Rc::new(RObject::string(this + &other)))
+    let (mut bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let other: Vec<u8> = args[0].as_ref().try_into()?;
+    bytes.extend_from_slice(&other);
+    Ok(new_string(bytes, is_utf8))
 }
 
 fn mrb_string_mul(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: String = vm.getself()?.as_ref().try_into()?;
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
     let times: i64 = args[0].as_ref().try_into()?;
     if times < 0 {
         return Err(Error::ArgumentError("negative argument".to_string()));
     }
-    Ok(Rc::new(RObject::string(this.repeat(times as usize))))
+    Ok(new_string(bytes.repeat(times as usize), is_utf8))
 }
 
 fn mrb_string_append(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
-    let other: String = args[0].as_ref().try_into()?;
-    this.string_borrow_mut()?
-        .extend_from_slice(other.as_bytes());
+    this.check_frozen(vm)?;
+    let other: Vec<u8> = args[0].as_ref().try_into()?;
+    this.string_borrow_mut()?.extend_from_slice(&other);
     Ok(this)
 }
 
 fn mrb_string_slice(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: String = vm.getself()?.as_ref().try_into()?;
-    let chars: Vec<char> = this.chars().collect();
-
-    if args.is_empty() {
-        return Err(Error::ArgumentError(
-            "wrong number of arguments".to_string(),
-        ));
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+
+    // str[other_str] returns a copy of other_str when it is included
+    if let [pattern] = args
+        && let RValue::String(_, _) = pattern.value
+    {
+        let (needle, _) = string_parts(pattern)?;
+        return match find_bytes(&bytes, &needle, 0) {
+            Some(_) => Ok(new_string(needle, is_utf8)),
+            None => Ok(Rc::new(RObject::nil())),
+        };
     }
 
-    let index: i64 = args[0].as_ref().try_into()?;
-    let len = chars.len() as i64;
-    let idx = if index < 0 { len + index } else { index };
-
-    if idx < 0 || idx >= len {
-        return Ok(Rc::new(RObject::nil()));
+    let index = CharIndex::new(&bytes, is_utf8);
+    match slice_range(args, index.len())? {
+        Some((start, len)) => Ok(new_string(
+            bytes[index.byte_range(start, len)].to_vec(),
+            is_utf8,
+        )),
+        None => Ok(Rc::new(RObject::nil())),
     }
+}
 
-    if args.len() == 1 {
-        Ok(Rc::new(RObject::string(chars[idx as usize].to_string())))
+fn mrb_string_slice_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.check_frozen(vm)?;
+    let (mut bytes, is_utf8) = string_parts(&this)?;
+
+    let range = if let [pattern] = args
+        && let RValue::String(_, _) = pattern.value
+    {
+        let (needle, _) = string_parts(pattern)?;
+        find_bytes(&bytes, &needle, 0).map(|pos| pos..pos + needle.len())
     } else {
-        let length: i64 = args[1].as_ref().try_into()?;
-        if length < 0 {
-            return Ok(Rc::new(RObject::nil()));
-        }
-        let end = (idx + length).min(len);
-        let result: String = chars[idx as usize..end as usize].iter().collect();
-        Ok(Rc::new(RObject::string(result)))
-    }
+        let index = CharIndex::new(&bytes, is_utf8);
+        slice_range(args, index.len())?.map(|(start, len)| index.byte_range(start, len))
+    };
+    let Some(range) = range else {
+        return Ok(Rc::new(RObject::nil()));
+    };
+
+    let removed: Vec<u8> = bytes.drain(range).collect();
+    *this.string_borrow_mut()? = bytes;
+    Ok(new_string(removed, is_utf8))
 }
 
-fn mrb_string_slice_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+// Returns self with UTF-8 flag set to false (binary encoding).
+fn mrb_string_b(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
-    let s: String = this.as_ref().try_into()?;
-    let chars: Vec<char> = s.chars().collect();
 
-    if args.is_empty() {
-        return Err(Error::ArgumentError(
-            "wrong number of arguments".to_string(),
-        ));
+    if let RValue::String(value, _) = &this.value {
+        Ok(RObject::string_from_vec(value.borrow().to_owned()).to_refcount_assigned())
+    } else {
+        Err(Error::TypeMismatch)
     }
-
-    let index: i64 = args[0].as_ref().try_into()?;
-    let len = chars.len() as i64;
-    let idx = if index < 0 { len + index } else { index };
-
-    if idx < 0 || idx >= len {
-        return Ok(Rc::new(RObject::nil()));
-    }
-
-    let (removed, remaining) = if args.len() == 1 {
-        let removed = chars[idx as usize].to_string();
-        let mut remaining_chars = chars.clone();
-        remaining_chars.remove(idx as usize);
-        let remaining: String = remaining_chars.iter().collect();
-        (removed, remaining)
-    } else {
-        let length: i64 = args[1].as_ref().try_into()?;
-        if length < 0 {
-            return Ok(Rc::new(RObject::nil()));
-        }
-        let end = (idx + length).min(len);
-        let removed: String = chars[idx as usize..end as usize].iter().collect();
-        let mut remaining_chars = chars.clone();
-        remaining_chars.drain(idx as usize..end as usize);
-        let remaining: String = remaining_chars.iter().collect();
-        (removed, remaining)
-    };
-
-    *this.string_borrow_mut()? = remaining.as_bytes().to_vec();
-    Ok(Rc::new(RObject::string(removed)))
-}
-
-// Returns self with UTF-8 flag set to false (binary encoding).
-fn mrb_string_b(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this = vm.getself()?;
-
-    if let RValue::String(value, _) = &this.value {
-        Ok(RObject::string_from_vec(value.borrow().to_owned()).to_refcount_assigned())
-    } else {
-        Err(Error::TypeMismatch)
-    }
-}
+}
 
 fn mrb_string_clear(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     this.string_borrow_mut()?.clear();
     Ok(this)
 }
@@ -481,6 +930,7 @@ fn mrb_string_chomp(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
 
 fn mrb_string_chomp_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let s: String = this.as_ref().try_into()?;
     let result = s
         .strip_suffix("\r\n")
@@ -497,8 +947,8 @@ fn mrb_string_chomp_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObjec
 }
 
 fn mrb_string_dup(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: String = vm.getself()?.as_ref().try_into()?;
-    Ok(Rc::new(RObject::string(this)))
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    Ok(new_string(bytes, is_utf8))
 }
 
 fn mrb_string_empty(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -520,6 +970,7 @@ fn mrb_string_getbyte(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
 fn mrb_string_setbyte(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let mut bytes: Vec<u8> = this.as_ref().try_into()?;
     let index: i64 = args[0].as_ref().try_into()?;
     let value: i64 = args[1].as_ref().try_into()?;
@@ -544,26 +995,45 @@ fn mrb_string_setbyte(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>,
     Ok(Rc::new(RObject::integer(value)))
 }
 
+// index(substring, start = 0): the character position of the first match
 fn mrb_string_index(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: String = vm.getself()?.as_ref().try_into()?;
-    let search: String = args[0].as_ref().try_into()?;
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let search: Vec<u8> = args[0].as_ref().try_into()?;
+    let index = CharIndex::new(&bytes, is_utf8);
+    let len = index.len() as i64;
+    let start: i64 = match args.get(1) {
+        Some(start) => start.as_ref().try_into()?,
+        None => 0,
+    };
+    let start = if start < 0 { start + len } else { start };
+    if start < 0 || start > len {
+        return Ok(Rc::new(RObject::nil()));
+    }
 
-    match this.find(&search) {
-        Some(pos) => Ok(Rc::new(RObject::integer(pos as i64))),
+    match find_bytes(&bytes, &search, index.byte_offset(start as usize)) {
+        Some(pos) => Ok(Rc::new(RObject::integer(index.char_index(pos) as i64))),
         None => Ok(Rc::new(RObject::nil())),
     }
 }
 
 fn mrb_string_ord(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: String = vm.getself()?.as_ref().try_into()?;
-    if let Some(ch) = this.chars().next() {
-        Ok(Rc::new(RObject::integer(ch as i64)))
-    } else {
-        Err(Error::ArgumentError("empty string".to_string()))
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let Some(&first) = bytes.first() else {
+        return Err(Error::ArgumentError("empty string".to_string()));
+    };
+    if !is_utf8 {
+        return Ok(Rc::new(RObject::integer(first as i64)));
+    }
+    let width = utf8_char_len(&bytes);
+    match std::str::from_utf8(&bytes[..width]) {
+        Ok(s) => Ok(Rc::new(RObject::integer(
+            s.chars().next().map_or(0, |ch| ch as i64),
+        ))),
+        Err(_) => Err(invalid_byte_sequence()),
     }
 }
 
-fn mrb_string_split(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+pub(crate) fn mrb_string_split(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this: String = vm.getself()?.as_ref().try_into()?;
 
     let result = if args.is_empty() {
@@ -588,6 +1058,7 @@ fn mrb_string_lstrip(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
 fn mrb_string_lstrip_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let s: String = this.as_ref().try_into()?;
     let result = s.trim_start();
 
@@ -606,6 +1077,7 @@ fn mrb_string_rstrip(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>,
 
 fn mrb_string_rstrip_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let s: String = this.as_ref().try_into()?;
     let result = s.trim_end();
 
@@ -624,6 +1096,7 @@ fn mrb_string_strip(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
 
 fn mrb_string_strip_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    this.check_frozen(vm)?;
     let s: String = this.as_ref().try_into()?;
     let result = s.trim();
 
@@ -637,7 +1110,7 @@ fn mrb_string_strip_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObjec
 
 fn mrb_string_to_sym(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this: String = vm.getself()?.as_ref().try_into()?;
-    Ok(Rc::new(RObject::symbol(RSym::new(this))))
+    Ok(vm.intern(&this))
 }
 
 fn mrb_string_start_with(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -668,44 +1141,131 @@ fn mrb_string_bytes(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, E
 }
 
 /// Returns an array of characters.
-/// If UTF-8 flag is true, splits by runes (UTF-8 characters).
+/// If UTF-8 flag is true, splits by runes (UTF-8 characters); a byte outside
+/// any valid sequence becomes a character of its own.
 /// If UTF-8 flag is false, splits by bytes.
 fn mrb_string_chars(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    Ok(Rc::new(RObject::array(char_strings(&bytes, is_utf8))))
+}
+fn mrb_string_each_char(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
+    let Some(block) = block_arg(args) else {
+        return Ok(mrb_to_enum(vm, this, "each_char", &[]));
+    };
+    let (bytes, is_utf8) = string_parts(&this)?;
+    for ch in char_strings(&bytes, is_utf8) {
+        mrb_call_block(vm, block.clone(), None, &[ch], 0)?;
+    }
+    Ok(this)
+}
 
-    let is_utf8 = this.string_is_utf8()?;
-
-    let bytes: Vec<u8> = this.as_ref().try_into()?;
-
-    let result: Vec<Rc<RObject>> = if is_utf8 {
-        // Split by UTF-8 characters (runes)
-        let s = String::from_utf8_lossy(&bytes);
-        s.chars()
-            .map(|c| Rc::new(RObject::string(c.to_string())))
+fn mrb_string_codepoints(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let codepoints: Vec<Rc<RObject>> = if is_utf8 {
+        std::str::from_utf8(&bytes)
+            .map_err(|_| invalid_byte_sequence())?
+            .chars()
+            .map(|ch| Rc::new(RObject::integer(ch as i64)))
             .collect()
     } else {
-        // Split by bytes
         bytes
-            .into_iter()
-            .map(|b| Rc::new(RObject::string_from_vec(vec![b])))
+            .iter()
+            .map(|b| Rc::new(RObject::integer(*b as i64)))
             .collect()
     };
+    Ok(Rc::new(RObject::array(codepoints)))
+}
 
-    Ok(Rc::new(RObject::array(result)))
+fn mrb_string_encoding(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let is_utf8 = vm.getself()?.string_is_utf8()?;
+    Ok(encoding_object(vm, is_utf8))
+}
+
+// Switches between UTF-8 and binary in place; the bytes are left alone
+fn mrb_string_force_encoding(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.check_frozen(vm)?;
+    let encoding = args.first().ok_or_else(|| {
+        Error::ArgumentError("wrong number of arguments (given 0, expected 1)".to_string())
+    })?;
+    this.set_string_utf8(encoding_is_utf8(encoding)?)?;
+    Ok(this)
+}
+
+fn mrb_string_valid_encoding(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let valid = !is_utf8 || std::str::from_utf8(&bytes).is_ok();
+    Ok(Rc::new(RObject::boolean(valid)))
+}
+
+// scrub(replacement = "�") { |bytes| ... }: replaces each invalid byte
+// sequence with the replacement or the block's result
+fn mrb_string_scrub(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    if !is_utf8 {
+        return Ok(new_string(bytes, is_utf8));
+    }
+    let block = block_arg(args);
+    let args = if block.is_some() {
+        &args[..args.len() - 1]
+    } else {
+        args
+    };
+    let replacement: Vec<u8> = match args.first() {
+        Some(replacement) => replacement.as_ref().try_into()?,
+        None => "\u{FFFD}".as_bytes().to_vec(),
+    };
+
+    let mut out = Vec::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        out.extend_from_slice(chunk.valid().as_bytes());
+        let invalid = chunk.invalid();
+        if invalid.is_empty() {
+            continue;
+        }
+        match &block {
+            Some(block) => {
+                let invalid = new_string(invalid.to_vec(), false);
+                let result = mrb_call_block(vm, block.clone(), None, &[invalid], 0)?;
+                let result: Vec<u8> = result.as_ref().try_into()?;
+                out.extend_from_slice(&result);
+            }
+            None => out.extend_from_slice(&replacement),
+        }
+    }
+    Ok(new_string(out, is_utf8))
+}
+
+// unicode_normalize(form = :nfc): no normalization tables are bundled, so
+// this only checks the form and returns a copy
+fn mrb_string_unicode_normalize(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    if let Some(form) = args.first() {
+        let form = form.intern()?;
+        if !matches!(form.name.as_str(), "nfc" | "nfd" | "nfkc" | "nfkd") {
+            return Err(Error::ArgumentError(format!(
+                "Invalid normalization form :{}.",
+                form.name
+            )));
+        }
+    }
+    Ok(new_string(bytes, is_utf8))
 }
 
 fn mrb_string_upcase(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: String = vm.getself()?.as_ref().try_into()?;
-    Ok(Rc::new(RObject::string(this.to_uppercase())))
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    Ok(new_string(convert_case(&bytes, is_utf8, true), is_utf8))
 }
 
 fn mrb_string_upcase_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
-    let s: String = this.as_ref().try_into()?;
-    let result = s.to_uppercase();
+    this.check_frozen(vm)?;
+    let (bytes, is_utf8) = string_parts(&this)?;
+    let result = convert_case(&bytes, is_utf8, true);
 
-    if result != s {
-        *this.string_borrow_mut()? = result.as_bytes().to_vec();
+    if result != bytes {
+        *this.string_borrow_mut()? = result;
         Ok(this)
     } else {
         Ok(Rc::new(RObject::nil()))
@@ -713,17 +1273,18 @@ fn mrb_string_upcase_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObje
 }
 
 fn mrb_string_downcase(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
-    let this: String = vm.getself()?.as_ref().try_into()?;
-    Ok(Rc::new(RObject::string(this.to_lowercase())))
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    Ok(new_string(convert_case(&bytes, is_utf8, false), is_utf8))
 }
 
 fn mrb_string_downcase_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let this = vm.getself()?;
-    let s: String = this.as_ref().try_into()?;
-    let result = s.to_lowercase();
+    this.check_frozen(vm)?;
+    let (bytes, is_utf8) = string_parts(&this)?;
+    let result = convert_case(&bytes, is_utf8, false);
 
-    if result != s {
-        *this.string_borrow_mut()? = result.as_bytes().to_vec();
+    if result != bytes {
+        *this.string_borrow_mut()? = result;
         Ok(this)
     } else {
         Ok(Rc::new(RObject::nil()))
@@ -744,6 +1305,824 @@ fn mrb_string_to_f(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Er
     Ok(Rc::new(RObject::float(result)))
 }
 
+/// Characters of a string as byte slices
+fn char_slices(bytes: &[u8], is_utf8: bool) -> Vec<&[u8]> {
+    let index = CharIndex::new(bytes, is_utf8);
+    (0..index.len())
+        .map(|i| &bytes[index.byte_range(i, 1)])
+        .collect()
+}
+
+// Bytes outside a valid UTF-8 character get codes past char::MAX, so they
+// never collide with a codepoint
+const RAW_BYTE_CODE: u32 = 0x110000;
+
+fn char_code(ch: &[u8], is_utf8: bool) -> u32 {
+    match std::str::from_utf8(ch) {
+        Ok(s) if is_utf8 => s.chars().next().map_or(0, u32::from),
+        _ if is_utf8 => RAW_BYTE_CODE + ch[0] as u32,
+        _ => ch[0] as u32,
+    }
+}
+
+fn push_char_code(out: &mut Vec<u8>, code: u32, is_utf8: bool) {
+    match char::from_u32(code) {
+        Some(ch) if is_utf8 => out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes()),
+        _ => out.push((code & 0xFF) as u8),
+    }
+}
+
+/// A character list of `tr`, `delete`, `squeeze` and `count`, such as
+/// `"a-z0-9"` or `"^aeiou"`. `\` escapes `^`, `-` and itself.
+struct CharSet {
+    negated: bool,
+    ranges: Vec<(u32, u32)>,
+}
+
+impl CharSet {
+    fn parse(spec: &RObject, allow_negation: bool) -> Result<Self, Error> {
+        let (spec, is_utf8) = string_parts(spec)?;
+        let codes: Vec<u32> = char_slices(&spec, is_utf8)
+            .into_iter()
+            .map(|ch| char_code(ch, is_utf8))
+            .collect();
+        let negated = allow_negation && codes.len() > 1 && codes[0] == '^' as u32;
+        let mut pos = negated as usize;
+        let mut ranges = Vec::new();
+        while pos < codes.len() {
+            if codes[pos] == '\\' as u32 && pos + 1 < codes.len() {
+                pos += 1;
+            }
+            let first = codes[pos];
+            if pos + 2 < codes.len() && codes[pos + 1] == '-' as u32 {
+                let last = codes[pos + 2];
+                if last < first {
+                    let show = |code| char::from_u32(code).unwrap_or('?');
+                    return Err(Error::ArgumentError(format!(
+                        "invalid range \"{}-{}\" in string transliteration",
+                        show(first),
+                        show(last)
+                    )));
+                }
+                ranges.push((first, last));
+                pos += 3;
+            } else {
+                ranges.push((first, first));
+                pos += 1;
+            }
+        }
+        Ok(CharSet { negated, ranges })
+    }
+
+    fn contains(&self, code: u32) -> bool {
+        self.ranges
+            .iter()
+            .any(|&(first, last)| (first..=last).contains(&code))
+            != self.negated
+    }
+
+    /// Position of `code` in the expanded list; the last one when listed twice
+    fn position(&self, code: u32) -> Option<u32> {
+        let mut offset = 0;
+        let mut found = None;
+        for &(first, last) in &self.ranges {
+            if (first..=last).contains(&code) {
+                found = Some(offset + code - first);
+            }
+            offset += last - first + 1;
+        }
+        found
+    }
+
+    /// Character at `position` of the expanded list, or its last character
+    fn nth_or_last(&self, position: u32) -> Option<u32> {
+        let mut offset = 0;
+        for &(first, last) in &self.ranges {
+            if position < offset + last - first + 1 {
+                return Some(first + position - offset);
+            }
+            offset += last - first + 1;
+        }
+        self.ranges.last().map(|&(_, last)| last)
+    }
+}
+
+fn char_sets(args: &[Rc<RObject>]) -> Result<Vec<CharSet>, Error> {
+    if args.is_empty() {
+        return Err(Error::ArgumentError(
+            "wrong number of arguments (given 0, expected 1+)".to_string(),
+        ));
+    }
+    args.iter().map(|arg| CharSet::parse(arg, true)).collect()
+}
+
+/// Body of `tr` and, with `squeeze`, `tr_s`
+fn translate(
+    bytes: &[u8],
+    is_utf8: bool,
+    from: &RObject,
+    to: &RObject,
+    squeeze: bool,
+) -> Result<Vec<u8>, Error> {
+    let from = CharSet::parse(from, true)?;
+    let to = CharSet::parse(to, false)?;
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut last_translated = None;
+    for ch in char_slices(bytes, is_utf8) {
+        let code = char_code(ch, is_utf8);
+        if !from.contains(code) {
+            out.extend_from_slice(ch);
+            last_translated = None;
+            continue;
+        }
+        // An empty replacement list deletes the characters
+        let mapped = if from.negated {
+            to.nth_or_last(u32::MAX)
+        } else {
+            from.position(code).and_then(|pos| to.nth_or_last(pos))
+        };
+        let Some(mapped) = mapped else {
+            continue;
+        };
+        if squeeze && last_translated == Some(mapped) {
+            continue;
+        }
+        push_char_code(&mut out, mapped, is_utf8);
+        last_translated = Some(mapped);
+    }
+    Ok(out)
+}
+
+fn delete_chars(bytes: &[u8], is_utf8: bool, args: &[Rc<RObject>]) -> Result<Vec<u8>, Error> {
+    let sets = char_sets(args)?;
+    let mut out = Vec::with_capacity(bytes.len());
+    for ch in char_slices(bytes, is_utf8) {
+        let code = char_code(ch, is_utf8);
+        if !sets.iter().all(|set| set.contains(code)) {
+            out.extend_from_slice(ch);
+        }
+    }
+    Ok(out)
+}
+
+// squeeze(*sets): runs of the same character become one; with sets, only
+// runs of the characters in all of them
+fn squeeze_chars(bytes: &[u8], is_utf8: bool, args: &[Rc<RObject>]) -> Result<Vec<u8>, Error> {
+    let sets = if args.is_empty() {
+        Vec::new()
+    } else {
+        char_sets(args)?
+    };
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut prev: Option<&[u8]> = None;
+    for ch in char_slices(bytes, is_utf8) {
+        let code = char_code(ch, is_utf8);
+        if prev == Some(ch) && sets.iter().all(|set| set.contains(code)) {
+            continue;
+        }
+        out.extend_from_slice(ch);
+        prev = Some(ch);
+    }
+    Ok(out)
+}
+
+fn reverse_chars(bytes: &[u8], is_utf8: bool) -> Vec<u8> {
+    char_slices(bytes, is_utf8)
+        .into_iter()
+        .rev()
+        .flatten()
+        .copied()
+        .collect()
+}
+
+fn capitalize(bytes: &[u8], is_utf8: bool) -> Vec<u8> {
+    let head = char_slices(bytes, is_utf8).first().map_or(0, |ch| ch.len());
+    let mut out = convert_case(&bytes[..head], is_utf8, true);
+    out.extend(convert_case(&bytes[head..], is_utf8, false));
+    out
+}
+
+fn swap_case(bytes: &[u8], is_utf8: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    for ch in char_slices(bytes, is_utf8) {
+        let upper = convert_case(ch, is_utf8, true);
+        if upper != ch {
+            out.extend(upper);
+        } else {
+            out.extend(convert_case(ch, is_utf8, false));
+        }
+    }
+    out
+}
+
+/// `succ`: increments the rightmost alphanumeric, carrying to the
+/// alphanumerics on its left. Without any, increments the last character.
+fn successor(bytes: &[u8], is_utf8: bool) -> Vec<u8> {
+    let mut out = bytes.to_vec();
+    let alnums: Vec<usize> = (0..out.len())
+        .filter(|&i| out[i].is_ascii_alphanumeric())
+        .collect();
+    if alnums.is_empty() {
+        return increment_last_char(out, is_utf8);
+    }
+    for &i in alnums.iter().rev() {
+        let (next, carry) = match out[i] {
+            b'z' => (b'a', b'a'),
+            b'Z' => (b'A', b'A'),
+            b'9' => (b'0', b'1'),
+            c => {
+                out[i] = c + 1;
+                return out;
+            }
+        };
+        out[i] = next;
+        if i == alnums[0] {
+            out.insert(i, carry);
+        }
+    }
+    out
+}
+
+fn increment_last_char(mut bytes: Vec<u8>, is_utf8: bool) -> Vec<u8> {
+    if bytes.is_empty() {
+        return bytes;
+    }
+    if is_utf8 {
+        let width = char_slices(&bytes, is_utf8).last().map_or(1, |ch| ch.len());
+        let start = bytes.len() - width;
+        if let Ok(s) = std::str::from_utf8(&bytes[start..])
+            && let Some(ch) = s.chars().next()
+        {
+            let next = (ch as u32 + 1..=char::MAX as u32).find_map(char::from_u32);
+            if let Some(next) = next {
+                bytes.truncate(start);
+                bytes.extend_from_slice(next.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+            return bytes;
+        }
+    }
+    for i in (0..bytes.len()).rev() {
+        if bytes[i] == 0xFF {
+            bytes[i] = 0;
+        } else {
+            bytes[i] += 1;
+            return bytes;
+        }
+    }
+    bytes.insert(0, 1);
+    bytes
+}
+
+enum Justify {
+    Left,
+    Right,
+    Center,
+}
+
+fn justify(
+    bytes: &[u8],
+    is_utf8: bool,
+    args: &[Rc<RObject>],
+    justify: Justify,
+) -> Result<Vec<u8>, Error> {
+    let width: i64 = args[0].as_ref().try_into()?;
+    let (pad, pad_utf8) = match args.get(1) {
+        Some(pad) => string_parts(pad)?,
+        None => (b" ".to_vec(), true),
+    };
+    if pad.is_empty() {
+        return Err(Error::ArgumentError("zero width padding".to_string()));
+    }
+    let len = CharIndex::new(bytes, is_utf8).len() as i64;
+    if width <= len {
+        return Ok(bytes.to_vec());
+    }
+    let total = (width - len) as usize;
+    let (left, right) = match justify {
+        Justify::Left => (0, total),
+        Justify::Right => (total, 0),
+        Justify::Center => (total / 2, total - total / 2),
+    };
+    let pad_chars = char_slices(&pad, pad_utf8);
+    let padding = |count: usize| -> Vec<u8> {
+        pad_chars
+            .iter()
+            .cycle()
+            .take(count)
+            .flat_map(|ch| ch.iter().copied())
+            .collect()
+    };
+    let mut out = padding(left);
+    out.extend_from_slice(bytes);
+    out.extend(padding(right));
+    Ok(out)
+}
+
+/// Lines of `each_line` and `lines`. An empty separator splits paragraphs,
+/// keeping every newline that ends one.
+fn split_lines(bytes: &[u8], separator: Option<&[u8]>, chomp: bool) -> Vec<Vec<u8>> {
+    let Some(separator) = separator else {
+        return if bytes.is_empty() {
+            Vec::new()
+        } else {
+            vec![bytes.to_vec()]
+        };
+    };
+    let paragraph = separator.is_empty();
+    let separator: &[u8] = if paragraph { b"\n\n" } else { separator };
+    let mut lines = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let Some(found) = find_bytes(bytes, separator, pos) else {
+            lines.push(bytes[pos..].to_vec());
+            break;
+        };
+        let mut end = found + separator.len();
+        if paragraph {
+            while bytes.get(end) == Some(&b'\n') {
+                end += 1;
+            }
+        }
+        let mut line_end = if chomp { found } else { end };
+        if chomp && separator == b"\n" && line_end > pos && bytes[line_end - 1] == b'\r' {
+            line_end -= 1;
+        }
+        lines.push(bytes[pos..line_end].to_vec());
+        pos = end;
+    }
+    lines
+}
+
+fn line_args(vm: &VM, args: &[Rc<RObject>]) -> Result<(Option<Vec<u8>>, bool), Error> {
+    let separator = match args.first() {
+        Some(separator) if !matches!(separator.value, RValue::Proc(_)) => {
+            if separator.is_nil() {
+                None
+            } else {
+                Some(separator.as_ref().try_into()?)
+            }
+        }
+        _ => Some(b"\n".to_vec()),
+    };
+    let chomp = given_kwargs(vm)
+        .and_then(|kwargs| kwargs.get("chomp").cloned())
+        .is_some_and(|chomp| chomp.is_truthy());
+    Ok((separator, chomp))
+}
+
+fn rfind_bytes(haystack: &[u8], needle: &[u8], until: usize) -> Option<usize> {
+    if needle.len() > haystack.len() {
+        return None;
+    }
+    let last = until.min(haystack.len() - needle.len());
+    (0..=last)
+        .rev()
+        .find(|&pos| haystack[pos..].starts_with(needle))
+}
+
+/// Leading integer as read by `hex` and `oct`: optional whitespace and sign,
+/// a radix prefix, then digits with single underscores between them.
+fn leading_integer(bytes: &[u8], radix: u32) -> Rc<RObject> {
+    let s = String::from_utf8_lossy(bytes).to_ascii_lowercase();
+    let s = s.trim_start();
+    let (negative, body) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (radix, body) = match (radix, body.get(..2)) {
+        (_, Some("0x")) => (16, &body[2..]),
+        (8, Some("0b")) => (2, &body[2..]),
+        (8, Some("0o")) => (8, &body[2..]),
+        _ => (radix, body),
+    };
+    let mut digits = String::new();
+    let mut after_underscore = false;
+    for ch in body.chars() {
+        if ch.is_digit(radix) {
+            digits.push(ch);
+            after_underscore = false;
+        } else if ch == '_' && !digits.is_empty() && !after_underscore {
+            after_underscore = true;
+        } else {
+            break;
+        }
+    }
+    let n = RBigInt::parse(&digits, radix).unwrap_or_else(RBigInt::zero);
+    let n = if negative { n.neg() } else { n };
+    RObject::bigint(n).to_refcount_assigned()
+}
+
+/// A copy of self transformed by `f`
+fn transformed(
+    vm: &mut VM,
+    f: impl FnOnce(&[u8], bool) -> Result<Vec<u8>, Error>,
+) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    Ok(new_string(f(&bytes, is_utf8)?, is_utf8))
+}
+
+/// Bang form of `transformed`: updates self, or returns nil when `f`
+/// changes nothing
+fn transform_self(
+    vm: &mut VM,
+    f: impl FnOnce(&[u8], bool) -> Result<Vec<u8>, Error>,
+) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.check_frozen(vm)?;
+    let (bytes, is_utf8) = string_parts(&this)?;
+    let result = f(&bytes, is_utf8)?;
+    if result != bytes {
+        *this.string_borrow_mut()? = result;
+        Ok(this)
+    } else {
+        Ok(Rc::new(RObject::nil()))
+    }
+}
+
+fn mrb_string_reverse(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| Ok(reverse_chars(bytes, is_utf8)))
+}
+
+// reverse! returns self even when nothing changes
+fn mrb_string_reverse_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.check_frozen(vm)?;
+    let (bytes, is_utf8) = string_parts(&this)?;
+    *this.string_borrow_mut()? = reverse_chars(&bytes, is_utf8);
+    Ok(this)
+}
+
+fn mrb_string_tr(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| {
+        translate(bytes, is_utf8, &args[0], &args[1], false)
+    })
+}
+
+fn mrb_string_tr_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transform_self(vm, |bytes, is_utf8| {
+        translate(bytes, is_utf8, &args[0], &args[1], false)
+    })
+}
+
+fn mrb_string_tr_s(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| {
+        translate(bytes, is_utf8, &args[0], &args[1], true)
+    })
+}
+
+fn mrb_string_tr_s_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transform_self(vm, |bytes, is_utf8| {
+        translate(bytes, is_utf8, &args[0], &args[1], true)
+    })
+}
+
+fn mrb_string_delete(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| delete_chars(bytes, is_utf8, args))
+}
+
+fn mrb_string_delete_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transform_self(vm, |bytes, is_utf8| delete_chars(bytes, is_utf8, args))
+}
+
+fn mrb_string_squeeze(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| squeeze_chars(bytes, is_utf8, args))
+}
+
+fn mrb_string_squeeze_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transform_self(vm, |bytes, is_utf8| squeeze_chars(bytes, is_utf8, args))
+}
+
+// count(*sets): characters that are in all of the sets
+fn mrb_string_count(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let sets = char_sets(args)?;
+    let count = char_slices(&bytes, is_utf8)
+        .into_iter()
+        .filter(|ch| {
+            let code = char_code(ch, is_utf8);
+            sets.iter().all(|set| set.contains(code))
+        })
+        .count();
+    Ok(Rc::new(RObject::integer(count as i64)))
+}
+
+fn mrb_string_center(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| {
+        justify(bytes, is_utf8, args, Justify::Center)
+    })
+}
+
+fn mrb_string_ljust(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| {
+        justify(bytes, is_utf8, args, Justify::Left)
+    })
+}
+
+fn mrb_string_rjust(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| {
+        justify(bytes, is_utf8, args, Justify::Right)
+    })
+}
+
+fn mrb_string_succ(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| Ok(successor(bytes, is_utf8)))
+}
+
+fn mrb_string_succ_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.check_frozen(vm)?;
+    let (bytes, is_utf8) = string_parts(&this)?;
+    *this.string_borrow_mut()? = successor(&bytes, is_utf8);
+    Ok(this)
+}
+
+// upto(last, exclusive = false) { |s| ... }: iterates with succ. Strings of
+// digits count numerically, keeping the width of self
+fn mrb_string_upto(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let Some(block) = block_arg(args) else {
+        return Ok(mrb_to_enum(vm, this, "upto", args));
+    };
+    let (first, is_utf8) = string_parts(&this)?;
+    let (last, _) = string_parts(&args[0])?;
+    let exclusive = args.len() > 2 && args[1].is_truthy();
+    let yield_str = |vm: &mut VM, bytes: Vec<u8>| -> Result<(), Error> {
+        mrb_call_block(vm, block.clone(), None, &[new_string(bytes, is_utf8)], 0)?;
+        Ok(())
+    };
+
+    let is_digits = |s: &[u8]| !s.is_empty() && s.iter().all(u8::is_ascii_digit);
+    if is_digits(&first) && is_digits(&last) {
+        let parse = |s: &[u8]| String::from_utf8_lossy(s).parse::<i64>();
+        if let (Ok(from), Ok(to)) = (parse(&first), parse(&last)) {
+            let to = if exclusive { to - 1 } else { to };
+            for n in from..=to {
+                yield_str(
+                    vm,
+                    format!("{:0width$}", n, width = first.len()).into_bytes(),
+                )?;
+            }
+            return Ok(this);
+        }
+    }
+    if let ([from], [to]) = (first.as_slice(), last.as_slice())
+        && from.is_ascii()
+        && to.is_ascii()
+    {
+        let to = if exclusive { to.saturating_sub(1) } else { *to };
+        for ch in *from..=to {
+            yield_str(vm, vec![ch])?;
+        }
+        return Ok(this);
+    }
+
+    if first > last || (exclusive && first == last) {
+        return Ok(this);
+    }
+    let after_last = successor(&last, is_utf8);
+    let mut current = first;
+    while current != after_last {
+        let next = (exclusive || current != last).then(|| successor(&current, is_utf8));
+        yield_str(vm, current)?;
+        let Some(next) = next else {
+            break;
+        };
+        current = next;
+        if (exclusive && current == last) || current.len() > last.len() || current.is_empty() {
+            break;
+        }
+    }
+    Ok(this)
+}
+
+// each_line(separator = "\n", chomp: false) { |line| ... }
+fn mrb_string_each_line(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let Some(block) = block_arg(args) else {
+        return Ok(mrb_to_enum(vm, this, "each_line", args));
+    };
+    let (separator, chomp) = line_args(vm, args)?;
+    let (bytes, is_utf8) = string_parts(&this)?;
+    for line in split_lines(&bytes, separator.as_deref(), chomp) {
+        mrb_call_block(vm, block.clone(), None, &[new_string(line, is_utf8)], 0)?;
+    }
+    Ok(this)
+}
+
+fn mrb_string_lines(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (separator, chomp) = line_args(vm, args)?;
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let lines = split_lines(&bytes, separator.as_deref(), chomp)
+        .into_iter()
+        .map(|line| new_string(line, is_utf8))
+        .collect();
+    Ok(Rc::new(RObject::array(lines)))
+}
+
+fn mrb_string_capitalize(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| Ok(capitalize(bytes, is_utf8)))
+}
+
+fn mrb_string_capitalize_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transform_self(vm, |bytes, is_utf8| Ok(capitalize(bytes, is_utf8)))
+}
+
+fn mrb_string_swapcase(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, is_utf8| Ok(swap_case(bytes, is_utf8)))
+}
+
+fn mrb_string_swapcase_self(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transform_self(vm, |bytes, is_utf8| Ok(swap_case(bytes, is_utf8)))
+}
+
+// insert(index, other): a negative index counts from the end, -1 appends
+fn mrb_string_insert(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.check_frozen(vm)?;
+    let (mut bytes, is_utf8) = string_parts(&this)?;
+    let index: i64 = args[0].as_ref().try_into()?;
+    let other: Vec<u8> = args[1].as_ref().try_into()?;
+    let chars = CharIndex::new(&bytes, is_utf8);
+    let len = chars.len() as i64;
+    let pos = if index < 0 { index + len + 1 } else { index };
+    if pos < 0 || pos > len {
+        return Err(Error::TaggedError(
+            "IndexError",
+            format!("index {} out of string", index),
+        ));
+    }
+    let offset = chars.byte_offset(pos as usize);
+    bytes.splice(offset..offset, other);
+    *this.string_borrow_mut()? = bytes;
+    Ok(this)
+}
+
+fn mrb_string_prepend(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.check_frozen(vm)?;
+    let mut bytes = Vec::new();
+    for arg in args {
+        let other: Vec<u8> = arg.as_ref().try_into()?;
+        bytes.extend(other);
+    }
+    bytes.extend(string_parts(&this)?.0);
+    *this.string_borrow_mut()? = bytes;
+    Ok(this)
+}
+
+fn mrb_string_replace(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.check_frozen(vm)?;
+    let (bytes, is_utf8) = string_parts(&args[0])?;
+    *this.string_borrow_mut()? = bytes;
+    this.set_string_utf8(is_utf8)?;
+    Ok(this)
+}
+
+// casecmp ignores ASCII case only; nil for a non-String
+fn mrb_string_casecmp(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, _) = string_parts(&*vm.getself()?)?;
+    let Ok((other, _)) = string_parts(&args[0]) else {
+        return Ok(Rc::new(RObject::nil()));
+    };
+    let order = bytes.to_ascii_lowercase().cmp(&other.to_ascii_lowercase());
+    Ok(Rc::new(RObject::integer(order as i64)))
+}
+
+// casecmp? compares with Unicode case folding; nil for a non-String
+fn mrb_string_casecmp_p(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let Ok((other, other_utf8)) = string_parts(&args[0]) else {
+        return Ok(Rc::new(RObject::nil()));
+    };
+    let equal = convert_case(&bytes, is_utf8, false) == convert_case(&other, other_utf8, false);
+    Ok(Rc::new(RObject::boolean(equal)))
+}
+
+fn delete_prefix(bytes: &[u8], prefix: &RObject) -> Result<Vec<u8>, Error> {
+    let prefix: Vec<u8> = prefix.try_into()?;
+    Ok(bytes
+        .strip_prefix(prefix.as_slice())
+        .unwrap_or(bytes)
+        .to_vec())
+}
+
+fn delete_suffix(bytes: &[u8], suffix: &RObject) -> Result<Vec<u8>, Error> {
+    let suffix: Vec<u8> = suffix.try_into()?;
+    Ok(bytes
+        .strip_suffix(suffix.as_slice())
+        .unwrap_or(bytes)
+        .to_vec())
+}
+
+fn mrb_string_delete_prefix(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, _| delete_prefix(bytes, &args[0]))
+}
+
+fn mrb_string_delete_prefix_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transform_self(vm, |bytes, _| delete_prefix(bytes, &args[0]))
+}
+
+fn mrb_string_delete_suffix(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transformed(vm, |bytes, _| delete_suffix(bytes, &args[0]))
+}
+
+fn mrb_string_delete_suffix_self(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    transform_self(vm, |bytes, _| delete_suffix(bytes, &args[0]))
+}
+
+pub(crate) fn partition_at(
+    bytes: &[u8],
+    is_utf8: bool,
+    found: Option<(usize, usize)>,
+    last: bool,
+) -> Rc<RObject> {
+    let parts = match found {
+        Some((start, end)) => vec![&bytes[..start], &bytes[start..end], &bytes[end..]],
+        None if last => vec![&[][..], &[][..], bytes],
+        None => vec![bytes, &[][..], &[][..]],
+    };
+    let parts = parts
+        .into_iter()
+        .map(|part| new_string(part.to_vec(), is_utf8))
+        .collect();
+    Rc::new(RObject::array(parts))
+}
+
+pub(crate) fn mrb_string_partition(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let separator: Vec<u8> = args[0].as_ref().try_into()?;
+    let found = find_bytes(&bytes, &separator, 0).map(|pos| (pos, pos + separator.len()));
+    Ok(partition_at(&bytes, is_utf8, found, false))
+}
+
+pub(crate) fn mrb_string_rpartition(
+    vm: &mut VM,
+    args: &[Rc<RObject>],
+) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let separator: Vec<u8> = args[0].as_ref().try_into()?;
+    let found =
+        rfind_bytes(&bytes, &separator, bytes.len()).map(|pos| (pos, pos + separator.len()));
+    Ok(partition_at(&bytes, is_utf8, found, true))
+}
+
+// rindex(substring, start = size): the character position of the last
+// match that begins at or before start
+fn mrb_string_rindex(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, is_utf8) = string_parts(&*vm.getself()?)?;
+    let search: Vec<u8> = args[0].as_ref().try_into()?;
+    let index = CharIndex::new(&bytes, is_utf8);
+    let len = index.len() as i64;
+    let start: i64 = match args.get(1) {
+        Some(start) => start.as_ref().try_into()?,
+        None => len,
+    };
+    let start = if start < 0 {
+        start + len
+    } else {
+        start.min(len)
+    };
+    if start < 0 {
+        return Ok(Rc::new(RObject::nil()));
+    }
+
+    match rfind_bytes(&bytes, &search, index.byte_offset(start as usize)) {
+        Some(pos) => Ok(Rc::new(RObject::integer(index.char_index(pos) as i64))),
+        None => Ok(Rc::new(RObject::nil())),
+    }
+}
+
+fn mrb_string_hex(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, _) = string_parts(&*vm.getself()?)?;
+    Ok(leading_integer(&bytes, 16))
+}
+
+// oct also honors the 0x, 0b and 0o prefixes
+fn mrb_string_oct(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, _) = string_parts(&*vm.getself()?)?;
+    Ok(leading_integer(&bytes, 8))
+}
+
+// sum(bits = 16): the sum of the bytes modulo 2**bits
+fn mrb_string_sum(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let (bytes, _) = string_parts(&*vm.getself()?)?;
+    let bits: i64 = match args.first() {
+        Some(bits) => bits.as_ref().try_into()?,
+        None => 16,
+    };
+    let sum: u64 = bytes.iter().map(|&b| b as u64).sum();
+    let sum = if (1..64).contains(&bits) {
+        sum & ((1 << bits) - 1)
+    } else {
+        sum
+    };
+    Ok(Rc::new(RObject::integer(sum as i64)))
+}
+
 #[test]
 fn test_mrb_string_size() {
     use crate::yamrb::*;
@@ -0,0 +1,543 @@
+use std::mem;
+use std::rc::Rc;
+
+use crate::{
+    Error,
+    yamrb::{
+        helpers::{
+            mrb_call_block, mrb_call_inspect, mrb_define_class_cmethod, mrb_define_cmethod,
+            mrb_funcall,
+        },
+        prelude::{
+            enumerator::{block_arg, mrb_to_enum},
+            module::mrb_include_module,
+        },
+        value::*,
+        vm::{TargetContext, VM},
+    },
+};
+
+// Member names live on the generated class object under keys that are not
+// valid instance variable names, so Ruby code cannot see or clobber them.
+const MEMBERS_KEY: &str = "__members__";
+const KEYWORD_INIT_KEY: &str = "__keyword_init__";
+
+pub(crate) fn initialize_struct(vm: &mut VM) {
+    let struct_class = vm.define_standard_class("Struct");
+    let enumerable_module = vm.get_module_by_name("Enumerable");
+    mrb_include_module(&struct_class, enumerable_module).expect("failed to include Enumerable");
+
+    mrb_define_class_cmethod(vm, struct_class.clone(), "new", Box::new(mrb_struct_s_new));
+    mrb_define_class_cmethod(vm, struct_class.clone(), "[]", Box::new(mrb_struct_s_new));
+    mrb_define_class_cmethod(
+        vm,
+        struct_class.clone(),
+        "members",
+        Box::new(mrb_struct_s_members),
+    );
+
+    mrb_define_cmethod(
+        vm,
+        struct_class.clone(),
+        "initialize",
+        Box::new(mrb_struct_initialize),
+    );
+    mrb_define_cmethod(vm, struct_class.clone(), "==", Box::new(mrb_struct_eq));
+    mrb_define_cmethod(vm, struct_class.clone(), "[]", Box::new(mrb_struct_aref));
+    mrb_define_cmethod(vm, struct_class.clone(), "[]=", Box::new(mrb_struct_aset));
+    mrb_define_cmethod(vm, struct_class.clone(), "to_a", Box::new(mrb_struct_to_a));
+    mrb_define_cmethod(
+        vm,
+        struct_class.clone(),
+        "values",
+        Box::new(mrb_struct_to_a),
+    );
+    mrb_define_cmethod(
+        vm,
+        struct_class.clone(),
+        "deconstruct",
+        Box::new(mrb_struct_to_a),
+    );
+    mrb_define_cmethod(vm, struct_class.clone(), "to_h", Box::new(mrb_struct_to_h));
+    mrb_define_cmethod(
+        vm,
+        struct_class.clone(),
+        "deconstruct_keys",
+        Box::new(mrb_struct_deconstruct_keys),
+    );
+    mrb_define_cmethod(
+        vm,
+        struct_class.clone(),
+        "members",
+        Box::new(mrb_struct_members),
+    );
+    mrb_define_cmethod(vm, struct_class.clone(), "each", Box::new(mrb_struct_each));
+    mrb_define_cmethod(
+        vm,
+        struct_class.clone(),
+        "each_pair",
+        Box::new(mrb_struct_each_pair),
+    );
+    mrb_define_cmethod(vm, struct_class.clone(), "size", Box::new(mrb_struct_size));
+    mrb_define_cmethod(
+        vm,
+        struct_class.clone(),
+        "length",
+        Box::new(mrb_struct_size),
+    );
+    mrb_define_cmethod(
+        vm,
+        struct_class.clone(),
+        "inspect",
+        Box::new(mrb_struct_inspect),
+    );
+    mrb_define_cmethod(vm, struct_class, "to_s", Box::new(mrb_struct_inspect));
+}
+
+pub(crate) fn class_of_self(vm: &mut VM) -> Result<Rc<RClass>, Error> {
+    let this = vm.getself()?;
+    match &this.value {
+        RValue::Class(c) => Ok(c.clone()),
+        _ => Err(Error::RuntimeError("must be called on a class".to_string())),
+    }
+}
+
+/// Reads symbol (or string) arguments as member names, rejecting duplicates.
+pub(crate) fn member_names(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Vec<String>, Error> {
+    let mut names: Vec<String> = Vec::with_capacity(args.len());
+    for arg in args {
+        let name = match &arg.value {
+            RValue::Symbol(sym) => sym.name.clone(),
+            RValue::String(_, _) => arg.as_ref().try_into()?,
+            _ => {
+                let inspect = mrb_call_inspect(vm, arg.clone())?;
+                let inspect: String = inspect.as_ref().try_into()?;
+                return Err(Error::TaggedError(
+                    "TypeError",
+                    format!("{} is not a symbol nor a string", inspect),
+                ));
+            }
+        };
+        if names.contains(&name) {
+            return Err(Error::ArgumentError(format!("duplicate member: {}", name)));
+        }
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// Records the members on a generated class and defines their readers
+/// (and writers, when `writable`).
+pub(crate) fn define_members(vm: &mut VM, class: &Rc<RClass>, members: &[String], writable: bool) {
+    RObject::class(class.clone(), vm).set_ivar(MEMBERS_KEY, symbols(members));
+
+    for name in members {
+        let member: &'static str = name.clone().leak();
+        let reader = move |vm: &mut VM, _args: &[Rc<RObject>]| {
+            let this = vm.getself()?;
+            Ok(this.get_ivar(member))
+        };
+        mrb_define_cmethod(vm, class.clone(), member, Box::new(reader));
+
+        if writable {
+            let writer = move |vm: &mut VM, args: &[Rc<RObject>]| {
+                let this = vm.getself()?;
+                this.check_frozen(vm)?;
+                this.set_ivar(member, args[0].clone());
+                Ok(args[0].clone())
+            };
+            mrb_define_cmethod(vm, class.clone(), &format!("{}=", member), Box::new(writer));
+        }
+    }
+}
+
+/// Member names of a generated class, looked up through its superclasses so
+/// that `class Point < Struct.new(:x, :y)` sees the members too.
+pub(crate) fn class_members(vm: &mut VM, class: &Rc<RClass>) -> Vec<String> {
+    let mut current = Some(class.clone());
+    while let Some(class) = current {
+        let members = RObject::class(class.clone(), vm).get_ivar(MEMBERS_KEY);
+        if let RValue::Array(members) = &members.value {
+            return members
+                .borrow()
+                .iter()
+                .filter_map(|sym| match &sym.value {
+                    RValue::Symbol(sym) => Some(sym.name.clone()),
+                    _ => None,
+                })
+                .collect();
+        }
+        current = class.super_class.clone();
+    }
+    Vec::new()
+}
+
+fn is_keyword_init(vm: &mut VM, class: &Rc<RClass>) -> bool {
+    let mut current = Some(class.clone());
+    while let Some(class) = current {
+        let object = RObject::class(class.clone(), vm);
+        if object.ivar.borrow().contains_key(MEMBERS_KEY) {
+            return object.get_ivar(KEYWORD_INIT_KEY).is_truthy();
+        }
+        current = class.super_class.clone();
+    }
+    false
+}
+
+pub(crate) fn members_of(vm: &mut VM, this: &Rc<RObject>) -> Vec<String> {
+    let class = this.get_class(vm);
+    class_members(vm, &class)
+}
+
+pub(crate) fn values_of(vm: &mut VM, this: &Rc<RObject>) -> Vec<Rc<RObject>> {
+    members_of(vm, this)
+        .iter()
+        .map(|name| this.get_ivar(name))
+        .collect()
+}
+
+pub(crate) fn symbols(members: &[String]) -> Rc<RObject> {
+    let symbols = members
+        .iter()
+        .map(|name| RObject::symbol(RSym::new(name.clone())).to_refcount_assigned())
+        .collect();
+    RObject::array(symbols).to_refcount_assigned()
+}
+
+/// Keyword arguments of the current call, with an empty set treated as none.
+pub(crate) fn given_kwargs(vm: &VM) -> Option<RHashMap<String, Rc<RObject>>> {
+    vm.get_kwargs().filter(|kwargs| !kwargs.is_empty())
+}
+
+pub(crate) fn unknown_keywords<'a>(
+    members: &[String],
+    kwargs: &'a RHashMap<String, Rc<RObject>>,
+) -> Vec<&'a str> {
+    let mut unknown: Vec<&str> = kwargs
+        .keys()
+        .filter(|key| !members.contains(key))
+        .map(|key| key.as_str())
+        .collect();
+    unknown.sort();
+    unknown
+}
+
+/// `#<struct Point x=1, y=2>`; anonymous classes leave out the name.
+pub(crate) fn inspect_members(
+    vm: &mut VM,
+    this: &Rc<RObject>,
+    kind: &str,
+) -> Result<Rc<RObject>, Error> {
+    let class = this.get_class(vm);
+    let mut out = format!("#<{}", kind);
+    if !class.is_anonymous() {
+        out.push(' ');
+        out.push_str(&class.full_name());
+    }
+    for (i, name) in members_of(vm, this).iter().enumerate() {
+        let value = mrb_call_inspect(vm, this.get_ivar(name))?;
+        let value: String = value.as_ref().try_into()?;
+        out.push_str(if i == 0 { " " } else { ", " });
+        out.push_str(&format!("{}={}", name, value));
+    }
+    out.push('>');
+    Ok(Rc::new(RObject::string(out)))
+}
+
+pub(crate) fn members_eq(
+    vm: &mut VM,
+    this: &Rc<RObject>,
+    other: &Rc<RObject>,
+) -> Result<bool, Error> {
+    if Rc::ptr_eq(this, other) {
+        return Ok(true);
+    }
+    if !matches!(other.value, RValue::Instance(_))
+        || !Rc::ptr_eq(&this.get_class(vm), &other.get_class(vm))
+    {
+        return Ok(false);
+    }
+    for name in members_of(vm, this) {
+        let eq = mrb_funcall(
+            vm,
+            Some(this.get_ivar(&name)),
+            "==",
+            &[other.get_ivar(&name)],
+        )?;
+        if !eq.is_truthy() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+pub(crate) fn members_to_h(vm: &mut VM, this: &Rc<RObject>) -> Result<Rc<RObject>, Error> {
+    let mut hash = RHash::default();
+    for name in members_of(vm, this) {
+        let key = RObject::symbol(RSym::new(name.clone())).to_refcount_assigned();
+        hash.insert(key.as_hash_key()?, (key, this.get_ivar(&name)));
+    }
+    Ok(Rc::new(RObject::hash(hash)))
+}
+
+// deconstruct_keys(nil) returns every member; otherwise only the requested
+// ones, stopping at the first key that is not a member.
+pub(crate) fn members_deconstruct_keys(
+    vm: &mut VM,
+    this: &Rc<RObject>,
+    keys: &Rc<RObject>,
+) -> Result<Rc<RObject>, Error> {
+    let keys = match &keys.value {
+        RValue::Nil => return members_to_h(vm, this),
+        RValue::Array(keys) => keys.borrow().clone(),
+        _ => {
+            return Err(Error::TaggedError(
+                "TypeError",
+                "wrong argument type (expected Array or nil)".to_string(),
+            ));
+        }
+    };
+    let members = members_of(vm, this);
+    let mut hash = RHash::default();
+    for key in keys {
+        let RValue::Symbol(sym) = &key.value else {
+            break;
+        };
+        if !members.contains(&sym.name) {
+            break;
+        }
+        hash.insert(key.as_hash_key()?, (key.clone(), this.get_ivar(&sym.name)));
+    }
+    Ok(Rc::new(RObject::hash(hash)))
+}
+
+// Struct.new(*members, keyword_init: nil) defines a new struct class; on a
+// generated class it builds an instance instead.
+fn mrb_struct_s_new(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let class = class_of_self(vm)?;
+    let struct_class = vm.get_class_by_name("Struct");
+    if !Rc::ptr_eq(&class, &struct_class) {
+        let obj = RObject::instance(class).to_refcount_assigned();
+        mrb_funcall(vm, Some(obj.clone()), "initialize", args)?;
+        return Ok(obj);
+    }
+
+    let keyword_init = vm
+        .get_kwargs()
+        .and_then(|kwargs| kwargs.get("keyword_init").cloned());
+    let block = block_arg(args);
+    let args = &args[..args.len() - block.is_some() as usize];
+    let (name, members) = match args.split_first() {
+        Some((first, rest)) if matches!(first.value, RValue::String(_, _)) => {
+            let name: String = first.as_ref().try_into()?;
+            (Some(name), rest)
+        }
+        _ => (None, args),
+    };
+    let members = member_names(vm, members)?;
+    let new_class = match name {
+        Some(name) => vm.define_class(
+            &name,
+            Some(struct_class.clone()),
+            Some(struct_class.module.clone()),
+        ),
+        None => vm.define_anonymous_class(struct_class),
+    };
+    define_members(vm, &new_class, &members, true);
+
+    let object = RObject::class(new_class.clone(), vm);
+    object.initialize_or_get_singleton_class_for_class(vm);
+    if let Some(keyword_init) = keyword_init {
+        object.set_ivar(KEYWORD_INIT_KEY, keyword_init);
+    }
+    if let Some(block) = block {
+        eval_class_block(vm, &object, new_class, block)?;
+    }
+    Ok(object)
+}
+
+/// Runs the block given to `Struct.new` or `Data.define` in the body of the
+/// new class, as `class_eval` would.
+pub(crate) fn eval_class_block(
+    vm: &mut VM,
+    object: &Rc<RObject>,
+    class: Rc<RClass>,
+    block: Rc<RObject>,
+) -> Result<(), Error> {
+    let prev_target = mem::replace(&mut vm.target_class, TargetContext::Class(class));
+    let res = mrb_call_block(
+        vm,
+        block,
+        Some(object.clone()),
+        std::slice::from_ref(object),
+        0,
+    );
+    vm.target_class = prev_target;
+    res.map(|_| ())
+}
+
+fn mrb_struct_s_members(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let class = class_of_self(vm)?;
+    let members = class_members(vm, &class);
+    Ok(symbols(&members))
+}
+
+fn mrb_struct_initialize(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let class = this.get_class(vm);
+    let members = class_members(vm, &class);
+    let kwargs = given_kwargs(vm);
+
+    if is_keyword_init(vm, &class) || (args.is_empty() && kwargs.is_some()) {
+        if !args.is_empty() {
+            return Err(Error::ArgumentError(format!(
+                "wrong number of arguments (given {}, expected 0)",
+                args.len()
+            )));
+        }
+        let kwargs = kwargs.unwrap_or_default();
+        let unknown = unknown_keywords(&members, &kwargs);
+        if !unknown.is_empty() {
+            return Err(Error::ArgumentError(format!(
+                "unknown keywords: {}",
+                unknown.join(", ")
+            )));
+        }
+        for name in members.iter() {
+            let value = kwargs
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| Rc::new(RObject::nil()));
+            this.set_ivar(name, value);
+        }
+    } else {
+        if args.len() > members.len() {
+            return Err(Error::ArgumentError("struct size differs".to_string()));
+        }
+        for (i, name) in members.iter().enumerate() {
+            let value = args
+                .get(i)
+                .cloned()
+                .unwrap_or_else(|| Rc::new(RObject::nil()));
+            this.set_ivar(name, value);
+        }
+    }
+    Ok(Rc::new(RObject::nil()))
+}
+
+/// Resolves a `[]` / `[]=` argument (member name or index) to a member name.
+fn member_for_key(members: &[String], key: &RObject) -> Result<String, Error> {
+    match &key.value {
+        RValue::Symbol(_) | RValue::String(_, _) => {
+            let name = match &key.value {
+                RValue::Symbol(sym) => sym.name.clone(),
+                _ => key.try_into()?,
+            };
+            if members.contains(&name) {
+                Ok(name)
+            } else {
+                Err(Error::NameError(format!("no member '{}' in struct", name)))
+            }
+        }
+        RValue::Integer(i) => {
+            let size = members.len() as i64;
+            let index = if *i < 0 { i + size } else { *i };
+            if index < 0 {
+                Err(Error::TaggedError(
+                    "IndexError",
+                    format!("offset {} too small for struct(size:{})", i, size),
+                ))
+            } else if index >= size {
+                Err(Error::TaggedError(
+                    "IndexError",
+                    format!("offset {} too large for struct(size:{})", i, size),
+                ))
+            } else {
+                Ok(members[index as usize].clone())
+            }
+        }
+        _ => Err(Error::TaggedError(
+            "TypeError",
+            "no implicit conversion into Integer".to_string(),
+        )),
+    }
+}
+
+fn mrb_struct_eq(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let eq = members_eq(vm, &this, &args[0])?;
+    Ok(Rc::new(RObject::boolean(eq)))
+}
+
+fn mrb_struct_aref(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let members = members_of(vm, &this);
+    let name = member_for_key(&members, &args[0])?;
+    Ok(this.get_ivar(&name))
+}
+
+fn mrb_struct_aset(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    this.check_frozen(vm)?;
+    let members = members_of(vm, &this);
+    let name = member_for_key(&members, &args[0])?;
+    this.set_ivar(&name, args[1].clone());
+    Ok(args[1].clone())
+}
+
+fn mrb_struct_to_a(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let values = values_of(vm, &this);
+    Ok(RObject::array(values).to_refcount_assigned())
+}
+
+fn mrb_struct_to_h(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    members_to_h(vm, &this)
+}
+
+fn mrb_struct_deconstruct_keys(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    members_deconstruct_keys(vm, &this, &args[0])
+}
+
+fn mrb_struct_members(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let members = members_of(vm, &this);
+    Ok(symbols(&members))
+}
+
+fn mrb_struct_each(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let Some(block) = block_arg(args) else {
+        return Ok(mrb_to_enum(vm, this, "each", &[]));
+    };
+    for value in values_of(vm, &this) {
+        mrb_call_block(vm, block.clone(), None, &[value], 0)?;
+    }
+    Ok(this)
+}
+
+fn mrb_struct_each_pair(vm: &mut VM, args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let Some(block) = block_arg(args) else {
+        return Ok(mrb_to_enum(vm, this, "each_pair", &[]));
+    };
+    for name in members_of(vm, &this) {
+        let key = RObject::symbol(RSym::new(name.clone())).to_refcount_assigned();
+        let pair = RObject::array(vec![key, this.get_ivar(&name)]).to_refcount_assigned();
+        mrb_call_block(vm, block.clone(), None, &[pair], 0)?;
+    }
+    Ok(this)
+}
+
+fn mrb_struct_size(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    let size = members_of(vm, &this).len();
+    Ok(Rc::new(RObject::integer(size as i64)))
+}
+
+fn mrb_struct_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let this = vm.getself()?;
+    inspect_members(vm, &this, "struct")
+}
@@ -1,10 +1,10 @@
 use std::rc::Rc;
 
 use crate::Error;
-use crate::yamrb::helpers::{mrb_define_cmethod, mrb_funcall};
+use crate::yamrb::helpers::{mrb_define_class_cmethod, mrb_define_cmethod, mrb_funcall};
 
 use crate::yamrb::{
-    value::{RFn, RObject, RProc},
+    value::{RFn, RObject, RProc, Visibility},
     vm::VM,
 };
 
@@ -23,6 +23,18 @@ pub(crate) fn initialize_symbol(vm: &mut VM) {
         "to_proc",
         Box::new(mrb_symbol_to_proc),
     );
+    mrb_define_class_cmethod(
+        vm,
+        symbol_class.clone(),
+        "all_symbols",
+        Box::new(mrb_symbol_all_symbols),
+    );
+}
+
+fn mrb_symbol_all_symbols(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+    let names = vm.symbol_table.names().to_vec();
+    let symbols = names.iter().map(|name| vm.intern(name)).collect();
+    Ok(RObject::array(symbols).to_refcount_assigned())
 }
 
 fn mrb_symbol_inspect(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
@@ -37,24 +49,35 @@ fn mrb_symbol_to_s(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Er
 
 fn mrb_symbol_to_proc(vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
     let method_name: String = vm.getself()?.as_ref().try_into()?;
-    let rfn: RFn = Box::new(move |vm: &mut VM, args: &[Rc<RObject>]| {
-        let recv = args
-            .first()
-            .cloned()
-            .ok_or_else(|| Error::ArgumentError("no receiver given".to_string()))?;
-        let method_args = if args.len() > 1 { &args[1..] } else { &[] };
-        mrb_funcall(vm, Some(recv), &method_name, method_args)
-    });
-    vm.push_fnblock(Rc::new(rfn))?;
+    // The proc may be kept and called at any time later, so it refers to
+    // its function by index instead of through the fnblock stack
+    let func = match vm.symbol_procs.get(&method_name) {
+        Some(index) => *index,
+        None => {
+            let name = method_name.clone();
+            let rfn: RFn = Box::new(move |vm: &mut VM, args: &[Rc<RObject>]| {
+                let recv = args
+                    .first()
+                    .cloned()
+                    .ok_or_else(|| Error::ArgumentError("no receiver given".to_string()))?;
+                let method_args = if args.len() > 1 { &args[1..] } else { &[] };
+                mrb_funcall(vm, Some(recv), &name, method_args)
+            });
+            let index = vm.register_fn(rfn);
+            vm.symbol_procs.insert(method_name.clone(), index);
+            index
+        }
+    };
     let block = RProc {
         is_rb_func: false,
-        is_fnblock: true,
+        is_fnblock: false,
         sym_id: None,
         next: None,
         irep: None,
-        func: None,
+        func: Some(func),
         environ: None,
         block_self: vm.getself().ok(),
+        visibility: Visibility::Public,
     };
     Ok(RObject::proc(block).to_refcount_assigned())
 }
@@ -6,6 +6,9 @@ use std::{cell::RefCell, fmt::Debug, rc::Rc};
 use crate::Error;
 use crate::yamrb::helpers::mrb_call_inspect;
 
+use super::bigint::RBigInt;
+use super::method_cache::invalidate_method_caches;
+pub use super::ordered_hash::RHash;
 use super::shared_memory::SharedMemory;
 use super::vm::{ENV, IREP, VM};
 
@@ -34,14 +37,10 @@ pub enum RType {
 pub type RHashMap<K, V> = fnv::FnvHashMap<K, V>;
 #[cfg(feature = "mruby-hash-fnv")]
 pub type RHashSet<K> = fnv::FnvHashSet<K>;
-#[cfg(feature = "mruby-hash-fnv")]
-pub type RHash = fnv::FnvHashMap<ValueHasher, (Rc<RObject>, Rc<RObject>)>;
 #[cfg(not(feature = "mruby-hash-fnv"))]
 pub type RHashMap<K, V> = std::collections::HashMap<K, V>;
 #[cfg(not(feature = "mruby-hash-fnv"))]
 pub type RHashSet<K> = std::collections::HashSet<K>;
-#[cfg(not(feature = "mruby-hash-fnv"))]
-pub type RHash = std::collections::HashMap<ValueHasher, (Rc<RObject>, Rc<RObject>)>;
 
 /// Actual storage for Ruby values, including boxed objects and immediates.
 #[derive(Debug, Clone)]
@@ -49,6 +48,8 @@ pub enum RValue {
     Bool(bool),
     Symbol(RSym),
     Integer(i64),
+    /// Integers outside the i64 range; always normalized back to `Integer` when they fit
+    BigInt(Rc<RBigInt>),
     Float(f64),
     Class(Rc<RClass>),
     Module(Rc<RModule>),
@@ -57,7 +58,8 @@ pub enum RValue {
     Array(RefCell<Vec<Rc<RObject>>>),
     Hash(RefCell<RHash>),
     /// (bytes, is_utf8)
-    /// FIXME: currently, we compare strings by bytes only, so is_utf8 is unused.
+    /// UTF-8 strings index by characters, binary ones by bytes.
+    /// Strings are compared by bytes regardless of the flag.
     String(RefCell<Vec<u8>>, Cell<bool>),
     Range(Rc<RObject>, Rc<RObject>, bool),
     SharedMemory(Rc<RefCell<SharedMemory>>),
@@ -72,6 +74,7 @@ pub enum RValue {
 pub enum ValueHasher {
     Bool(bool),
     Integer(i64),
+    BigInt(RBigInt),
     Float(Vec<u8>),
     Symbol(String),
     String(Vec<u8>),
@@ -83,6 +86,7 @@ pub enum ValueHasher {
 pub enum ValueEquality {
     Bool(bool),
     Integer(i64),
+    BigInt(RBigInt),
     Float(f64),
     Symbol(String),
     String(Vec<u8>),
@@ -121,7 +125,59 @@ pub struct RObject {
 
     pub singleton_class: RefCell<Option<Rc<RClass>>>,
 
-    pub ivar: RefCell<RHashMap<String, Rc<RObject>>>,
+    pub ivar: RefCell<IvarTable>,
+
+    pub frozen: Cell<bool>,
+}
+
+/// Instance variables of an object, kept in assignment order as
+/// `instance_variables` reports them. Objects hold few ivars, so a
+/// linear scan is cheaper than hashing.
+#[derive(Debug, Clone, Default)]
+pub struct IvarTable {
+    entries: Vec<(String, Rc<RObject>)>,
+}
+
+impl IvarTable {
+    pub fn get(&self, key: &str) -> Option<&Rc<RObject>> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Replaces the value in place when `key` is already set.
+    pub fn insert(&mut self, key: String, value: Rc<RObject>) -> Option<Rc<RObject>> {
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, slot)) => Some(std::mem::replace(slot, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Rc<RObject>> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(pos).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Rc<RObject>)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
 }
 
 const UNSET_OBJECT_ID: u64 = u64::MAX;
@@ -133,7 +189,8 @@ impl RObject {
             value: RValue::Nil,
             object_id: 4.into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -143,7 +200,8 @@ impl RObject {
             value: RValue::Bool(b),
             object_id: (if b { 20 } else { 0 }).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -151,9 +209,11 @@ impl RObject {
         RObject {
             tt: RType::Symbol,
             value: RValue::Symbol(sym),
-            object_id: 2.into(), // TODO: calc the same id for the same symbol
+            // The stable id is assigned from the symbol table by VM::intern
+            object_id: 2.into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -169,7 +229,24 @@ impl RObject {
             value: RValue::Integer(n),
             object_id: object_id.into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
+        }
+    }
+
+    /// Integer from an arbitrary-precision value, demoted to a plain
+    /// `Integer` whenever it fits in i64.
+    pub fn bigint(n: RBigInt) -> Self {
+        match n.to_i64() {
+            Some(i) => Self::integer(i),
+            None => RObject {
+                tt: RType::Integer,
+                value: RValue::BigInt(Rc::new(n)),
+                object_id: (UNSET_OBJECT_ID).into(),
+                singleton_class: RefCell::new(None),
+                ivar: RefCell::new(IvarTable::default()),
+                frozen: Cell::new(false),
+            },
         }
     }
 
@@ -179,7 +256,8 @@ impl RObject {
             value: RValue::Float(f),
             object_id: f.to_bits().into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -189,17 +267,24 @@ impl RObject {
             value: RValue::String(RefCell::new(s.into_bytes()), Cell::new(true)),
             object_id: (UNSET_OBJECT_ID).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
     pub fn string_from_vec(v: Vec<u8>) -> Self {
+        Self::string_from_bytes(v, false)
+    }
+
+    /// Builds a string from raw bytes, UTF-8 or binary by `is_utf8`.
+    pub fn string_from_bytes(v: Vec<u8>, is_utf8: bool) -> Self {
         RObject {
             tt: RType::String,
-            value: RValue::String(RefCell::new(v), Cell::new(false)),
+            value: RValue::String(RefCell::new(v), Cell::new(is_utf8)),
             object_id: (UNSET_OBJECT_ID).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -209,7 +294,8 @@ impl RObject {
             value: RValue::Array(RefCell::new(v)),
             object_id: (UNSET_OBJECT_ID).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -219,7 +305,8 @@ impl RObject {
             value: RValue::Hash(RefCell::new(h)),
             object_id: (UNSET_OBJECT_ID).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -229,7 +316,8 @@ impl RObject {
             value: RValue::Range(start, end, exclusive),
             object_id: (UNSET_OBJECT_ID).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -250,7 +338,8 @@ impl RObject {
             value: RValue::Class(c),
             object_id: (UNSET_OBJECT_ID).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
         .to_refcount_assigned()
     }
@@ -266,7 +355,8 @@ impl RObject {
             value: RValue::Module(m),
             object_id: (UNSET_OBJECT_ID).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -292,7 +382,8 @@ impl RObject {
             }),
             object_id: (UNSET_OBJECT_ID).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -302,7 +393,8 @@ impl RObject {
             value: RValue::Proc(p),
             object_id: (UNSET_OBJECT_ID).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -312,7 +404,8 @@ impl RObject {
             value: RValue::Exception(e),
             object_id: (UNSET_OBJECT_ID).into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
     }
 
@@ -348,6 +441,40 @@ impl RObject {
         self.object_id.get() == 0
     }
 
+    /// Symbols, numbers, nil, true and false are always frozen.
+    pub fn is_frozen(&self) -> bool {
+        match &self.value {
+            RValue::Nil
+            | RValue::Bool(_)
+            | RValue::Symbol(_)
+            | RValue::Integer(_)
+            | RValue::BigInt(_)
+            | RValue::Float(_) => true,
+            _ => self.frozen.get(),
+        }
+    }
+
+    pub fn freeze(&self) {
+        self.frozen.set(true);
+    }
+
+    /// Raises `FrozenError` when the object is frozen. Every builtin that
+    /// modifies its receiver calls this first.
+    pub(crate) fn check_frozen(self: &Rc<Self>, vm: &mut VM) -> Result<(), Error> {
+        if !self.is_frozen() {
+            return Ok(());
+        }
+        let inspect: String = mrb_call_inspect(vm, self.clone())?.as_ref().try_into()?;
+        Err(Error::TaggedError(
+            "FrozenError",
+            format!(
+                "can't modify frozen {}: {}",
+                self.get_class(vm).full_name(),
+                inspect
+            ),
+        ))
+    }
+
     pub fn set_ivar(&self, key: &str, value: Rc<RObject>) {
         self.ivar.borrow_mut().insert(key.to_string(), value);
     }
@@ -361,11 +488,43 @@ impl RObject {
             .unwrap()
     }
 
+    /// Message of an exception object; `Exception#initialize` stores it in
+    /// the hidden `mesg` ivar.
+    pub fn exception_message(&self) -> String {
+        let mesg = self.get_ivar("mesg");
+        if let RValue::String(s, _) = &mesg.value {
+            return String::from_utf8_lossy(&s.borrow()).to_string();
+        }
+        match &self.value {
+            RValue::Exception(e) => e.message.clone(),
+            _ => String::new(),
+        }
+    }
+
+    /// Backtrace of an exception object, or `None` before it is raised.
+    pub fn exception_backtrace(&self) -> Option<Vec<String>> {
+        let backtrace = self.get_ivar("backtrace");
+        if let RValue::Array(entries) = &backtrace.value {
+            return Some(
+                entries
+                    .borrow()
+                    .iter()
+                    .filter_map(|entry| entry.as_ref().try_into().ok())
+                    .collect(),
+            );
+        }
+        match &self.value {
+            RValue::Exception(e) if !e.backtrace.is_empty() => Some(e.backtrace.clone()),
+            _ => None,
+        }
+    }
+
     // TODO: implment Object#hash
     pub fn as_hash_key(&self) -> Result<ValueHasher, Error> {
         match &self.value {
             RValue::Bool(b) => Ok(ValueHasher::Bool(*b)),
             RValue::Integer(i) => Ok(ValueHasher::Integer(*i)),
+            RValue::BigInt(b) => Ok(ValueHasher::BigInt(b.as_ref().clone())),
             RValue::Float(f) => Ok(ValueHasher::Float(f.to_be_bytes().to_vec())),
             RValue::Symbol(s) => Ok(ValueHasher::Symbol(s.name.clone())),
             RValue::String(s, _) => Ok(ValueHasher::String(s.borrow().clone())),
@@ -378,6 +537,7 @@ impl RObject {
         match &self.value {
             RValue::Bool(b) => ValueEquality::Bool(*b),
             RValue::Integer(i) => ValueEquality::Integer(*i),
+            RValue::BigInt(b) => ValueEquality::BigInt(b.as_ref().clone()),
             RValue::Float(f) => ValueEquality::Float(*f),
             RValue::Symbol(s) => ValueEquality::Symbol(s.name.clone()),
             RValue::String(s, _) => ValueEquality::String(s.borrow().clone()),
@@ -417,7 +577,7 @@ impl RObject {
                 }
             }
             RValue::Symbol(_) => vm.get_class_by_name("Symbol"),
-            RValue::Integer(_) => vm.get_class_by_name("Integer"),
+            RValue::Integer(_) | RValue::BigInt(_) => vm.get_class_by_name("Integer"),
             RValue::Float(_) => vm.get_class_by_name("Float"),
             RValue::Proc(_) => vm.get_class_by_name("Proc"),
             RValue::Array(_) => vm.get_class_by_name("Array"),
@@ -435,6 +595,9 @@ impl RObject {
         if let Some(sclass) = self.singleton_class.borrow().as_ref() {
             return sclass.clone();
         }
+        if let RValue::Module(_) = &self.value {
+            return self.initialize_or_get_singleton_class_for_class(vm);
+        }
 
         let class_name = {
             let inspect = mrb_call_inspect(vm, self.clone());
@@ -456,6 +619,7 @@ impl RObject {
         sclass.update_module_weakref();
 
         self.singleton_class.replace(Some(sclass.clone()));
+        invalidate_method_caches();
         sclass
     }
 
@@ -463,12 +627,24 @@ impl RObject {
         self: &Rc<Self>,
         vm: &mut VM,
     ) -> Rc<RClass> {
-        if self.singleton_class.borrow().is_some() {
-            return self.singleton_class.borrow().as_ref().unwrap().clone();
+        if let Some(sclass) = self.singleton_class() {
+            return sclass;
         }
 
         let class = match &self.value {
             RValue::Class(c) => c.clone(),
+            RValue::Module(m) => {
+                let sclass = Rc::new(RClass::new_singleton(
+                    &format!("#<Class:{}>", m.full_name()),
+                    Some(vm.get_class_by_name("Module")),
+                    m.parent.borrow().clone(),
+                ));
+                sclass.update_module_weakref();
+                self.singleton_class.replace(Some(sclass.clone()));
+                m.singleton_class_ref.replace(Some(sclass.clone()));
+                invalidate_method_caches();
+                return sclass;
+            }
             _ => panic!("Not called on a class"),
         };
         let class_name = format!("#<Class:{}>", class.full_name());
@@ -493,16 +669,28 @@ impl RObject {
             .singleton_class_ref
             .borrow_mut()
             .replace(sclass.clone());
+        invalidate_method_caches();
         sclass
     }
 
     pub fn singleton_or_this_class(self: &Rc<Self>, vm: &mut VM) -> Rc<RClass> {
-        if let Some(sclass) = self.singleton_class.borrow().as_ref() {
-            return sclass.clone();
+        if let Some(sclass) = self.singleton_class() {
+            return sclass;
         }
         self.get_class(vm)
     }
 
+    /// The singleton class of this object, if one has been created.
+    pub fn singleton_class(&self) -> Option<Rc<RClass>> {
+        if let Some(sclass) = self.singleton_class.borrow().as_ref() {
+            return Some(sclass.clone());
+        }
+        match &self.value {
+            RValue::Module(m) => m.singleton_class_ref.borrow().clone(),
+            _ => None,
+        }
+    }
+
     pub fn intern(&self) -> Result<RSym, Error> {
         match &self.value {
             RValue::String(s, _) => Ok(RSym::new(String::from_utf8_lossy(&s.borrow()).to_string())),
@@ -541,6 +729,16 @@ impl RObject {
         }
     }
 
+    pub(crate) fn set_string_utf8(&self, utf8: bool) -> Result<(), Error> {
+        match &self.value {
+            RValue::String(_, is_utf8) => {
+                is_utf8.set(utf8);
+                Ok(())
+            }
+            _ => Err(Error::TypeMismatch),
+        }
+    }
+
     pub fn as_vec_owned(&self) -> Result<Vec<Rc<RObject>>, Error> {
         match &self.value {
             RValue::Array(arr) => Ok(arr.borrow().to_owned()),
@@ -778,6 +976,7 @@ impl TryFrom<&RObject> for f64 {
     fn try_from(value: &RObject) -> Result<Self, Self::Error> {
         match value.value {
             RValue::Integer(i) => Ok(i as f64),
+            RValue::BigInt(ref b) => Ok(b.to_f64()),
             RValue::Bool(b) => {
                 if b {
                     Ok(1.0)
@@ -791,6 +990,18 @@ impl TryFrom<&RObject> for f64 {
     }
 }
 
+impl TryFrom<&RObject> for RBigInt {
+    type Error = Error;
+
+    fn try_from(value: &RObject) -> Result<Self, Self::Error> {
+        match &value.value {
+            RValue::Integer(i) => Ok(RBigInt::from_i64(*i)),
+            RValue::BigInt(b) => Ok(b.as_ref().clone()),
+            _ => Err(Error::TypeMismatch),
+        }
+    }
+}
+
 impl TryFrom<&RObject> for bool {
     type Error = Error;
 
@@ -882,10 +1093,16 @@ impl PartialEq for RObject {
 #[derive(Debug, Clone)]
 pub struct RModule {
     pub sym_id: RSym,
+    /// Name given when an anonymous module is assigned to a constant
+    assigned_name: RefCell<Option<String>>,
     pub procs: RefCell<RHashMap<String, RProc>>,
     pub consts: RefCell<RHashMap<String, Rc<RObject>>>,
+    pub class_vars: RefCell<RHashMap<String, Rc<RObject>>>,
     pub mixed_in_modules: RefCell<Vec<Rc<RModule>>>,
     pub parent: RefCell<Option<Rc<RModule>>>,
+    // Module objects are created on demand, so the singleton class of a
+    // plain module is kept here rather than on one of its objects
+    pub singleton_class_ref: RefCell<Option<Rc<RClass>>>,
 
     pub underlying: RefCell<Option<Weak<RClass>>>,
 }
@@ -895,14 +1112,34 @@ impl RModule {
         let name = name.to_string();
         RModule {
             sym_id: RSym::new(name),
+            assigned_name: RefCell::new(None),
             procs: RefCell::new(RHashMap::default()),
             consts: RefCell::new(RHashMap::default()),
+            class_vars: RefCell::new(RHashMap::default()),
             mixed_in_modules: RefCell::new(Vec::new()),
             parent: RefCell::new(None),
+            singleton_class_ref: RefCell::new(None),
             underlying: RefCell::new(None),
         }
     }
 
+    pub fn name(&self) -> String {
+        match self.assigned_name.borrow().as_ref() {
+            Some(name) => name.clone(),
+            None => self.sym_id.name.clone(),
+        }
+    }
+
+    pub(crate) fn set_name(&self, name: &str) {
+        self.assigned_name.replace(Some(name.to_string()));
+    }
+
+    /// Anonymous modules and classes carry a `#<Class:0x...>` placeholder
+    /// until they are assigned to a constant.
+    pub fn is_anonymous(&self) -> bool {
+        self.name().starts_with("#<")
+    }
+
     pub fn getmcnst(&self, name: &str) -> Option<Rc<RObject>> {
         let consts = self.consts.borrow();
         consts.get(name).cloned()
@@ -936,7 +1173,7 @@ impl RModule {
         let mut names = Vec::new();
         let mut current: Option<Rc<RModule>> = Some(self.clone());
         while let Some(module) = current {
-            names.push(module.sym_id.name.clone());
+            names.push(module.name());
             current = module.parent.borrow().clone();
         }
         names.reverse();
@@ -1037,6 +1274,18 @@ impl RClass {
         self.module.getmcnst(name)
     }
 
+    /// Whether `other` is this class or one of its superclasses
+    pub fn is_subclass_of(self: &Rc<Self>, other: &Rc<RClass>) -> bool {
+        let mut klass = Some(self.clone());
+        while let Some(k) = klass {
+            if Rc::ptr_eq(&k, other) {
+                return true;
+            }
+            klass = k.super_class.clone();
+        }
+        false
+    }
+
     // find_method will search method from self to superclass
     pub fn find_method(&self, name: &str) -> Option<RProc> {
         // First check this class's module
@@ -1107,6 +1356,81 @@ pub(crate) fn build_module_lookup_chain(module: &Rc<RModule>) -> Vec<Rc<RModule>
     chain
 }
 
+/// Returns the ancestors of `module`, following its superclasses when the
+/// module backs a class.
+pub(crate) fn const_lookup_chain(module: &Rc<RModule>) -> Vec<Rc<RModule>> {
+    let underlying = module
+        .underlying
+        .borrow()
+        .as_ref()
+        .and_then(|w| w.upgrade());
+    match underlying {
+        Some(klass) => build_lookup_chain(&klass),
+        None => build_module_lookup_chain(module),
+    }
+}
+
+/// Looks up a constant in `module` and its ancestors. Constants of `Object`
+/// are only visible when `toplevel` is set or `module` is `Object` itself,
+/// as in Ruby where `Foo::String` does not resolve to `::String`.
+pub(crate) fn resolve_const(
+    vm: &VM,
+    module: &Rc<RModule>,
+    name: &str,
+    toplevel: bool,
+) -> Option<Rc<RObject>> {
+    let object_module = &vm.object_class.module;
+    let toplevel = toplevel || Rc::ptr_eq(module, object_module);
+    for m in const_lookup_chain(module) {
+        if !toplevel && Rc::ptr_eq(&m, object_module) {
+            break;
+        }
+        if let Some(val) = m.consts.borrow().get(name) {
+            return Some(val.clone());
+        }
+    }
+    if toplevel {
+        return vm.get_const_by_name(name);
+    }
+    None
+}
+
+/// Returns the modules searched for class variables of a class or module object.
+pub(crate) fn class_var_lookup_chain(owner: &RObject) -> Result<Vec<Rc<RModule>>, Error> {
+    match &owner.value {
+        RValue::Class(klass) => Ok(build_lookup_chain(klass)),
+        RValue::Module(module) => Ok(build_module_lookup_chain(module)),
+        _ => Err(Error::TypeMismatch),
+    }
+}
+
+/// Class variables are shared with subclasses, so the first ancestor
+/// defining `name` wins.
+pub(crate) fn resolve_class_var(owner: &RObject, name: &str) -> Result<Option<Rc<RObject>>, Error> {
+    for module in class_var_lookup_chain(owner)? {
+        if let Some(val) = module.class_vars.borrow().get(name) {
+            return Ok(Some(val.clone()));
+        }
+    }
+    Ok(None)
+}
+
+/// Assigns to the ancestor already holding `name`, or to `owner` itself.
+pub(crate) fn assign_class_var(owner: &RObject, name: &str, val: Rc<RObject>) -> Result<(), Error> {
+    let chain = class_var_lookup_chain(owner)?;
+    for module in chain.iter() {
+        if let Some(slot) = module.class_vars.borrow_mut().get_mut(name) {
+            *slot = val;
+            return Ok(());
+        }
+    }
+    chain[0]
+        .class_vars
+        .borrow_mut()
+        .insert(name.to_string(), val);
+    Ok(())
+}
+
 pub(crate) fn resolve_method(self_class: &Rc<RClass>, name: &str) -> Option<(Rc<RModule>, RProc)> {
     for module in build_lookup_chain(self_class) {
         if let Some(proc) = module.procs.borrow().get(name) {
@@ -1172,6 +1496,26 @@ pub struct RProc {
     pub func: Option<usize>,
     pub environ: Option<Rc<ENV>>,
     pub block_self: Option<Rc<RObject>>,
+    pub visibility: Visibility,
+}
+
+/// Visibility of a method entry, checked by `SEND` against the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visibility {
+    #[default]
+    Public,
+    Private,
+    Protected,
+}
+
+impl Visibility {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Private => "private",
+            Visibility::Protected => "protected",
+        }
+    }
 }
 
 /// Native Rust callable used to implement Ruby methods in the VM.
@@ -1203,6 +1547,7 @@ pub enum RPool {
     Str(String),
     Data(Vec<u8>),
     Int(i64),
+    BigInt(RBigInt),
     Float(f64),
 }
 
@@ -1221,7 +1566,7 @@ pub struct RException {
     pub class: Rc<RClass>,
     pub error_type: RefCell<Error>,
     pub message: String,
-    pub backtrace: Vec<String>, // TODO
+    pub backtrace: Vec<String>,
 }
 
 impl RClass {
@@ -1248,20 +1593,72 @@ impl RClass {
                     }
                 })
                 .unwrap_or_else(|| vm.get_class_by_name("Exception")),
+            Error::Exception(obj) => obj.get_class(vm),
 
             Error::Break(_) => vm.get_class_by_name("_Break"),
             Error::BlockReturn(_, _) => vm.get_class_by_name("_BlockReturn"),
+            Error::Throw(_, _) => vm.get_class_by_name("_Throw"),
         }
     }
 }
 
 impl RException {
+    /// Blank exception for `Exception.new` and its subclasses. The message,
+    /// backtrace and cause set from Ruby are kept in the object's ivars.
+    pub fn new(class: Rc<RClass>) -> Self {
+        RException {
+            message: class.full_name(),
+            error_type: RefCell::new(Error::General),
+            class,
+            backtrace: Vec::new(),
+        }
+    }
+
+    /// Wraps an error raised at the current VM position. When the same error
+    /// is still propagating from an inner frame, its backtrace is kept.
     pub fn from_error(vm: &mut VM, e: &Error) -> Self {
+        if let Error::Exception(obj) = e {
+            return Self::from_object(vm, obj);
+        }
+        let message = e.message();
+        let backtrace = match vm.exception.as_ref() {
+            Some(inner)
+                if inner.message == message
+                    && std::mem::discriminant(&*inner.error_type.borrow())
+                        == std::mem::discriminant(e) =>
+            {
+                inner.backtrace.clone()
+            }
+            _ => vm.backtrace(),
+        };
         RException {
             class: RClass::from_error(vm, e),
             error_type: RefCell::new(e.clone()),
-            message: e.message(),
-            backtrace: Vec::new(),
+            message,
+            backtrace,
+        }
+    }
+
+    /// Exception objects remember the backtrace of their first raise, so
+    /// re-raising them reports the original location.
+    fn from_object(vm: &mut VM, obj: &Rc<RObject>) -> Self {
+        let backtrace = match obj.exception_backtrace() {
+            Some(backtrace) => backtrace,
+            None => {
+                let backtrace = vm.backtrace();
+                let entries = backtrace
+                    .iter()
+                    .map(|entry| RObject::string(entry.clone()).to_refcount_assigned())
+                    .collect();
+                obj.set_ivar("backtrace", RObject::array(entries).to_refcount_assigned());
+                backtrace
+            }
+        };
+        RException {
+            class: obj.get_class(vm),
+            error_type: RefCell::new(Error::Exception(obj.clone())),
+            message: obj.exception_message(),
+            backtrace,
         }
     }
 }
@@ -1,11 +1,13 @@
 use std::cell::{Cell, RefCell};
-use std::mem::MaybeUninit;
-use std::rc::Rc;
-use std::{array, env};
+use std::env;
+use std::ops::Range;
+use std::rc::{Rc, Weak};
 
 use crate::Error;
-use crate::rite::{Irep, Rite, insn};
+use crate::rite::{CatchHandler, DebugFile, Irep, Rite, insn};
 
+use super::bigint::RBigInt;
+use super::method_cache::{InlineCaches, MethodCache};
 use super::op::Op;
 use super::prelude::prelude;
 use super::value::RHashMap;
@@ -15,7 +17,21 @@ use super::{op, optable::*};
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const ENGINE: &str = "mruby/edge";
 
-const MAX_REGS_SIZE: usize = 256;
+/// Registers a single frame can address from its offset
+pub(crate) const FRAME_REGS_SIZE: usize = 256;
+/// Default limit of the register stack, the same as mruby's MRB_STACK_MAX
+pub const DEFAULT_MAX_REGS_SIZE: usize = 0x40000;
+/// Default for how deep Ruby calls may nest on the Rust stack,
+/// e.g. blocks yielded from native methods
+pub const DEFAULT_MAX_NATIVE_DEPTH: usize = 512;
+/// Default bytes of Rust stack those nested calls may use. A level takes
+/// a few KiB in release builds but tens of KiB in debug builds, so the
+/// depth alone cannot keep a 2 MiB thread stack from overflowing.
+pub const DEFAULT_MAX_NATIVE_STACK_SIZE: usize = 1024 * 1024;
+
+/// Register stack of one execution context: the root or a Fiber.
+/// While a context runs, its registers are moved out into [`VM::regs`].
+pub type RegisterStack = RefCell<Vec<Option<Rc<RObject>>>>;
 
 #[derive(Debug, Clone)]
 pub enum TargetContext {
@@ -74,14 +90,42 @@ pub struct VM {
     pub bytecode: Vec<u8>,
     pub current_irep: Rc<IREP>,
     pub pc: Cell<usize>,
-    pub regs: [Option<Rc<RObject>>; MAX_REGS_SIZE],
+    pub regs: Vec<Option<Rc<RObject>>>,
     pub current_regs_offset: usize,
+    /// Upper bound of `regs`. Calls nesting deeper than this raise SystemStackError.
+    pub max_regs_size: usize,
+    /// Number of `run_internal` calls nested inside native code
+    pub native_depth: usize,
+    /// Upper bound of `native_depth`, keeping the Rust stack from overflowing
+    pub max_native_depth: usize,
+    /// Upper bound of the Rust stack used since the outermost native entry
+    pub max_native_stack_size: usize,
+    /// Stack address of the outermost native entry
+    native_stack_base: usize,
+    /// Holder `regs` is moved back into when another context is switched in
+    pub regs_stack: Rc<RegisterStack>,
+    /// Fiber running right now, `None` in the root context
+    pub current_fiber: Option<Rc<RObject>>,
     pub current_callinfo: Option<Rc<CALLINFO>>,
+    /// Callinfo chains suspended while a block body runs, innermost last
+    pub outer_callinfo: Vec<Rc<CALLINFO>>,
     pub current_breadcrumb: Option<Rc<Breadcrumb>>,
     pub kargs: RefCell<Option<RHashMap<RSym, Rc<RObject>>>>,
     pub current_kargs: RefCell<Option<Rc<KArgs>>>,
     pub target_class: TargetContext,
     pub exception: Option<Rc<RException>>,
+    /// MatchData of the last successful match in the current frame (`$~`)
+    pub last_match: Option<Rc<RObject>>,
+    /// Exception being handled by a rescue clause in the current frame (`$!`)
+    pub current_exception: Option<Rc<RObject>>,
+    /// Rescue and ensure clauses of the current frame handling an exception
+    pub(crate) handler_clauses: Vec<HandlerClause>,
+    /// Tags of the `catch` blocks currently running, innermost last
+    pub catch_tags: Vec<Rc<RObject>>,
+    /// Irep of the frame a `break` being raised returns to
+    pub(crate) break_target: Option<usize>,
+    /// Freeze string literals, as with `# frozen_string_literal: true`
+    pub frozen_string_literal: bool,
 
     pub flag_preemption: Cell<bool>,
 
@@ -96,7 +140,6 @@ pub struct VM {
     pub class_object_table: RHashMap<String, Rc<RObject>>,
 
     pub globals: RHashMap<String, Rc<RObject>>,
-    pub consts: RHashMap<String, Rc<RObject>>,
 
     pub upper: Option<Rc<ENV>>,
     // TODO: using fixed array?
@@ -105,100 +148,132 @@ pub struct VM {
 
     pub fn_table: RFnTable,
     pub fn_block_stack: RFnStack,
+    /// `fn_table` index of the function behind each `Symbol#to_proc`
+    pub symbol_procs: RHashMap<String, usize>,
+
+    pub symbol_table: RSymTable,
+    pub method_cache: MethodCache,
+}
+
+// Address of a local in a fresh frame, approximating the stack pointer
+#[inline(never)]
+fn stack_address() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+/// First irep id of builtin bytecode, far above those of the program's ireps
+const BUILTIN_IREP_ID_BASE: usize = usize::MAX / 2;
+
+fn stack_level_too_deep() -> Error {
+    Error::TaggedError("SystemStackError", "stack level too deep".to_string())
 }
 
+#[derive(Default)]
 pub struct RFnTable {
-    pub size: Cell<usize>,
-    pub table: [MaybeUninit<Rc<RFn>>; 4096],
+    pub table: Vec<Rc<RFn>>,
 }
 
 impl RFnTable {
-    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self {
-            size: Cell::new(0),
-            table: array::from_fn(|_| MaybeUninit::uninit()),
-        }
+        Self::default()
     }
 
     pub fn set(&mut self, f: Rc<RFn>) {
-        let i = self.size.get();
-        if i >= self.table.len() {
-            panic!("RFnTable overflow");
-        }
-
-        self.table[i].write(f);
-        let size = self.size.get();
-        if i >= size {
-            self.size.set(i + 1);
-        }
+        self.table.push(f);
     }
 
     pub fn get(&self, i: usize) -> Option<Rc<RFn>> {
-        if i >= self.size.get() {
-            return None;
-        }
-
-        unsafe { self.table[i].assume_init_ref() }.clone().into()
+        self.table.get(i).cloned()
     }
 
     pub fn len(&self) -> usize {
-        self.size.get()
+        self.table.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.size.get() == 0
+        self.table.is_empty()
     }
 }
 
+#[derive(Default)]
 pub struct RFnStack {
-    pub size: Cell<usize>,
-    pub stack: [Option<Rc<RFn>>; 64],
+    pub stack: Vec<Rc<RFn>>,
 }
 
 impl RFnStack {
-    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self {
-            size: Cell::new(0),
-            stack: array::from_fn(|_| None),
-        }
+        Self::default()
     }
 
     pub fn push(&mut self, f: Rc<RFn>) -> Result<(), Error> {
-        let i = self.size.get();
-        if i >= self.stack.len() {
-            return Err(Error::internal("RFnStack overflow"));
-        }
-
-        self.stack[i] = Some(f);
-        let size = self.size.get();
-        if i >= size {
-            self.size.set(i + 1);
-        }
+        self.stack.push(f);
         Ok(())
     }
 
-    pub fn pop(&self) -> Result<Rc<RFn>, Error> {
-        let i = self.size.get();
-        if i == 0 {
-            return Err(Error::internal("RFnStack underflow"));
+    pub fn pop(&mut self) -> Result<Rc<RFn>, Error> {
+        self.stack
+            .pop()
+            .ok_or_else(|| Error::internal("RFnStack underflow"))
+    }
+
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+}
+
+/// VM-wide table of interned symbols. Each distinct name gets a stable id
+/// in interning order, which also determines the Symbol's `object_id`.
+#[derive(Debug, Default)]
+pub struct RSymTable {
+    ids: RHashMap<String, usize>,
+    names: Vec<String>,
+}
+
+impl RSymTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id for `name`, registering it on first use.
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
         }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
 
-        self.size.set(i - 1);
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
 
-        self.stack[i - 1]
-            .as_ref()
-            .cloned()
-            .ok_or_else(|| Error::internal("RFnStack invalid state"))
+    pub fn name(&self, id: usize) -> Option<&str> {
+        self.names.get(id).map(|name| name.as_str())
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
     }
 
     pub fn len(&self) -> usize {
-        self.size.get()
+        self.names.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.size.get() == 0
+        self.names.is_empty()
+    }
+
+    /// Symbol ids are tagged so they never collide with Integer (odd) or
+    /// heap object (pointer-aligned) ids.
+    pub fn object_id(id: usize) -> u64 {
+        ((id as u64) << 8) | 0x0e
     }
 }
 
@@ -230,6 +305,9 @@ impl VM {
             reps: Vec::new(),
             lv: None,
             catch_target_pos: Vec::new(),
+            catch_handlers: Vec::new(),
+            debug_files: Vec::new(),
+            inline_caches: InlineCaches::default(),
         };
         Self::new_by_raw_irep(irep)
     }
@@ -240,7 +318,6 @@ impl VM {
     pub fn new_by_raw_irep(irep: IREP) -> VM {
         let irep = Rc::new(irep);
         let globals = RHashMap::default();
-        let consts = RHashMap::default();
         let builtin_class_table = RHashMap::default();
         let class_object_table = RHashMap::default();
 
@@ -251,9 +328,17 @@ impl VM {
         let bytecode = Vec::new();
         let current_irep = irep.clone();
         let pc = Cell::new(0);
-        let regs: [Option<Rc<RObject>>; MAX_REGS_SIZE] = [const { None }; MAX_REGS_SIZE];
+        let regs = vec![None; FRAME_REGS_SIZE];
         let current_regs_offset = 0;
+        let max_regs_size = DEFAULT_MAX_REGS_SIZE;
+        let native_depth = 0;
+        let max_native_depth = DEFAULT_MAX_NATIVE_DEPTH;
+        let max_native_stack_size = DEFAULT_MAX_NATIVE_STACK_SIZE;
+        let native_stack_base = 0;
+        let regs_stack = Rc::new(RefCell::new(Vec::new()));
+        let current_fiber = None;
         let current_callinfo = None;
+        let outer_callinfo = Vec::new();
         let current_breadcrumb = Some(Rc::new(Breadcrumb {
             upper: None,
             event: "root",
@@ -264,9 +349,13 @@ impl VM {
         let current_kargs = RefCell::new(None);
         let target_class = TargetContext::Class(object_class.clone());
         let exception = None;
+        let last_match = None;
+        let current_exception = None;
         let flag_preemption = Cell::new(false);
         let fn_table = RFnTable::new();
         let fn_block_stack = RFnStack::new();
+        let symbol_table = RSymTable::new();
+        let method_cache = MethodCache::default();
         let upper = None;
         let cur_env = RHashMap::default();
         let has_env_ref = RHashMap::default();
@@ -292,12 +381,26 @@ impl VM {
             pc,
             regs,
             current_regs_offset,
+            max_regs_size,
+            native_depth,
+            max_native_depth,
+            max_native_stack_size,
+            native_stack_base,
+            regs_stack,
+            current_fiber,
             current_callinfo,
+            outer_callinfo,
             current_breadcrumb,
             kargs,
             current_kargs,
             target_class,
             exception,
+            last_match,
+            current_exception,
+            handler_clauses: Vec::new(),
+            catch_tags: Vec::new(),
+            break_target: None,
+            frozen_string_literal: false,
             flag_preemption,
             #[cfg(feature = "insn-limit")]
             insn_count,
@@ -307,15 +410,18 @@ impl VM {
             builtin_class_table,
             class_object_table,
             globals,
-            consts,
             upper,
             cur_env,
             has_env_ref,
             fn_table,
+            symbol_procs: RHashMap::default(),
             fn_block_stack,
+            symbol_table,
+            method_cache,
         };
 
         prelude(&mut vm);
+        vm.intern_irep_syms(&vm.irep.clone());
 
         vm
     }
@@ -382,6 +488,24 @@ impl VM {
         self.__run()
     }
 
+    /// Runs bytecode shipped with the VM itself, such as mrblib. Its irep ids
+    /// are kept apart from the program's, which is left ready to [`VM::run`].
+    pub(crate) fn eval_builtin_rite(
+        &mut self,
+        rite: &mut Rite,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (irep, _) = load_irep_0(&mut rite.irep, 0, BUILTIN_IREP_ID_BASE);
+        self.intern_irep_syms(&irep);
+        let breadcrumb = self.current_breadcrumb.clone();
+        self.current_irep = Rc::new(irep);
+        self.pc.set(0);
+        let res = self.__run();
+        self.current_breadcrumb = breadcrumb;
+        self.current_irep = self.irep.clone();
+        self.pc.set(0);
+        res.map(|_| ())
+    }
+
     fn __run(&mut self) -> Result<Rc<RObject>, Box<dyn std::error::Error>> {
         let class = self.object_class.clone();
         // Insert top_self
@@ -393,7 +517,8 @@ impl VM {
             }),
             object_id: 0.into(),
             singleton_class: RefCell::new(None),
-            ivar: RefCell::new(RHashMap::default()),
+            ivar: RefCell::new(IvarTable::default()),
+            frozen: Cell::new(false),
         }
         .to_refcount_assigned();
         if self.current_regs()[0].is_none() {
@@ -422,6 +547,27 @@ impl VM {
                     continue;
                 }
 
+                if let Error::Break(val) = e.error_type.borrow().clone()
+                    && let Some(target) = self.break_target
+                {
+                    // Leave frames up to the block's home frame, where the
+                    // value is the result of the call it made
+                    let ci = self.current_callinfo.clone();
+                    if op_return(self, &operand).is_err() {
+                        break;
+                    }
+                    if let Some(ci) = ci
+                        && ci.pc_irep.__id == target
+                    {
+                        self.current_regs()[ci.return_reg].replace(val);
+                        self.exception.take();
+                        self.break_target = None;
+                    }
+                    if self.flag_preemption.get() {
+                        break;
+                    }
+                    continue;
+                }
                 if matches!(e.error_type.borrow().clone(), Error::Break(_)) {
                     retreg = match self.current_breadcrumb.as_ref() {
                         Some(bc) if bc.event == "do_op_send" => {
@@ -519,20 +665,143 @@ impl VM {
         retval
     }
 
-    pub(crate) fn find_next_handler_pos(&mut self) -> Option<usize> {
-        let ci = self.pc.get();
-        for p in self.current_irep.catch_target_pos.iter() {
-            if ci < *p {
-                return Some(*p);
-            }
+    /// Callinfo of the running method or block. Bodies entered through
+    /// `call_block` (blocks, mrb_funcall) keep theirs in `outer_callinfo`.
+    pub(crate) fn active_callinfo(&self) -> Option<&Rc<CALLINFO>> {
+        self.current_callinfo
+            .as_ref()
+            .or(self.outer_callinfo.last())
+    }
+
+    /// Builds `file:line:in 'method'` entries for the current call stack,
+    /// innermost first. Frames without debug info are skipped.
+    pub fn backtrace(&self) -> Vec<String> {
+        // (irep, op index, label); blocks are labelled after their caller
+        let mut frames: Vec<(Rc<IREP>, usize, Option<String>)> = Vec::new();
+        let mut irep = self.current_irep.clone();
+        let mut pc = self.pc.get().saturating_sub(1);
+        let mut ci = self.current_callinfo.clone();
+        let mut outer = self.outer_callinfo.iter().rev();
+        loop {
+            let caller = match ci.or_else(|| outer.next().cloned()) {
+                Some(ci) => ci,
+                None => {
+                    frames.push((irep, pc, Some("<main>".to_string())));
+                    break;
+                }
+            };
+            let label = (caller.method_id.name != "<block>").then(|| caller.method_id.name.clone());
+            frames.push((irep, pc, label));
+            irep = caller.pc_irep.clone();
+            pc = caller.pc.saturating_sub(1);
+            ci = caller.prev.clone();
         }
-        None
+
+        // Frames of builtin methods written in Ruby have no debug info; drop
+        // them first, so a block they call is named after its caller
+        let frames: Vec<_> = frames
+            .into_iter()
+            .filter_map(|(irep, pc, label)| {
+                let (file, line) = irep.source_position(pc)?;
+                Some((format!("{}:{}", file, line), label))
+            })
+            .collect();
+        let mut labels = vec![String::new(); frames.len()];
+        for i in (0..frames.len()).rev() {
+            labels[i] = match &frames[i].1 {
+                Some(label) => label.clone(),
+                None => match labels.get(i + 1) {
+                    Some(outer) if outer.starts_with("block in ") => outer.clone(),
+                    Some(outer) => format!("block in {}", outer),
+                    None => "block".to_string(),
+                },
+            };
+        }
+
+        frames
+            .iter()
+            .zip(labels)
+            .map(|((position, _), label)| format!("{}:in '{}'", position, label))
+            .collect()
+    }
+
+    /// Finds the innermost rescue or ensure handler covering the op that
+    /// raised, i.e. the one just before `pc`.
+    pub(crate) fn find_next_handler_pos(&mut self) -> Option<usize> {
+        let irep = self.current_irep.clone();
+        let pc = self.pc.get();
+        let catch_pc = match pc.checked_sub(1).and_then(|i| irep.code.get(i)) {
+            Some(op) => op.pos + op.len,
+            None => return None,
+        };
+        // Inner handlers come later in the table
+        let handler = irep
+            .catch_handlers
+            .iter()
+            .rev()
+            .find(|ch| ch.start < catch_pc && catch_pc <= ch.end)?;
+        irep.code.iter().position(|op| op.pos == handler.target)
     }
 
     pub(crate) fn current_regs(&mut self) -> &mut [Option<Rc<RObject>>] {
         &mut self.regs[self.current_regs_offset..]
     }
 
+    /// Grows the register stack so that a frame starting `a` registers above
+    /// the current one fits. Must be called before moving the offset.
+    pub(crate) fn reserve_frame(&mut self, a: usize) -> Result<(), Error> {
+        let required = self.current_regs_offset + a + FRAME_REGS_SIZE;
+        if required > self.max_regs_size {
+            return Err(stack_level_too_deep());
+        }
+        if self.regs.len() < required {
+            let size = required.next_power_of_two().min(self.max_regs_size);
+            self.regs.resize(size, None);
+        }
+        Ok(())
+    }
+
+    /// Enters Ruby code from native code. Fails with SystemStackError once
+    /// `max_native_depth` nested entries are active, or they have used
+    /// `max_native_stack_size` bytes of the Rust stack.
+    pub(crate) fn enter_native_depth(&mut self) -> Result<(), Error> {
+        let here = stack_address();
+        if self.native_depth == 0 {
+            self.native_stack_base = here;
+        }
+        if self.native_depth >= self.max_native_depth
+            || self.native_stack_base.abs_diff(here) >= self.max_native_stack_size
+        {
+            return Err(stack_level_too_deep());
+        }
+        self.native_depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn leave_native_depth(&mut self) {
+        self.native_depth -= 1;
+    }
+
+    /// Runs `f` on the registers of the frame `environ` was created in.
+    /// They are looked up in a suspended context when the frame is not in
+    /// the running one, e.g. for a block passed out of a Fiber.
+    pub(crate) fn with_env_regs<R>(
+        &mut self,
+        environ: &ENV,
+        f: impl FnOnce(&mut [Option<Rc<RObject>>]) -> R,
+    ) -> Result<R, Error> {
+        let offset = environ.current_regs_offset;
+        if environ.regs_stack.as_ptr() == Rc::as_ptr(&self.regs_stack) {
+            return Ok(f(&mut self.regs[offset..]));
+        }
+        let stack = environ
+            .regs_stack
+            .upgrade()
+            .ok_or_else(|| Error::internal("register stack of environment is gone"))?;
+        let mut regs = stack.borrow_mut();
+        Ok(f(&mut regs[offset..]))
+    }
+
     pub(crate) fn get_current_regs_cloned(&mut self, i: usize) -> Result<Rc<RObject>, Error> {
         self.current_regs()[i]
             .clone()
@@ -598,7 +867,7 @@ impl VM {
     }
 
     pub fn get_module_by_name(&self, name: &str) -> Rc<RModule> {
-        match self.consts.get(name).cloned() {
+        match self.get_const_by_name(name) {
             Some(obj) => match &obj.value {
                 RValue::Module(m) => m.clone(),
                 _ => panic!("Module {} not found", name),
@@ -607,8 +876,26 @@ impl VM {
         }
     }
 
+    /// Returns the Symbol for `name`, registering it in the symbol table.
+    pub fn intern(&mut self, name: &str) -> Rc<RObject> {
+        let id = self.symbol_table.intern(name);
+        let sym = RObject::symbol(RSym::new(name.to_string()));
+        sym.object_id.set(RSymTable::object_id(id));
+        Rc::new(sym)
+    }
+
+    fn intern_irep_syms(&mut self, irep: &IREP) {
+        for sym in irep.syms.iter() {
+            self.symbol_table.intern(&sym.name);
+        }
+        for rep in irep.reps.iter() {
+            self.intern_irep_syms(rep);
+        }
+    }
+
+    /// Looks up a top-level constant, i.e. one defined under `Object`.
     pub fn get_const_by_name(&self, name: &str) -> Option<Rc<RObject>> {
-        self.consts.get(name).cloned()
+        self.object_class.consts.borrow().get(name).cloned()
     }
 
     /// Defines a new class under the optional parent module, inheriting from
@@ -628,12 +915,8 @@ impl VM {
         class.update_module_weakref();
 
         let object = RObject::class(class.clone(), self);
-        self.consts.insert(name.to_string(), object.clone());
         if let Some(parent) = parent_module {
-            parent
-                .consts
-                .borrow_mut()
-                .insert(name.to_string(), object.clone());
+            parent.consts.borrow_mut().insert(name.to_string(), object);
         } else {
             self.object_class
                 .consts
@@ -643,6 +926,38 @@ impl VM {
         class
     }
 
+    /// Defines a class that is not bound to any constant yet. It gets a
+    /// placeholder name until it is first assigned to a constant.
+    pub fn define_anonymous_class(&mut self, superclass: Rc<RClass>) -> Rc<RClass> {
+        let class = Rc::new(RClass::new("", Some(superclass), None));
+        class.update_module_weakref();
+        class.set_name(&format!("#<Class:{:#018x}>", Rc::as_ptr(&class) as usize));
+        // Class methods inherited from the superclass resolve through the singleton
+        RObject::class(class.clone(), self).initialize_or_get_singleton_class_for_class(self);
+        class
+    }
+
+    /// Names an anonymous class after the constant it is assigned to,
+    /// keeping its class object registered under the new name.
+    pub(crate) fn name_anonymous_class(
+        &mut self,
+        class: &Rc<RClass>,
+        scope: &Rc<RModule>,
+        name: &str,
+    ) {
+        if !class.is_anonymous() || class.is_singleton {
+            return;
+        }
+        let object = self.class_object_table.remove(&class.full_name());
+        class.set_name(name);
+        if !Rc::ptr_eq(scope, &self.object_class.module) {
+            class.parent.replace(Some(scope.clone()));
+        }
+        if let Some(object) = object {
+            self.class_object_table.insert(class.full_name(), object);
+        }
+    }
+
     /// Defines a new module, optionally nested under another module, and stores
     /// it in the VM's constant table so it becomes accessible to Ruby code.
     /// If a module with the same name already exists, it returns the existing one.
@@ -650,7 +965,7 @@ impl VM {
         let existing = if let Some(ref parent) = parent_module {
             parent.consts.borrow().get(name).cloned()
         } else {
-            self.consts.get(name).cloned()
+            self.get_const_by_name(name)
         };
         if let Some(existing) = existing
             && let RValue::Module(ref m) = existing.value
@@ -662,7 +977,6 @@ impl VM {
             module.parent.replace(Some(parent.clone()));
         }
         let object = RObject::module(module.clone()).to_refcount_assigned();
-        self.consts.insert(name.to_string(), object.clone());
         if let Some(parent) = parent_module {
             parent.consts.borrow_mut().insert(name.to_string(), object);
         } else {
@@ -780,7 +1094,7 @@ impl VM {
     pub fn get_outermost_env(&self) -> Option<Rc<ENV>> {
         let mut env = self.upper.clone();
         while let Some(e) = env.clone() {
-            if e.upper.is_none() {
+            if e.upper.is_none() || e.method_owner.is_some() {
                 return env;
             }
             env = e.upper.clone();
@@ -793,19 +1107,28 @@ fn interpret_insn(mut insns: &[u8]) -> Vec<Op> {
     let mut pos: usize = 0;
     let mut ops = Vec::new();
     while !insns.is_empty() {
-        let op = insns[0];
-        let opcode: insn::OpCode = op.try_into().unwrap();
-        let fetched = insn::FETCH_TABLE[op as usize](&mut insns).unwrap();
-        ops.push(Op::new(opcode, fetched, pos, 1 + fetched.len()));
-        pos += 1 + fetched.len();
+        // EXT prefixes are folded into the following op so jump offsets,
+        // which count the prefix byte, still line up with `pos`
+        let (opcode, fetched, len) = insn::fetch(&mut insns).unwrap();
+        ops.push(Op::new(opcode, fetched, pos, len));
+        pos += len;
     }
     ops
 }
 
-fn load_irep_1(reps: &mut [Irep], pos: usize) -> (IREP, usize) {
+/// Decodes a BigInt pool entry: a radix byte, negated for negative
+/// literals, followed by the digits as written in the source.
+fn parse_bigint_literal(data: &[u8]) -> RBigInt {
+    let radix = data[0] as i8;
+    let digits = String::from_utf8_lossy(&data[1..]);
+    let n = RBigInt::parse(&digits, radix.unsigned_abs() as u32).unwrap_or_default();
+    if radix < 0 { n.neg() } else { n }
+}
+
+fn load_irep_1(reps: &mut [Irep], pos: usize, id_base: usize) -> (IREP, usize) {
     let irep = &mut reps[pos];
     let mut irep1 = IREP {
-        __id: pos,
+        __id: id_base + pos,
         nlocals: irep.nlocals(),
         nregs: irep.nregs(),
         rlen: irep.rlen(),
@@ -815,6 +1138,9 @@ fn load_irep_1(reps: &mut [Irep], pos: usize) -> (IREP, usize) {
         reps: Vec::new(),
         lv: None,
         catch_target_pos: Vec::new(),
+        catch_handlers: Vec::new(),
+        debug_files: irep.debug_files.clone(),
+        inline_caches: InlineCaches::default(),
     };
     for sym in irep.syms.iter() {
         irep1
@@ -835,9 +1161,8 @@ fn load_irep_1(reps: &mut [Irep], pos: usize) -> (IREP, usize) {
             crate::rite::PoolValue::Float(f) => {
                 irep1.pool.push(RPool::Float(*f));
             }
-            crate::rite::PoolValue::BigInt(_) => {
-                // BigInt not yet supported, store as 0 for now
-                irep1.pool.push(RPool::Int(0));
+            crate::rite::PoolValue::BigInt(data) => {
+                irep1.pool.push(RPool::BigInt(parse_bigint_literal(data)));
             }
         }
     }
@@ -862,16 +1187,17 @@ fn load_irep_1(reps: &mut [Irep], pos: usize) -> (IREP, usize) {
         irep1.lv = Some(map);
     }
     irep1.catch_target_pos.sort();
+    irep1.catch_handlers = irep.catch_handlers.clone();
 
     irep1.code = code;
     (irep1, pos + 1)
 }
 
-fn load_irep_0(reps: &mut [Irep], pos: usize) -> (IREP, usize) {
-    let (mut irep0, newpos) = load_irep_1(reps, pos);
+fn load_irep_0(reps: &mut [Irep], pos: usize, id_base: usize) -> (IREP, usize) {
+    let (mut irep0, newpos) = load_irep_1(reps, pos, id_base);
     let mut pos = newpos;
     for _ in 0..irep0.rlen {
-        let (rep, newpos) = load_irep_0(reps, pos);
+        let (rep, newpos) = load_irep_0(reps, pos, id_base);
         pos = newpos;
         irep0.reps.push(Rc::new(rep));
     }
@@ -880,7 +1206,7 @@ fn load_irep_0(reps: &mut [Irep], pos: usize) -> (IREP, usize) {
 
 // This will consume the Rite object and return the IREP
 fn rite_to_irep(rite: &mut Rite) -> IREP {
-    let (irep0, _) = load_irep_0(&mut rite.irep, 0);
+    let (irep0, _) = load_irep_0(&mut rite.irep, 0, 0);
     irep0
 }
 
@@ -897,6 +1223,38 @@ pub struct IREP {
     pub reps: Vec<Rc<IREP>>,
     pub lv: Option<RHashMap<usize, String>>,
     pub catch_target_pos: Vec<usize>,
+    /// Catch table with byte offsets, as in the rite binary
+    pub catch_handlers: Vec<CatchHandler>,
+    pub debug_files: Vec<DebugFile>,
+    pub inline_caches: InlineCaches,
+}
+
+impl IREP {
+    /// Whether this is bytecode of a method defined in mrblib
+    pub(crate) fn is_builtin(&self) -> bool {
+        self.__id >= BUILTIN_IREP_ID_BASE
+    }
+
+    /// Returns the source file and line of the op at `index`, when the
+    /// bytecode was compiled with debug info.
+    pub fn source_position(&self, index: usize) -> Option<(&str, usize)> {
+        let pos = self.code.get(index)?.pos;
+        let file = self
+            .debug_files
+            .iter()
+            .rev()
+            .find(|file| file.start_pos <= pos)?;
+        let line = file.line_at(pos)?;
+        Some((file.filename.as_str(), line))
+    }
+}
+
+/// Rescue or ensure clause entered with an exception: its bytecode range in
+/// the frame's irep and the `$!` to restore once control leaves it
+#[derive(Debug, Clone)]
+pub(crate) struct HandlerClause {
+    pub(crate) range: Range<usize>,
+    pub(crate) previous: Option<Rc<RObject>>,
 }
 
 #[derive(Debug, Clone)]
@@ -911,6 +1269,15 @@ pub struct CALLINFO {
     pub return_reg: usize,
     pub method_owner: Option<Rc<RModule>>,
     pub has_block: Cell<bool>,
+    pub last_match: Option<Rc<RObject>>,
+    pub current_exception: Option<Rc<RObject>>,
+    pub(crate) handler_clauses: Vec<HandlerClause>,
+    /// Environment of the caller's block, restored on return
+    pub upper: Option<Rc<ENV>>,
+    /// Visibility given to methods defined by `def` in this frame
+    pub visibility: Cell<Visibility>,
+    /// Set by `module_function` without arguments
+    pub module_function: Cell<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -919,7 +1286,13 @@ pub struct ENV {
     pub upper: Option<Rc<ENV>>,
     pub captured: RefCell<Option<Vec<Option<Rc<RObject>>>>>,
     pub current_regs_offset: usize,
+    /// Register stack `current_regs_offset` points into
+    pub regs_stack: Weak<RegisterStack>,
     pub is_expired: Cell<bool>,
+    /// Owner of the method whose own frame made it, even when the method is
+    /// a block given to define_method. `return` in its blocks stops here,
+    /// and class variables in them resolve through it.
+    pub method_owner: Option<Rc<RModule>>,
 }
 
 impl ENV {
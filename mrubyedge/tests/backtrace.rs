@@ -0,0 +1,126 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+fn strings(value: std::rc::Rc<mrubyedge::yamrb::value::RObject>) -> Vec<String> {
+    let values: Vec<std::rc::Rc<mrubyedge::yamrb::value::RObject>> =
+        value.as_ref().try_into().unwrap();
+    values
+        .iter()
+        .map(|v| v.as_ref().try_into().unwrap())
+        .collect()
+}
+
+#[test]
+fn exception_backtrace_test() {
+    let code = "def inner
+  raise \"boom\"
+end
+
+def outer
+  inner
+end
+
+begin
+  outer
+rescue => e
+  e.backtrace
+end
+";
+    let binary = mrbc_compile_with_lines("exception_backtrace", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let backtrace = strings(vm.run().unwrap());
+    assert_eq!(backtrace.len(), 3);
+    assert!(backtrace[0].ends_with(":2:in 'inner'"), "{:?}", backtrace);
+    assert!(backtrace[1].ends_with(":6:in 'outer'"), "{:?}", backtrace);
+    assert!(backtrace[2].ends_with(":10:in '<main>'"), "{:?}", backtrace);
+    assert!(backtrace[0].contains("exception_backtrace"));
+}
+
+#[test]
+fn backtrace_through_block_test() {
+    let code = "def each_item
+  [1].each do |x|
+    raise \"in block\"
+  end
+end
+
+begin
+  each_item
+rescue => e
+  e.backtrace
+end
+";
+    let binary = mrbc_compile_with_lines("backtrace_through_block", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let backtrace = strings(vm.run().unwrap());
+    assert_eq!(backtrace.len(), 3, "{:?}", backtrace);
+    assert!(backtrace[0].ends_with(":3:in 'block in each_item'"));
+    assert!(backtrace[1].ends_with(":2:in 'each_item'"));
+    assert!(backtrace[2].ends_with(":8:in '<main>'"));
+}
+
+#[test]
+fn backtrace_line_moving_backwards_test() {
+    // The modifier condition is compiled before the raise, so the line map
+    // steps back from line 3 to line 2
+    let code = "def check(v)
+  raise \"stop\" if
+    v
+end
+
+begin
+  check(true)
+rescue => e
+  e.backtrace
+end
+";
+    let binary = mrbc_compile_with_lines("backtrace_line_moving_backwards", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let backtrace = strings(vm.run().unwrap());
+    assert_eq!(backtrace.len(), 2, "{:?}", backtrace);
+    assert!(backtrace[0].ends_with(":2:in 'check'"), "{:?}", backtrace);
+    assert!(backtrace[1].ends_with(":7:in '<main>'"), "{:?}", backtrace);
+}
+
+#[test]
+fn kernel_caller_test() {
+    let code = "def where
+  caller
+end
+
+def call_where
+  where
+end
+
+call_where
+";
+    let binary = mrbc_compile_with_lines("kernel_caller", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let callers = strings(vm.run().unwrap());
+    assert_eq!(callers.len(), 2, "{:?}", callers);
+    assert!(callers[0].ends_with(":6:in 'call_where'"));
+    assert!(callers[1].ends_with(":9:in '<main>'"));
+}
+
+#[test]
+fn backtrace_without_debug_info_test() {
+    let code = "
+    begin
+      raise \"no lines\"
+    rescue => e
+      e.backtrace.size
+    end
+    ";
+    let binary = mrbc_compile("backtrace_without_debug_info", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: i64 = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, 0);
+}
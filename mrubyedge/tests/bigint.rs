@@ -0,0 +1,155 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+use mrubyedge::yamrb::bigint::RBigInt;
+
+#[test]
+fn bigint_literal_test() {
+    let code = "
+    [123456789012345678901234567890, -0x1234567890abcdef1234, 9223372036854775807]
+    ";
+    let binary = mrbc_compile("bigint_literal", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: Vec<std::rc::Rc<mrubyedge::yamrb::value::RObject>> =
+        result.as_ref().try_into().unwrap();
+    let big: RBigInt = result[0].as_ref().try_into().unwrap();
+    assert_eq!(big.to_string(), "123456789012345678901234567890");
+    let hex: RBigInt = result[1].as_ref().try_into().unwrap();
+    assert_eq!(hex.to_string_radix(16), "-1234567890abcdef1234");
+    let max: i64 = result[2].as_ref().try_into().unwrap();
+    assert_eq!(max, i64::MAX);
+}
+
+#[test]
+fn overflow_promotion_test() {
+    let code = "
+    max = 9223372036854775807
+    total = max + 1
+    [
+      total.to_s,
+      (max * max).to_s,
+      (total - 1 == max),
+      (-max - 2).to_s,
+      (2 ** 100).inspect,
+      (2 ** 100).to_s(16),
+      ((2 ** 100) / (2 ** 98)).to_s,
+    ].inspect
+    ";
+    let binary = mrbc_compile("bigint_overflow", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(
+        result,
+        "[\"9223372036854775808\", \"85070591730234615847396907784232501249\", true, \
+         \"-9223372036854775809\", \"1267650600228229401496703205376\", \
+         \"10000000000000000000000000\", \"4\"]"
+    );
+}
+
+#[test]
+fn bigint_division_test() {
+    let code = "
+    big = 100000000000000000000000
+    [
+      (big / 7).to_s,
+      (big % 7),
+      (-big / 7).to_s,
+      (-big % 7),
+      big.divmod(-30000000000000000000).inspect,
+      -7 / 2,
+      -7 % 2,
+    ].inspect
+    ";
+    let binary = mrbc_compile("bigint_division", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(
+        result,
+        "[\"14285714285714285714285\", 5, \"-14285714285714285714286\", 2, \
+         \"[-3334, -20000000000000000000]\", -4, 1]"
+    );
+}
+
+#[test]
+fn bigint_compare_and_hash_test() {
+    let code = "
+    big = 2 ** 64
+    totals = { big => :big, 1 => :small }
+    [
+      big > 9223372036854775807,
+      -big < -9223372036854775807,
+      big <=> big + 1,
+      big > 1.5,
+      big == 18446744073709551616,
+      totals[18446744073709551616],
+      totals[big - big + 1],
+      (big - big + 1).class == Integer,
+      big.class == Integer,
+      [3, big, -big].max == big,
+    ].inspect
+    ";
+    let binary = mrbc_compile("bigint_compare", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(
+        result,
+        "[true, true, -1, true, true, :big, :small, true, true, true]"
+    );
+}
+
+#[test]
+fn bigint_zero_division_test() {
+    let code = "
+    def divide
+      (2 ** 70) / 0
+    rescue ZeroDivisionError => e
+      e.message
+    end
+    divide
+    ";
+    let binary = mrbc_compile("bigint_zero_division", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, "divided by 0");
+}
+
+#[test]
+fn bigint_float_mixed_arithmetic_test() {
+    let code = "
+    big = 2 ** 70
+    f = 2.0 ** 70
+    [
+      1.5 + big == f + 1.5, big + 1.5 == f + 1.5,
+      1.5 - big == 1.5 - f, big - 1.5 == f - 1.5,
+      1.5 * big == f * 1.5, big * 1.5 == f * 1.5,
+      f / big == 1.0, big / f == 1.0,
+      1.5 < big, 1.5 <= big, 1.5 > big, 1.5 >= big, big > 1.5, big < 1.5,
+      1.5 <=> big, f <=> big, [1.5, big].max == big, [1.5, big].min,
+      f == big, big == f, f != big, big != f, big == f + 2.0 ** 20,
+      1.5.clamp(0, big), 2.0.send(:+, big) == f + 2.0,
+    ]
+    ";
+    let binary = mrbc_compile("bigint_float_mixed_arithmetic", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: String =
+        mrubyedge::yamrb::helpers::mrb_funcall(&mut vm, Some(result), "inspect", &[])
+            .unwrap()
+            .as_ref()
+            .try_into()
+            .unwrap();
+    assert_eq!(
+        result,
+        "[true, true, true, true, true, true, true, true, true, true, false, false, true, false, -1, 0, true, 1.5, true, true, false, false, false, 1.5, true]"
+    );
+}
@@ -186,3 +186,35 @@ fn break_test_toplevel() {
     let result: i32 = vm.run().unwrap().as_ref().try_into().unwrap();
     assert_eq!(result, 10);
 }
+
+#[test]
+fn break_test_returns_from_yielding_method() {
+    let code = "
+    def twice
+      yield 1
+      yield 2
+      :not_reached
+    end
+
+    def each_break
+      r = [1, 2, 3].each { |i| break i * 10 if i == 2 }
+      [r, :after]
+    end
+
+    def yield_break
+      r = twice { |i| break i + 5 }
+      [r, :after]
+    end
+
+    def native_break
+      r = [1, 2].map { |i| break :mapped }
+      [r, :after]
+    end
+
+    [each_break, yield_break, native_break, loop { break 42 }]
+    ";
+    assert_eq!(
+        run_to_string("break_test_returns_from_yielding_method", code),
+        "[[20, :after], [6, :after], [:mapped, :after], 42]"
+    );
+}
@@ -0,0 +1,136 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn catch_returns_thrown_value_test() {
+    let code = "
+a = catch(:done) do
+  throw :done, 42
+  :not_reached
+end
+b = catch(:done) { :block_value }
+c = catch(:done) { throw :done }
+[a, b, c]
+";
+    assert_eq!(
+        run_to_string("catch_value", code),
+        "[42, :block_value, nil]"
+    );
+}
+
+#[test]
+fn throw_unwinds_nested_blocks_and_methods_test() {
+    let code = "
+def search(n)
+  throw :found, n if n == 0
+  search(n - 1)
+end
+
+pair = catch(:outer) do
+  catch(:inner) do
+    [1, 2, 3].each do |i|
+      [4, 5].each { |j| throw :outer, [i, j] if i * j == 10 }
+    end
+  end
+  :not_found
+end
+from_loop = catch(:stop) { loop { throw :stop, :loop } }
+[pair, catch(:found) { search(20) }, from_loop]
+";
+    assert_eq!(run_to_string("throw_unwind", code), "[[2, 5], 0, :loop]");
+}
+
+#[test]
+fn throw_runs_ensure_clauses_test() {
+    let code = "
+log = []
+def work(log)
+  begin
+    throw :halt, :halted
+  ensure
+    log << :method_ensure
+  end
+end
+result = catch(:halt) do
+  begin
+    work(log)
+  rescue Exception
+    log << :rescued
+  ensure
+    log << :block_ensure
+  end
+end
+[result, log]
+";
+    assert_eq!(
+        run_to_string("throw_ensure", code),
+        "[:halted, [:method_ensure, :block_ensure]]"
+    );
+}
+
+#[test]
+fn catch_tags_compare_by_identity_test() {
+    let code = "
+anonymous = catch do |tag|
+  catch(:other) { throw tag, :anonymous }
+end
+strings = begin
+  catch('tag') { throw 'tag' }
+rescue UncaughtThrowError
+  :not_identical
+end
+[anonymous, strings]
+";
+    assert_eq!(
+        run_to_string("catch_identity", code),
+        "[:anonymous, :not_identical]"
+    );
+}
+
+#[test]
+fn uncaught_throw_error_test() {
+    let code = "
+begin
+  throw :nowhere, 3
+rescue ArgumentError => e
+  [e.class, e.message, e.tag, e.value]
+end
+";
+    assert_eq!(
+        run_to_string("uncaught_throw", code),
+        "[UncaughtThrowError, \"uncaught throw :nowhere\", :nowhere, 3]"
+    );
+}
+
+#[test]
+fn ensure_runs_when_exception_propagates_test() {
+    let code = "
+log = []
+begin
+  begin
+    raise 'boom'
+  rescue TypeError
+    log << :type_error
+  ensure
+    log << :ensure
+  end
+rescue => e
+  log << e.message
+end
+n = 0
+begin
+  n += 1
+  raise 'again' if n < 3
+rescue
+  retry
+end
+[log, n]
+";
+    assert_eq!(
+        run_to_string("ensure_propagate", code),
+        "[[:ensure, \"boom\"], 3]"
+    );
+}
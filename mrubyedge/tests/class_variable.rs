@@ -0,0 +1,152 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn class_variable_get_set_test() {
+    let code = "
+    class Counter
+      @@count = 0
+
+      def self.count
+        @@count
+      end
+
+      def increment
+        @@count += 1
+      end
+    end
+
+    Counter.new.increment
+    Counter.new.increment
+    Counter.count
+    ";
+    let binary = mrbc_compile("class_variable_get_set", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: i64 = result.as_ref().try_into().unwrap();
+    assert_eq!(result, 2);
+}
+
+#[test]
+fn class_variable_shared_with_subclass_test() {
+    let code = "
+    class Base
+      @@name = \"base\"
+
+      def self.name_of
+        @@name
+      end
+    end
+
+    class Child < Base
+      def self.rename(n)
+        @@name = n
+      end
+    end
+
+    Child.rename(\"child\")
+    Base.name_of
+    ";
+    let binary = mrbc_compile("class_variable_shared", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: String = result.as_ref().try_into().unwrap();
+    assert_eq!(result, "child");
+}
+
+#[test]
+fn class_variable_uninitialized_test() {
+    let code = "
+    class Foo
+      def self.read
+        @@missing
+      end
+    end
+    ";
+    let binary = mrbc_compile("class_variable_uninitialized", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.run().unwrap();
+
+    let foo = vm.get_const_by_name("Foo").unwrap();
+    let err = mrb_funcall(&mut vm, Some(foo), "read", &[]).err().unwrap();
+    assert!(matches!(err, mrubyedge::Error::NameError(_)));
+    assert!(
+        err.message()
+            .contains("uninitialized class variable @@missing in Foo")
+    );
+}
+
+#[test]
+fn class_variable_reflection_test() {
+    let code = "
+    class Config
+      @@debug = false
+    end
+
+    class AppConfig < Config
+    end
+
+    AppConfig.class_variable_set(:@@level, 3)
+    [
+      AppConfig.class_variable_get(:@@level),
+      AppConfig.class_variable_get(\"@@debug\"),
+      AppConfig.class_variable_defined?(:@@debug),
+      Config.class_variable_defined?(:@@level),
+      AppConfig.class_variables.size,
+    ]
+    ";
+    let binary = mrbc_compile("class_variable_reflection", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: Vec<std::rc::Rc<mrubyedge::yamrb::value::RObject>> =
+        result.as_ref().try_into().unwrap();
+    let level: i64 = result[0].as_ref().try_into().unwrap();
+    assert_eq!(level, 3);
+    let debug: bool = result[1].as_ref().try_into().unwrap();
+    assert!(!debug);
+    let defined: bool = result[2].as_ref().try_into().unwrap();
+    assert!(defined);
+    let defined_on_super: bool = result[3].as_ref().try_into().unwrap();
+    assert!(!defined_on_super);
+    let size: i64 = result[4].as_ref().try_into().unwrap();
+    assert_eq!(size, 2);
+}
+
+#[test]
+fn class_variable_owned_by_defining_class_test() {
+    let code = "
+    class Base
+      def self.setup
+        @@registry = [1]
+      end
+
+      def self.registry
+        @@registry
+      end
+
+      def register(x)
+        [0].each { @@registry << x }
+      end
+    end
+
+    class Sub < Base
+    end
+
+    Sub.setup
+    Sub.new.register(2)
+    [Base.registry, Sub.registry, Base.class_variable_defined?(:@@registry)].inspect
+    ";
+    let binary = mrbc_compile("class_variable_owned_by_defining_class", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: String = result.as_ref().try_into().unwrap();
+    assert_eq!(result, "[[1, 2], [1, 2], true]");
+}
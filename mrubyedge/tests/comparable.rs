@@ -0,0 +1,122 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+macro_rules! version_class {
+    () => {
+        "
+class Version
+  include Comparable
+  attr_reader :major, :minor
+
+  def initialize(major, minor)
+    @major = major
+    @minor = minor
+  end
+
+  def <=>(other)
+    return nil unless other.is_a?(Version)
+    [major, minor] <=> [other.major, other.minor]
+  end
+
+  def to_s
+    \"#{major}.#{minor}\"
+  end
+end
+"
+    };
+}
+
+#[test]
+fn comparable_operators_test() {
+    let code = concat!(
+        version_class!(),
+        "
+    a = Version.new(1, 2)
+    b = Version.new(1, 10)
+    [a < b, a <= b, a > b, a >= b, a == Version.new(1, 2), a == b, a == 1, a != Version.new(1, 2)]
+    "
+    );
+    assert_eq!(
+        run_to_string("comparable_operators", code),
+        "[true, true, false, false, true, false, false, false]"
+    );
+}
+
+#[test]
+fn comparable_between_clamp_test() {
+    let code = concat!(
+        version_class!(),
+        "
+    low = Version.new(1, 0)
+    high = Version.new(2, 0)
+    v = Version.new(3, 1)
+    [
+      Version.new(1, 5).between?(low, high),
+      v.between?(low, high),
+      v.clamp(low, high).to_s,
+      Version.new(0, 9).clamp(low..high).to_s,
+      Version.new(1, 5).clamp(low, high).to_s,
+      5.between?(1, 10),
+      \"b\".between?(\"a\", \"c\"),
+      \"a\" < \"b\",
+    ]
+    "
+    );
+    assert_eq!(
+        run_to_string("comparable_between_clamp", code),
+        "[true, false, \"2.0\", \"1.0\", \"1.5\", true, true, true]"
+    );
+}
+
+#[test]
+fn comparable_sort_min_max_test() {
+    let code = concat!(
+        version_class!(),
+        "
+    versions = [Version.new(1, 10), Version.new(0, 1), Version.new(1, 2)]
+    [
+      versions.sort.map(&:to_s),
+      versions.min.to_s,
+      versions.max.to_s,
+      versions.minmax.map(&:to_s),
+      versions.sort_by { |v| v }.map(&:to_s),
+      [[2, 1], [1, 3], [1, 2]].sort,
+    ]
+    "
+    );
+    assert_eq!(
+        run_to_string("comparable_sort_min_max", code),
+        "[[\"0.1\", \"1.2\", \"1.10\"], \"0.1\", \"1.10\", [\"0.1\", \"1.10\"], [\"0.1\", \"1.2\", \"1.10\"], [[1, 2], [1, 3], [2, 1]]]"
+    );
+}
+
+#[test]
+fn comparable_incomparable_raises_test() {
+    let code = concat!(
+        version_class!(),
+        "
+    results = []
+    [
+      -> { [Version.new(1, 0), 3].sort },
+      -> { [1, \"a\"].max },
+      -> { [Object.new, Object.new].min },
+      -> { Version.new(1, 0) < 1 },
+    ].each do |f|
+      begin
+        f.call
+        results << :no_error
+      rescue ArgumentError => e
+        results << e.message.include?(\"comparison of\")
+      end
+    end
+    results
+    "
+    );
+    assert_eq!(
+        run_to_string("comparable_incomparable_raises", code),
+        "[true, true, true, true]"
+    );
+}
@@ -0,0 +1,173 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn scoped_constant_assignment_test() {
+    let code = "
+    module Config
+    end
+
+    Config::LIMIT = 10
+    Config::LIMIT + 1
+    ";
+    let binary = mrbc_compile("const_setmcnst", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: i64 = result.as_ref().try_into().unwrap();
+    assert_eq!(result, 11);
+}
+
+#[test]
+fn lexical_constant_lookup_test() {
+    let code = "
+    module Outer
+      VALUE = 1
+
+      class Inner
+        def value
+          VALUE
+        end
+
+        def in_block
+          [1].map { |x| VALUE + x }.first
+        end
+      end
+    end
+
+    Outer::Inner.new.value + Outer::Inner.new.in_block
+    ";
+    let binary = mrbc_compile("const_lexical", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: i64 = result.as_ref().try_into().unwrap();
+    assert_eq!(result, 3);
+}
+
+#[test]
+fn ancestor_constant_lookup_test() {
+    let code = "
+    class Base
+      LEVEL = 2
+      NAME = \"base\"
+    end
+
+    module Scope
+      NAME = \"scope\"
+
+      class Child < Base
+        def level
+          LEVEL
+        end
+
+        def name
+          NAME
+        end
+      end
+    end
+
+    child = Scope::Child.new
+    [child.level, Scope::Child::LEVEL, child.name]
+    ";
+    let binary = mrbc_compile("const_ancestor", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: Vec<std::rc::Rc<mrubyedge::yamrb::value::RObject>> =
+        result.as_ref().try_into().unwrap();
+    let level: i64 = result[0].as_ref().try_into().unwrap();
+    assert_eq!(level, 2);
+    let scoped_level: i64 = result[1].as_ref().try_into().unwrap();
+    assert_eq!(scoped_level, 2);
+    // lexical scope wins over ancestors
+    let name: String = result[2].as_ref().try_into().unwrap();
+    assert_eq!(name, "scope");
+}
+
+#[test]
+fn scoped_lookup_skips_toplevel_test() {
+    let code = "
+    TOP = 1
+    class Foo
+    end
+
+    def test_scoped
+      Foo::TOP
+    end
+    ";
+    let binary = mrbc_compile("const_scoped_toplevel", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.run().unwrap();
+
+    let err = mrb_funcall(&mut vm, None, "test_scoped", &[])
+        .err()
+        .unwrap();
+    assert!(matches!(err, mrubyedge::Error::NameError(_)));
+    assert_eq!(err.message(), "Cannot found name: Foo::TOP");
+}
+
+#[test]
+fn const_reflection_test() {
+    let code = "
+    module Plugins
+      class Loader
+      end
+    end
+
+    Plugins.const_set(:VERSION, 3)
+    [
+      Plugins.const_get(:VERSION),
+      Plugins.const_get(\"Loader\") == Plugins::Loader,
+      Object.const_get(\"Plugins::Loader\") == Plugins::Loader,
+      Plugins.const_defined?(:VERSION),
+      Plugins.const_defined?(:Missing),
+      Plugins.constants.size,
+    ]
+    ";
+    let binary = mrbc_compile("const_reflection", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: Vec<std::rc::Rc<mrubyedge::yamrb::value::RObject>> =
+        result.as_ref().try_into().unwrap();
+    let version: i64 = result[0].as_ref().try_into().unwrap();
+    assert_eq!(version, 3);
+    let loader: bool = result[1].as_ref().try_into().unwrap();
+    assert!(loader);
+    let loader_path: bool = result[2].as_ref().try_into().unwrap();
+    assert!(loader_path);
+    let defined: bool = result[3].as_ref().try_into().unwrap();
+    assert!(defined);
+    let missing: bool = result[4].as_ref().try_into().unwrap();
+    assert!(!missing);
+    let size: i64 = result[5].as_ref().try_into().unwrap();
+    assert_eq!(size, 2);
+}
+
+#[test]
+fn const_missing_hook_test() {
+    let code = "
+    class Registry
+      def self.const_missing(name)
+        \"autoloaded #{name}\"
+      end
+
+      def self.lookup
+        Widget
+      end
+    end
+
+    Registry::Gadget + \", \" + Registry.lookup
+    ";
+    let binary = mrbc_compile("const_missing", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: String = result.as_ref().try_into().unwrap();
+    assert_eq!(result, "autoloaded Gadget, autoloaded Widget");
+}
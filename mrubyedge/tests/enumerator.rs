@@ -0,0 +1,196 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn enumerator_without_block_test() {
+    let code = "
+    [
+      [1, 2, 3].each.class,
+      [1, 2, 3].map.class,
+      3.times.to_a,
+      {a: 1}.each.to_a,
+      (1..3).each.to_a,
+      [1, 2].each.inspect,
+    ]
+    ";
+    assert_eq!(
+        run_to_string("enumerator_without_block", code),
+        "[Enumerator, Enumerator, [0, 1, 2], [[:a, 1]], [1, 2, 3], \"#<Enumerator: [1, 2]:each>\"]"
+    );
+}
+
+#[test]
+fn enumerator_next_peek_rewind_test() {
+    let code = "
+    e = [1, 2, 3].each
+    result = [e.next, e.peek, e.next, e.next]
+    begin
+      e.next
+    rescue StopIteration => ex
+      result << ex.class
+    end
+    e.rewind
+    result << e.next
+    result
+    ";
+    assert_eq!(
+        run_to_string("enumerator_next_peek_rewind", code),
+        "[1, 2, 2, 3, StopIteration, 1]"
+    );
+}
+
+#[test]
+fn enumerator_next_past_read_ahead_test() {
+    let code = "
+    e = (1..100).each
+    sum = 0
+    loop { sum += e.next }
+    sum
+    ";
+    assert_eq!(
+        run_to_string("enumerator_next_past_read_ahead", code),
+        "5050"
+    );
+}
+
+#[test]
+fn enumerator_next_runs_generator_once_test() {
+    let code = "
+    $c = 0
+    e = Enumerator.new { |y| loop { $c += 1; y << $c } }
+    result = []
+    20.times { result << e.next }
+    [result, e.peek, $c]
+    ";
+    assert_eq!(
+        run_to_string("enumerator_next_runs_generator_once", code),
+        "[[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20], 21, 21]"
+    );
+}
+
+#[test]
+fn enumerator_next_over_lazy_and_native_sources_test() {
+    let code = "
+    lazy = (1..Float::INFINITY).lazy.map { |x| x * 2 }.select { |x| x % 3 == 0 }
+    taken = [1, 2, 3].lazy.take(2)
+    mapped = [1, 2].map
+    outer = [1, 2].each
+    inner = outer.to_enum
+    [
+      [lazy.next, lazy.next, lazy.peek],
+      [taken.next, taken.next, (taken.next rescue :stop)],
+      [mapped.next, mapped.next, (mapped.next rescue :stop)],
+      [inner.next, outer.next, inner.next],
+    ]
+    ";
+    assert_eq!(
+        run_to_string("enumerator_next_over_lazy_and_native_sources", code),
+        "[[6, 12, 18], [1, 2, :stop], [1, 2, :stop], [1, 1, 2]]"
+    );
+}
+
+#[test]
+fn enumerator_loop_stops_on_stop_iteration_test() {
+    let code = "
+    a = [1, 2, 3].each
+    b = %w[x y].each
+    result = []
+    r = loop do
+      result << [a.next, b.next]
+    end
+    [result, r]
+    ";
+    assert_eq!(
+        run_to_string("enumerator_loop_stops", code),
+        "[[[1, \"x\"], [2, \"y\"]], nil]"
+    );
+}
+
+#[test]
+fn enumerator_with_index_test() {
+    let code = "
+    [
+      %w[a b c].map.with_index { |s, i| s * (i + 1) },
+      %w[a b].each.with_index(1).to_a,
+      %w[a b].each_with_index.map { |s, i| [i, s] },
+      [1, 2, 3].each.with_object([]) { |x, acc| acc << x * 2 },
+      [3, 1, 2].each.size,
+      4.times.size,
+      (1..).each.size == Float::INFINITY,
+      [5.downto(3).size, 1.upto(4).size, 3.upto(1).size, 1.upto(2.5).size],
+    ]
+    ";
+    assert_eq!(
+        run_to_string("enumerator_with_index", code),
+        "[[\"a\", \"bb\", \"ccc\"], [[\"a\", 1], [\"b\", 2]], [[0, \"a\"], [1, \"b\"]], [2, 4, 6], 3, 4, true, [3, 4, 0, 2]]"
+    );
+}
+
+#[test]
+fn enumerator_new_generator_test() {
+    let code = "
+    fib = Enumerator.new do |y|
+      a, b = 0, 1
+      loop do
+        y << a
+        a, b = b, a + b
+      end
+    end
+    [fib.first(8), fib.next, fib.next, fib.lazy.select { |x| x % 2 == 0 }.first(3)]
+    ";
+    assert_eq!(
+        run_to_string("enumerator_new_generator", code),
+        "[[0, 1, 1, 2, 3, 5, 8, 13], 0, 1, [0, 2, 8]]"
+    );
+}
+
+#[test]
+fn enumerator_lazy_test() {
+    let code = "
+    calls = 0
+    lazy = (1..Float::INFINITY).lazy.map { |x| calls += 1; x * 2 }.select { |x| x % 3 == 0 }
+    [
+      lazy.first(3),
+      calls,
+      (1..20).lazy.reject { |x| x % 2 == 1 }.take(3).to_a,
+      [1, 2, 3, 4, 1].lazy.take_while { |x| x < 3 }.force,
+      [1, 2, 3, 4, 1].lazy.drop_while { |x| x < 3 }.drop(1).to_a,
+      (1..3).lazy.filter_map { |x| x * 10 if x != 2 }.to_a,
+      (1..3).lazy.map(&:to_s).eager.first(2),
+      [1, 2].lazy.take(1).inspect,
+    ]
+    ";
+    assert_eq!(
+        run_to_string("enumerator_lazy", code),
+        "[[6, 12, 18], 9, [2, 4, 6], [1, 2], [4, 1], [10, 30], [\"1\", \"2\"], \"#<Enumerator::Lazy: #<Enumerator::Lazy: [1, 2]>:take(1)>\"]"
+    );
+}
+
+#[test]
+fn enumerator_to_enum_test() {
+    let code = "
+    class Countdown
+      include Enumerable
+      def initialize(n)
+        @n = n
+      end
+      def each(&block)
+        i = @n
+        while i > 0
+          block.call(i)
+          i -= 1
+        end
+      end
+    end
+
+    e = Countdown.new(3).to_enum
+    [e.next, e.next, Countdown.new(4).first, Countdown.new(5).first(2), Countdown.new(3).lazy.map { |x| x * x }.to_a]
+    ";
+    assert_eq!(
+        run_to_string("enumerator_to_enum", code),
+        "[3, 2, 4, [5, 4], [9, 4, 1]]"
+    );
+}
@@ -0,0 +1,276 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn raise_class_and_message_test() {
+    let code = "
+    def rescued
+      yield
+    rescue => e
+      \"#{e.class.inspect}: #{e.message}\"
+    end
+
+    [
+      rescued { raise ArgumentError },
+      rescued { raise ArgumentError, \"bad value\" },
+      rescued { raise RuntimeError.new(\"built\") },
+      rescued { raise RuntimeError.new(\"old\"), \"replaced\" },
+    ].join(\", \")
+    ";
+    let binary = mrbc_compile("exception_raise_class", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(
+        result,
+        "ArgumentError: ArgumentError, ArgumentError: bad value, \
+         RuntimeError: built, RuntimeError: replaced"
+    );
+}
+
+#[test]
+fn rescue_user_defined_hierarchy_test() {
+    let code = "
+    class AppError < StandardError
+    end
+
+    class NotFound < AppError
+    end
+
+    class Unrelated < StandardError
+    end
+
+    def classify(klass)
+      begin
+        raise klass, \"boom\"
+      rescue Unrelated
+        \"unrelated\"
+      rescue AppError => e
+        \"app(#{e.class.inspect})\"
+      end
+    rescue => e
+      \"standard(#{e.class.inspect})\"
+    end
+
+    [classify(NotFound), classify(AppError), classify(Unrelated), classify(RuntimeError)].join(\",\")
+    ";
+    let binary = mrbc_compile("exception_hierarchy", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(
+        result,
+        "app(NotFound),app(AppError),unrelated,standard(RuntimeError)"
+    );
+}
+
+#[test]
+fn custom_exception_keeps_ivars_test() {
+    let code = "
+    class HttpError < StandardError
+      attr_reader :status
+
+      def initialize(status, msg = \"http error\")
+        super(msg)
+        @status = status
+      end
+    end
+
+    def fetch
+      raise HttpError.new(404, \"not found\")
+    end
+
+    begin
+      fetch
+    rescue HttpError => e
+      \"#{e.status} #{e.message} #{e.is_a?(StandardError)}\"
+    end
+    ";
+    let binary = mrbc_compile("exception_ivars", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, "404 not found true");
+}
+
+#[test]
+fn exception_cause_test() {
+    let code = "
+    class WrapError < StandardError
+    end
+
+    begin
+      begin
+        raise ArgumentError, \"inner\"
+      rescue => e
+        raise WrapError, \"outer\"
+      end
+    rescue => e
+      [e.message, e.cause.class.inspect, e.cause.message, e.cause.cause.nil?].join(\",\")
+    end
+    ";
+    let binary = mrbc_compile("exception_cause", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, "outer,ArgumentError,inner,true");
+}
+
+#[test]
+fn exception_cause_cleared_after_rescue_test() {
+    let code = "
+    def handled
+      begin
+        raise \"a\"
+      rescue
+      end
+      begin
+        raise ArgumentError, \"b\"
+      rescue => e
+        e.cause.inspect
+      end
+    end
+
+    def retried
+      tries = 0
+      begin
+        tries += 1
+        raise \"first\" if tries == 1
+        raise \"second\"
+      rescue => e
+        retry if tries == 1
+        e.cause.inspect
+      end
+    end
+
+    def nested
+      begin
+        raise \"outer\"
+      rescue
+        begin
+          raise \"inner\"
+        rescue
+        end
+        raise ArgumentError, \"again\"
+      end
+    rescue => e
+      e.cause.message
+    end
+
+    def after_ensure
+      begin
+        begin
+          raise \"x\"
+        ensure
+          1
+        end
+      rescue
+      end
+      begin
+        raise \"y\"
+      rescue => e
+        e.cause.inspect
+      end
+    end
+
+    def bare_raise
+      begin
+        raise \"stale\"
+      rescue
+      end
+      raise
+    rescue => e
+      e.message
+    end
+
+    [handled, retried, nested, after_ensure, bare_raise].join(\",\")
+    ";
+    let binary = mrbc_compile("exception_cause_cleared_after_rescue", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, "nil,nil,outer,nil,unhandled exception");
+}
+
+#[test]
+fn exception_global_bang_test() {
+    let code = "
+    def read_bang
+      r = [$!.inspect]
+      v = (raise \"one\" rescue $!)
+      r << v.message
+      r << $!.inspect
+      begin
+        raise \"outer\"
+      rescue
+        begin
+          raise \"inner\"
+        rescue
+          r << $!.message
+        end
+        r << $!.message
+      end
+      r << $!.inspect
+      r.join(\",\")
+    end
+
+    read_bang
+    ";
+    let binary = mrbc_compile("exception_global_bang", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, "nil,one,nil,inner,outer,nil");
+}
+
+#[test]
+fn exception_full_message_test() {
+    let code = "
+    class ConfigError < StandardError
+    end
+
+    def load_config
+      raise ConfigError, \"missing key\"
+    end
+
+    begin
+      load_config
+    rescue => e
+      e.full_message
+    end
+    ";
+    let binary = mrbc_compile_with_lines("exception_full_message", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    let mut lines = result.lines();
+    let first = lines.next().unwrap();
+    assert!(first.ends_with(":6:in 'load_config': missing key (ConfigError)"));
+    assert!(lines.next().unwrap().ends_with(":10:in '<main>'"));
+}
+
+#[test]
+fn uncaught_custom_exception_test() {
+    let code = "
+    class QuotaExceeded < StandardError
+      def message
+        \"quota exceeded\"
+      end
+    end
+
+    raise QuotaExceeded
+    ";
+    let binary = mrbc_compile("exception_uncaught", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let err = vm.run().err().unwrap();
+    let err = err.downcast_ref::<mrubyedge::Error>().unwrap();
+    assert!(matches!(err, mrubyedge::Error::Exception(_)));
+    let class = vm.get_class_by_name("StandardError");
+    assert!(err.is_a(&mut vm, class));
+    let exc = vm.exception.as_ref().unwrap();
+    assert_eq!(exc.class.full_name(), "QuotaExceeded");
+}
@@ -0,0 +1,49 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn large_pool_and_syms_test() {
+    // Over 255 pool entries and symbols forces mrbc to emit EXT2 prefixes
+    let mut code = String::from("table = {}\n");
+    for i in 0..300 {
+        code.push_str(&format!("table[:key_{i}] = \"value_{i}\"\n"));
+    }
+    code.push_str("table[:key_0] + table[:key_299]\n");
+
+    let binary = mrbc_compile("large_pool_and_syms", code.leak());
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: String = result.as_ref().try_into().unwrap();
+    assert_eq!(result, "value_0value_299");
+}
+
+#[test]
+fn large_case_table_test() {
+    let mut code = String::from("def lookup(n)\n  case n\n");
+    for i in 0..300 {
+        code.push_str(&format!("  when {i} then \"entry_{i}\"\n"));
+    }
+    code.push_str("  else \"none\"\n  end\nend\n");
+
+    let binary = mrbc_compile("large_case_table", code.leak());
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.run().unwrap();
+
+    let result: String = mrb_funcall(&mut vm, None, "lookup", &[int(280)])
+        .unwrap()
+        .as_ref()
+        .try_into()
+        .unwrap();
+    assert_eq!(result, "entry_280");
+    let result: String = mrb_funcall(&mut vm, None, "lookup", &[int(999)])
+        .unwrap()
+        .as_ref()
+        .try_into()
+        .unwrap();
+    assert_eq!(result, "none");
+}
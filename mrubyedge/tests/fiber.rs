@@ -0,0 +1,201 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn fiber_resume_yield_test() {
+    let code = "
+    fiber = Fiber.new do |x|
+      y = Fiber.yield(x + 1)
+      z = Fiber.yield(y * 2)
+      [x, y, z]
+    end
+
+    [fiber.resume(1), fiber.resume(10), fiber.alive?, fiber.resume(:done), fiber.alive?]
+    ";
+    assert_eq!(
+        run_to_string("fiber_resume_yield", code),
+        "[2, 20, true, [1, 10, :done], false]"
+    );
+}
+
+#[test]
+fn fiber_generator_test() {
+    let code = "
+    fib = Fiber.new do
+      a, b = 0, 1
+      while true
+        Fiber.yield a
+        a, b = b, a + b
+      end
+    end
+
+    result = []
+    10.times { result << fib.resume }
+    result
+    ";
+    assert_eq!(
+        run_to_string("fiber_generator", code),
+        "[0, 1, 1, 2, 3, 5, 8, 13, 21, 34]"
+    );
+}
+
+#[test]
+fn fiber_shares_outer_variables_test() {
+    let code = "
+    def produce(log)
+      Fiber.yield log.size
+      log << :produced
+    end
+
+    log = []
+    count = 0
+    fiber = Fiber.new do
+      count += 1
+      produce(log)
+      count += 1
+      Fiber.yield count
+      log << :finished
+      nil
+    end
+
+    first = fiber.resume
+    count += 10
+    second = fiber.resume
+    fiber.resume
+    [first, second, count, log]
+    ";
+    assert_eq!(
+        run_to_string("fiber_shares_outer_variables", code),
+        "[0, 12, 12, [:produced, :finished]]"
+    );
+}
+
+#[test]
+fn fiber_nested_test() {
+    let code = "
+    inner = Fiber.new do
+      Fiber.yield :inner1
+      :inner2
+    end
+    outer = Fiber.new do
+      a = inner.resume
+      Fiber.yield a
+      b = inner.resume
+      [a, b]
+    end
+
+    [outer.resume, outer.resume, inner.alive?, outer.alive?]
+    ";
+    assert_eq!(
+        run_to_string("fiber_nested", code),
+        "[:inner1, [:inner1, :inner2], false, false]"
+    );
+}
+
+#[test]
+fn fiber_error_test() {
+    let code = "
+    def rescued
+      yield
+    rescue FiberError => e
+      e.class.inspect
+    end
+
+    done = Fiber.new { 1 }
+    done.resume
+    me = nil
+    me = Fiber.new { me.resume }
+    [
+      rescued { done.resume },
+      rescued { Fiber.yield },
+      rescued { me.resume },
+      rescued { Fiber.new { [1].map { |x| Fiber.yield x } }.resume },
+    ]
+    ";
+    assert_eq!(
+        run_to_string("fiber_error", code),
+        "[\"FiberError\", \"FiberError\", \"FiberError\", \"FiberError\"]"
+    );
+}
+
+#[test]
+fn fiber_yield_from_iterator_block_test() {
+    let code = "
+    def twice
+      yield :a
+      yield :b
+    end
+
+    f = Fiber.new do
+      [1, 2].each { |i| Fiber.yield i }
+      [:x].each_with_index { |v, i| Fiber.yield [v, i] }
+      2.times { |i| Fiber.yield i * 10 }
+      (5..6).each { |i| Fiber.yield i }
+      { k: 1 }.each { |k, v| Fiber.yield [k, v] }
+      twice { |s| Fiber.yield s }
+      n = 0
+      loop do
+        n += 1
+        break if n > 2
+        Fiber.yield n * 100
+      end
+      :done
+    end
+    values = []
+    values << f.resume while f.alive?
+
+    m = Fiber.new { [1].map { |x| Fiber.yield x } }
+    error = begin
+      m.resume
+    rescue FiberError => e
+      e.message
+    end
+    [values, error]
+    ";
+    assert_eq!(
+        run_to_string("fiber_yield_from_iterator_block", code),
+        "[[1, 2, [:x, 0], 0, 10, 5, 6, [:k, 1], :a, :b, 100, 200, :done], \
+         \"[FiberError] can't cross native method boundary\"]"
+    );
+}
+
+#[test]
+fn fiber_exception_propagates_test() {
+    let code = "
+    fiber = Fiber.new do
+      Fiber.yield 1
+      raise ArgumentError, \"boom\"
+    end
+
+    first = fiber.resume
+    begin
+      fiber.resume
+    rescue ArgumentError => e
+      [first, e.message, fiber.alive?]
+    end
+    ";
+    assert_eq!(
+        run_to_string("fiber_exception_propagates", code),
+        "[1, \"boom\", false]"
+    );
+}
+
+#[test]
+fn fiber_block_escapes_test() {
+    let code = "
+    fiber = Fiber.new do
+      n = 0
+      Fiber.yield(lambda { n += 1 })
+      n
+    end
+
+    inc = fiber.resume
+    inc.call
+    inc.call
+    fiber.resume
+    ";
+    assert_eq!(run_to_string("fiber_block_escapes", code), "2");
+}
@@ -0,0 +1,103 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn format_integer_directives_test() {
+    let code = r#"
+[
+  format("%d|%5d|%-5d|%05d|%+d|% d", 42, 42, 42, -42, 42, 42),
+  format("%i %u %.3d", 7, 8, 9),
+  format("%x %X %#x %o %#o %b %#B", 255, 255, 255, 8, 8, 5, 5),
+  format("%x %o %b %+x", -255, -8, -5, -255),
+  format("%#.3o %#.5o %#.1o %#5.3o", 8, 8, 8, 8),
+  format("%c%c", 65, "bcd"),
+  format("%d %d %d", "0x1f", 3.99, 2**70)
+]
+"#;
+    assert_eq!(
+        run_to_string("format_integer_directives_test", code),
+        r#"["42|   42|42   |-0042|+42| 42", "7 8 009", "ff FF 0xff 10 010 101 0B101", "..f01 ..70 ..1011 -ff", "010 00010 010   010", "Ab", "31 3 1180591620717411303424"]"#
+    );
+}
+
+#[test]
+fn format_float_directives_test() {
+    let code = r#"
+[
+  format("%f|%.2f|%08.3f|%+.1f|%#.0f", 1.5, 3.14159, 3.14159, 2.0, 3.0),
+  format("%e %E %.2e", 12345.678, 0.000123, 0.0),
+  format("%g %g %g %g %G", 100000.0, 1000000.0, 0.0001, 0.00001, 1.5e-10),
+  format("%f %5.1f %+f", Float::INFINITY, -Float::INFINITY, 0.0 / 0.0),
+  format("%.1f", 3)
+]
+"#;
+    assert_eq!(
+        run_to_string("format_float_directives_test", code),
+        r#"["1.500000|3.14|0003.142|+2.0|3.", "1.234568e+04 1.230000E-04 0.00e+00", "100000 1e+06 0.0001 1e-05 1.5E-10", "Inf  -Inf +NaN", "3.0"]"#
+    );
+}
+
+#[test]
+fn format_string_and_width_test() {
+    let code = r#"
+[
+  format("%s|%6s|%-6s|%.2s|%p|%%", "hi", "hi", "hi", "hello", :sym),
+  format("%*d|%-*d|%.*f", 5, 1, 5, 1, 2, 3.14159),
+  format("%2$s %1$s %2$s", "a", "b"),
+  sprintf("%s and %s", 1, nil)
+]
+"#;
+    assert_eq!(
+        run_to_string("format_string_and_width_test", code),
+        r#"["hi|    hi|hi    |he|:sym|%", "    1|1    |3.14", "b a b", "1 and "]"#
+    );
+}
+
+#[test]
+fn format_named_references_test() {
+    let code = r#"
+[
+  format("%<a>05.1f %{b}", a: 3.14159, b: :sym),
+  format("%<x>d", { x: 2 }),
+  format("%-4{x}|", x: 1),
+  "%{a}-%<b>x" % { a: 1, b: 255 },
+  "%s-%s" % [1, 2],
+  "%05d" % 3
+]
+"#;
+    assert_eq!(
+        run_to_string("format_named_references_test", code),
+        r#"["003.1 sym", "2", "1   |", "1-ff", "1-2", "00003"]"#
+    );
+}
+
+#[test]
+fn format_argument_errors_test() {
+    let code = r#"
+def error_of
+  yield
+  nil
+rescue => e
+  e.class
+end
+
+[
+  error_of { format("%d") },
+  error_of { format("%y", 1) },
+  error_of { format("abc %") },
+  error_of { format("%d", "abc") },
+  error_of { format("%d", nil) },
+  error_of { format("%f", "x") },
+  error_of { format("%<a>s", 1) },
+  error_of { format("%<a>s", { b: 1 }) },
+  error_of { format("%d %<a>s", 1, { a: 1 }) }
+]
+"#;
+    assert_eq!(
+        run_to_string("format_argument_errors_test", code),
+        "[ArgumentError, ArgumentError, ArgumentError, ArgumentError, TypeError, ArgumentError, ArgumentError, KeyError, ArgumentError]"
+    );
+}
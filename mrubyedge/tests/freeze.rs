@@ -0,0 +1,164 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn frozen_containers_raise_frozen_error_test() {
+    let code = r#"
+def attempt
+  yield
+  :ok
+rescue FrozenError => e
+  e.class
+end
+
+s = "abc".freeze
+a = [1, 2].freeze
+h = { a: 1 }.freeze
+[
+  s.frozen?,
+  attempt { s << "d" },
+  attempt { s.upcase! },
+  attempt { a << 3 },
+  attempt { a[0] = 5 },
+  attempt { a.pop },
+  attempt { h[:b] = 2 },
+  attempt { h.delete(:a) },
+  s.upcase,
+  a.map { |x| x * 2 },
+  s.dup.frozen?,
+  (a.dup << 3)
+]
+"#;
+    assert_eq!(
+        run_to_string("frozen_containers_raise_frozen_error_test", code),
+        r#"[true, FrozenError, FrozenError, FrozenError, FrozenError, FrozenError, FrozenError, FrozenError, "ABC", [2, 4], false, [1, 2, 3]]"#
+    );
+}
+
+#[test]
+fn frozen_error_message_test() {
+    let code = r#"
+begin
+  [1].freeze << 2
+rescue => e
+  [e.class.ancestors.include?(RuntimeError), e.message]
+end
+"#;
+    assert_eq!(
+        run_to_string("frozen_error_message_test", code),
+        r#"[true, "[FrozenError] can't modify frozen Array: [1]"]"#
+    );
+}
+
+#[test]
+fn immediates_are_always_frozen_test() {
+    let code = r#"
+[1.frozen?, 1.5.frozen?, :sym.frozen?, nil.frozen?, true.frozen?, "str".frozen?, [].frozen?, Object.new.frozen?]
+"#;
+    assert_eq!(
+        run_to_string("immediates_are_always_frozen_test", code),
+        "[true, true, true, true, true, false, false, false]"
+    );
+}
+
+#[test]
+fn frozen_object_rejects_instance_variable_assignment_test() {
+    let code = r#"
+class Point
+  attr_accessor :x
+
+  def initialize(x)
+    @x = x
+  end
+
+  def move!
+    @x += 1
+  end
+end
+
+def attempt
+  yield
+  :ok
+rescue FrozenError
+  :frozen
+end
+
+pt = Point.new(1)
+pt.move!
+pt.freeze
+[
+  pt.frozen?,
+  attempt { pt.move! },
+  attempt { pt.x = 10 },
+  attempt { pt.instance_variable_set(:@x, 10) },
+  pt.x
+]
+"#;
+    assert_eq!(
+        run_to_string(
+            "frozen_object_rejects_instance_variable_assignment_test",
+            code
+        ),
+        "[true, :frozen, :frozen, :frozen, 2]"
+    );
+}
+
+#[test]
+fn data_instances_are_frozen_test() {
+    let code = r#"
+Coord = Data.define(:lat, :lng)
+c = Coord.new(lat: 1, lng: 2)
+[c.frozen?, c.with(lat: 3).frozen?]
+"#;
+    assert_eq!(
+        run_to_string("data_instances_are_frozen_test", code),
+        "[true, true]"
+    );
+}
+
+#[test]
+fn frozen_string_literal_mode_test() {
+    let code = r##"
+def attempt
+  yield
+  :ok
+rescue FrozenError
+  :frozen
+end
+
+name = "world"
+literal = "hello"
+interpolated = "hello #{name}"
+[literal.frozen?, attempt { literal << "!" }, interpolated.frozen?, attempt { interpolated << "!" }, interpolated]
+"##;
+    let binary = mrbc_compile("frozen_string_literal_mode_test", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.frozen_string_literal = true;
+    let result = vm.run().unwrap();
+    let result: String = mrb_funcall(&mut vm, Some(result), "inspect", &[])
+        .unwrap()
+        .as_ref()
+        .try_into()
+        .unwrap();
+    assert_eq!(result, r#"[true, :frozen, false, :ok, "hello world!"]"#);
+}
+
+#[test]
+fn string_plus_leaves_frozen_receiver_unchanged_test() {
+    let code = r##"
+S = "const".freeze
+s = "a".freeze
+t = s + "b"
+u = S + "!"
+v = "#{s}c"
+[s, t, s.frozen?, t.frozen?, S, u, u.frozen?, v, v.frozen?]
+"##;
+    assert_eq!(
+        run_to_string("string_plus_leaves_frozen_receiver_unchanged_test", code),
+        r#"["a", "ab", true, false, "const", "const!", false, "ac", false]"#
+    );
+}
@@ -405,3 +405,56 @@ fn hash_flatten_test() {
     assert!(ints.contains(&1));
     assert!(ints.contains(&2));
 }
+
+#[test]
+fn hash_insertion_order_test() {
+    let code = r#"
+    def test_hash_order
+      h = {"z" => 1, "a" => 2, :m => 3}
+      h[10] = 4
+      h["a"] = 5
+      keys = []
+      h.each { |k, v| keys << k }
+      [h.inspect, h.keys.inspect, h.values.inspect, keys.inspect, h.flatten.inspect].join(" | ")
+    end
+    "#;
+    let binary = mrbc_compile("hash_insertion_order", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.run().unwrap();
+
+    let args = vec![];
+    let result = mrb_funcall(&mut vm, None, "test_hash_order", &args).unwrap();
+    let result: String = result.as_ref().try_into().unwrap();
+    assert_eq!(
+        result,
+        "{\"z\"=>1, \"a\"=>5, :m=>3, 10=>4} | [\"z\", \"a\", :m, 10] | [1, 5, 3, 4] | \
+         [\"z\", \"a\", :m, 10] | [\"z\", 1, \"a\", 5, :m, 3, 10, 4]"
+    );
+}
+
+#[test]
+fn hash_delete_and_reinsert_order_test() {
+    let code = r#"
+    def test_hash_reinsert
+      h = {}
+      20.times { |i| h[i] = i * i }
+      (0...18).each { |i| h.delete(i) unless i == 5 }
+      h[0] = :back
+      other = h.merge({5 => :five, :new => 1})
+      [h.keys.inspect, other.inspect].join(" | ")
+    end
+    "#;
+    let binary = mrbc_compile("hash_reinsert_order", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.run().unwrap();
+
+    let args = vec![];
+    let result = mrb_funcall(&mut vm, None, "test_hash_reinsert", &args).unwrap();
+    let result: String = result.as_ref().try_into().unwrap();
+    assert_eq!(
+        result,
+        "[5, 18, 19, 0] | {5=>:five, 18=>324, 19=>361, 0=>:back, :new=>1}"
+    );
+}
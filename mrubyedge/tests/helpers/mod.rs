@@ -7,7 +7,7 @@ use mrubyedge::yamrb::value::RObject;
 pub use mrubyedge::yamrb::helpers::mrb_funcall;
 
 macro_rules! mrbc_compile_ {
-    ($fname:expr, $code:expr) => {{
+    ($fname:expr, $code:expr $(, $flag:expr)*) => {{
         use std::{ffi::CStr, fs::File, io::Write};
 
         let mut src = std::env::temp_dir();
@@ -28,6 +28,7 @@ macro_rules! mrbc_compile_ {
         let args = [
             CStr::from_bytes_with_nul(b"mrbc\0").unwrap().as_ptr(),
             // CStr::from_bytes_with_nul(b"-v\0").unwrap().as_ptr(),
+            $(CStr::from_bytes_with_nul($flag).unwrap().as_ptr(),)*
             CStr::from_bytes_with_nul(b"-o\0").unwrap().as_ptr(),
             CStr::from_bytes_with_nul(dest0.as_bytes())
                 .unwrap()
@@ -47,6 +48,12 @@ pub(crate) fn mrbc_compile(fname: &'static str, code: &'static str) -> Vec<u8> {
     std::fs::read(dest).unwrap()
 }
 
+/// Compiles with `-g` so the binary carries filenames and line numbers.
+pub(crate) fn mrbc_compile_with_lines(fname: &'static str, code: &'static str) -> Vec<u8> {
+    let dest = mrbc_compile_!(fname, code, b"-g\0");
+    std::fs::read(dest).unwrap()
+}
+
 macro_rules! mrbc_compile_debug_ {
     ($fname:expr, $code:expr) => {{
         use std::{ffi::CStr, fs::File, io::Write};
@@ -88,6 +95,20 @@ pub(crate) fn mrbc_compile_debug(fname: &'static str, code: &'static str) -> Vec
     std::fs::read(dest).unwrap()
 }
 
+/// Runs the compiled code and returns `inspect` of its result.
+pub(crate) fn run_to_string(name: &'static str, code: &'static str) -> String {
+    let binary = mrbc_compile(name, code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: String = mrb_funcall(&mut vm, Some(result), "inspect", &[])
+        .unwrap()
+        .as_ref()
+        .try_into()
+        .unwrap();
+    result
+}
+
 pub(crate) fn int(n: i64) -> Rc<RObject> {
     Rc::new(RObject::integer(n))
 }
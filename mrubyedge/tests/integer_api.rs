@@ -0,0 +1,140 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn integer_division_family_test() {
+    let code = r#"
+[
+  7.divmod(2), -7.divmod(2), 7.divmod(-2), 7.divmod(2.5),
+  -7.div(2), 7.div(2.5), 7.modulo(-3),
+  7.remainder(-3), -7.remainder(3), 7.remainder(2.5),
+  7.fdiv(2)
+]
+"#;
+    assert_eq!(
+        run_to_string("integer_division_family_test", code),
+        "[[3, 1], [-4, 1], [-4, -1], [2, 2], -4, 2, -2, 1, -1, 2, 3.5]"
+    );
+}
+
+#[test]
+fn integer_gcd_lcm_pow_test() {
+    let code = r#"
+[
+  12.gcd(18), -12.gcd(18), 0.gcd(5),
+  4.lcm(6), -4.lcm(6), 0.lcm(3),
+  3.pow(4, 5), 2.pow(100, 1_000_000_007), (-3).pow(3, 7), 5.pow(0, 1),
+  2.pow(10), 2.pow(70) == 2 ** 70
+]
+"#;
+    assert_eq!(
+        run_to_string("integer_gcd_lcm_pow_test", code),
+        "[6, 6, 5, 12, 12, 0, 1, 976371285, 1, 0, 1024, true]"
+    );
+}
+
+#[test]
+fn integer_digits_bit_length_test() {
+    let code = r#"
+[
+  1234.digits, 0.digits, 255.digits(16), (2 ** 64).digits.size,
+  0.bit_length, 255.bit_length, 256.bit_length, -1.bit_length, -256.bit_length, -257.bit_length,
+  (2 ** 64).bit_length,
+  255.to_s(2), 255.to_s(16), -255.to_s(36)
+]
+"#;
+    assert_eq!(
+        run_to_string("integer_digits_bit_length_test", code),
+        r#"[[4, 3, 2, 1], [0], [15, 15], 20, 0, 8, 9, 0, 8, 9, 65, "11111111", "ff", "-73"]"#
+    );
+}
+
+#[test]
+fn integer_iteration_test() {
+    let code = r#"
+r = []
+1.upto(3) { |i| r << i }
+3.downto(1) { |i| r << i }
+1.upto(2.5) { |i| r << i }
+1.step(10, 4) { |i| r << i }
+10.step(1, -4) { |i| r << i }
+1.step(by: 2, to: 5) { |i| r << i }
+1.step(2, 0.5) { |f| r << f }
+1.step { |i| break if i > 3; r << i }
+r << 1.upto(3).to_a
+r << 3.downto(1).to_a
+r << 1.step(by: 2, to: 7).to_a
+r << 10.step(1, -3).to_a
+r << [1.succ, 1.next, 1.pred, (2 ** 64 - 1).succ == 2 ** 64]
+r
+"#;
+    assert_eq!(
+        run_to_string("integer_iteration_test", code),
+        "[1, 2, 3, 3, 2, 1, 1, 2, 1, 5, 9, 10, 6, 2, 1, 3, 5, 1, 1.5, 2, 1, 2, 3, \
+         [1, 2, 3], [3, 2, 1], [1, 3, 5, 7], [10, 7, 4, 1], [2, 2, 0, true]]"
+    );
+}
+
+#[test]
+fn integer_predicates_and_sqrt_test() {
+    let code = r#"
+[
+  [2.even?, 3.even?, 3.odd?, -3.odd?, (2 ** 64).even?],
+  [0.zero?, 1.zero?, 1.positive?, 0.positive?, -1.negative?, 0.negative?],
+  [Integer.sqrt(0), Integer.sqrt(24), Integer.sqrt(25), Integer.sqrt(10 ** 40) == 10 ** 20]
+]
+"#;
+    assert_eq!(
+        run_to_string("integer_predicates_and_sqrt_test", code),
+        "[[true, false, true, true, true], [true, false, true, false, true, false], [0, 4, 5, true]]"
+    );
+}
+
+#[test]
+fn integer_rounding_test() {
+    let code = r#"
+[
+  15.round(-1), 14.round(-1), -15.round(-1), 25.round(-1, half: :even),
+  35.round(-1, half: :even), 25.round(-1, half: :down), -25.round(-1, half: :down),
+  1234.floor(-2), -1234.floor(-2), 1234.ceil(-2), -1234.ceil(-2), 1200.ceil(-2),
+  12.round(2), 12.floor, 5.round(-2)
+]
+"#;
+    assert_eq!(
+        run_to_string("integer_rounding_test", code),
+        "[20, 10, -20, 20, 40, 20, -20, 1200, -1300, 1300, -1200, 1200, 12, 12, 0]"
+    );
+}
+
+#[test]
+fn integer_api_errors_test() {
+    let code = r#"
+def error_of
+  yield
+  nil
+rescue => e
+  # Drop the "Invalid argument: " style prefix the VM adds to its errors
+  message = e.message.delete_prefix("Invalid argument: ").delete_prefix("Out of range: ")
+  [e.class, message.delete_prefix("[TypeError] ")]
+end
+
+[
+  error_of { 2.pow(-1, 5) },
+  error_of { 2.pow(3, 0) },
+  error_of { -1.digits },
+  error_of { 10.digits(1) },
+  error_of { 10.digits(-2) },
+  error_of { Integer.sqrt(-1) },
+  error_of { 1.step(5, 0) {} },
+  error_of { 1.gcd(1.5) },
+  error_of { 15.round(-1, half: :sideways) },
+]
+"#;
+    assert_eq!(
+        run_to_string("integer_api_errors_test", code),
+        r#"[[RangeError, "Integer#pow() 2nd argument not allowed to be negative when 3rd argument specified"], [ZeroDivisionError, "divided by 0"], [ArgumentError, "out of domain"], [ArgumentError, "invalid radix 1"], [ArgumentError, "negative radix"], [ArgumentError, "Numerical argument is out of domain - \"isqrt\""], [ArgumentError, "step can't be 0"], [TypeError, "not an integer"], [ArgumentError, "invalid rounding mode: sideways"]]"#
+    );
+}
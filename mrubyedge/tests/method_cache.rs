@@ -0,0 +1,121 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn method_cache_redefine_test() {
+    let code = "
+    class Greeter
+      def hello
+        \"v1\"
+      end
+    end
+
+    def call(obj)
+      obj.hello
+    end
+
+    g = Greeter.new
+    results = [call(g)]
+    class Greeter
+      def hello
+        \"v2\"
+      end
+    end
+    results << call(g)
+    results.join(\",\")
+    ";
+    let binary = mrbc_compile("method_cache_redefine", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, "v1,v2");
+}
+
+#[test]
+fn method_cache_include_and_extend_test() {
+    let code = "
+    module Loud
+      def speak
+        \"LOUD\"
+      end
+    end
+
+    class Base
+      def speak
+        \"base\"
+      end
+    end
+
+    class Child < Base
+    end
+
+    def call(obj)
+      obj.speak
+    end
+
+    c = Child.new
+    other = Child.new
+    results = [call(c)]
+    Child.include(Loud)
+    results << call(c)
+    class Base
+      def speak
+        \"ignored\"
+      end
+    end
+    results << call(c)
+    o = Base.new
+    results << call(o)
+    o.extend(Loud)
+    results << call(o)
+    def other.speak
+      \"singleton\"
+    end
+    results << call(other)
+    results.join(\",\")
+    ";
+    let binary = mrbc_compile("method_cache_include", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, "base,LOUD,LOUD,ignored,LOUD,singleton");
+}
+
+#[test]
+fn method_cache_alias_and_undef_test() {
+    let code = "
+    class Box
+      def value
+        1
+      end
+
+      def method_missing(name, *args)
+        \"missing #{name}\"
+      end
+    end
+
+    def call(obj)
+      obj.other
+    end
+
+    b = Box.new
+    results = [call(b)]
+    class Box
+      alias other value
+    end
+    results << call(b)
+    class Box
+      undef other
+    end
+    results << call(b)
+    results.inspect
+    ";
+    let binary = mrbc_compile("method_cache_alias", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, "[\"missing other\", 1, \"missing other\"]");
+}
@@ -53,6 +53,36 @@ User.new.greet
     assert_eq!(value, "hello");
 }
 
+#[test]
+fn module_can_define_singleton_methods() {
+    let script = r#"
+module Config
+  def self.name_prefix
+    "app"
+  end
+end
+
+module Config
+  def self.full_name(name)
+    name_prefix + "-" + name
+  end
+end
+
+Config.full_name("web")
+"#;
+
+    let binary = mrbc_compile("module_singleton_method", script);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+
+    let value: String = result
+        .as_ref()
+        .try_into()
+        .expect("full_name should return string");
+    assert_eq!(value, "app-web");
+}
+
 #[test]
 fn modules_can_be_used_as_namespace() {
     let script = r#"
@@ -0,0 +1,41 @@
+extern crate mec_mrbc_sys;
+
+use std::ffi::CString;
+use std::path::Path;
+
+// Set MRUBYEDGE_UPDATE_MRBLIB=1 to regenerate mrblib.mrb after editing the sources
+#[test]
+fn mrblib_bytecode_is_up_to_date_test() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/yamrb/prelude/mrblib");
+    let mut sources: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rb"))
+        .collect();
+    sources.sort();
+
+    let dest = std::env::temp_dir().join(format!("mrblib.{}.mrb", std::process::id()));
+    let mut args = vec![
+        CString::new("mrbc").unwrap(),
+        CString::new("-o").unwrap(),
+        CString::new(dest.to_str().unwrap()).unwrap(),
+    ];
+    for src in sources.iter() {
+        args.push(CString::new(src.to_str().unwrap()).unwrap());
+    }
+    let argv: Vec<_> = args.iter().map(|arg| arg.as_ptr()).collect();
+    let status =
+        unsafe { mec_mrbc_sys::mrbc_main(argv.len() as i32, argv.as_ptr() as *mut *mut i8) };
+    assert_eq!(status, 0, "failed to compile mrblib");
+    let compiled = std::fs::read(&dest).unwrap();
+    std::fs::remove_file(&dest).unwrap();
+
+    let mrblib = dir.join("mrblib.mrb");
+    if std::env::var_os("MRUBYEDGE_UPDATE_MRBLIB").is_some() {
+        std::fs::write(&mrblib, &compiled).unwrap();
+    }
+    assert!(
+        std::fs::read(&mrblib).is_ok_and(|checked_in| checked_in == compiled),
+        "mrblib.mrb is stale; run `MRUBYEDGE_UPDATE_MRBLIB=1 cargo test --test mrblib`"
+    );
+}
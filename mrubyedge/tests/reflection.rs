@@ -0,0 +1,183 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn define_method_captures_closure_test() {
+    let code = r##"
+class Greeter
+  def initialize(name)
+    @name = name
+  end
+
+  greeting = "Hello"
+  define_method(:greet) { |suffix| "#{greeting}, #{@name}#{suffix}" }
+  define_method(:twice) { |x, &blk| blk.call(blk.call(x)) }
+  greeting = "Hi"
+end
+
+module Shouting
+  define_method(:shout, proc { |s| s.upcase })
+end
+Greeter.include(Shouting)
+
+g = Greeter.new("mruby")
+[g.greet("!"), g.twice(3) { |v| v * 2 }, g.shout("hey")]
+"##;
+    assert_eq!(
+        run_to_string("define_method", code),
+        r#"["Hi, mruby!", 12, "HEY"]"#
+    );
+}
+
+#[test]
+fn define_method_runs_as_method_test() {
+    let code = r##"
+def error_of
+  yield
+rescue ArgumentError => e
+  e.message
+end
+
+class Base
+  def greet(x)
+    "base #{x}"
+  end
+end
+
+class Child < Base
+  limit = 10
+  define_method(:clamp) { |x| return limit if x > limit; x }
+  define_method(:first_even) { |xs| xs.each { |x| return x if x.even? }; nil }
+  define_method(:pair) { |a, b = :b| [a, b] }
+  define_method(:greet) { |x| "child " + super(x) }
+  define_method(:depth) { |n| n == 0 ? 0 : 1 + depth(n - 1) }
+  define_method(:pause) { |n| Fiber.yield(n); n + 1 }
+end
+
+c = Child.new
+fiber = Fiber.new { c.pause(1) }
+o = Object.new
+o.define_singleton_method(:hi) { |name| "hi #{name}" }
+[
+  c.clamp(3), c.clamp(30), c.first_even([1, 4, 6]),
+  c.pair(1), c.pair(1, 2), error_of { c.pair }, error_of { c.pair(1, 2, 3) },
+  c.greet(:x), c.depth(1000), fiber.resume, fiber.resume,
+  o.hi("you"), error_of { o.hi },
+]
+"##;
+    assert_eq!(
+        run_to_string("define_method_runs_as_method", code),
+        r#"[3, 10, 4, [1, :b], [1, 2], "Invalid argument: wrong number of arguments (given 0, expected 1..2)", "Invalid argument: wrong number of arguments (given 3, expected 1..2)", "child base x", 1000, 1, 2, "hi you", "Invalid argument: wrong number of arguments (given 0, expected 1)"]"#
+    );
+}
+
+#[test]
+fn closures_in_same_frame_share_variables_test() {
+    let code = "
+def counter
+  n = 0
+  inc = -> { n += 1 }
+  get = -> { n }
+  [inc, get]
+end
+inc, get = counter
+inc.call
+inc.call
+get.call
+";
+    assert_eq!(run_to_string("shared_closure", code), "2");
+}
+
+#[test]
+fn instance_variable_reflection_test() {
+    let code = "
+class Point
+  def initialize(x, y)
+    @x = x
+    @y = y
+  end
+end
+pt = Point.new(1, 2)
+pt.instance_variable_set(:@z, 3)
+pt.instance_variable_set('@x', 10)
+[
+  pt.instance_variables,
+  pt.instance_variable_get(:@x),
+  pt.instance_variable_get(:@w),
+  pt.instance_variable_defined?(:@z),
+  pt.instance_variable_defined?(:@w),
+]
+";
+    assert_eq!(
+        run_to_string("ivar_reflection", code),
+        "[[:@x, :@y, :@z], 10, nil, true, false]"
+    );
+}
+
+#[test]
+fn instance_variable_name_error_test() {
+    let code = "
+errors = []
+['foo', '@@bar', '@'].each do |name|
+  begin
+    Object.new.instance_variable_get(name)
+  rescue NameError
+    errors << name
+  end
+end
+errors
+";
+    assert_eq!(
+        run_to_string("ivar_name_error", code),
+        r#"["foo", "@@bar", "@"]"#
+    );
+}
+
+#[test]
+fn define_singleton_method_test() {
+    let code = "
+base = 10
+obj = Object.new
+obj.define_singleton_method(:plus) { |x| x + base }
+
+class Config
+  def self.defaults
+    { debug: false }
+  end
+end
+Config.define_singleton_method(:debug?) { defaults[:debug] }
+
+[obj.plus(5), obj.singleton_methods, Config.debug?, Config.singleton_methods, Object.new.singleton_methods]
+";
+    assert_eq!(
+        run_to_string("define_singleton_method", code),
+        "[15, [:plus], false, [:debug?, :defaults], []]"
+    );
+}
+
+#[test]
+fn send_test() {
+    let code = "
+class Calc
+  def add(a, b)
+    a + b
+  end
+
+  def apply(x)
+    block_given? ? yield(x) : x
+  end
+end
+c = Calc.new
+[
+  c.send(:add, 1, 2),
+  c.__send__('add', 3, 4),
+  c.send(:apply, 5) { |x| x * 10 },
+  c.send(:apply, 5),
+  [1, 2].send(:map) { |x| x + 1 },
+]
+";
+    assert_eq!(run_to_string("send", code), "[3, 7, 50, 5, [2, 3]]");
+}
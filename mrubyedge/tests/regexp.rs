@@ -4,7 +4,10 @@ extern crate mrubyedge;
 
 mod helpers;
 
+use std::rc::Rc;
+
 use helpers::*;
+use mrubyedge::yamrb::value::RObject;
 
 #[test]
 fn regexp_match_operator_test() {
@@ -146,3 +149,93 @@ fn regexp_no_match_test() {
         mrubyedge::yamrb::value::RValue::Nil
     ));
 }
+
+#[test]
+fn regexp_special_vars_test() {
+    let code = r#"
+    def test_special_vars
+      if "version 3.4.1" =~ /([0-9]+)\.([0-9]+)/
+        [$~[0], $1, $2, $3, $&, $`, $']
+      end
+    end
+    "#;
+    let binary = mrbc_compile("regexp_special_vars", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.run().unwrap();
+
+    let args = vec![];
+    let result = mrb_funcall(&mut vm, None, "test_special_vars", &args).unwrap();
+    let result: Vec<Rc<RObject>> = result.as_ref().try_into().unwrap();
+    let s = |i: usize| -> String { result[i].as_ref().try_into().unwrap() };
+    assert_eq!(s(0), "3.4");
+    assert_eq!(s(1), "3");
+    assert_eq!(s(2), "4");
+    assert!(result[3].is_nil());
+    assert_eq!(s(4), "3.4");
+    assert_eq!(s(5), "version ");
+    assert_eq!(s(6), ".1");
+}
+
+#[test]
+fn regexp_special_vars_frame_local_test() {
+    let code = r#"
+    def inner_match
+      "abc" =~ /(b)/
+      $1
+    end
+
+    def test_frame_local
+      "xyz" =~ /(y)/
+      inner = inner_match
+      in_block = nil
+      [1].each { in_block = $1 }
+      [$1, inner, in_block, from_block]
+    end
+
+    def from_block
+      [1].each { "abc" =~ /(c)/ }
+      $1
+    end
+    "#;
+    let binary = mrbc_compile("regexp_special_vars_frame", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.run().unwrap();
+
+    let args = vec![];
+    let result = mrb_funcall(&mut vm, None, "test_frame_local", &args).unwrap();
+    let result: Vec<Rc<RObject>> = result.as_ref().try_into().unwrap();
+    let outer: String = result[0].as_ref().try_into().unwrap();
+    let inner: String = result[1].as_ref().try_into().unwrap();
+    assert_eq!(outer, "y");
+    assert_eq!(inner, "b");
+    // Blocks share `$~` with the method they appear in, both ways
+    let in_block: String = result[2].as_ref().try_into().unwrap();
+    assert_eq!(in_block, "y");
+    let from_block: String = result[3].as_ref().try_into().unwrap();
+    assert_eq!(from_block, "c");
+}
+
+#[test]
+fn regexp_special_vars_reset_test() {
+    let code = r#"
+    def test_reset
+      "abc" =~ /(b)/
+      "abc" =~ /(z)/
+      failed = $1
+      "abc" =~ /(c)/
+      $~ = nil
+      [failed, $1, $~]
+    end
+    "#;
+    let binary = mrbc_compile("regexp_special_vars_reset", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.run().unwrap();
+
+    let args = vec![];
+    let result = mrb_funcall(&mut vm, None, "test_reset", &args).unwrap();
+    let result: Vec<Rc<RObject>> = result.as_ref().try_into().unwrap();
+    assert!(result.iter().all(|v| v.is_nil()));
+}
@@ -0,0 +1,186 @@
+#![cfg(feature = "mruby-regexp")]
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn matchdata_groups_test() {
+    let code = r#"
+m = /(?<year>\d+)-(?<mon>\d+)?x?/.match("on 2024-x")
+[m, m[0], m[-1], m[:year], m["mon"], m.captures, m.named_captures, m.pre_match, m.post_match, m.to_a, m.size]
+"#;
+    assert_eq!(
+        run_to_string("matchdata_groups_test", code),
+        r#"[#<MatchData "2024-x" year:"2024" mon:nil>, "2024-x", nil, "2024", nil, ["2024", nil], {"year"=>"2024", "mon"=>nil}, "on ", "", ["2024-x", "2024", nil], 3]"#
+    );
+}
+
+#[test]
+fn matchdata_positions_count_characters_test() {
+    let code = r#"
+m = "日本語テキスト".match(/テ(?<ki>キ)/)
+[m.begin(0), m.end(0), m.begin(1), m.end(:ki), m.pre_match, "日本語テキスト" =~ /キ/]
+"#;
+    assert_eq!(
+        run_to_string("matchdata_positions_count_characters_test", code),
+        r#"[3, 5, 4, 5, "日本語", 4]"#
+    );
+}
+
+#[test]
+fn sub_and_gsub_replacements_test() {
+    let code = r#"
+[
+  "hello world".sub(/o/, "0"),
+  "hello world".gsub(/o/, "0"),
+  "2024-05".sub(/(\d+)-(\d+)/, '\2/\1'),
+  "2024-05".sub(/(?<y>\d+)-(?<m>\d+)/, '\k<m>/\k<y> (\0)'),
+  "a.b".sub(".", '[\&]'),
+  "a-b_c".gsub(/[-_]/, { "-" => "_", "_" => "-" }),
+  "hello world".gsub(/[eo]/, "e" => "3", "o" => "0"),
+  "john smith".gsub(/\w+/) { |w| w.upcase },
+  "abc".gsub(/x/, "y")
+]
+"#;
+    assert_eq!(
+        run_to_string("sub_and_gsub_replacements_test", code),
+        r#"["hell0 world", "hell0 w0rld", "05/2024", "05/2024 (2024-05)", "a[.]b", "a_b-c", "h3ll0 w0rld", "JOHN SMITH", "abc"]"#
+    );
+}
+
+#[test]
+fn destructive_sub_and_last_match_test() {
+    let code = r#"
+s = "foo"
+r = [s.sub!(/x/, "y"), s.gsub!(/(o)/, '<\1>'), s, $~[1]]
+begin
+  "bar".freeze.sub!(/a/, "o")
+rescue FrozenError => e
+  r << e.class
+end
+r
+"#;
+    assert_eq!(
+        run_to_string("destructive_sub_and_last_match_test", code),
+        r#"[nil, "f<o><o>", "f<o><o>", "o", FrozenError]"#
+    );
+}
+
+#[test]
+fn scan_test() {
+    let code = r#"
+found = []
+"a1b22".scan(/\d+/) { |n| found << n.to_i }
+["a1b22c333".scan(/\d+/), "a1b22".scan(/([a-z])(\d+)/), "a.b.c".scan("."), found]
+"#;
+    assert_eq!(
+        run_to_string("scan_test", code),
+        r#"[["1", "22", "333"], [["a", "1"], ["b", "22"]], [".", "."], [1, 22]]"#
+    );
+}
+
+#[test]
+fn split_by_regexp_test() {
+    let code = r#"
+[
+  "a, b,c,,".split(/,\s*/),
+  "abc".split(//),
+  "a1b2c".split(/(\d)/),
+  "a,b,c".split(/,/, 2),
+  "a,b,,".split(/,/, -1),
+  "a b".split(" ")
+]
+"#;
+    assert_eq!(
+        run_to_string("split_by_regexp_test", code),
+        r#"[["a", "b", "c"], ["a", "b", "c"], ["a", "1", "b", "2", "c"], ["a", "b,c"], ["a", "b", "", ""], ["a", "b"]]"#
+    );
+}
+
+#[test]
+fn regexp_options_test() {
+    let code = r#"
+r = [
+  /abc/i.match?("xABCx"),
+  /a.c/.match?("a\nc"),
+  /a.c/m.match?("a\nc"),
+  /a b c/x.match?("abc"),
+  Regexp.new("abc", Regexp::IGNORECASE).match?("ABC"),
+  Regexp.new("abc", "i").options,
+  /^b$/.match?("a\nb\nc"),
+  /abc/mix,
+  "ab".match?("a."),
+  $~
+]
+begin
+  Regexp.new("(")
+rescue RegexpError => e
+  r << e.class
+end
+r
+"#;
+    assert_eq!(
+        run_to_string("regexp_options_test", code),
+        "[true, false, true, true, true, 1, true, /abc/mix, true, nil, RegexpError]"
+    );
+}
+
+#[test]
+fn string_block_sees_match_variables_test() {
+    let code = r##"
+s = "hello world"
+scanned = []
+s.scan(/(o)(.)?/) { scanned << [$1, $2, $~[0]] }
+[
+  s.gsub(/(l+)/) { $1.upcase },
+  s.sub(/(w)(o)/) { "#{$2}#{$1}#{$~.pre_match.size}" },
+  scanned,
+  $~[0],
+]
+"##;
+    assert_eq!(
+        run_to_string("string_block_sees_match_variables_test", code),
+        r#"["heLLo worLd", "hello ow6rld", [["o", " ", "o "], ["o", "r", "or"]], "wo"]"#
+    );
+}
+
+#[test]
+fn partition_by_regexp_test() {
+    let code = r#"
+r = [
+  "a1b2".partition(/\d/),
+  "a1b2".rpartition(/\d/),
+  "aaa".rpartition(/a+/),
+  "abc".partition(/x/),
+  "abc".rpartition(/x/),
+  "日本語".rpartition(/本/),
+  "a-b".partition("-"),
+]
+"key=value".partition(/(=)/)
+r << $1
+"#;
+    assert_eq!(
+        run_to_string("partition_by_regexp_test", code),
+        r#"[["a", "1", "b2"], ["a1b", "2", ""], ["aa", "a", ""], ["abc", "", ""], ["", "", "abc"], ["日", "本", "語"], ["a", "-", "b"], "="]"#
+    );
+}
+
+#[test]
+fn empty_match_after_match_test() {
+    let code = r#"
+[
+  "aaa".gsub(/a*/, "-"),
+  "abc".gsub(/x*/, "-"),
+  "hello".gsub(/l*/, "_"),
+  "日本".gsub(//, "|"),
+  "aaa".scan(/a*/),
+  "aaa".sub(/a*/, "-")
+]
+"#;
+    assert_eq!(
+        run_to_string("empty_match_after_match_test", code),
+        r#"["--", "-a-b-c-", "_h_e__o_", "|日|本|", ["aaa", ""], "-"]"#
+    );
+}
@@ -0,0 +1,100 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn array_splat_test() {
+    let code = "
+    a = [1, 2]
+    b = [*a, 3, 4]
+    c = [0, *a]
+    d = [*nil, *5]
+    [b, c, d].inspect
+    ";
+    let binary = mrbc_compile("array_splat", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: String = result.as_ref().try_into().unwrap();
+    assert_eq!(result, "[[1, 2, 3, 4], [0, 1, 2], [5]]");
+}
+
+#[test]
+fn call_with_splat_args_test() {
+    let code = "
+    def sum3(a, b, c)
+      a + b * 10 + c * 100
+    end
+
+    def scaled(a, b, scale: 1, offset: 0)
+      (a + b) * scale + offset
+    end
+
+    args = [1, 2]
+    opts = {scale: 3}
+    [sum3(*args, 3), sum3(0, *args), scaled(*args, **opts), scaled(*args, **opts, offset: 1)].inspect
+    ";
+    let binary = mrbc_compile("call_with_splat_args", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: String = result.as_ref().try_into().unwrap();
+    assert_eq!(result, "[321, 210, 9, 10]");
+}
+
+#[test]
+fn hash_double_splat_test() {
+    let code = "
+    defaults = {color: \"red\", size: 1}
+    merged = {**defaults, size: 2}
+    [merged[:color], merged[:size], merged.size]
+    ";
+    let binary = mrbc_compile("hash_double_splat", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: Vec<std::rc::Rc<mrubyedge::yamrb::value::RObject>> =
+        result.as_ref().try_into().unwrap();
+    let color: String = result[0].as_ref().try_into().unwrap();
+    assert_eq!(color, "red");
+    let size: i64 = result[1].as_ref().try_into().unwrap();
+    assert_eq!(size, 2);
+    let len: i64 = result[2].as_ref().try_into().unwrap();
+    assert_eq!(len, 2);
+}
+
+#[test]
+fn zsuper_forwards_arguments_test() {
+    let code = "
+    class Base
+      def calc(a, b, *rest)
+        a * 100 + b * 10 + rest.size
+      end
+
+      def tagged(name, sep: \"-\")
+        name + sep + \"b\"
+      end
+    end
+
+    class Child < Base
+      def calc(a, b, *rest)
+        super + 1
+      end
+
+      def tagged(name, sep: \"-\")
+        super
+      end
+    end
+
+    c = Child.new
+    c.calc(1, 2, 3, 4).to_s + \",\" + c.tagged(\"a\", sep: \":\")
+    ";
+    let binary = mrbc_compile("zsuper_forwards_arguments", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: String = result.as_ref().try_into().unwrap();
+    assert_eq!(result, "123,a:b");
+}
@@ -0,0 +1,166 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+use mrubyedge::Error;
+use mrubyedge::yamrb::helpers::mrb_define_cmethod;
+use mrubyedge::yamrb::value::RObject;
+use mrubyedge::yamrb::vm::VM;
+use std::rc::Rc;
+
+#[test]
+fn deep_recursion_test() {
+    let code = "
+    def sum(n)
+      return 0 if n == 0
+      n + sum(n - 1)
+    end
+
+    sum(10000)
+    ";
+    let binary = mrbc_compile("stack_deep_recursion", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: i64 = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, 50005000);
+}
+
+#[test]
+fn stack_level_too_deep_test() {
+    let code = "
+    def forever(n)
+      forever(n + 1)
+    end
+
+    begin
+      forever(0)
+    rescue SystemStackError => e
+      [e.class.inspect, e.message.include?(\"stack level too deep\")].inspect
+    end
+    ";
+    let binary = mrbc_compile("stack_level_too_deep", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result: String = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, "[\"SystemStackError\", true]");
+}
+
+#[test]
+fn max_regs_size_test() {
+    let code = "
+    def depth(n)
+      depth(n + 1)
+    rescue SystemStackError
+      n
+    end
+
+    depth(0)
+    ";
+    let binary = mrbc_compile("stack_max_regs_size", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.max_regs_size = 1024;
+    let result: i64 = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert!(0 < result && result < 1024, "depth = {}", result);
+}
+
+#[test]
+fn max_native_depth_test() {
+    let code = "
+    def nest(n)
+      $depth = n
+      n.upto(n) { |i| nest(i + 1) }
+    end
+
+    begin
+      nest(0)
+    rescue SystemStackError
+      $depth
+    end
+    ";
+    let binary = mrbc_compile("stack_max_native_depth", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.max_native_depth = 32;
+    let result: i64 = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, 32);
+    assert_eq!(vm.native_depth, 0);
+}
+
+#[test]
+fn default_native_limits_fit_thread_stack_test() {
+    // Test threads get a 2 MiB stack; nesting through native frames must
+    // raise SystemStackError under the default limits before it runs out
+    let code = "
+    def f(n)
+      $depth = n
+      n.upto(n) { f(n + 1) }
+    end
+
+    class C
+      def initialize(n)
+        $depth = n
+        C.new(n + 1)
+      end
+    end
+
+    def reached
+      yield
+    rescue SystemStackError => e
+      [e.class, $depth > 8]
+    end
+
+    [reached { f(0) }, reached { C.new(0) }]
+    ";
+    assert_eq!(
+        run_to_string("stack_default_native_limits", code),
+        "[[SystemStackError, true], [SystemStackError, true]]"
+    );
+}
+
+#[test]
+fn iterator_recursion_bounded_by_registers_test() {
+    // Blocks of Ruby-defined iterators run on the register stack, not the
+    // Rust one, so this nesting is limited by max_regs_size
+    let code = "
+    def nest(n)
+      $depth = n
+      [n].each { |i| nest(i + 1) }
+    end
+
+    begin
+      nest(0)
+    rescue SystemStackError
+      $depth
+    end
+    ";
+    let binary = mrbc_compile("stack_iterator_recursion", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    vm.max_native_depth = 4;
+    let result: i64 = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert!(result > 4, "depth = {}", result);
+    assert_eq!(vm.native_depth, 0);
+}
+
+#[test]
+fn many_native_methods_test() {
+    fn answer(_vm: &mut VM, _args: &[Rc<RObject>]) -> Result<Rc<RObject>, Error> {
+        Ok(Rc::new(RObject::integer(42)))
+    }
+
+    let code = "
+    method_4999
+    ";
+    let binary = mrbc_compile("stack_many_native_methods", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let klass = vm.object_class.clone();
+    for i in 0..5000 {
+        let name = format!("method_{}", i);
+        mrb_define_cmethod(&mut vm, klass.clone(), &name, Box::new(answer));
+    }
+    let result: i64 = vm.run().unwrap().as_ref().try_into().unwrap();
+    assert_eq!(result, 42);
+}
@@ -0,0 +1,148 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn string_tr_delete_squeeze_count_test() {
+    let code = r#"
+[
+  "hello".tr('el', 'ip'),
+  "hello".tr('a-y', 'b-z'),
+  "hello".tr('^l', '*'),
+  "hello".tr('lo', ''),
+  "héllo".tr('é', 'e'),
+  "aabbcc".tr_s('ab', 'x'),
+  "hello".delete('l', 'lo'),
+  "hello".delete('^l'),
+  "aaabbbccc".squeeze,
+  "aaabbbccc".squeeze('a-b'),
+  "hello world".count('lo'),
+  "hello".count('a-z', '^l')
+]
+"#;
+    assert_eq!(
+        run_to_string("string_tr_delete_squeeze_count_test", code),
+        r#"["hippo", "ifmmp", "**ll*", "he", "hello", "xcc", "heo", "ll", "abc", "abccc", 5, 3]"#
+    );
+}
+
+#[test]
+fn string_justify_and_case_test() {
+    let code = r#"
+[
+  "abc".center(8),
+  "abc".center(10, "12"),
+  "abc".ljust(6, "."),
+  "日本".rjust(4, "*"),
+  "abc".center(2),
+  "hELLO wORLD".capitalize,
+  "éa".capitalize,
+  "hELLO".swapcase,
+  "héllo".reverse,
+  "aBc".casecmp("AbC"),
+  "a".casecmp("b"),
+  "a".casecmp(1),
+  "ÄB".casecmp?("äb")
+]
+"#;
+    assert_eq!(
+        run_to_string("string_justify_and_case_test", code),
+        r#"["  abc   ", "121abc1212", "abc...", "**日本", "abc", "Hello world", "Éa", "Hello", "olléh", 0, -1, nil, true]"#
+    );
+}
+
+#[test]
+fn string_succ_and_upto_test() {
+    let code = r#"
+r = ["az".succ, "zz99".succ, "Zz".succ, "-9".succ, "1.9.9".succ, "***".succ, "あ".succ, "".next]
+a = []; "a".upto("e") { |s| a << s }; r << a
+a = []; "9".upto("11") { |s| a << s }; r << a
+a = []; "az".upto("bc") { |s| a << s }; r << a
+a = []; "a".upto("c", true) { |s| a << s }; r << a
+r
+"#;
+    assert_eq!(
+        run_to_string("string_succ_and_upto_test", code),
+        r#"["ba", "aaa00", "AAa", "-10", "2.0.0", "**+", "ぃ", "", ["a", "b", "c", "d", "e"], ["9", "10", "11"], ["az", "ba", "bb", "bc"], ["a", "b"]]"#
+    );
+}
+
+#[test]
+fn string_lines_test() {
+    let code = r#"
+each = []
+"x\ny".each_line { |line| each << line }
+[
+  "a\nb\r\nc".lines,
+  "a\nb\r\nc\n".lines(chomp: true),
+  "a,b,c".lines(","),
+  "p1\np1\n\n\np2".lines(""),
+  each
+]
+"#;
+    assert_eq!(
+        run_to_string("string_lines_test", code),
+        r#"[["a\n", "b\r\n", "c"], ["a", "b", "c"], ["a,", "b,", "c"], ["p1\np1\n\n\n", "p2"], ["x\n", "y"]]"#
+    );
+}
+
+#[test]
+fn string_search_and_edit_test() {
+    let code = r#"
+[
+  "abcd".insert(1, "X"),
+  "abcd".insert(-1, "X"),
+  "abcd".insert(-2, "X"),
+  "world".prepend("hello "),
+  "x".replace("yz"),
+  "hello".delete_prefix("he"),
+  "hello".delete_suffix("lo"),
+  "a-b-c".partition("-"),
+  "a-b-c".rpartition("-"),
+  "abc".partition("x"),
+  "abc".rpartition("x"),
+  "hello".rindex("l"),
+  "hello".rindex("l", 2),
+  "日本日本".rindex("日"),
+  "hello".rindex("x")
+]
+"#;
+    assert_eq!(
+        run_to_string("string_search_and_edit_test", code),
+        r#"["aXbcd", "abcdX", "abcXd", "hello world", "yz", "llo", "hel", ["a", "-", "b-c"], ["a-b", "-", "c"], ["abc", "", ""], ["", "", "abc"], 3, 2, 2, nil]"#
+    );
+}
+
+#[test]
+fn string_hex_oct_sum_test() {
+    let code = r#"
+["0x1F".hex, "-ff".hex, "zz".hex, "0b101".oct, "777".oct, "0o17".oct, "1_0".oct, "ffffffffffffffffffff".hex, "abc".sum, "abc".sum(4)]
+"#;
+    assert_eq!(
+        run_to_string("string_hex_oct_sum_test", code),
+        "[31, -255, 0, 5, 511, 15, 8, 1208925819614629174706175, 294, 6]"
+    );
+}
+
+#[test]
+fn string_bang_methods_test() {
+    let code = r#"
+def attempt
+  yield
+rescue FrozenError
+  :frozen
+end
+
+s = "hello"
+r = [s.tr!('z', 'y'), s.squeeze!.dup, s.capitalize!.dup, s.swapcase!.dup, s.reverse!.dup, s.succ!.dup, s.delete_suffix!("x")]
+f = "abc".freeze
+r << attempt { f.reverse! } << attempt { f.insert(0, "x") } << attempt { f.tr!('z', 'y') }
+r
+"#;
+    assert_eq!(
+        run_to_string("string_bang_methods_test", code),
+        r#"[nil, "helo", "Helo", "hELO", "OLEh", "OLEi", nil, :frozen, :frozen, :frozen]"#
+    );
+}
@@ -0,0 +1,81 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn utf8_string_indexes_by_character_test() {
+    let code = r#"
+s = "héllo wörld"
+[s.length, s.bytesize, s[1], s[1, 4], s[-5..], s[7..-2], s[11], s[11, 2], s[12, 1], s.index("w"), s.index("l", 4), s["wö"], s["xyz"]]
+"#;
+    assert_eq!(
+        run_to_string("utf8_string_indexes_by_character_test", code),
+        r#"[11, 13, "é", "éllo", "wörld", "örl", nil, "", nil, 6, 9, "wö", nil]"#
+    );
+}
+
+#[test]
+fn utf8_string_iteration_test() {
+    let code = r#"
+collected = []
+ret = "añb".each_char { |c| collected << c }
+s = "日本語"
+removed = s.slice!(0, 2)
+[collected, ret, "añb".each_char.to_a, "日本語".chars, "日本".codepoints, "é".ord, s, removed]
+"#;
+    assert_eq!(
+        run_to_string("utf8_string_iteration_test", code),
+        r#"[["a", "ñ", "b"], "añb", ["a", "ñ", "b"], ["日", "本", "語"], [26085, 26412], 233, "語", "日本"]"#
+    );
+}
+
+#[test]
+fn utf8_string_case_and_inspect_test() {
+    let code = r##"
+["ÉCOLE".downcase, "straße".upcase, "a\tb\"c\\".inspect, "\u0001é".inspect, '#{x}'.inspect]
+"##;
+    assert_eq!(
+        run_to_string("utf8_string_case_and_inspect_test", code),
+        r##"["école", "STRASSE", "\"a\\tb\\\"c\\\\\"", "\"\\u0001é\"", "\"\\\#{x}\""]"##
+    );
+}
+
+#[test]
+fn binary_string_keeps_byte_semantics_test() {
+    let code = r#"
+s = "héllo".b
+[s.length, s[1], s[1, 2].bytes, s.encoding, "x".encoding, s.upcase, s.chars.size, s.ord, s.valid_encoding?]
+"#;
+    assert_eq!(
+        run_to_string("binary_string_keeps_byte_semantics_test", code),
+        r#"[6, "\xC3", [195, 169], #<Encoding:ASCII-8BIT>, #<Encoding:UTF-8>, "H\xC3\xA9LLO", 6, 104, true]"#
+    );
+}
+
+#[test]
+fn force_encoding_and_scrub_test() {
+    let code = r#"
+s = "日本"
+s.force_encoding("BINARY")
+binary = [s.length, s.encoding.to_s]
+s.force_encoding(Encoding::UTF_8)
+broken = "ab"
+broken.setbyte(1, 255)
+[
+  binary,
+  s.length,
+  broken.valid_encoding?,
+  broken.scrub,
+  broken.scrub("?"),
+  broken.scrub { |bytes| "<" + bytes.bytes[0].to_s + ">" },
+  broken.inspect,
+  s.unicode_normalize(:nfc)
+]
+"#;
+    assert_eq!(
+        run_to_string("force_encoding_and_scrub_test", code),
+        r#"[[6, "ASCII-8BIT"], 2, false, "a�", "a?", "a<255>", "\"a\\xFF\"", "日本"]"#
+    );
+}
@@ -0,0 +1,139 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn struct_accessors_and_conversions_test() {
+    let code = "
+Point = Struct.new(:x, :y)
+p = Point.new(1)
+p.y = 2
+[p, p.x, p.y, p.to_a, p.to_h, p.members, Point.members]
+";
+    assert_eq!(
+        run_to_string("struct_accessors", code),
+        "[#<struct Point x=1, y=2>, 1, 2, [1, 2], {:x=>1, :y=>2}, [:x, :y], [:x, :y]]"
+    );
+}
+
+#[test]
+fn struct_index_and_equality_test() {
+    let code = "
+Point = Struct.new(:x, :y)
+p = Point.new(1, 2)
+p[:x] = 10
+p[1] = 20
+errors = []
+begin
+  p[:z]
+rescue NameError
+  errors << :name
+end
+begin
+  p[2]
+rescue IndexError
+  errors << :index
+end
+[p['x'], p[-1], p == Point.new(10, 20), p == Point.new(10, 21), errors]
+";
+    assert_eq!(
+        run_to_string("struct_index", code),
+        "[10, 20, true, false, [:name, :index]]"
+    );
+}
+
+#[test]
+fn struct_keyword_init_test() {
+    let code = "
+Opts = Struct.new(:name, :size, keyword_init: true)
+o = Opts.new(size: 3)
+error = begin
+  Opts.new('a', 3)
+  nil
+rescue ArgumentError
+  :argument_error
+end
+[o, o.name, error]
+";
+    assert_eq!(
+        run_to_string("struct_keyword_init", code),
+        "[#<struct Opts name=nil, size=3>, nil, :argument_error]"
+    );
+}
+
+#[test]
+fn struct_subclass_and_enumerable_test() {
+    let code = "
+class Pair < Struct.new(:left, :right)
+  def sum
+    left + right
+  end
+end
+pair = Pair.new(1, 2)
+[pair, pair.sum, pair.map { |v| v * 10 }, Struct.new(:a).new(1)]
+";
+    assert_eq!(
+        run_to_string("struct_subclass", code),
+        "[#<struct Pair left=1, right=2>, 3, [10, 20], #<struct a=1>]"
+    );
+}
+
+#[test]
+fn struct_and_data_block_defines_methods_test() {
+    let code = "
+Box = Struct.new(:v) do
+  def double
+    v * 2
+  end
+end
+Point = Data.define(:x, :y) do
+  def sum
+    x + y
+  end
+end
+klass = nil
+Struct.new(:a) { |c| klass = c }
+[Box.new(4).double, Box.members, Point.new(x: 1, y: 2).sum, klass.members]
+";
+    assert_eq!(
+        run_to_string("struct_and_data_block", code),
+        "[8, [:v], 3, [:a]]"
+    );
+}
+
+#[test]
+fn data_define_test() {
+    let code = "
+Coord = Data.define(:lat, :lng)
+a = Coord.new(lat: 1, lng: 2)
+b = Coord.new(1, 2)
+moved = a.with(lng: 5)
+[a, a == b, moved, a.lng, a.to_h, Coord.members, a.respond_to?(:lat=)]
+";
+    assert_eq!(
+        run_to_string("data_define", code),
+        "[#<data Coord lat=1, lng=2>, true, #<data Coord lat=1, lng=5>, 2, {:lat=>1, :lng=>2}, [:lat, :lng], false]"
+    );
+}
+
+#[test]
+fn data_argument_errors_test() {
+    let code = "
+Coord = Data.define(:lat, :lng)
+errors = []
+[-> { Coord.new(lat: 1) }, -> { Coord.new(1, 2, 3) }, -> { Coord.new(1, 2).with(alt: 3) }].each do |f|
+  begin
+    f.call
+  rescue ArgumentError
+    errors << :argument_error
+  end
+end
+errors
+";
+    assert_eq!(
+        run_to_string("data_errors", code),
+        "[:argument_error, :argument_error, :argument_error]"
+    );
+}
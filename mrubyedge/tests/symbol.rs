@@ -0,0 +1,68 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn dynamic_symbol_test() {
+    let code = "
+    handlers = {handler_start: \"started\"}
+    name = \"start\"
+    sym = :\"handler_#{name}\"
+    [sym == :handler_start, handlers[sym]]
+    ";
+    let binary = mrbc_compile("dynamic_symbol", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: Vec<std::rc::Rc<mrubyedge::yamrb::value::RObject>> =
+        result.as_ref().try_into().unwrap();
+    let same: bool = result[0].as_ref().try_into().unwrap();
+    assert!(same);
+    let called: String = result[1].as_ref().try_into().unwrap();
+    assert_eq!(called, "started");
+}
+
+#[test]
+fn symbol_object_id_test() {
+    let code = "
+    n = \"ready\"
+    [
+      :ready.object_id == :ready.object_id,
+      :ready.object_id == n.to_sym.object_id,
+      :ready.object_id == :\"#{n}\".object_id,
+      :ready.object_id == :other.object_id,
+    ]
+    ";
+    let binary = mrbc_compile("symbol_object_id", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: Vec<std::rc::Rc<mrubyedge::yamrb::value::RObject>> =
+        result.as_ref().try_into().unwrap();
+    let flags: Vec<bool> = result
+        .iter()
+        .map(|v| v.as_ref().try_into().unwrap())
+        .collect();
+    assert_eq!(flags, vec![true, true, true, false]);
+}
+
+#[test]
+fn symbol_all_symbols_test() {
+    let code = "
+    \"freshly_made\".to_sym
+    all = Symbol.all_symbols
+    [all.include?(:freshly_made), all.include?(:all_symbols)]
+    ";
+    let binary = mrbc_compile("symbol_all_symbols", code);
+    let mut rite = mrubyedge::rite::load(&binary).unwrap();
+    let mut vm = mrubyedge::yamrb::vm::VM::open(&mut rite);
+    let result = vm.run().unwrap();
+    let result: Vec<std::rc::Rc<mrubyedge::yamrb::value::RObject>> =
+        result.as_ref().try_into().unwrap();
+    let fresh: bool = result[0].as_ref().try_into().unwrap();
+    assert!(fresh);
+    let from_irep: bool = result[1].as_ref().try_into().unwrap();
+    assert!(from_irep);
+}
@@ -0,0 +1,237 @@
+extern crate mec_mrbc_sys;
+extern crate mrubyedge;
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn private_method_test() {
+    let code = "
+class Account
+  def initialize(balance)
+    @balance = balance
+  end
+
+  def summary
+    format_balance
+  end
+
+  def via_self
+    self.format_balance
+  end
+
+  private
+
+  def format_balance
+    \"$#{@balance}\"
+  end
+end
+a = Account.new(5)
+r = [a.summary, a.via_self, a.send(:format_balance)]
+begin
+  a.format_balance
+rescue NoMethodError
+  r << :private
+end
+begin
+  a.initialize(1)
+rescue NoMethodError
+  r << :initialize
+end
+r
+";
+    assert_eq!(
+        run_to_string("private_method", code),
+        r#"["$5", "$5", "$5", :private, :initialize]"#
+    );
+}
+
+#[test]
+fn protected_method_test() {
+    let code = "
+class Money
+  def initialize(cents)
+    @cents = cents
+  end
+
+  def >(other)
+    cents > other.cents
+  end
+
+  protected
+
+  def cents
+    @cents
+  end
+end
+r = [Money.new(5) > Money.new(3)]
+begin
+  Money.new(1).cents
+rescue NoMethodError
+  r << :protected
+end
+r
+";
+    assert_eq!(
+        run_to_string("protected_method", code),
+        "[true, :protected]"
+    );
+}
+
+#[test]
+fn visibility_with_arguments_test() {
+    let code = "
+class Base
+  def a; 1; end
+  def b; 2; end
+  private :a, :b
+  private def c; 3; end
+  private attr_reader :d
+  public
+
+  def sum
+    a + b + c
+  end
+end
+
+class Derived < Base
+  public :a
+end
+
+r = [Base.new.sum, Derived.new.a]
+begin
+  Base.new.a
+rescue NoMethodError
+  r << :private_in_base
+end
+r
+";
+    assert_eq!(
+        run_to_string("visibility_with_arguments", code),
+        "[6, 1, :private_in_base]"
+    );
+}
+
+#[test]
+fn attr_methods_follow_default_visibility_test() {
+    let code = "
+class E
+  attr_reader :p
+  private
+  attr_accessor :q
+  attr_writer :w
+
+  public
+
+  def initialize
+    self.q = 1
+    @p = 2
+  end
+
+  def get_q
+    q
+  end
+end
+
+e = E.new
+r = [e.get_q, e.p]
+[-> { e.q }, -> { e.q = 3 }, -> { e.w = 4 }].each do |call|
+  begin
+    call.call
+  rescue NoMethodError
+    r << :private
+  end
+end
+r
+";
+    assert_eq!(
+        run_to_string("attr_methods_follow_default_visibility", code),
+        "[1, 2, :private, :private, :private]"
+    );
+}
+
+#[test]
+fn public_send_and_respond_to_test() {
+    let code = "
+class Box
+  def open; :opened; end
+  private
+  def secret; :secret; end
+end
+b = Box.new
+r = [b.public_send(:open), b.respond_to?(:secret), b.respond_to?(:secret, true), b.respond_to?(:open)]
+begin
+  b.public_send(:secret)
+rescue NoMethodError
+  r << :rejected
+end
+r
+";
+    assert_eq!(
+        run_to_string("public_send_visibility", code),
+        "[:opened, false, true, true, :rejected]"
+    );
+}
+
+#[test]
+fn private_class_method_test() {
+    let code = "
+class Connection
+  def self.open
+    new
+  end
+  private_class_method :new
+end
+r = [Connection.open.class]
+begin
+  Connection.new
+rescue NoMethodError
+  r << :private_new
+end
+r
+";
+    assert_eq!(
+        run_to_string("private_class_method", code),
+        "[Connection, :private_new]"
+    );
+}
+
+#[test]
+fn module_function_test() {
+    let code = "
+module Util
+  module_function
+
+  def twice(x)
+    x * 2
+  end
+end
+
+module Helper
+  def thrice(x)
+    x * 3
+  end
+  module_function :thrice
+end
+
+class User
+  include Util
+
+  def go
+    twice(4)
+  end
+end
+
+r = [Util.twice(2), Helper.thrice(2), User.new.go, Util.singleton_methods]
+begin
+  User.new.twice(1)
+rescue NoMethodError
+  r << :private
+end
+r
+";
+    assert_eq!(
+        run_to_string("module_function", code),
+        "[4, 6, 8, [:twice], :private]"
+    );
+}